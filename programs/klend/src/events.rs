@@ -0,0 +1,234 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{LastUpdate, Reserve};
+
+#[derive(Debug, Clone)]
+#[event]
+pub struct DepositEvent {
+    pub lending_market: Pubkey,
+    pub reserve: Pubkey,
+    pub obligation: Pubkey,
+    pub owner: Pubkey,
+    pub liquidity_amount: u64,
+    pub collateral_amount: u64,
+}
+
+#[derive(Debug, Clone)]
+#[event]
+pub struct WithdrawEvent {
+    pub lending_market: Pubkey,
+    pub reserve: Pubkey,
+    pub obligation: Pubkey,
+    pub owner: Pubkey,
+    pub liquidity_amount: u64,
+    pub collateral_amount: u64,
+}
+
+#[derive(Debug, Clone)]
+#[event]
+pub struct BorrowEvent {
+    pub lending_market: Pubkey,
+    pub reserve: Pubkey,
+    pub obligation: Pubkey,
+    pub owner: Pubkey,
+    pub receive_amount: u64,
+    pub borrow_fee: u64,
+    pub referrer_fee: u64,
+}
+
+#[derive(Debug, Clone)]
+#[event]
+pub struct RepayEvent {
+    pub lending_market: Pubkey,
+    pub reserve: Pubkey,
+    pub obligation: Pubkey,
+    pub owner: Pubkey,
+    pub repay_amount: u64,
+}
+
+#[derive(Debug, Clone)]
+#[event]
+pub struct LiquidationEvent {
+    pub lending_market: Pubkey,
+    pub obligation: Pubkey,
+    pub liquidator: Pubkey,
+    pub repay_reserve: Pubkey,
+    pub withdraw_reserve: Pubkey,
+    pub repay_amount: u64,
+    pub withdraw_collateral_amount: u64,
+    pub withdraw_liquidity_amount: u64,
+    pub liquidation_bonus_bps: u64,
+    pub protocol_fee: u64,
+}
+
+#[derive(Debug, Clone)]
+#[event]
+pub struct FlashLoanEvent {
+    pub lending_market: Pubkey,
+    pub reserve: Pubkey,
+    pub user: Pubkey,
+    pub liquidity_amount: u64,
+    pub protocol_fee: u64,
+    pub referrer_fee: u64,
+}
+
+#[derive(Debug, Clone)]
+#[event]
+pub struct ReserveConfigUpdated {
+    pub lending_market: Pubkey,
+    pub reserve: Pubkey,
+    pub mode: u64,
+    pub offset: u32,
+    pub old_value: Vec<u8>,
+    pub new_value: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+#[event]
+pub struct MarketConfigUpdated {
+    pub lending_market: Pubkey,
+    pub mode: u64,
+    pub offset: u32,
+    pub old_value: Vec<u8>,
+    pub new_value: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+#[event]
+pub struct SocializeLossEvent {
+    pub lending_market: Pubkey,
+    pub reserve: Pubkey,
+    pub obligation: Pubkey,
+    pub forgiven_amount_sf: u128,
+}
+
+#[derive(Debug, Clone)]
+#[event]
+pub struct ElevationGroupChanged {
+    pub lending_market: Pubkey,
+    pub obligation: Pubkey,
+    pub owner: Pubkey,
+    pub previous_elevation_group: u8,
+    pub new_elevation_group: u8,
+}
+
+#[derive(Debug, Clone)]
+#[event]
+pub struct ObligationOwnerUpdated {
+    pub lending_market: Pubkey,
+    pub obligation: Pubkey,
+    pub previous_owner: Pubkey,
+    pub new_owner: Pubkey,
+}
+
+#[derive(Debug, Clone)]
+#[event]
+pub struct InterestAccrued {
+    pub lending_market: Pubkey,
+    pub reserve: Pubkey,
+    pub slots_elapsed: u64,
+    pub borrowed_amount_sf: u128,
+    pub interest_sf: u128,
+    pub protocol_fees_sf: u128,
+    pub referrer_fees_sf: u128,
+}
+
+pub struct ReserveInterestSnapshot {
+    last_update: LastUpdate,
+    borrowed_amount_sf: u128,
+    accumulated_protocol_fees_sf: u128,
+    accumulated_referrer_fees_sf: u128,
+}
+
+impl ReserveInterestSnapshot {
+    pub fn new(reserve: &Reserve) -> Self {
+        Self {
+            last_update: reserve.last_update,
+            borrowed_amount_sf: reserve.liquidity.borrowed_amount_sf,
+            accumulated_protocol_fees_sf: reserve.liquidity.accumulated_protocol_fees_sf,
+            accumulated_referrer_fees_sf: reserve.liquidity.accumulated_referrer_fees_sf,
+        }
+    }
+
+    pub fn accrued(
+        &self,
+        reserve_pk: Pubkey,
+        reserve: &Reserve,
+        slot: u64,
+    ) -> Option<InterestAccrued> {
+        let slots_elapsed = self.last_update.slots_elapsed(slot).unwrap_or(0);
+        if slots_elapsed == 0 {
+            return None;
+        }
+        Some(InterestAccrued {
+            lending_market: reserve.lending_market,
+            reserve: reserve_pk,
+            slots_elapsed,
+            borrowed_amount_sf: reserve.liquidity.borrowed_amount_sf,
+            interest_sf: reserve
+                .liquidity
+                .borrowed_amount_sf
+                .saturating_sub(self.borrowed_amount_sf),
+            protocol_fees_sf: reserve
+                .liquidity
+                .accumulated_protocol_fees_sf
+                .saturating_sub(self.accumulated_protocol_fees_sf),
+            referrer_fees_sf: reserve
+                .liquidity
+                .accumulated_referrer_fees_sf
+                .saturating_sub(self.accumulated_referrer_fees_sf),
+        })
+    }
+}
+
+/// Returns the offset and the old/new content of the smallest byte range covering every change.
+pub fn changed_bytes(old: &[u8], new: &[u8]) -> (u32, Vec<u8>, Vec<u8>) {
+    let Some(start) = old.iter().zip(new).position(|(o, n)| o != n) else {
+        return (0, vec![], vec![]);
+    };
+    let end = old.len()
+        - old
+            .iter()
+            .rev()
+            .zip(new.iter().rev())
+            .position(|(o, n)| o != n)
+            .unwrap();
+    (
+        u32::try_from(start).unwrap(),
+        old[start..end].to_vec(),
+        new[start..end].to_vec(),
+    )
+}
+
+impl ReserveConfigUpdated {
+    pub fn new(
+        lending_market: Pubkey,
+        reserve: Pubkey,
+        mode: u64,
+        old_config: &[u8],
+        new_config: &[u8],
+    ) -> Self {
+        let (offset, old_value, new_value) = changed_bytes(old_config, new_config);
+        Self {
+            lending_market,
+            reserve,
+            mode,
+            offset,
+            old_value,
+            new_value,
+        }
+    }
+}
+
+impl MarketConfigUpdated {
+    pub fn new(lending_market: Pubkey, mode: u64, old_market: &[u8], new_market: &[u8]) -> Self {
+        let (offset, old_value, new_value) = changed_bytes(old_market, new_market);
+        Self {
+            lending_market,
+            mode,
+            offset,
+            old_value,
+            new_value,
+        }
+    }
+}
@@ -10,12 +10,18 @@ use lending_checks::validate_referrer_token_state;
 
 use super::handler_refresh_obligation_farms_for_reserve::*;
 use crate::{
-    check_refresh_ixs, gen_signer_seeds,
+    check_refresh_ixs,
+    events::BorrowEvent,
+    gen_signer_seeds,
     lending_market::{lending_checks, lending_operations},
     refresh_farms,
-    state::{obligation::Obligation, CalculateBorrowResult, LendingMarket, Reserve},
+    state::{
+        obligation::{Obligation, ObligationAccountMut},
+        CalculateBorrowResult, FixedRateBorrowTerms, LendingMarket, Reserve,
+    },
     utils::{seeds, token_transfer, FatAccountLoader},
-    xmsg, LendingAction, LendingError, ReferrerTokenState, ReserveFarmKind,
+    xmsg, LendingAction, LendingError, ObligationDelegatePermissions, ReferrerTokenState,
+    ReserveFarmKind,
 };
 
 pub fn process_v1<'info>(
@@ -27,7 +33,7 @@ pub fn process_v1<'info>(
         ctx.accounts.borrow_reserve,
         ReserveFarmKind::Debt
     );
-    process_impl(ctx.accounts, ctx.remaining_accounts, liquidity_amount)
+    process_impl(ctx.accounts, ctx.remaining_accounts, liquidity_amount, None)
 }
 
 pub fn process_v2<'info>(
@@ -38,6 +44,47 @@ pub fn process_v2<'info>(
         &ctx.accounts.borrow_accounts,
         ctx.remaining_accounts,
         liquidity_amount,
+        None,
+    )?;
+    refresh_farms!(
+        ctx.accounts.borrow_accounts,
+        [(
+            ctx.accounts.borrow_accounts.borrow_reserve,
+            ctx.accounts.farms_accounts,
+            Debt,
+        )],
+    );
+    Ok(())
+}
+
+pub fn process_fixed_rate_v1<'info>(
+    ctx: Context<'_, '_, '_, 'info, BorrowObligationLiquidity<'info>>,
+    liquidity_amount: u64,
+    terms: FixedRateBorrowTerms,
+) -> Result<()> {
+    check_refresh_ixs!(
+        ctx.accounts,
+        ctx.accounts.borrow_reserve,
+        ReserveFarmKind::Debt
+    );
+    process_impl(
+        ctx.accounts,
+        ctx.remaining_accounts,
+        liquidity_amount,
+        Some(terms),
+    )
+}
+
+pub fn process_fixed_rate_v2<'info>(
+    ctx: Context<'_, '_, '_, 'info, BorrowObligationLiquidityV2<'info>>,
+    liquidity_amount: u64,
+    terms: FixedRateBorrowTerms,
+) -> Result<()> {
+    process_impl(
+        &ctx.accounts.borrow_accounts,
+        ctx.remaining_accounts,
+        liquidity_amount,
+        Some(terms),
     )?;
     refresh_farms!(
         ctx.accounts.borrow_accounts,
@@ -54,16 +101,33 @@ fn process_impl<'info>(
     accounts: &BorrowObligationLiquidity<'info>,
     remaining_accounts: &[AccountInfo<'info>],
     liquidity_amount: u64,
+    fixed_rate_terms: Option<FixedRateBorrowTerms>,
 ) -> Result<()> {
     msg!("liquidity_amount {}", liquidity_amount);
     lending_checks::borrow_obligation_liquidity_checks(accounts)?;
 
     let borrow_reserve = &mut accounts.borrow_reserve.load_mut()?;
     let lending_market = &accounts.lending_market.load()?;
-    let obligation = &mut accounts.obligation.load_mut()?;
+    let mut obligation_account = ObligationAccountMut::load(accounts.obligation.as_ref())?;
+    let obligation = &mut obligation_account.get_mut();
     let lending_market_key = accounts.lending_market.key();
     let clock = &Clock::get()?;
 
+    let (remaining_accounts, owner_withdrawal_caps) = lending_checks::split_owner_withdrawal_caps(
+        accounts.borrow_reserve.key(),
+        obligation.owner,
+        remaining_accounts,
+    )?;
+    let (remaining_accounts, obligation_permission) =
+        lending_checks::split_obligation_permission(lending_market, remaining_accounts);
+    lending_checks::validate_obligation_permission(
+        &crate::ID,
+        lending_market,
+        lending_market_key,
+        obligation.owner,
+        obligation_permission,
+    )?;
+
     let authority_signer_seeds =
         gen_signer_seeds!(lending_market_key.as_ref(), lending_market.bump_seed as u8);
 
@@ -98,20 +162,50 @@ fn process_impl<'info>(
         token_interface::accessor::amount(&accounts.reserve_source_liquidity.to_account_info())?;
     let initial_reserve_available_liquidity = borrow_reserve.liquidity.available_amount;
 
+    let is_delegate = accounts.owner.key() != obligation.owner;
+    let mut owner_withdrawal_caps = owner_withdrawal_caps
+        .as_ref()
+        .map(|caps| caps.load_mut())
+        .transpose()?;
+
     let CalculateBorrowResult {
+        borrow_amount_f,
         receive_amount,
         borrow_fee,
-        ..
-    } = lending_operations::borrow_obligation_liquidity(
-        lending_market,
-        borrow_reserve,
-        obligation,
-        liquidity_amount,
-        clock,
-        accounts.borrow_reserve.key(),
-        referrer_token_state_option,
-        deposit_reserves_iter,
-    )?;
+        referrer_fee,
+    } = match fixed_rate_terms {
+        Some(terms) => lending_operations::borrow_obligation_liquidity_fixed_rate(
+            lending_market,
+            borrow_reserve,
+            obligation,
+            liquidity_amount,
+            terms,
+            clock,
+            accounts.borrow_reserve.key(),
+            referrer_token_state_option,
+            deposit_reserves_iter,
+            owner_withdrawal_caps.as_deref_mut(),
+        )?,
+        None => lending_operations::borrow_obligation_liquidity(
+            lending_market,
+            borrow_reserve,
+            obligation,
+            liquidity_amount,
+            clock,
+            accounts.borrow_reserve.key(),
+            referrer_token_state_option,
+            deposit_reserves_iter,
+            owner_withdrawal_caps.as_deref_mut(),
+        )?,
+    };
+
+    if is_delegate {
+        lending_operations::check_delegate_borrow_within_ltv_ceiling(
+            obligation,
+            borrow_reserve,
+            borrow_amount_f,
+        )?;
+    }
 
     xmsg!("pnl: Borrow obligation liquidity {receive_amount} with borrow_fee {borrow_fee}",);
 
@@ -150,6 +244,16 @@ fn process_impl<'info>(
         LendingAction::Subtractive(borrow_fee + receive_amount),
     )?;
 
+    emit!(BorrowEvent {
+        lending_market: lending_market_key,
+        reserve: accounts.borrow_reserve.key(),
+        obligation: accounts.obligation.key(),
+        owner: accounts.owner.key(),
+        receive_amount,
+        borrow_fee,
+        referrer_fee,
+    });
+
     Ok(())
 }
 
@@ -159,7 +263,7 @@ pub struct BorrowObligationLiquidity<'info> {
 
     #[account(mut,
         has_one = lending_market,
-        has_one = owner @ LendingError::InvalidObligationOwner
+        constraint = obligation.load()?.is_authorized(owner.key(), ObligationDelegatePermissions::REBALANCE) @ LendingError::InvalidObligationOwner
     )]
     pub obligation: AccountLoader<'info, Obligation>,
 
@@ -193,7 +297,7 @@ pub struct BorrowObligationLiquidity<'info> {
 
     #[account(mut,
         token::mint = reserve_source_liquidity.mint,
-        token::authority = owner,
+        token::authority = obligation.load()?.owner,
     )]
     pub user_destination_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
 
@@ -0,0 +1,39 @@
+use anchor_lang::{prelude::*, Accounts};
+
+use crate::{
+    state::{LendingMarket, PendingConfigChange},
+    LendingError,
+};
+
+pub fn process(ctx: Context<CancelConfigChange>) -> Result<()> {
+    let pending_config_change = ctx.accounts.pending_config_change.load()?;
+
+    msg!(
+        "Cancelled config change of {} with mode {} queued at {}",
+        pending_config_change.target,
+        pending_config_change.mode,
+        pending_config_change.queued_timestamp
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelConfigChange<'info> {
+    #[account(
+        constraint = lending_market.load()?.is_owner_or_risk_council(authority.key) @ LendingError::InvalidSigner
+    )]
+    pub authority: Signer<'info>,
+
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+
+    #[account(mut,
+        has_one = lending_market,
+        has_one = payer,
+        close = payer
+    )]
+    pub pending_config_change: AccountLoader<'info, PendingConfigChange>,
+
+    #[account(mut)]
+    pub payer: AccountInfo<'info>,
+}
@@ -0,0 +1,80 @@
+use anchor_lang::{prelude::*, Accounts};
+use anchor_spl::{
+    token::Token,
+    token_interface::{Mint, TokenAccount},
+};
+
+use crate::{
+    gen_signer_seeds,
+    lending_market::lending_operations,
+    state::{LendingMarket, Reserve, WithdrawQueue},
+    utils::{seeds, token_transfer},
+};
+
+pub fn process(ctx: Context<CancelWithdrawal>, ticket_id: u64) -> Result<()> {
+    let reserve = &mut ctx.accounts.reserve.load_mut()?;
+    let withdraw_queue = &mut ctx.accounts.withdraw_queue.load_mut()?;
+    let lending_market = &ctx.accounts.lending_market.load()?;
+
+    let lending_market_key = ctx.accounts.lending_market.key();
+    let authority_signer_seeds =
+        gen_signer_seeds!(lending_market_key.as_ref(), lending_market.bump_seed as u8);
+
+    let collateral_amount = lending_operations::cancel_withdrawal(
+        reserve,
+        withdraw_queue,
+        ctx.accounts.owner.key(),
+        ticket_id,
+    )?;
+
+    msg!(
+        "pnl: Cancelled withdrawal ticket {} returning collateral {}",
+        ticket_id,
+        collateral_amount
+    );
+
+    token_transfer::cancel_withdrawal_transfer(
+        ctx.accounts.collateral_token_program.to_account_info(),
+        ctx.accounts.reserve_collateral_mint.to_account_info(),
+        ctx.accounts.lending_market_authority.clone(),
+        ctx.accounts.user_destination_collateral.to_account_info(),
+        authority_signer_seeds,
+        collateral_amount,
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelWithdrawal<'info> {
+    pub owner: Signer<'info>,
+
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+    #[account(
+        seeds = [seeds::LENDING_MARKET_AUTH, lending_market.key().as_ref()],
+        bump = lending_market.load()?.bump_seed as u8,
+    )]
+    pub lending_market_authority: AccountInfo<'info>,
+
+    #[account(mut,
+        has_one = lending_market
+    )]
+    pub reserve: AccountLoader<'info, Reserve>,
+
+    #[account(mut,
+        has_one = reserve
+    )]
+    pub withdraw_queue: AccountLoader<'info, WithdrawQueue>,
+
+    #[account(mut,
+        address = reserve.load()?.collateral.mint_pubkey,
+    )]
+    pub reserve_collateral_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut,
+        token::mint = reserve_collateral_mint
+    )]
+    pub user_destination_collateral: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub collateral_token_program: Program<'info, Token>,
+}
@@ -0,0 +1,97 @@
+use anchor_lang::{prelude::*, Accounts};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    gen_signer_seeds,
+    lending_market::lending_operations,
+    state::{LendingMarket, Reserve, WithdrawQueue},
+    utils::{seeds, token_transfer},
+    LendingError,
+};
+
+pub fn process(ctx: Context<ClaimWithdrawal>) -> Result<()> {
+    let reserve = &mut ctx.accounts.reserve.load_mut()?;
+    let withdraw_queue = &mut ctx.accounts.withdraw_queue.load_mut()?;
+    let lending_market = &ctx.accounts.lending_market.load()?;
+    let clock = Clock::get()?;
+
+    let lending_market_key = ctx.accounts.lending_market.key();
+    let authority_signer_seeds =
+        gen_signer_seeds!(lending_market_key.as_ref(), lending_market.bump_seed as u8);
+
+    let initial_reserve_token_balance = token_interface::accessor::amount(
+        &ctx.accounts.reserve_liquidity_supply.to_account_info(),
+    )?;
+
+    lending_operations::refresh_reserve(reserve, &clock, None, lending_market.referral_fee_bps)?;
+    let liquidity_amount = lending_operations::claim_withdrawals(
+        reserve,
+        withdraw_queue,
+        ctx.accounts.owner.key(),
+        &clock,
+    )?;
+
+    msg!("pnl: Claimed queued withdrawals {}", liquidity_amount);
+
+    token_transfer::claim_withdrawal_transfer(
+        ctx.accounts.liquidity_token_program.to_account_info(),
+        ctx.accounts.reserve_liquidity_mint.to_account_info(),
+        ctx.accounts.reserve_liquidity_supply.to_account_info(),
+        ctx.accounts.user_destination_liquidity.to_account_info(),
+        ctx.accounts.lending_market_authority.clone(),
+        authority_signer_seeds,
+        liquidity_amount,
+        ctx.accounts.reserve_liquidity_mint.decimals,
+    )?;
+
+    let final_reserve_token_balance = token_interface::accessor::amount(
+        &ctx.accounts.reserve_liquidity_supply.to_account_info(),
+    )?;
+    require_eq!(
+        initial_reserve_token_balance - liquidity_amount,
+        final_reserve_token_balance,
+        LendingError::ReserveVaultBalanceMismatch
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ClaimWithdrawal<'info> {
+    pub owner: Signer<'info>,
+
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+    #[account(
+        seeds = [seeds::LENDING_MARKET_AUTH, lending_market.key().as_ref()],
+        bump = lending_market.load()?.bump_seed as u8,
+    )]
+    pub lending_market_authority: AccountInfo<'info>,
+
+    #[account(mut,
+        has_one = lending_market
+    )]
+    pub reserve: AccountLoader<'info, Reserve>,
+
+    #[account(mut,
+        has_one = reserve
+    )]
+    pub withdraw_queue: AccountLoader<'info, WithdrawQueue>,
+
+    #[account(
+        address = reserve.load()?.liquidity.mint_pubkey,
+        mint::token_program = liquidity_token_program,
+    )]
+    pub reserve_liquidity_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut,
+        address = reserve.load()?.liquidity.supply_vault,
+    )]
+    pub reserve_liquidity_supply: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+        token::mint = reserve.load()?.liquidity.mint_pubkey,
+        token::authority = owner
+    )]
+    pub user_destination_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub liquidity_token_program: Interface<'info, TokenInterface>,
+}
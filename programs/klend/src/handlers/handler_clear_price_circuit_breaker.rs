@@ -0,0 +1,37 @@
+use anchor_lang::{prelude::*, Accounts};
+
+use crate::{
+    lending_market::lending_operations,
+    state::{LendingMarket, Reserve},
+    LendingError,
+};
+
+pub fn process(ctx: Context<ClearPriceCircuitBreaker>) -> Result<()> {
+    let reserve = &mut ctx.accounts.reserve.load_mut()?;
+
+    msg!(
+        "Clearing price circuit breaker of reserve {:?} {}",
+        ctx.accounts.reserve.key(),
+        reserve.config.token_info.symbol(),
+    );
+
+    lending_operations::clear_price_circuit_breaker(reserve);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ClearPriceCircuitBreaker<'info> {
+    pub signer: Signer<'info>,
+
+    #[account(
+        constraint = lending_market.load()?.lending_market_owner == signer.key()
+            || lending_market.load()?.risk_council == signer.key() @ LendingError::InvalidMarketAuthority
+    )]
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+
+    #[account(mut,
+        has_one = lending_market
+    )]
+    pub reserve: AccountLoader<'info, Reserve>,
+}
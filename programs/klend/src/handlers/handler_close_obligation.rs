@@ -0,0 +1,66 @@
+use anchor_lang::{prelude::*, Accounts};
+use farms::state::UserState as FarmsUserState;
+
+use crate::{
+    lending_market::lending_checks,
+    state::{
+        obligation::{Obligation, ObligationAccount},
+        LendingMarket,
+    },
+    utils::{close_account_loader, FatAccountLoader},
+    LendingError,
+};
+
+pub fn process(ctx: Context<CloseObligation>) -> Result<()> {
+    let obligation_key = ctx.accounts.obligation.key();
+    let lending_market = {
+        let obligation = ObligationAccount::load(ctx.accounts.obligation.as_ref())?;
+        require!(
+            obligation.deposits_empty() && obligation.borrows_empty(),
+            LendingError::ObligationNotEmpty
+        );
+        obligation.lending_market
+    };
+
+    // The farm user states are derived from the farms of the reserves the obligation used, every
+    // farm of a passed reserve has to come with its farm user state
+    let farm_user_states = lending_checks::get_obligation_farm_user_states(
+        &obligation_key,
+        &lending_market,
+        ctx.remaining_accounts,
+    )?;
+
+    for account_info in farm_user_states.initialized {
+        let obligation_farm_user_state =
+            FatAccountLoader::<FarmsUserState>::try_from(account_info)?;
+        let obligation_farm_user_state = obligation_farm_user_state.load()?;
+        require_keys_eq!(
+            obligation_farm_user_state.delegatee,
+            obligation_key,
+            LendingError::InvalidAccountInput
+        );
+        if obligation_farm_user_state.active_stake_scaled != 0 {
+            msg!(
+                "Obligation farm {} still has stake, refresh the obligation farms first",
+                obligation_farm_user_state.farm_state
+            );
+            return err!(LendingError::ObligationFarmStakeNotZero);
+        }
+    }
+
+    close_account_loader(true, &ctx.accounts.owner, &ctx.accounts.obligation)
+}
+
+#[derive(Accounts)]
+pub struct CloseObligation<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut,
+        has_one = lending_market,
+        has_one = owner @ LendingError::InvalidObligationOwner
+    )]
+    pub obligation: AccountLoader<'info, Obligation>,
+
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+}
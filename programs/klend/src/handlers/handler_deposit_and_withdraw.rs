@@ -5,9 +5,9 @@ use crate::{
     handler_refresh_obligation,
     handler_refresh_obligation_farms_for_reserve::*,
     handler_withdraw_obligation_collateral_and_redeem_reserve_collateral::{self, *},
-    lending_market::lending_operations,
+    lending_market::{lending_checks, lending_operations},
     refresh_farms, LendingError, LtvMaxWithdrawalCheck, MaxReservesAsCollateralCheck,
-    RefreshObligation, RefreshObligationBumps, ReserveFarmKind,
+    ObligationAccountMut, RefreshObligation, RefreshObligationBumps, ReserveFarmKind,
 };
 
 pub fn process(
@@ -15,6 +15,18 @@ pub fn process(
     liquidity_amount: u64,
     withdraw_collateral_amount: u64,
 ) -> Result<()> {
+    let (remaining_accounts, owner_withdrawal_caps) = lending_checks::split_owner_withdrawal_caps(
+        ctx.accounts.withdraw_accounts.withdraw_reserve.key(),
+        ctx.accounts.withdraw_accounts.obligation.load()?.owner,
+        ctx.remaining_accounts,
+    )?;
+    let (remaining_accounts, withdraw_queue) = lending_checks::split_withdraw_queue(
+        ctx.accounts.deposit_accounts.reserve.key(),
+        remaining_accounts,
+    )?;
+    let is_same_reserve = ctx.accounts.deposit_accounts.reserve.key()
+        == ctx.accounts.withdraw_accounts.withdraw_reserve.key();
+
     let initial_ltv = {
         let obligation = ctx.accounts.deposit_accounts.obligation.load()?;
         obligation.loan_to_value()
@@ -25,6 +37,8 @@ pub fn process(
             &ctx.accounts.deposit_accounts,
             liquidity_amount,
             MaxReservesAsCollateralCheck::Skip,
+            withdraw_queue,
+            owner_withdrawal_caps.as_ref().filter(|_| is_same_reserve),
         )?;
     }
 
@@ -50,7 +64,7 @@ pub fn process(
                 obligation: ctx.accounts.deposit_accounts.obligation.clone(),
                 lending_market: ctx.accounts.deposit_accounts.lending_market.clone(),
             },
-            remaining_accounts: ctx.remaining_accounts,
+            remaining_accounts,
             bumps: RefreshObligationBumps {},
         };
 
@@ -65,6 +79,7 @@ pub fn process(
             &ctx.accounts.withdraw_accounts,
             withdraw_collateral_amount,
             LtvMaxWithdrawalCheck::LiquidationThreshold,
+            owner_withdrawal_caps.as_ref(),
         )?
     };
 
@@ -85,8 +100,10 @@ pub fn process(
 
     if !is_obligation_closed {
         let is_full_withdrawal = {
-            let obligation = ctx.accounts.withdraw_accounts.obligation.load()?;
-            let final_deposit_amount = obligation
+            let mut obligation_account =
+                ObligationAccountMut::load(ctx.accounts.withdraw_accounts.obligation.as_ref())?;
+            let final_deposit_amount = obligation_account
+                .get_mut()
                 .find_collateral_in_deposits(ctx.accounts.withdraw_accounts.withdraw_reserve.key())
                 .map_or(0, |collateral| collateral.deposited_amount);
             final_deposit_amount == 0
@@ -94,7 +111,7 @@ pub fn process(
 
         let remaining_accounts: Vec<AccountInfo> = if is_full_withdrawal {
             let mut withdraw_reserve_found = false;
-            ctx.remaining_accounts
+            remaining_accounts
                 .iter()
                 .filter_map(|account| {
                     if account.key() == ctx.accounts.withdraw_accounts.withdraw_reserve.key()
@@ -108,7 +125,7 @@ pub fn process(
                 })
                 .collect()
         } else {
-            ctx.remaining_accounts.to_vec()
+            remaining_accounts.to_vec()
         };
 
         let refresh_obligation_ctx = Context {
@@ -126,7 +143,9 @@ pub fn process(
             MaxReservesAsCollateralCheck::Perform,
         )?;
 
-        let mut obligation = ctx.accounts.withdraw_accounts.obligation.load_mut()?;
+        let mut obligation_account =
+            ObligationAccountMut::load(ctx.accounts.withdraw_accounts.obligation.as_ref())?;
+        let obligation = &mut obligation_account.get_mut();
         obligation.last_update.mark_stale();
 
         let mut withdraw_reserve = ctx.accounts.withdraw_accounts.withdraw_reserve.load_mut()?;
@@ -134,7 +153,7 @@ pub fn process(
         let lending_market = ctx.accounts.withdraw_accounts.lending_market.load()?;
 
         lending_operations::utils::post_deposit_and_withdraw_obligation_enforcements(
-            &obligation,
+            obligation,
             &withdraw_reserve,
             &lending_market,
             initial_ltv,
@@ -8,12 +8,16 @@ use anchor_spl::{token::Token, token_interface::TokenAccount};
 use super::OptionalObligationFarmsAccounts;
 use crate::{
     check_refresh_ixs,
+    events::DepositEvent,
     handler_refresh_obligation_farms_for_reserve::*,
     lending_market::{lending_checks, lending_operations},
     refresh_farms,
-    state::{obligation::Obligation, DepositObligationCollateralAccounts, LendingMarket, Reserve},
+    state::{
+        obligation::{Obligation, ObligationAccountMut},
+        DepositObligationCollateralAccounts, LendingMarket, Reserve,
+    },
     utils::{seeds, token_transfer},
-    MaxReservesAsCollateralCheck, ReserveFarmKind,
+    LendingError, MaxReservesAsCollateralCheck, ObligationDelegatePermissions, ReserveFarmKind,
 };
 
 pub fn process_v1(ctx: Context<DepositObligationCollateral>, collateral_amount: u64) -> Result<()> {
@@ -22,14 +26,18 @@ pub fn process_v1(ctx: Context<DepositObligationCollateral>, collateral_amount:
         ctx.accounts.deposit_reserve,
         ReserveFarmKind::Collateral
     );
-    process_impl(ctx.accounts, collateral_amount)
+    process_impl(ctx.accounts, ctx.remaining_accounts, collateral_amount)
 }
 
 pub fn process_v2(
     ctx: Context<DepositObligationCollateralV2>,
     collateral_amount: u64,
 ) -> Result<()> {
-    process_impl(&ctx.accounts.deposit_accounts, collateral_amount)?;
+    process_impl(
+        &ctx.accounts.deposit_accounts,
+        ctx.remaining_accounts,
+        collateral_amount,
+    )?;
     refresh_farms!(
         ctx.accounts.deposit_accounts,
         ctx.accounts.lending_market_authority,
@@ -42,7 +50,11 @@ pub fn process_v2(
     Ok(())
 }
 
-fn process_impl(accounts: &DepositObligationCollateral, collateral_amount: u64) -> Result<()> {
+fn process_impl(
+    accounts: &DepositObligationCollateral,
+    remaining_accounts: &[AccountInfo],
+    collateral_amount: u64,
+) -> Result<()> {
     lending_checks::deposit_obligation_collateral_checks(&DepositObligationCollateralAccounts {
         obligation: accounts.obligation.clone(),
         deposit_reserve: accounts.deposit_reserve.clone(),
@@ -56,7 +68,18 @@ fn process_impl(accounts: &DepositObligationCollateral, collateral_amount: u64)
 
     let lending_market = &accounts.lending_market.load()?;
     let deposit_reserve = &mut accounts.deposit_reserve.load_mut()?;
-    let obligation = &mut accounts.obligation.load_mut()?;
+    let mut obligation_account = ObligationAccountMut::load(accounts.obligation.as_ref())?;
+    let obligation = &mut obligation_account.get_mut();
+
+    let (_, owner_withdrawal_caps) = lending_checks::split_owner_withdrawal_caps(
+        accounts.deposit_reserve.key(),
+        obligation.owner,
+        remaining_accounts,
+    )?;
+    let mut owner_withdrawal_caps = owner_withdrawal_caps
+        .as_ref()
+        .map(|caps| caps.load_mut())
+        .transpose()?;
 
     lending_operations::refresh_reserve(
         deposit_reserve,
@@ -69,10 +92,11 @@ fn process_impl(accounts: &DepositObligationCollateral, collateral_amount: u64)
         lending_market,
         deposit_reserve,
         obligation,
-        clock.slot,
+        &clock,
         collateral_amount,
         accounts.deposit_reserve.key(),
         MaxReservesAsCollateralCheck::Perform,
+        owner_withdrawal_caps.as_deref_mut(),
     )?;
 
     msg!(
@@ -88,6 +112,17 @@ fn process_impl(accounts: &DepositObligationCollateral, collateral_amount: u64)
         collateral_amount,
     )?;
 
+    emit!(DepositEvent {
+        lending_market: accounts.lending_market.key(),
+        reserve: accounts.deposit_reserve.key(),
+        obligation: accounts.obligation.key(),
+        owner: accounts.owner.key(),
+        liquidity_amount: deposit_reserve
+            .collateral_exchange_rate()
+            .collateral_to_liquidity(collateral_amount),
+        collateral_amount,
+    });
+
     Ok(())
 }
 
@@ -96,8 +131,8 @@ pub struct DepositObligationCollateral<'info> {
     pub owner: Signer<'info>,
 
     #[account(mut,
-        has_one = owner,
         has_one = lending_market,
+        constraint = obligation.load()?.is_authorized(owner.key(), ObligationDelegatePermissions::DEPOSIT) @ LendingError::InvalidObligationOwner
     )]
     pub obligation: AccountLoader<'info, Obligation>,
 
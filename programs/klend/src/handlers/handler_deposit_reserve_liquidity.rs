@@ -10,6 +10,7 @@ use anchor_spl::{
 use lending_operations::refresh_reserve;
 
 use crate::{
+    events::DepositEvent,
     gen_signer_seeds,
     lending_market::{lending_checks, lending_operations},
     state::{LendingMarket, Reserve},
@@ -38,6 +39,8 @@ pub fn process(ctx: Context<DepositReserveLiquidity>, liquidity_amount: u64) ->
     let lending_market = &ctx.accounts.lending_market.load()?;
 
     let lending_market_key = ctx.accounts.lending_market.key();
+    let (_, withdraw_queue) =
+        lending_checks::split_withdraw_queue(ctx.accounts.reserve.key(), ctx.remaining_accounts)?;
     let authority_signer_seeds =
         gen_signer_seeds!(lending_market_key.as_ref(), lending_market.bump_seed as u8);
 
@@ -83,6 +86,25 @@ pub fn process(ctx: Context<DepositReserveLiquidity>, liquidity_amount: u64) ->
         LendingAction::Additive(liquidity_amount),
     )?;
 
+    if let Some(withdraw_queue) = withdraw_queue {
+        lending_operations::fill_withdraw_queue(
+            reserve,
+            &mut *withdraw_queue.load_mut()?,
+            u64::try_from(clock.unix_timestamp).unwrap(),
+        )?;
+    } else {
+        lending_checks::check_withdraw_queue_not_pending(reserve)?;
+    }
+
+    emit!(DepositEvent {
+        lending_market: lending_market_key,
+        reserve: ctx.accounts.reserve.key(),
+        obligation: Pubkey::default(),
+        owner: ctx.accounts.owner.key(),
+        liquidity_amount,
+        collateral_amount,
+    });
+
     Ok(())
 }
 
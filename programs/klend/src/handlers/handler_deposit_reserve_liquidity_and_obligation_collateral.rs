@@ -9,13 +9,20 @@ use anchor_spl::{
 };
 
 use crate::{
-    check_refresh_ixs, gen_signer_seeds,
+    check_refresh_ixs,
+    events::DepositEvent,
+    gen_signer_seeds,
     handler_refresh_obligation_farms_for_reserve::*,
     lending_market::{lending_checks, lending_operations},
     refresh_farms,
-    state::{nested_accounts::*, obligation::Obligation, LendingMarket, Reserve},
-    utils::{seeds, token_transfer},
-    DepositLiquidityResult, LendingAction, MaxReservesAsCollateralCheck, ReserveFarmKind,
+    state::{
+        nested_accounts::*,
+        obligation::{Obligation, ObligationAccountMut},
+        LendingMarket, OwnerWithdrawalCaps, Reserve, WithdrawQueue,
+    },
+    utils::{seeds, token_transfer, FatAccountLoader},
+    DepositLiquidityResult, LendingAction, LendingError, MaxReservesAsCollateralCheck,
+    ObligationDelegatePermissions, ReserveFarmKind,
 };
 
 pub fn process_v1(
@@ -27,10 +34,19 @@ pub fn process_v1(
         ctx.accounts.reserve,
         ReserveFarmKind::Collateral
     );
+    let (remaining_accounts, owner_withdrawal_caps) = lending_checks::split_owner_withdrawal_caps(
+        ctx.accounts.reserve.key(),
+        ctx.accounts.obligation.load()?.owner,
+        ctx.remaining_accounts,
+    )?;
+    let (_, withdraw_queue) =
+        lending_checks::split_withdraw_queue(ctx.accounts.reserve.key(), remaining_accounts)?;
     process_impl(
         ctx.accounts,
         liquidity_amount,
         MaxReservesAsCollateralCheck::Perform,
+        withdraw_queue,
+        owner_withdrawal_caps.as_ref(),
     )
 }
 
@@ -38,10 +54,21 @@ pub fn process_v2(
     ctx: Context<DepositReserveLiquidityAndObligationCollateralV2>,
     liquidity_amount: u64,
 ) -> Result<()> {
+    let (remaining_accounts, owner_withdrawal_caps) = lending_checks::split_owner_withdrawal_caps(
+        ctx.accounts.deposit_accounts.reserve.key(),
+        ctx.accounts.deposit_accounts.obligation.load()?.owner,
+        ctx.remaining_accounts,
+    )?;
+    let (_, withdraw_queue) = lending_checks::split_withdraw_queue(
+        ctx.accounts.deposit_accounts.reserve.key(),
+        remaining_accounts,
+    )?;
     process_impl(
         &ctx.accounts.deposit_accounts,
         liquidity_amount,
         MaxReservesAsCollateralCheck::Perform,
+        withdraw_queue,
+        owner_withdrawal_caps.as_ref(),
     )?;
 
     refresh_farms!(
@@ -60,6 +87,8 @@ pub(super) fn process_impl(
     accounts: &DepositReserveLiquidityAndObligationCollateral,
     liquidity_amount: u64,
     max_reserves_as_collateral_check: MaxReservesAsCollateralCheck,
+    withdraw_queue: Option<FatAccountLoader<WithdrawQueue>>,
+    owner_withdrawal_caps: Option<&FatAccountLoader<OwnerWithdrawalCaps>>,
 ) -> Result<()> {
     msg!(
         "DepositReserveLiquidityAndObligationCollateral Reserve {} amount {}",
@@ -76,7 +105,11 @@ pub(super) fn process_impl(
     )?;
 
     let reserve = &mut accounts.reserve.load_mut()?;
-    let obligation = &mut accounts.obligation.load_mut()?;
+    let mut obligation_account = ObligationAccountMut::load(accounts.obligation.as_ref())?;
+    let obligation = &mut obligation_account.get_mut();
+    let mut owner_withdrawal_caps = owner_withdrawal_caps
+        .map(|caps| caps.load_mut())
+        .transpose()?;
 
     let lending_market = &accounts.lending_market.load()?;
     let lending_market_key = accounts.lending_market.key();
@@ -99,10 +132,11 @@ pub(super) fn process_impl(
         lending_market,
         reserve,
         obligation,
-        clock.slot,
+        &clock,
         collateral_amount,
         accounts.reserve.key(),
         max_reserves_as_collateral_check,
+        owner_withdrawal_caps.as_deref_mut(),
     )?;
 
     msg!(
@@ -138,6 +172,25 @@ pub(super) fn process_impl(
         LendingAction::Additive(liquidity_amount),
     )?;
 
+    if let Some(withdraw_queue) = withdraw_queue {
+        lending_operations::fill_withdraw_queue(
+            reserve,
+            &mut *withdraw_queue.load_mut()?,
+            u64::try_from(clock.unix_timestamp).unwrap(),
+        )?;
+    } else {
+        lending_checks::check_withdraw_queue_not_pending(reserve)?;
+    }
+
+    emit!(DepositEvent {
+        lending_market: lending_market_key,
+        reserve: accounts.reserve.key(),
+        obligation: accounts.obligation.key(),
+        owner: accounts.owner.key(),
+        liquidity_amount,
+        collateral_amount,
+    });
+
     Ok(())
 }
 
@@ -148,7 +201,7 @@ pub struct DepositReserveLiquidityAndObligationCollateral<'info> {
 
     #[account(mut,
         has_one = lending_market,
-        has_one = owner
+        constraint = obligation.load()?.is_authorized(owner.key(), ObligationDelegatePermissions::DEPOSIT) @ LendingError::InvalidObligationOwner
     )]
     pub obligation: AccountLoader<'info, Obligation>,
 
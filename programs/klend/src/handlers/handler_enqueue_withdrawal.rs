@@ -0,0 +1,74 @@
+use anchor_lang::{prelude::*, Accounts};
+use anchor_spl::{
+    token::Token,
+    token_interface::{Mint, TokenAccount},
+};
+
+use crate::{
+    lending_market::lending_operations,
+    state::{LendingMarket, Reserve, WithdrawQueue},
+    utils::token_transfer,
+};
+
+pub fn process(ctx: Context<EnqueueWithdrawal>, collateral_amount: u64) -> Result<()> {
+    let reserve = &mut ctx.accounts.reserve.load_mut()?;
+    let withdraw_queue = &mut ctx.accounts.withdraw_queue.load_mut()?;
+    let lending_market = &ctx.accounts.lending_market.load()?;
+    let clock = Clock::get()?;
+
+    lending_operations::refresh_reserve(reserve, &clock, None, lending_market.referral_fee_bps)?;
+    let ticket_id = lending_operations::enqueue_withdrawal(
+        lending_market,
+        reserve,
+        withdraw_queue,
+        ctx.accounts.owner.key(),
+        collateral_amount,
+        &clock,
+    )?;
+
+    msg!(
+        "pnl: Enqueued withdrawal ticket {} for collateral {}",
+        ticket_id,
+        collateral_amount
+    );
+
+    token_transfer::enqueue_withdrawal_transfer(
+        ctx.accounts.collateral_token_program.to_account_info(),
+        ctx.accounts.reserve_collateral_mint.to_account_info(),
+        ctx.accounts.user_source_collateral.to_account_info(),
+        ctx.accounts.owner.to_account_info(),
+        collateral_amount,
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct EnqueueWithdrawal<'info> {
+    pub owner: Signer<'info>,
+
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+
+    #[account(mut,
+        has_one = lending_market
+    )]
+    pub reserve: AccountLoader<'info, Reserve>,
+
+    #[account(mut,
+        has_one = reserve
+    )]
+    pub withdraw_queue: AccountLoader<'info, WithdrawQueue>,
+
+    #[account(mut,
+        address = reserve.load()?.collateral.mint_pubkey,
+    )]
+    pub reserve_collateral_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut,
+        token::mint = reserve_collateral_mint,
+        token::authority = owner
+    )]
+    pub user_source_collateral: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub collateral_token_program: Program<'info, Token>,
+}
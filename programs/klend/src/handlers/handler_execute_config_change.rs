@@ -0,0 +1,128 @@
+use anchor_lang::{prelude::*, Accounts};
+
+use crate::{
+    events::{MarketConfigUpdated, ReserveConfigUpdated},
+    handlers::handler_update_lending_market::update_lending_market_config,
+    lending_market::lending_operations,
+    state::{
+        LendingMarket, PendingConfigChange, PendingConfigChangeKind, Reserve, UpdateConfigMode,
+        UpdateLendingMarketMode,
+    },
+    LendingError, VALUE_BYTE_MAX_ARRAY_LEN_MARKET_UPDATE,
+};
+
+pub fn process(ctx: Context<ExecuteConfigChange>) -> Result<()> {
+    let pending_config_change = ctx.accounts.pending_config_change.load()?;
+    let clock = Clock::get()?;
+    let timestamp = u64::try_from(clock.unix_timestamp).unwrap();
+
+    if !pending_config_change.is_executable(timestamp) {
+        msg!(
+            "Config change is executable at {}, current timestamp {}",
+            pending_config_change.execution_timestamp,
+            timestamp
+        );
+        return err!(LendingError::ConfigChangeNotExecutable);
+    }
+    if pending_config_change.is_expired(timestamp) {
+        msg!(
+            "Config change expired at {}, current timestamp {}",
+            pending_config_change.expiration_timestamp,
+            timestamp
+        );
+        return err!(LendingError::ConfigChangeExpired);
+    }
+
+    match pending_config_change.kind()? {
+        PendingConfigChangeKind::Reserve => {
+            let mode = UpdateConfigMode::try_from(pending_config_change.mode)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            let reserve_loader = ctx
+                .accounts
+                .reserve
+                .as_ref()
+                .ok_or_else(|| error!(LendingError::InvalidAccountInput))?;
+            require_keys_eq!(
+                reserve_loader.key(),
+                pending_config_change.target,
+                LendingError::InvalidAccountInput
+            );
+
+            let reserve = &mut reserve_loader.load_mut()?;
+            let market = ctx.accounts.lending_market.load()?;
+            msg!(
+                "Executing reserve {} {} config change with mode {:?}",
+                reserve_loader.key(),
+                reserve.config.token_info.symbol(),
+                mode
+            );
+
+            lending_operations::refresh_reserve(reserve, &clock, None, market.referral_fee_bps)?;
+
+            let previous_config = reserve.config;
+            lending_operations::update_reserve_config(
+                reserve,
+                mode,
+                pending_config_change.value(),
+                false,
+            )?;
+            lending_operations::utils::validate_reserve_config(
+                &reserve.config,
+                &market,
+                reserve_loader.key(),
+            )?;
+
+            emit!(ReserveConfigUpdated::new(
+                ctx.accounts.lending_market.key(),
+                reserve_loader.key(),
+                mode as u64,
+                bytemuck::bytes_of(&previous_config),
+                bytemuck::bytes_of(&reserve.config),
+            ));
+        }
+        PendingConfigChangeKind::LendingMarket => {
+            let mode = UpdateLendingMarketMode::try_from(pending_config_change.mode)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            let market = &mut ctx.accounts.lending_market.load_mut()?;
+            msg!(
+                "Executing lending market config change with mode {:?}",
+                mode
+            );
+
+            let previous_market = bytemuck::bytes_of(&**market).to_vec();
+
+            let pending_value = pending_config_change.value();
+            let mut value = [0; VALUE_BYTE_MAX_ARRAY_LEN_MARKET_UPDATE];
+            value[..pending_value.len()].copy_from_slice(pending_value);
+            update_lending_market_config(market, mode, &value)?;
+
+            emit!(MarketConfigUpdated::new(
+                ctx.accounts.lending_market.key(),
+                mode as u64,
+                &previous_market,
+                bytemuck::bytes_of(&**market),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ExecuteConfigChange<'info> {
+    #[account(mut)]
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+
+    #[account(mut, has_one = lending_market)]
+    pub reserve: Option<AccountLoader<'info, Reserve>>,
+
+    #[account(mut,
+        has_one = lending_market,
+        has_one = payer,
+        close = payer
+    )]
+    pub pending_config_change: AccountLoader<'info, PendingConfigChange>,
+
+    #[account(mut)]
+    pub payer: AccountInfo<'info>,
+}
@@ -0,0 +1,56 @@
+use anchor_lang::{prelude::*, Accounts};
+
+use crate::{
+    state::{
+        obligation::{Obligation, ObligationExtension, ObligationLayout},
+        LendingMarket,
+    },
+    utils::consts::{OBLIGATION_EXTENSION_SIZE, OBLIGATION_SIZE},
+    LendingError,
+};
+
+pub fn process(ctx: Context<ExtendObligation>) -> Result<()> {
+    {
+        let obligation = &mut ctx.accounts.obligation.load_mut()?;
+
+        require!(
+            !obligation.is_extended(),
+            LendingError::ObligationAlreadyExtended
+        );
+
+        obligation.layout_version = ObligationLayout::Extended.into();
+    }
+
+    let obligation_info = ctx.accounts.obligation.to_account_info();
+    let mut data = obligation_info.try_borrow_mut_data()?;
+    let extension_start = 8 + OBLIGATION_SIZE;
+    let extension: &mut ObligationExtension = bytemuck::from_bytes_mut(
+        &mut data[extension_start..extension_start + OBLIGATION_EXTENSION_SIZE],
+    );
+    *extension = ObligationExtension::default();
+
+    msg!("Obligation {} extended", ctx.accounts.obligation.key());
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ExtendObligation<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+
+    #[account(mut,
+        has_one = lending_market,
+        has_one = owner @ LendingError::InvalidObligationOwner,
+        realloc = 8 + OBLIGATION_SIZE + OBLIGATION_EXTENSION_SIZE,
+        realloc::payer = fee_payer,
+        realloc::zero = false,
+    )]
+    pub obligation: AccountLoader<'info, Obligation>,
+
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+
+    pub system_program: Program<'info, System>,
+}
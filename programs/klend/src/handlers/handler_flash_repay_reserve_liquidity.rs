@@ -3,6 +3,7 @@ use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface};
 use lending_checks::validate_referrer_token_state;
 
 use crate::{
+    events::FlashLoanEvent,
     lending_market::{flash_ixs, lending_checks, lending_operations},
     state::{LendingMarket, Reserve},
     utils::{seeds, token_transfer},
@@ -97,6 +98,15 @@ pub fn process(
         LendingAction::Additive(flash_loan_amount_with_referrer_fee),
     )?;
 
+    emit!(FlashLoanEvent {
+        lending_market: ctx.accounts.lending_market.key(),
+        reserve: ctx.accounts.reserve.key(),
+        user: ctx.accounts.user_transfer_authority.key(),
+        liquidity_amount,
+        protocol_fee: reserve_origination_fee,
+        referrer_fee: flash_loan_amount_with_referrer_fee - liquidity_amount,
+    });
+
     Ok(())
 }
 
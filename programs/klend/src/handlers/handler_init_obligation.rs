@@ -2,6 +2,7 @@ use anchor_lang::{prelude::*, Accounts};
 use anchor_spl::token_interface::Mint;
 
 use crate::{
+    lending_market::lending_checks,
     state::{
         obligation::{Obligation, ObligationCollateral, ObligationLiquidity},
         LendingMarket,
@@ -20,6 +21,17 @@ pub fn process(ctx: Context<InitObligation>, args: InitObligationArgs) -> Result
     )
     .unwrap();
 
+    let lending_market = &ctx.accounts.lending_market.load()?;
+    let (_, obligation_permission) =
+        lending_checks::split_obligation_permission(lending_market, ctx.remaining_accounts);
+    lending_checks::validate_obligation_permission(
+        &crate::ID,
+        lending_market,
+        ctx.accounts.lending_market.key(),
+        ctx.accounts.obligation_owner.key(),
+        obligation_permission,
+    )?;
+
     let obligation = &mut ctx.accounts.obligation.load_init()?;
     let owner_user_metadata = &ctx.accounts.owner_user_metadata.load()?;
 
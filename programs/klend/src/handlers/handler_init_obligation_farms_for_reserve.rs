@@ -28,8 +28,20 @@ pub fn process(ctx: Context<InitObligationFarmsForReserve>, mode: u8) -> Result<
         LendingError::InvalidAccountInput
     );
 
+    require_keys_eq!(
+        ctx.accounts.obligation_farm.key(),
+        seeds::pda::obligation_farm_user_state(farm, ctx.accounts.obligation.key()).0,
+        LendingError::InvalidAccountInput
+    );
+
     farms_ixs::cpi_initialize_farmer_delegated(&ctx, obligation, farm)?;
 
+    let obligation = &mut ctx.accounts.obligation.load_mut()?;
+    obligation.num_of_farm_user_states = obligation
+        .num_of_farm_user_states
+        .checked_add(1)
+        .ok_or(LendingError::IntegerOverflow)?;
+
     Ok(())
 }
 
@@ -0,0 +1,53 @@
+use anchor_lang::{prelude::*, Accounts};
+
+use crate::{
+    state::{LendingMarket, ObligationPermission},
+    utils::{seeds::BASE_SEED_OBLIGATION_PERMISSION, OBLIGATION_PERMISSION_SIZE},
+    LendingError,
+};
+
+pub fn process(ctx: Context<InitObligationPermission>) -> Result<()> {
+    let mut obligation_permission = ctx.accounts.obligation_permission.load_init()?;
+
+    *obligation_permission = ObligationPermission {
+        lending_market: ctx.accounts.lending_market.key(),
+        owner: ctx.accounts.owner.key(),
+        authority: ctx.accounts.authority.key(),
+        issued_slot: Clock::get()?.slot,
+        bump: ctx.bumps.obligation_permission.into(),
+        ..Default::default()
+    };
+
+    msg!(
+        "Obligation permission issued to {} by {}",
+        obligation_permission.owner,
+        obligation_permission.authority
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitObligationPermission<'info> {
+    #[account(
+        constraint = lending_market.load()?.is_owner_or_risk_council(authority.key) @ LendingError::InvalidSigner
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+
+    pub owner: AccountInfo<'info>,
+
+    #[account(init,
+        seeds = [BASE_SEED_OBLIGATION_PERMISSION, lending_market.key().as_ref(), owner.key().as_ref()],
+        bump,
+        payer = payer,
+        space = OBLIGATION_PERMISSION_SIZE + 8,
+    )]
+    pub obligation_permission: AccountLoader<'info, ObligationPermission>,
+
+    pub system_program: Program<'info, System>,
+}
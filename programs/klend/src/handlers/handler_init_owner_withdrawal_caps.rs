@@ -0,0 +1,49 @@
+use anchor_lang::{prelude::*, Accounts};
+
+use crate::{
+    state::{LendingMarket, OwnerWithdrawalCaps, Reserve},
+    utils::{seeds::BASE_SEED_OWNER_WITHDRAWAL_CAPS, OWNER_WITHDRAWAL_CAPS_SIZE},
+};
+
+pub fn process(ctx: Context<InitOwnerWithdrawalCaps>) -> Result<()> {
+    let mut owner_withdrawal_caps = ctx.accounts.owner_withdrawal_caps.load_init()?;
+
+    *owner_withdrawal_caps = OwnerWithdrawalCaps {
+        lending_market: ctx.accounts.lending_market.key(),
+        reserve: ctx.accounts.reserve.key(),
+        owner: ctx.accounts.owner.key(),
+        bump: ctx.bumps.owner_withdrawal_caps.into(),
+        ..Default::default()
+    };
+
+    msg!(
+        "Owner withdrawal caps initialized for {} on reserve {}",
+        owner_withdrawal_caps.owner,
+        owner_withdrawal_caps.reserve
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitOwnerWithdrawalCaps<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub owner: AccountInfo<'info>,
+
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+
+    #[account(has_one = lending_market)]
+    pub reserve: AccountLoader<'info, Reserve>,
+
+    #[account(init,
+        seeds = [BASE_SEED_OWNER_WITHDRAWAL_CAPS, reserve.key().as_ref(), owner.key().as_ref()],
+        bump,
+        payer = payer,
+        space = OWNER_WITHDRAWAL_CAPS_SIZE + 8,
+    )]
+    pub owner_withdrawal_caps: AccountLoader<'info, OwnerWithdrawalCaps>,
+
+    pub system_program: Program<'info, System>,
+}
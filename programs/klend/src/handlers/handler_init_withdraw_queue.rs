@@ -0,0 +1,46 @@
+use anchor_lang::{prelude::*, Accounts};
+
+use crate::{
+    state::{LendingMarket, Reserve, WithdrawQueue},
+    utils::{seeds::BASE_SEED_WITHDRAW_QUEUE, WITHDRAW_QUEUE_SIZE},
+};
+
+pub fn process(ctx: Context<InitWithdrawQueue>) -> Result<()> {
+    let mut withdraw_queue = ctx.accounts.withdraw_queue.load_init()?;
+
+    *withdraw_queue = WithdrawQueue {
+        lending_market: ctx.accounts.lending_market.key(),
+        reserve: ctx.accounts.reserve.key(),
+        bump: ctx.bumps.withdraw_queue.into(),
+        ..Default::default()
+    };
+
+    msg!(
+        "Withdraw queue initialized for reserve {}",
+        withdraw_queue.reserve
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitWithdrawQueue<'info> {
+    #[account(mut)]
+    pub lending_market_owner: Signer<'info>,
+
+    #[account(has_one = lending_market_owner)]
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+
+    #[account(has_one = lending_market)]
+    pub reserve: AccountLoader<'info, Reserve>,
+
+    #[account(init,
+        seeds = [BASE_SEED_WITHDRAW_QUEUE, reserve.key().as_ref()],
+        bump,
+        payer = lending_market_owner,
+        space = WITHDRAW_QUEUE_SIZE + 8,
+    )]
+    pub withdraw_queue: AccountLoader<'info, WithdrawQueue>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -11,11 +11,17 @@ use anchor_spl::{
 };
 
 use crate::{
-    check_refresh_ixs, gen_signer_seeds,
+    check_refresh_ixs,
+    events::LiquidationEvent,
+    fraction::FractionExtra,
+    gen_signer_seeds,
     handler_refresh_obligation_farms_for_reserve::*,
     lending_market::{lending_checks, lending_operations},
     refresh_farms,
-    state::{obligation::Obligation, LendingMarket, RedeemReserveCollateralAccounts, Reserve},
+    state::{
+        obligation::{Obligation, ObligationAccountMut},
+        LendingMarket, RedeemReserveCollateralAccounts, Reserve,
+    },
     utils::{seeds, token_transfer, FatAccountLoader},
     xmsg, LendingAction, LiquidateAndRedeemResult, ReserveFarmKind,
 };
@@ -102,7 +108,8 @@ fn process_impl(
     })?;
 
     let lending_market = &accounts.lending_market.load()?;
-    let obligation = &mut accounts.obligation.load_mut()?;
+    let mut obligation_account = ObligationAccountMut::load(accounts.obligation.as_ref())?;
+    let obligation = &mut obligation_account.get_mut();
     let lending_market_key = accounts.lending_market.key();
     let clock = &Clock::get()?;
 
@@ -138,7 +145,7 @@ fn process_impl(
         withdraw_collateral_amount,
         withdraw_amount,
         total_withdraw_liquidity_amount,
-        ..
+        liquidation_bonus_rate,
     } = lending_operations::liquidate_and_redeem(
         lending_market,
         &accounts.repay_reserve,
@@ -255,6 +262,21 @@ fn process_impl(
 
     lending_checks::post_liquidate_repay_amount_check(liquidity_amount, repay_amount)?;
 
+    let (withdraw_liquidity_amount, protocol_fee) =
+        total_withdraw_liquidity_amount.unwrap_or_default();
+    emit!(LiquidationEvent {
+        lending_market: lending_market_key,
+        obligation: accounts.obligation.key(),
+        liquidator: accounts.liquidator.key(),
+        repay_reserve: accounts.repay_reserve.key(),
+        withdraw_reserve: accounts.withdraw_reserve.key(),
+        repay_amount,
+        withdraw_collateral_amount,
+        withdraw_liquidity_amount,
+        liquidation_bonus_bps: liquidation_bonus_rate.to_bps().unwrap(),
+        protocol_fee,
+    });
+
     Ok(())
 }
 
@@ -0,0 +1,48 @@
+use anchor_lang::{prelude::*, Accounts};
+
+use crate::{
+    lending_market::lending_checks,
+    state::{
+        obligation::{Obligation, ObligationAccount},
+        LendingMarket,
+    },
+    LendingError,
+};
+
+pub fn process(ctx: Context<ProposeObligationOwner>, new_owner: Pubkey) -> Result<()> {
+    lending_checks::check_obligation_farm_user_states_not_initialized(
+        &ctx.accounts.obligation.key(),
+        &ObligationAccount::load(ctx.accounts.obligation.as_ref())?,
+        ctx.remaining_accounts,
+    )?;
+
+    let obligation = &mut ctx.accounts.obligation.load_mut()?;
+
+    require_keys_neq!(
+        new_owner,
+        obligation.owner,
+        LendingError::InvalidAccountInput
+    );
+
+    msg!(
+        "Obligation owner proposal prv={} new={}",
+        obligation.owner_cached,
+        new_owner
+    );
+    obligation.owner_cached = new_owner;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ProposeObligationOwner<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(mut,
+        has_one = lending_market,
+        has_one = owner @ LendingError::InvalidObligationOwner
+    )]
+    pub obligation: AccountLoader<'info, Obligation>,
+
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+}
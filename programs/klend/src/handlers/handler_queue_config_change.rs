@@ -0,0 +1,89 @@
+use anchor_lang::{prelude::*, Accounts};
+
+use crate::{
+    state::{
+        LendingMarket, PendingConfigChange, PendingConfigChangeKind, Reserve, UpdateConfigMode,
+        UpdateLendingMarketMode,
+    },
+    utils::{
+        seeds::BASE_SEED_PENDING_CONFIG_CHANGE, CONFIG_CHANGE_EXPIRY_SECS,
+        PENDING_CONFIG_CHANGE_SIZE,
+    },
+    LendingError, VALUE_BYTE_MAX_ARRAY_LEN_MARKET_UPDATE,
+};
+
+pub fn process(ctx: Context<QueueConfigChange>, mode: u64, value: &[u8]) -> Result<()> {
+    let market = ctx.accounts.lending_market.load()?;
+    let timestamp = u64::try_from(Clock::get()?.unix_timestamp).unwrap();
+
+    let (kind, target) = match &ctx.accounts.reserve {
+        Some(reserve) => {
+            UpdateConfigMode::try_from(mode).map_err(|_| ProgramError::InvalidInstructionData)?;
+            (PendingConfigChangeKind::Reserve, reserve.key())
+        }
+        None => {
+            UpdateLendingMarketMode::try_from(mode)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            require_gte!(
+                VALUE_BYTE_MAX_ARRAY_LEN_MARKET_UPDATE,
+                value.len(),
+                LendingError::InvalidConfig
+            );
+            (
+                PendingConfigChangeKind::LendingMarket,
+                ctx.accounts.lending_market.key(),
+            )
+        }
+    };
+
+    let pending_config_change = &mut ctx.accounts.pending_config_change.load_init()?;
+    pending_config_change.lending_market = ctx.accounts.lending_market.key();
+    pending_config_change.target = target;
+    pending_config_change.payer = ctx.accounts.lending_market_owner.key();
+    pending_config_change.mode = mode;
+    pending_config_change.kind = kind as u8;
+    pending_config_change.queued_timestamp = timestamp;
+    pending_config_change.execution_timestamp = timestamp + market.config_timelock_secs;
+    pending_config_change.expiration_timestamp =
+        pending_config_change.execution_timestamp + CONFIG_CHANGE_EXPIRY_SECS;
+    pending_config_change.set_value(value)?;
+    pending_config_change.bump = ctx.bumps.pending_config_change.into();
+
+    msg!(
+        "Queued {:?} config change of {} with mode {} executable at {}",
+        kind,
+        target,
+        mode,
+        pending_config_change.execution_timestamp
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(mode: u64)]
+pub struct QueueConfigChange<'info> {
+    #[account(mut)]
+    pub lending_market_owner: Signer<'info>,
+
+    #[account(has_one = lending_market_owner)]
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+
+    #[account(has_one = lending_market)]
+    pub reserve: Option<AccountLoader<'info, Reserve>>,
+
+    #[account(init,
+        seeds = [
+            BASE_SEED_PENDING_CONFIG_CHANGE,
+            lending_market.key().as_ref(),
+            reserve.as_ref().map_or(lending_market.key(), |reserve| reserve.key()).as_ref(),
+            &mode.to_le_bytes(),
+        ],
+        bump,
+        payer = lending_market_owner,
+        space = PENDING_CONFIG_CHANGE_SIZE + 8,
+    )]
+    pub pending_config_change: AccountLoader<'info, PendingConfigChange>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -9,6 +9,7 @@ use anchor_spl::{
 };
 
 use crate::{
+    events::WithdrawEvent,
     gen_signer_seeds,
     lending_market::{lending_checks, lending_operations},
     state::{LendingMarket, RedeemReserveCollateralAccounts, Reserve},
@@ -78,6 +79,15 @@ pub fn process(ctx: Context<RedeemReserveCollateral>, collateral_amount: u64) ->
         LendingAction::Subtractive(withdraw_liquidity_amount),
     )?;
 
+    emit!(WithdrawEvent {
+        lending_market: lending_market_key,
+        reserve: ctx.accounts.reserve.key(),
+        obligation: Pubkey::default(),
+        owner: ctx.accounts.owner.key(),
+        liquidity_amount: withdraw_liquidity_amount,
+        collateral_amount,
+    });
+
     Ok(())
 }
 
@@ -2,7 +2,10 @@ use anchor_lang::{prelude::*, Accounts};
 
 use crate::{
     lending_market::lending_operations,
-    state::{obligation::Obligation, LendingMarket},
+    state::{
+        obligation::{Obligation, ObligationAccountMut},
+        LendingMarket,
+    },
     utils::FatAccountLoader,
     LendingError, MaxReservesAsCollateralCheck, ReferrerTokenState, Reserve,
 };
@@ -11,7 +14,8 @@ pub fn process(
     ctx: Context<RefreshObligation>,
     max_reserves_as_collateral_check: MaxReservesAsCollateralCheck,
 ) -> Result<()> {
-    let obligation = &mut ctx.accounts.obligation.load_mut()?;
+    let mut obligation_account = ObligationAccountMut::load(ctx.accounts.obligation.as_ref())?;
+    let obligation = &mut obligation_account.get_mut();
     let clock = &Clock::get()?;
     let lending_market = &ctx.accounts.lending_market.load()?;
     let borrow_count = obligation.borrows_count();
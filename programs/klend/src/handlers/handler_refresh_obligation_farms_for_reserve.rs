@@ -4,8 +4,8 @@ use farms::{program::Farms, state::UserState as FarmsUserState};
 use crate::{
     fraction::FractionExtra,
     lending_market::{farms_ixs, lending_checks},
-    state::{obligation::Obligation, LendingMarket},
-    utils::{constraints, seeds, FatAccountLoader, Fraction},
+    state::{obligation::ObligationAccount, LendingMarket},
+    utils::{constraints, seeds, Fraction},
     LendingError, Reserve, ReserveFarmKind,
 };
 
@@ -46,9 +46,7 @@ pub(crate) fn process_impl_refresh_obligation_farms_for_reserve(
     let amount = if account_ctx.obligation.data_is_empty() {
         0
     } else {
-        let obligation_account: FatAccountLoader<Obligation> =
-            FatAccountLoader::try_from(&account_ctx.obligation).unwrap();
-        let obligation = obligation_account.load()?;
+        let obligation = ObligationAccount::load(&account_ctx.obligation)?;
 
         amount_for_obligation(&obligation, &reserve_address, farm_kind)
     };
@@ -116,7 +114,7 @@ pub struct OptionalObligationFarmsAccounts<'info> {
 }
 
 fn amount_for_obligation(
-    obligation: &Obligation,
+    obligation: &ObligationAccount,
     reserve_address: &Pubkey,
     farm_kind: ReserveFarmKind,
 ) -> u64 {
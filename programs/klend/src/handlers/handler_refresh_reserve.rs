@@ -1,10 +1,15 @@
 use anchor_lang::{prelude::*, Accounts, Result};
 
 use crate::{
+    events::ReserveInterestSnapshot,
     fraction::FractionExtra,
     lending_market::lending_operations,
     state::Reserve,
-    utils::{constraints, prices::get_price, PROGRAM_VERSION},
+    utils::{
+        constraints,
+        prices::{get_derived_price, get_price},
+        PROGRAM_VERSION,
+    },
     LendingError, LendingMarket,
 };
 
@@ -13,13 +18,31 @@ pub fn process(ctx: Context<RefreshReserve>) -> Result<()> {
     let reserve = &mut ctx.accounts.reserve.load_mut()?;
     let lending_market = &ctx.accounts.lending_market.load()?;
 
-    constraints::check_remaining_accounts(&ctx)?;
-
     require!(
         reserve.version == PROGRAM_VERSION as u64,
         LendingError::ReserveDeprecated
     );
 
+    // Derived prices take the base reserve and the stake pool as remaining accounts, which keeps
+    // the accounts of the instruction unchanged for every other reserve
+    let (derived_price_base_reserve, derived_price_stake_pool) = if reserve
+        .config
+        .token_info
+        .derived_price_configuration
+        .is_enabled()
+    {
+        match ctx.remaining_accounts {
+            [base_reserve, stake_pool] => (Some(base_reserve), Some(stake_pool)),
+            _ => {
+                msg!("Derived price reserves expect the base reserve and the stake pool accounts");
+                return err!(LendingError::InvalidDerivedPriceAccount);
+            }
+        }
+    } else {
+        constraints::check_remaining_accounts(&ctx)?;
+        (None, None)
+    };
+
     let price_res = if lending_operations::is_price_refresh_needed(
         reserve,
         lending_market,
@@ -30,26 +53,49 @@ pub fn process(ctx: Context<RefreshReserve>) -> Result<()> {
             ctx.accounts.switchboard_price_oracle.as_ref(),
             ctx.accounts.switchboard_twap_oracle.as_ref(),
             ctx.accounts.scope_prices.as_ref(),
+            derived_price_base_reserve,
+            derived_price_stake_pool,
         )?;
 
-        get_price(
-            &reserve.config.token_info,
-            ctx.accounts.pyth_oracle.as_ref(),
-            ctx.accounts.switchboard_price_oracle.as_ref(),
-            ctx.accounts.switchboard_twap_oracle.as_ref(),
-            ctx.accounts.scope_prices.as_ref(),
-            clock,
-        )?
+        if reserve
+            .config
+            .token_info
+            .derived_price_configuration
+            .is_enabled()
+        {
+            get_derived_price(
+                &reserve.config.token_info,
+                &ctx.accounts.reserve.key(),
+                &reserve.lending_market,
+                derived_price_base_reserve.unwrap(),
+                derived_price_stake_pool.unwrap(),
+                clock,
+            )?
+        } else {
+            get_price(
+                &reserve.config.token_info,
+                ctx.accounts.pyth_oracle.as_ref(),
+                ctx.accounts.switchboard_price_oracle.as_ref(),
+                ctx.accounts.switchboard_twap_oracle.as_ref(),
+                ctx.accounts.scope_prices.as_ref(),
+                clock,
+            )?
+        }
     } else {
         None
     };
 
+    let interest_snapshot = ReserveInterestSnapshot::new(reserve);
     lending_operations::refresh_reserve(
         reserve,
         clock,
         price_res,
         lending_market.referral_fee_bps,
     )?;
+    if let Some(event) = interest_snapshot.accrued(ctx.accounts.reserve.key(), reserve, clock.slot)
+    {
+        emit!(event);
+    }
     let timestamp = u64::try_from(clock.unix_timestamp).unwrap();
     lending_operations::refresh_reserve_limit_timestamps(reserve, timestamp);
 
@@ -1,10 +1,14 @@
 use anchor_lang::{prelude::*, Accounts, Result};
 
 use crate::{
+    events::ReserveInterestSnapshot,
     fraction::FractionExtra,
     lending_market::lending_operations,
     state::Reserve,
-    utils::{prices::get_price, FatAccountLoader, PROGRAM_VERSION},
+    utils::{
+        prices::{get_derived_price, get_price},
+        FatAccountLoader, PROGRAM_VERSION,
+    },
     LendingError, LendingMarket,
 };
 
@@ -63,6 +67,20 @@ pub fn process(ctx: Context<RefreshReservesBatch>, skip_price_updates: bool) ->
             let switchboard_price_oracle = maybe_price_account(remaining_accounts_it.next())?;
             let switchboard_twap_oracle = maybe_price_account(remaining_accounts_it.next())?;
             let scope_prices = maybe_price_account(remaining_accounts_it.next())?;
+            // Derived price accounts are only expected for reserves using a derived price
+            let (derived_price_base_reserve, derived_price_stake_pool) = if reserve
+                .config
+                .token_info
+                .derived_price_configuration
+                .is_enabled()
+            {
+                (
+                    maybe_price_account(remaining_accounts_it.next())?,
+                    maybe_price_account(remaining_accounts_it.next())?,
+                )
+            } else {
+                (None, None)
+            };
 
             if lending_operations::is_price_refresh_needed(
                 reserve,
@@ -74,16 +92,34 @@ pub fn process(ctx: Context<RefreshReservesBatch>, skip_price_updates: bool) ->
                     switchboard_price_oracle,
                     switchboard_twap_oracle,
                     scope_prices,
+                    derived_price_base_reserve,
+                    derived_price_stake_pool,
                 )?;
 
-                get_price(
-                    &reserve.config.token_info,
-                    pyth_oracle,
-                    switchboard_price_oracle,
-                    switchboard_twap_oracle,
-                    scope_prices,
-                    clock,
-                )?
+                if reserve
+                    .config
+                    .token_info
+                    .derived_price_configuration
+                    .is_enabled()
+                {
+                    get_derived_price(
+                        &reserve.config.token_info,
+                        reserve_acc.key,
+                        &reserve.lending_market,
+                        derived_price_base_reserve.unwrap(),
+                        derived_price_stake_pool.unwrap(),
+                        clock,
+                    )?
+                } else {
+                    get_price(
+                        &reserve.config.token_info,
+                        pyth_oracle,
+                        switchboard_price_oracle,
+                        switchboard_twap_oracle,
+                        scope_prices,
+                        clock,
+                    )?
+                }
             } else {
                 None
             }
@@ -91,12 +127,16 @@ pub fn process(ctx: Context<RefreshReservesBatch>, skip_price_updates: bool) ->
             None
         };
 
+        let interest_snapshot = ReserveInterestSnapshot::new(reserve);
         lending_operations::refresh_reserve(
             reserve,
             clock,
             price_res,
             lending_market.referral_fee_bps,
         )?;
+        if let Some(event) = interest_snapshot.accrued(*reserve_acc.key, reserve, clock.slot) {
+            emit!(event);
+        }
         let timestamp = u64::try_from(clock.unix_timestamp).unwrap();
         lending_operations::refresh_reserve_limit_timestamps(reserve, timestamp);
 
@@ -5,11 +5,12 @@ use crate::{
     handler_refresh_obligation_farms_for_reserve::*,
     handler_repay_obligation_liquidity::{self, *},
     handler_withdraw_obligation_collateral_and_redeem_reserve_collateral::{self, *},
-    lending_market::lending_operations,
+    lending_market::{lending_checks, lending_operations},
     refresh_farms,
-    utils::seeds::pda,
-    LendingError, LtvMaxWithdrawalCheck, MaxReservesAsCollateralCheck, RefreshObligation,
-    RefreshObligationBumps, ReserveFarmKind,
+    state::obligation::ObligationAccount,
+    utils::{seeds::pda, FatAccountLoader},
+    LendingError, LtvMaxWithdrawalCheck, MaxReservesAsCollateralCheck, OwnerWithdrawalCaps,
+    RefreshObligation, RefreshObligationBumps, ReserveFarmKind, WithdrawQueue,
 };
 
 pub fn process(
@@ -17,10 +18,21 @@ pub fn process(
     repay_amount: u64,
     withdraw_collateral_amount: u64,
 ) -> Result<()> {
+    let (remaining_accounts, owner_withdrawal_caps) = lending_checks::split_owner_withdrawal_caps(
+        ctx.accounts.withdraw_accounts.withdraw_reserve.key(),
+        ctx.accounts.withdraw_accounts.obligation.load()?.owner,
+        ctx.remaining_accounts,
+    )?;
+    let (remaining_accounts, withdraw_queue) = lending_checks::split_withdraw_queue(
+        ctx.accounts.repay_accounts.repay_reserve.key(),
+        remaining_accounts,
+    )?;
     process_impl(
         &ctx.accounts.repay_accounts,
         &ctx.accounts.withdraw_accounts,
-        ctx.remaining_accounts,
+        remaining_accounts,
+        withdraw_queue,
+        owner_withdrawal_caps.as_ref(),
         ctx.program_id,
         repay_amount,
         withdraw_collateral_amount,
@@ -45,10 +57,13 @@ pub fn process(
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 fn process_impl(
     repay_accounts: &RepayObligationLiquidity,
     withdraw_accounts: &WithdrawObligationCollateralAndRedeemReserveCollateral,
     remaining_accounts: &[AccountInfo],
+    withdraw_queue: Option<FatAccountLoader<WithdrawQueue>>,
+    owner_withdrawal_caps: Option<&FatAccountLoader<OwnerWithdrawalCaps>>,
     program_id: &Pubkey,
     repay_amount: u64,
     withdraw_collateral_amount: u64,
@@ -64,7 +79,7 @@ fn process_impl(
     let initial_ltv;
     let has_referrer;
     {
-        let obligation = withdraw_accounts.obligation.load()?;
+        let obligation = ObligationAccount::load(withdraw_accounts.obligation.as_ref())?;
 
         deposit_count = obligation.deposits_count();
         previous_borrow_count = obligation.borrows_count();
@@ -80,11 +95,13 @@ fn process_impl(
             repay_accounts,
             deposit_reserves_iter,
             repay_amount,
+            withdraw_queue,
+            owner_withdrawal_caps.filter(|_| repay_reserve_key == withdraw_reserve_key),
         )?;
     }
 
     let borrow_count_post_repay = {
-        let obligation = repay_accounts.obligation.load()?;
+        let obligation = ObligationAccount::load(repay_accounts.obligation.as_ref())?;
         let borrow_count_post_repay = obligation.borrows_count();
         drop(obligation);
 
@@ -160,12 +177,13 @@ fn process_impl(
             withdraw_accounts,
             withdraw_collateral_amount,
             LtvMaxWithdrawalCheck::LiquidationThreshold,
+            owner_withdrawal_caps,
         )?
     };
 
     if !obligation_was_closed {
         let (final_deposit_amount, withdraw_reserve_key_is_repay_reserve) = {
-            let obligation = withdraw_accounts.obligation.load()?;
+            let obligation = ObligationAccount::load(withdraw_accounts.obligation.as_ref())?;
             let final_deposit_amount = obligation
                 .find_collateral_in_deposits(withdraw_reserve_key)
                 .map_or(0, |collateral| collateral.deposited_amount);
@@ -7,10 +7,14 @@ use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface};
 
 use crate::{
     check_refresh_ixs,
+    events::RepayEvent,
     handler_refresh_obligation_farms_for_reserve::*,
     lending_market::{lending_checks, lending_operations},
     refresh_farms,
-    state::{obligation::Obligation, LendingMarket, Reserve},
+    state::{
+        obligation::{Obligation, ObligationAccountMut},
+        LendingMarket, OwnerWithdrawalCaps, Reserve, WithdrawQueue,
+    },
     utils::{seeds, token_transfer, FatAccountLoader},
     xmsg, LendingAction, ReserveFarmKind,
 };
@@ -22,18 +26,38 @@ pub fn process_v1(ctx: Context<RepayObligationLiquidity>, liquidity_amount: u64)
         ReserveFarmKind::Debt
     );
 
+    let (remaining_accounts, owner_withdrawal_caps) = lending_checks::split_owner_withdrawal_caps(
+        ctx.accounts.repay_reserve.key(),
+        ctx.accounts.obligation.load()?.owner,
+        ctx.remaining_accounts,
+    )?;
+    let (deposit_reserves, withdraw_queue) =
+        lending_checks::split_withdraw_queue(ctx.accounts.repay_reserve.key(), remaining_accounts)?;
     process_impl(
         ctx.accounts,
-        ctx.remaining_accounts.iter(),
+        deposit_reserves.iter(),
         liquidity_amount,
+        withdraw_queue,
+        owner_withdrawal_caps.as_ref(),
     )
 }
 
 pub fn process_v2(ctx: Context<RepayObligationLiquidityV2>, liquidity_amount: u64) -> Result<()> {
+    let (remaining_accounts, owner_withdrawal_caps) = lending_checks::split_owner_withdrawal_caps(
+        ctx.accounts.repay_accounts.repay_reserve.key(),
+        ctx.accounts.repay_accounts.obligation.load()?.owner,
+        ctx.remaining_accounts,
+    )?;
+    let (deposit_reserves, withdraw_queue) = lending_checks::split_withdraw_queue(
+        ctx.accounts.repay_accounts.repay_reserve.key(),
+        remaining_accounts,
+    )?;
     process_impl(
         &ctx.accounts.repay_accounts,
-        ctx.remaining_accounts.iter(),
+        deposit_reserves.iter(),
         liquidity_amount,
+        withdraw_queue,
+        owner_withdrawal_caps.as_ref(),
     )?;
     refresh_farms!(
         ctx.accounts.repay_accounts,
@@ -51,6 +75,8 @@ pub(super) fn process_impl<'a, 'info>(
     accounts: &RepayObligationLiquidity,
     remaining_accounts: impl Iterator<Item = &'a AccountInfo<'info>>,
     liquidity_amount: u64,
+    withdraw_queue: Option<FatAccountLoader<WithdrawQueue>>,
+    owner_withdrawal_caps: Option<&FatAccountLoader<OwnerWithdrawalCaps>>,
 ) -> Result<()>
 where
     'info: 'a,
@@ -60,8 +86,12 @@ where
     let clock = Clock::get()?;
 
     let repay_reserve = &mut accounts.repay_reserve.load_mut()?;
-    let obligation = &mut accounts.obligation.load_mut()?;
+    let mut obligation_account = ObligationAccountMut::load(accounts.obligation.as_ref())?;
+    let obligation = &mut obligation_account.get_mut();
     let lending_market = &accounts.lending_market.load()?;
+    let mut owner_withdrawal_caps = owner_withdrawal_caps
+        .map(|caps| caps.load_mut())
+        .transpose()?;
 
     let initial_reserve_token_balance = token_interface::accessor::amount(
         &accounts.reserve_destination_liquidity.to_account_info(),
@@ -78,6 +108,7 @@ where
         remaining_accounts.map(|a| {
             FatAccountLoader::try_from(a).expect("Remaining account is not a valid deposit reserve")
         }),
+        owner_withdrawal_caps.as_deref_mut(),
     )?;
 
     xmsg!(
@@ -107,6 +138,24 @@ where
         LendingAction::Additive(repay_amount),
     )?;
 
+    if let Some(withdraw_queue) = withdraw_queue {
+        lending_operations::fill_withdraw_queue(
+            repay_reserve,
+            &mut *withdraw_queue.load_mut()?,
+            u64::try_from(clock.unix_timestamp).unwrap(),
+        )?;
+    } else {
+        lending_checks::check_withdraw_queue_not_pending(repay_reserve)?;
+    }
+
+    emit!(RepayEvent {
+        lending_market: accounts.lending_market.key(),
+        reserve: accounts.repay_reserve.key(),
+        obligation: accounts.obligation.key(),
+        owner: accounts.owner.key(),
+        repay_amount,
+    });
+
     Ok(())
 }
 
@@ -1,14 +1,16 @@
 use anchor_lang::prelude::*;
 
 use crate::{
-    lending_market::lending_operations, utils::FatAccountLoader, LendingError, LendingMarket,
-    Obligation, ReferrerTokenState, Reserve,
+    events::ElevationGroupChanged, lending_market::lending_operations, utils::FatAccountLoader,
+    LendingError, LendingMarket, Obligation, ObligationAccountMut, ReferrerTokenState, Reserve,
 };
 
 pub fn process(ctx: Context<RequestElevationGroup>, new_elevation_group: u8) -> Result<()> {
-    let obligation = &mut ctx.accounts.obligation.load_mut()?;
+    let mut obligation_account = ObligationAccountMut::load(ctx.accounts.obligation.as_ref())?;
+    let obligation = &mut obligation_account.get_mut();
     let lending_market = ctx.accounts.lending_market.load()?;
     let slot = Clock::get()?.slot;
+    let previous_elevation_group = obligation.elevation_group;
     let deposit_count = obligation.deposits_count();
     let borrow_count = obligation.borrows_count();
     let reserves_count = borrow_count + deposit_count;
@@ -55,6 +57,14 @@ pub fn process(ctx: Context<RequestElevationGroup>, new_elevation_group: u8) ->
         referrer_token_states_iter,
     )?;
 
+    emit!(ElevationGroupChanged {
+        lending_market: ctx.accounts.lending_market.key(),
+        obligation: ctx.accounts.obligation.key(),
+        owner: ctx.accounts.owner.key(),
+        previous_elevation_group,
+        new_elevation_group,
+    });
+
     Ok(())
 }
 
@@ -6,10 +6,14 @@ use anchor_lang::{
 
 use crate::{
     check_refresh_ixs,
+    events::SocializeLossEvent,
     handler_refresh_obligation_farms_for_reserve::*,
     lending_market::lending_operations,
     refresh_farms,
-    state::{obligation::Obligation, LendingMarket, Reserve},
+    state::{
+        obligation::{Obligation, ObligationAccountMut},
+        LendingMarket, Reserve,
+    },
     utils::{seeds, FatAccountLoader},
     ReserveFarmKind,
 };
@@ -45,9 +49,10 @@ fn process_impl(
     let clock = Clock::get()?;
 
     let repay_reserve = &mut accounts.reserve.load_mut()?;
-    let obligation = &mut accounts.obligation.load_mut()?;
+    let mut obligation_account = ObligationAccountMut::load(accounts.obligation.as_ref())?;
+    let obligation = &mut obligation_account.get_mut();
 
-    lending_operations::socialize_loss(
+    let forgiven_amount = lending_operations::socialize_loss(
         repay_reserve,
         &accounts.reserve.key(),
         obligation,
@@ -58,6 +63,13 @@ fn process_impl(
         }),
     )?;
 
+    emit!(SocializeLossEvent {
+        lending_market: accounts.lending_market.key(),
+        reserve: accounts.reserve.key(),
+        obligation: accounts.obligation.key(),
+        forgiven_amount_sf: forgiven_amount.to_bits(),
+    });
+
     Ok(())
 }
 
@@ -2,11 +2,12 @@ use anchor_lang::{prelude::*, Accounts};
 
 use crate::{
     borsh::BorshDeserialize,
+    events::MarketConfigUpdated,
     fraction::FractionExtra,
     state::{lending_market::ElevationGroup, LendingMarket, UpdateLendingMarketMode},
     utils::{
         validate_numerical_bool, Fraction, ELEVATION_GROUP_NONE, FULL_BPS,
-        MAX_NUM_ELEVATION_GROUPS, MIN_INITIAL_DEPOSIT_AMOUNT,
+        MAX_CONFIG_TIMELOCK_SECS, MAX_NUM_ELEVATION_GROUPS, MIN_INITIAL_DEPOSIT_AMOUNT,
     },
     LendingError, VALUE_BYTE_MAX_ARRAY_LEN_MARKET_UPDATE,
 };
@@ -27,6 +28,35 @@ pub fn process(
         &value[0..32]
     );
 
+    let previous_market = bytemuck::bytes_of(&**market).to_vec();
+    let previous_config_timelock_secs = market.config_timelock_secs;
+
+    update_lending_market_config(market, mode, &value)?;
+
+    if previous_config_timelock_secs > 0
+        && mode.is_timelocked()
+        && !(mode == UpdateLendingMarketMode::UpdateConfigTimelockSecs
+            && market.config_timelock_secs >= previous_config_timelock_secs)
+    {
+        msg!("Mode {:?} must be queued as a config change", mode);
+        return err!(LendingError::ConfigChangeTimelocked);
+    }
+
+    emit!(MarketConfigUpdated::new(
+        ctx.accounts.lending_market.key(),
+        mode as u64,
+        &previous_market,
+        bytemuck::bytes_of(&**market),
+    ));
+
+    Ok(())
+}
+
+pub(crate) fn update_lending_market_config(
+    market: &mut LendingMarket,
+    mode: UpdateLendingMarketMode,
+    value: &[u8; VALUE_BYTE_MAX_ARRAY_LEN_MARKET_UPDATE],
+) -> Result<()> {
     match mode {
         UpdateLendingMarketMode::UpdateOwner => {
             let value: [u8; 32] = value[0..32].try_into().unwrap();
@@ -285,6 +315,26 @@ pub fn process(
             }
             *current_value = new_value;
         }
+        UpdateLendingMarketMode::UpdateObligationPermissionRequired => {
+            let obligation_permission_required = value[0];
+            msg!("Prev Value is {:?}", market.obligation_permission_required);
+            msg!("New Value is {:?}", obligation_permission_required);
+            validate_numerical_bool(obligation_permission_required)?;
+            market.obligation_permission_required = obligation_permission_required;
+        }
+        UpdateLendingMarketMode::UpdateConfigTimelockSecs => {
+            let new_value = u64::from_le_bytes(value[..8].try_into().unwrap());
+            msg!("Prv value is {}", market.config_timelock_secs);
+            msg!("New value is {}", new_value);
+            if new_value > MAX_CONFIG_TIMELOCK_SECS {
+                msg!(
+                    "Config timelock cannot be set higher than {}",
+                    MAX_CONFIG_TIMELOCK_SECS
+                );
+                return err!(LendingError::InvalidConfig);
+            }
+            market.config_timelock_secs = new_value;
+        }
     }
 
     Ok(())
@@ -0,0 +1,57 @@
+use anchor_lang::{prelude::*, Accounts};
+
+use crate::{
+    state::{obligation::Obligation, LendingMarket},
+    LendingError, ObligationDelegatePermissions,
+};
+
+pub fn process(
+    ctx: Context<UpdateObligationDelegate>,
+    delegate: Pubkey,
+    permissions: u8,
+    max_ltv_pct: u8,
+) -> Result<()> {
+    let obligation = &mut ctx.accounts.obligation.load_mut()?;
+
+    if delegate == Pubkey::default() {
+        msg!("Obligation delegate removed prv={}", obligation.delegate);
+        obligation.clear_delegate();
+        return Ok(());
+    }
+
+    require_keys_neq!(
+        delegate,
+        obligation.owner,
+        LendingError::InvalidAccountInput
+    );
+    require!(
+        ObligationDelegatePermissions::from_bits(permissions).is_some() && max_ltv_pct <= 100,
+        LendingError::InvalidDelegatePermissions
+    );
+
+    msg!(
+        "Obligation delegate prv={} new={} permissions={:#05b} max_ltv_pct={}",
+        obligation.delegate,
+        delegate,
+        permissions,
+        max_ltv_pct
+    );
+    obligation.delegate = delegate;
+    obligation.delegate_permissions = permissions;
+    obligation.delegate_max_ltv_pct = max_ltv_pct;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateObligationDelegate<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(mut,
+        has_one = lending_market,
+        has_one = owner @ LendingError::InvalidObligationOwner
+    )]
+    pub obligation: AccountLoader<'info, Obligation>,
+
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+}
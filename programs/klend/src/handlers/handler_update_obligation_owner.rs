@@ -0,0 +1,48 @@
+use anchor_lang::{prelude::*, Accounts};
+
+use crate::{
+    events::ObligationOwnerUpdated,
+    lending_market::lending_checks,
+    state::{
+        obligation::{Obligation, ObligationAccount},
+        LendingMarket,
+    },
+    LendingError,
+};
+
+pub fn process(ctx: Context<UpdateObligationOwner>) -> Result<()> {
+    lending_checks::check_obligation_farm_user_states_not_initialized(
+        &ctx.accounts.obligation.key(),
+        &ObligationAccount::load(ctx.accounts.obligation.as_ref())?,
+        ctx.remaining_accounts,
+    )?;
+
+    let obligation = &mut ctx.accounts.obligation.load_mut()?;
+
+    let previous_owner = obligation.owner;
+    obligation.owner = obligation.owner_cached;
+    obligation.owner_cached = Pubkey::default();
+    obligation.clear_delegate();
+
+    emit!(ObligationOwnerUpdated {
+        lending_market: ctx.accounts.lending_market.key(),
+        obligation: ctx.accounts.obligation.key(),
+        previous_owner,
+        new_owner: obligation.owner,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateObligationOwner<'info> {
+    pub owner_cached: Signer<'info>,
+
+    #[account(mut,
+        has_one = lending_market,
+        has_one = owner_cached @ LendingError::InvalidObligationOwner
+    )]
+    pub obligation: AccountLoader<'info, Obligation>,
+
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+}
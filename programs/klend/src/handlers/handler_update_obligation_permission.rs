@@ -0,0 +1,43 @@
+use anchor_lang::{prelude::*, Accounts};
+
+use crate::{
+    state::{LendingMarket, ObligationPermission},
+    LendingError,
+};
+
+pub fn process(ctx: Context<UpdateObligationPermission>, revoked: bool) -> Result<()> {
+    let obligation_permission = &mut ctx.accounts.obligation_permission.load_mut()?;
+    let slot = Clock::get()?.slot;
+
+    msg!(
+        "Obligation permission of {} prv_revoked_slot={} revoked={}",
+        obligation_permission.owner,
+        obligation_permission.revoked_slot,
+        revoked
+    );
+
+    if revoked {
+        if !obligation_permission.is_revoked() {
+            obligation_permission.revoked_slot = slot;
+        }
+    } else {
+        obligation_permission.revoked_slot = 0;
+        obligation_permission.issued_slot = slot;
+    }
+    obligation_permission.authority = ctx.accounts.authority.key();
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateObligationPermission<'info> {
+    #[account(
+        constraint = lending_market.load()?.is_owner_or_risk_council(authority.key) @ LendingError::InvalidSigner
+    )]
+    pub authority: Signer<'info>,
+
+    pub lending_market: AccountLoader<'info, LendingMarket>,
+
+    #[account(mut, has_one = lending_market)]
+    pub obligation_permission: AccountLoader<'info, ObligationPermission>,
+}
@@ -1,6 +1,7 @@
 use anchor_lang::{prelude::*, Accounts};
 
 use crate::{
+    events::ReserveConfigUpdated,
     lending_market::lending_operations,
     state::{LendingMarket, Reserve, UpdateConfigMode},
     utils::Fraction,
@@ -30,21 +31,37 @@ pub fn process(
     let clock = Clock::get()?;
     lending_operations::refresh_reserve(reserve, &clock, None, market.referral_fee_bps)?;
 
-    lending_operations::update_reserve_config(reserve, mode, value);
+    let risk_reducing_only = ctx.accounts.signer.key() != market.lending_market_owner;
+    if risk_reducing_only && skip_validation {
+        msg!("Only the lending market owner can skip config validation");
+        return err!(LendingError::InvalidSigner);
+    }
 
-    if skip_validation {
-        let reserve_is_used = reserve.liquidity.available_amount
-            > market.min_initial_deposit_amount
-            || reserve.liquidity.total_borrow() > Fraction::ZERO
-            || reserve.collateral.mint_total_supply > market.min_initial_deposit_amount;
+    let previous_config = reserve.config;
+    lending_operations::update_reserve_config(reserve, mode, value, risk_reducing_only)?;
+
+    let reserve_is_used = reserve.liquidity.available_amount > market.min_initial_deposit_amount
+        || reserve.liquidity.total_borrow() > Fraction::ZERO
+        || reserve.collateral.mint_total_supply > market.min_initial_deposit_amount;
+    let reserve_blocks_deposits = reserve.config.deposit_limit == 0;
+    let reserve_blocks_borrows = reserve.config.borrow_limit == 0;
+    let reserve_is_closed = !reserve_is_used && reserve_blocks_deposits && reserve_blocks_borrows;
 
-        let reserve_blocks_deposits = reserve.config.deposit_limit == 0;
-        let reserve_blocks_borrows = reserve.config.borrow_limit == 0;
+    if market.is_config_timelock_enabled()
+        && mode.is_timelocked()
+        && !reserve_is_closed
+        && !lending_operations::utils::is_reserve_config_change_risk_reducing(
+            mode,
+            &previous_config,
+            &reserve.config,
+        )
+    {
+        msg!("Mode {:?} must be queued as a config change", mode);
+        return err!(LendingError::ConfigChangeTimelocked);
+    }
 
-        require!(
-            !reserve_is_used && reserve_blocks_deposits && reserve_blocks_borrows,
-            LendingError::InvalidConfig
-        );
+    if skip_validation {
+        require!(reserve_is_closed, LendingError::InvalidConfig);
         msg!("WARNING! Skipping validation of the config");
     } else {
         lending_operations::utils::validate_reserve_config(
@@ -54,14 +71,24 @@ pub fn process(
         )?;
     }
 
+    emit!(ReserveConfigUpdated::new(
+        ctx.accounts.lending_market.key(),
+        ctx.accounts.reserve.key(),
+        mode as u64,
+        bytemuck::bytes_of(&previous_config),
+        bytemuck::bytes_of(&reserve.config),
+    ));
+
     Ok(())
 }
 
 #[derive(Accounts)]
 pub struct UpdateReserveConfig<'info> {
-    lending_market_owner: Signer<'info>,
+    signer: Signer<'info>,
 
-    #[account(has_one = lending_market_owner)]
+    #[account(
+        constraint = lending_market.load()?.is_owner_or_risk_council(signer.key) @ LendingError::InvalidMarketAuthority
+    )]
     lending_market: AccountLoader<'info, LendingMarket>,
 
     #[account(mut,
@@ -6,13 +6,18 @@ use anchor_lang::{
 use anchor_spl::{token::Token, token_interface::TokenAccount};
 
 use crate::{
-    check_refresh_ixs, gen_signer_seeds,
+    check_refresh_ixs,
+    events::WithdrawEvent,
+    gen_signer_seeds,
     handler_refresh_obligation_farms_for_reserve::*,
     lending_market::{lending_checks, lending_operations},
     refresh_farms,
-    state::{obligation::Obligation, LendingMarket, Reserve, WithdrawObligationCollateralAccounts},
+    state::{
+        obligation::{Obligation, ObligationAccountMut},
+        LendingMarket, Reserve, WithdrawObligationCollateralAccounts,
+    },
     utils::{close_account_loader, seeds, token_transfer},
-    LtvMaxWithdrawalCheck, ReserveFarmKind,
+    LendingError, LtvMaxWithdrawalCheck, ObligationDelegatePermissions, ReserveFarmKind,
 };
 
 pub fn process_v1(
@@ -24,14 +29,18 @@ pub fn process_v1(
         ctx.accounts.withdraw_reserve,
         ReserveFarmKind::Collateral
     );
-    process_impl(ctx.accounts, collateral_amount)
+    process_impl(ctx.accounts, ctx.remaining_accounts, collateral_amount)
 }
 
 pub fn process_v2(
     ctx: Context<WithdrawObligationCollateralV2>,
     collateral_amount: u64,
 ) -> Result<()> {
-    process_impl(&ctx.accounts.withdraw_accounts, collateral_amount)?;
+    process_impl(
+        &ctx.accounts.withdraw_accounts,
+        ctx.remaining_accounts,
+        collateral_amount,
+    )?;
     refresh_farms!(
         ctx.accounts.withdraw_accounts,
         [(
@@ -43,7 +52,17 @@ pub fn process_v2(
     Ok(())
 }
 
-fn process_impl(accounts: &WithdrawObligationCollateral, collateral_amount: u64) -> Result<()> {
+fn process_impl(
+    accounts: &WithdrawObligationCollateral,
+    remaining_accounts: &[AccountInfo],
+    collateral_amount: u64,
+) -> Result<()> {
+    let (_, owner_withdrawal_caps) = lending_checks::split_owner_withdrawal_caps(
+        accounts.withdraw_reserve.key(),
+        accounts.obligation.load()?.owner,
+        remaining_accounts,
+    )?;
+
     let close_obligation = {
         lending_checks::withdraw_obligation_collateral_checks(
             &WithdrawObligationCollateralAccounts {
@@ -60,19 +79,35 @@ fn process_impl(accounts: &WithdrawObligationCollateral, collateral_amount: u64)
         let clock = &Clock::get()?;
 
         let withdraw_reserve = &mut accounts.withdraw_reserve.load_mut()?;
-        let obligation = &mut accounts.obligation.load_mut()?;
+        let mut obligation_account = ObligationAccountMut::load(accounts.obligation.as_ref())?;
+        let obligation = &mut obligation_account.get_mut();
         let lending_market = &mut accounts.lending_market.load()?;
         let lending_market_key = accounts.lending_market.key();
 
+        let is_delegate = accounts.owner.key() != obligation.owner;
+        let collateral_before_withdraw =
+            *obligation.find_collateral_in_deposits(accounts.withdraw_reserve.key())?;
+        let mut owner_withdrawal_caps = owner_withdrawal_caps
+            .as_ref()
+            .map(|caps| caps.load_mut())
+            .transpose()?;
         let withdraw_amount = lending_operations::withdraw_obligation_collateral(
             lending_market,
             withdraw_reserve,
             obligation,
             collateral_amount,
-            clock.slot,
+            clock,
             accounts.withdraw_reserve.key(),
             LtvMaxWithdrawalCheck::MaxLtv,
+            owner_withdrawal_caps.as_deref_mut(),
         )?;
+        if is_delegate {
+            lending_operations::check_delegate_withdraw_within_ltv_ceiling(
+                obligation,
+                &collateral_before_withdraw,
+                withdraw_amount,
+            )?;
+        }
 
         let authority_signer_seeds =
             gen_signer_seeds!(lending_market_key, lending_market.bump_seed as u8);
@@ -88,7 +123,18 @@ fn process_impl(accounts: &WithdrawObligationCollateral, collateral_amount: u64)
 
         msg!("pnl: Withdraw obligation collateral {}", withdraw_amount);
 
-        obligation.deposits_empty() && obligation.borrows_empty()
+        emit!(WithdrawEvent {
+            lending_market: lending_market_key,
+            reserve: accounts.withdraw_reserve.key(),
+            obligation: accounts.obligation.key(),
+            owner: accounts.owner.key(),
+            liquidity_amount: withdraw_reserve
+                .collateral_exchange_rate()
+                .collateral_to_liquidity(withdraw_amount),
+            collateral_amount: withdraw_amount,
+        });
+
+        !is_delegate && obligation.deposits_empty() && obligation.borrows_empty()
     };
 
     close_account_loader(close_obligation, &accounts.owner, &accounts.obligation)?;
@@ -101,7 +147,7 @@ pub struct WithdrawObligationCollateral<'info> {
     pub owner: Signer<'info>,
     #[account(mut,
         has_one = lending_market,
-        has_one = owner
+        constraint = obligation.load()?.is_authorized(owner.key(), ObligationDelegatePermissions::REBALANCE) @ LendingError::InvalidObligationOwner
     )]
     pub obligation: AccountLoader<'info, Obligation>,
 
@@ -125,7 +171,7 @@ pub struct WithdrawObligationCollateral<'info> {
 
     #[account(mut,
         token::mint = withdraw_reserve.load()?.collateral.mint_pubkey,
-        token::authority = owner
+        token::authority = obligation.load()?.owner,
     )]
     pub user_destination_collateral: Box<InterfaceAccount<'info, TokenAccount>>,
 
@@ -9,13 +9,19 @@ use anchor_spl::{
 };
 
 use crate::{
-    check_refresh_ixs, gen_signer_seeds,
+    check_refresh_ixs,
+    events::WithdrawEvent,
+    gen_signer_seeds,
     handler_refresh_obligation_farms_for_reserve::*,
     lending_market::{lending_checks, lending_operations},
     refresh_farms,
-    state::{obligation::Obligation, LendingMarket, Reserve},
-    utils::{close_account_loader, seeds, token_transfer},
-    LendingAction, LtvMaxWithdrawalCheck, ReserveFarmKind,
+    state::{
+        obligation::{Obligation, ObligationAccountMut},
+        LendingMarket, Reserve,
+    },
+    utils::{close_account_loader, seeds, token_transfer, FatAccountLoader},
+    LendingAction, LendingError, LtvMaxWithdrawalCheck, ObligationDelegatePermissions,
+    OwnerWithdrawalCaps, ReserveFarmKind,
     WithdrawObligationCollateralAndRedeemReserveCollateralAccounts,
 };
 
@@ -29,10 +35,16 @@ pub fn process_v1(
         ReserveFarmKind::Collateral
     );
 
+    let (_, owner_withdrawal_caps) = lending_checks::split_owner_withdrawal_caps(
+        ctx.accounts.withdraw_reserve.key(),
+        ctx.accounts.obligation.load()?.owner,
+        ctx.remaining_accounts,
+    )?;
     let _ = process_impl(
         ctx.accounts,
         collateral_amount,
         LtvMaxWithdrawalCheck::MaxLtv,
+        owner_withdrawal_caps.as_ref(),
     )?;
 
     Ok(())
@@ -42,10 +54,16 @@ pub fn process_v2(
     ctx: Context<WithdrawObligationCollateralAndRedeemReserveCollateralV2>,
     collateral_amount: u64,
 ) -> Result<()> {
+    let (_, owner_withdrawal_caps) = lending_checks::split_owner_withdrawal_caps(
+        ctx.accounts.withdraw_accounts.withdraw_reserve.key(),
+        ctx.accounts.withdraw_accounts.obligation.load()?.owner,
+        ctx.remaining_accounts,
+    )?;
     process_impl(
         &ctx.accounts.withdraw_accounts,
         collateral_amount,
         LtvMaxWithdrawalCheck::MaxLtv,
+        owner_withdrawal_caps.as_ref(),
     )?;
     refresh_farms!(
         ctx.accounts.withdraw_accounts,
@@ -62,6 +80,7 @@ pub(super) fn process_impl(
     accounts: &WithdrawObligationCollateralAndRedeemReserveCollateral,
     collateral_amount: u64,
     ltv_max_withdrawal_check: LtvMaxWithdrawalCheck,
+    owner_withdrawal_caps: Option<&FatAccountLoader<OwnerWithdrawalCaps>>,
 ) -> Result<bool> {
     let close_obligation = {
         lending_checks::withdraw_obligation_collateral_and_redeem_reserve_collateral_checks(
@@ -74,7 +93,8 @@ pub(super) fn process_impl(
 
         let reserve = &mut accounts.withdraw_reserve.load_mut()?;
 
-        let obligation = &mut accounts.obligation.load_mut()?;
+        let mut obligation_account = ObligationAccountMut::load(accounts.obligation.as_ref())?;
+        let obligation = &mut obligation_account.get_mut();
         let lending_market = &accounts.lending_market.load()?;
         let lending_market_key = accounts.lending_market.key();
         let clock = &Clock::get()?;
@@ -86,15 +106,29 @@ pub(super) fn process_impl(
             &accounts.reserve_liquidity_supply.to_account_info(),
         )?;
         let initial_reserve_available_liquidity = reserve.liquidity.available_amount;
+        let is_delegate = accounts.owner.key() != obligation.owner;
+        let collateral_before_withdraw =
+            *obligation.find_collateral_in_deposits(accounts.withdraw_reserve.key())?;
+        let mut owner_withdrawal_caps = owner_withdrawal_caps
+            .map(|caps| caps.load_mut())
+            .transpose()?;
         let withdraw_obligation_amount = lending_operations::withdraw_obligation_collateral(
             lending_market,
             reserve,
             obligation,
             collateral_amount,
-            clock.slot,
+            clock,
             accounts.withdraw_reserve.key(),
             ltv_max_withdrawal_check,
+            owner_withdrawal_caps.as_deref_mut(),
         )?;
+        if is_delegate {
+            lending_operations::check_delegate_withdraw_within_ltv_ceiling(
+                obligation,
+                &collateral_before_withdraw,
+                withdraw_obligation_amount,
+            )?;
+        }
         let withdraw_liquidity_amount = lending_operations::redeem_reserve_collateral(
             reserve,
             withdraw_obligation_amount,
@@ -131,7 +165,16 @@ pub(super) fn process_impl(
             LendingAction::Subtractive(withdraw_liquidity_amount),
         )?;
 
-        obligation.deposits_empty() && obligation.borrows_empty()
+        emit!(WithdrawEvent {
+            lending_market: lending_market_key,
+            reserve: accounts.withdraw_reserve.key(),
+            obligation: accounts.obligation.key(),
+            owner: accounts.owner.key(),
+            liquidity_amount: withdraw_liquidity_amount,
+            collateral_amount: withdraw_obligation_amount,
+        });
+
+        !is_delegate && obligation.deposits_empty() && obligation.borrows_empty()
     };
 
     close_account_loader(close_obligation, &accounts.owner, &accounts.obligation)?;
@@ -146,7 +189,7 @@ pub struct WithdrawObligationCollateralAndRedeemReserveCollateral<'info> {
 
     #[account(mut,
         has_one = lending_market,
-        has_one = owner
+        constraint = obligation.load()?.is_authorized(owner.key(), ObligationDelegatePermissions::REBALANCE) @ LendingError::InvalidObligationOwner
     )]
     pub obligation: AccountLoader<'info, Obligation>,
 
@@ -179,7 +222,7 @@ pub struct WithdrawObligationCollateralAndRedeemReserveCollateral<'info> {
 
     #[account(mut,
         token::mint = withdraw_reserve.load()?.liquidity.mint_pubkey,
-        token::authority = owner,
+        token::authority = obligation.load()?.owner,
     )]
     pub user_destination_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
 
@@ -1,21 +1,34 @@
 pub mod handler_borrow_obligation_liquidity;
+pub mod handler_cancel_config_change;
+pub mod handler_cancel_withdrawal;
+pub mod handler_claim_withdrawal;
+pub mod handler_clear_price_circuit_breaker;
+pub mod handler_close_obligation;
 pub mod handler_delete_referrer_state_and_short_url;
 pub mod handler_deposit_and_withdraw;
 pub mod handler_deposit_obligation_collateral;
 pub mod handler_deposit_reserve_liquidity;
 pub mod handler_deposit_reserve_liquidity_and_obligation_collateral;
+pub mod handler_enqueue_withdrawal;
+pub mod handler_execute_config_change;
+pub mod handler_extend_obligation;
 pub mod handler_flash_borrow_reserve_liquidity;
 pub mod handler_flash_repay_reserve_liquidity;
 pub mod handler_init_farms_for_reserve;
 pub mod handler_init_lending_market;
 pub mod handler_init_obligation;
 pub mod handler_init_obligation_farms_for_reserve;
+pub mod handler_init_obligation_permission;
+pub mod handler_init_owner_withdrawal_caps;
 pub mod handler_init_referrer_state_and_short_url;
 pub mod handler_init_referrer_token_state;
 pub mod handler_init_reserve;
 pub mod handler_init_user_metadata;
+pub mod handler_init_withdraw_queue;
 pub mod handler_liquidate_obligation_and_redeem_reserve_collateral;
 pub mod handler_mark_obligation_for_deleveraging;
+pub mod handler_propose_obligation_owner;
+pub mod handler_queue_config_change;
 pub mod handler_redeem_fees;
 pub mod handler_redeem_reserve_collateral;
 pub mod handler_refresh_obligation;
@@ -28,6 +41,9 @@ pub mod handler_request_elevation_group;
 pub mod handler_socialize_loss;
 pub mod handler_update_lending_market;
 pub mod handler_update_lending_market_owner;
+pub mod handler_update_obligation_delegate;
+pub mod handler_update_obligation_owner;
+pub mod handler_update_obligation_permission;
 pub mod handler_update_reserve_config;
 pub mod handler_withdraw_obligation_collateral;
 pub mod handler_withdraw_obligation_collateral_and_redeem_reserve_collateral;
@@ -35,23 +51,36 @@ pub mod handler_withdraw_protocol_fees;
 pub mod handler_withdraw_referrer_fees;
 
 pub use handler_borrow_obligation_liquidity::*;
+pub use handler_cancel_config_change::*;
+pub use handler_cancel_withdrawal::*;
+pub use handler_claim_withdrawal::*;
+pub use handler_clear_price_circuit_breaker::*;
+pub use handler_close_obligation::*;
 pub use handler_delete_referrer_state_and_short_url::*;
 pub use handler_deposit_and_withdraw::*;
 pub use handler_deposit_obligation_collateral::*;
 pub use handler_deposit_reserve_liquidity::*;
 pub use handler_deposit_reserve_liquidity_and_obligation_collateral::*;
+pub use handler_enqueue_withdrawal::*;
+pub use handler_execute_config_change::*;
+pub use handler_extend_obligation::*;
 pub use handler_flash_borrow_reserve_liquidity::*;
 pub use handler_flash_repay_reserve_liquidity::*;
 pub use handler_init_farms_for_reserve::*;
 pub use handler_init_lending_market::*;
 pub use handler_init_obligation::*;
 pub use handler_init_obligation_farms_for_reserve::*;
+pub use handler_init_obligation_permission::*;
+pub use handler_init_owner_withdrawal_caps::*;
 pub use handler_init_referrer_state_and_short_url::*;
 pub use handler_init_referrer_token_state::*;
 pub use handler_init_reserve::*;
 pub use handler_init_user_metadata::*;
+pub use handler_init_withdraw_queue::*;
 pub use handler_liquidate_obligation_and_redeem_reserve_collateral::*;
 pub use handler_mark_obligation_for_deleveraging::*;
+pub use handler_propose_obligation_owner::*;
+pub use handler_queue_config_change::*;
 pub use handler_redeem_fees::*;
 pub use handler_redeem_reserve_collateral::*;
 pub use handler_refresh_obligation::*;
@@ -64,6 +93,9 @@ pub use handler_request_elevation_group::*;
 pub use handler_socialize_loss::*;
 pub use handler_update_lending_market::*;
 pub use handler_update_lending_market_owner::*;
+pub use handler_update_obligation_delegate::*;
+pub use handler_update_obligation_owner::*;
+pub use handler_update_obligation_permission::*;
 pub use handler_update_reserve_config::*;
 pub use handler_withdraw_obligation_collateral::*;
 pub use handler_withdraw_obligation_collateral_and_redeem_reserve_collateral::*;
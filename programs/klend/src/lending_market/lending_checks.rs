@@ -1,21 +1,27 @@
 use anchor_lang::{
     accounts::account_loader::AccountLoader,
     err, error,
-    prelude::{msg, Context, Pubkey},
-    require_eq, require_gte, Key, Result, ToAccountInfo,
+    prelude::{msg, AccountInfo, Context, Pubkey},
+    require_eq, require_gte, require_keys_eq, Discriminator, Key, Result, ToAccountInfo,
 };
 
 use crate::{
     handlers::*,
     state::{
-        DepositObligationCollateralAccounts, RedeemReserveCollateralAccounts,
-        WithdrawObligationCollateralAccounts,
+        obligation::ObligationAccount, DepositObligationCollateralAccounts,
+        RedeemReserveCollateralAccounts, WithdrawObligationCollateralAccounts,
         WithdrawObligationCollateralAndRedeemReserveCollateralAccounts,
     },
     utils::{
-        constraints, seeds::BASE_SEED_REFERRER_TOKEN_STATE, FatAccountLoader, PROGRAM_VERSION,
+        constraints,
+        seeds::{
+            self, BASE_SEED_OBLIGATION_PERMISSION, BASE_SEED_OWNER_WITHDRAWAL_CAPS,
+            BASE_SEED_REFERRER_TOKEN_STATE, BASE_SEED_WITHDRAW_QUEUE,
+        },
+        FatAccountLoader, PROGRAM_VERSION,
     },
-    LendingAction, LendingError, Obligation, ReferrerTokenState, Reserve, ReserveStatus,
+    LendingAction, LendingError, LendingMarket, Obligation, ObligationPermission,
+    OwnerWithdrawalCaps, ReferrerTokenState, Reserve, ReserveFarmKind, ReserveStatus, WithdrawQueue,
 };
 
 pub fn borrow_obligation_liquidity_checks(accounts: &BorrowObligationLiquidity) -> Result<()> {
@@ -477,3 +483,278 @@ pub fn validate_referrer_token_state(
 
     Ok(())
 }
+
+/// Separates the obligation permission from the other remaining accounts of an instruction.
+/// Markets requiring permissions expect it as the last remaining account, which keeps the
+/// account layouts of the instructions unchanged for every other market.
+pub fn split_obligation_permission<'a, 'info>(
+    lending_market: &LendingMarket,
+    remaining_accounts: &'a [AccountInfo<'info>],
+) -> (&'a [AccountInfo<'info>], Option<&'a AccountInfo<'info>>) {
+    if !lending_market.is_obligation_permission_required() {
+        return (remaining_accounts, None);
+    }
+    match remaining_accounts.split_last() {
+        Some((obligation_permission, remaining_accounts)) => {
+            (remaining_accounts, Some(obligation_permission))
+        }
+        None => (remaining_accounts, None),
+    }
+}
+
+pub fn validate_obligation_permission(
+    program_id: &Pubkey,
+    lending_market: &LendingMarket,
+    lending_market_key: Pubkey,
+    obligation_owner: Pubkey,
+    obligation_permission: Option<&AccountInfo>,
+) -> anchor_lang::Result<()> {
+    if !lending_market.is_obligation_permission_required() {
+        return Ok(());
+    }
+
+    let Some(obligation_permission_info) = obligation_permission else {
+        msg!("Lending market requires an obligation permission for {obligation_owner}");
+        return err!(LendingError::ObligationPermissionMissing);
+    };
+    let obligation_permission_loader =
+        FatAccountLoader::<ObligationPermission>::try_from(obligation_permission_info)
+            .map_err(|_| error!(LendingError::InvalidObligationPermission))?;
+    let obligation_permission = obligation_permission_loader.load()?;
+
+    let obligation_permission_valid_pda = Pubkey::create_program_address(
+        &[
+            BASE_SEED_OBLIGATION_PERMISSION,
+            lending_market_key.as_ref(),
+            obligation_owner.as_ref(),
+            &[obligation_permission.bump.try_into().unwrap()],
+        ],
+        program_id,
+    )
+    .map_err(|_| error!(LendingError::InvalidObligationPermission))?;
+
+    if obligation_permission_loader.key() != obligation_permission_valid_pda
+        || obligation_permission.lending_market != lending_market_key
+        || obligation_permission.owner != obligation_owner
+    {
+        return err!(LendingError::InvalidObligationPermission);
+    }
+
+    if obligation_permission.is_revoked() {
+        msg!(
+            "Obligation permission of {obligation_owner} revoked at slot {}",
+            obligation_permission.revoked_slot
+        );
+        return err!(LendingError::ObligationPermissionRevoked);
+    }
+
+    Ok(())
+}
+
+/// Separates the withdraw queue of `reserve_key` from the other remaining accounts of a deposit
+/// or repay. It is passed last so the liquidity coming in fills its tickets, see
+/// [`check_withdraw_queue_not_pending`].
+pub fn split_withdraw_queue<'a, 'info>(
+    reserve_key: Pubkey,
+    remaining_accounts: &'a [AccountInfo<'info>],
+) -> Result<(
+    &'a [AccountInfo<'info>],
+    Option<FatAccountLoader<'info, WithdrawQueue>>,
+)> {
+    let Some((withdraw_queue_info, other_accounts)) = remaining_accounts.split_last() else {
+        return Ok((remaining_accounts, None));
+    };
+    if withdraw_queue_info.owner != &crate::ID
+        || !withdraw_queue_info
+            .try_borrow_data()?
+            .starts_with(&WithdrawQueue::discriminator())
+    {
+        return Ok((remaining_accounts, None));
+    }
+
+    let withdraw_queue_loader = FatAccountLoader::<WithdrawQueue>::try_from(withdraw_queue_info)?;
+    {
+        let withdraw_queue = withdraw_queue_loader.load()?;
+        let withdraw_queue_valid_pda = Pubkey::create_program_address(
+            &[
+                BASE_SEED_WITHDRAW_QUEUE,
+                reserve_key.as_ref(),
+                &[withdraw_queue.bump.try_into().unwrap()],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| error!(LendingError::InvalidAccountInput))?;
+        require_keys_eq!(
+            withdraw_queue_info.key(),
+            withdraw_queue_valid_pda,
+            LendingError::InvalidAccountInput
+        );
+    }
+
+    Ok((other_accounts, Some(withdraw_queue_loader)))
+}
+
+/// Deposits and repays have to pass the withdraw queue of the reserve while it has tickets
+/// waiting for liquidity, so the tickets can't starve while liquidity flows in.
+pub fn check_withdraw_queue_not_pending(reserve: &Reserve) -> Result<()> {
+    if reserve.withdraw_queue_collateral_amount > 0 {
+        msg!(
+            "Reserve has {} collateral queued for withdrawal, the withdraw queue must be passed",
+            reserve.withdraw_queue_collateral_amount
+        );
+        return err!(LendingError::WithdrawQueueMissing);
+    }
+    Ok(())
+}
+
+/// Farm user states of an obligation, derived from the farms of the reserves passed as remaining
+/// accounts. Each reserve is followed by the user state of its collateral farm then the one of
+/// its debt farm, for the farms it has.
+pub struct ObligationFarmUserStates<'a, 'info> {
+    pub reserves: Vec<Pubkey>,
+    /// Only the farm user states that were initialized, the others can't hold any stake
+    pub initialized: Vec<&'a AccountInfo<'info>>,
+}
+
+pub fn get_obligation_farm_user_states<'a, 'info>(
+    obligation: &Pubkey,
+    lending_market: &Pubkey,
+    remaining_accounts: &'a [AccountInfo<'info>],
+) -> Result<ObligationFarmUserStates<'a, 'info>> {
+    let mut reserves = Vec::new();
+    let mut initialized = Vec::new();
+    let mut accounts = remaining_accounts.iter();
+
+    while let Some(reserve_info) = accounts.next() {
+        let reserve_loader = FatAccountLoader::<Reserve>::try_from(reserve_info)?;
+        let reserve = reserve_loader.load()?;
+        require_keys_eq!(
+            reserve.lending_market,
+            *lending_market,
+            LendingError::InvalidAccountInput
+        );
+        if reserves.contains(reserve_info.key) {
+            msg!("Reserve {} is passed twice", reserve_info.key);
+            return err!(LendingError::InvalidAccountInput);
+        }
+        reserves.push(reserve_info.key());
+
+        for mode in [ReserveFarmKind::Collateral, ReserveFarmKind::Debt] {
+            let farm = reserve.get_farm(mode);
+            if farm == Pubkey::default() {
+                continue;
+            }
+
+            let expected_farm_user_state =
+                seeds::pda::obligation_farm_user_state(farm, *obligation).0;
+            match accounts.next() {
+                Some(farm_user_state_info)
+                    if farm_user_state_info.key() == expected_farm_user_state =>
+                {
+                    if *farm_user_state_info.owner == farms::ID
+                        && !farm_user_state_info.data_is_empty()
+                    {
+                        initialized.push(farm_user_state_info);
+                    }
+                }
+                _ => {
+                    msg!(
+                        "Expected the farm user state {} of the {:?} farm of reserve {}",
+                        expected_farm_user_state,
+                        mode,
+                        reserve_info.key
+                    );
+                    return err!(LendingError::ObligationFarmUserStatesMissing);
+                }
+            }
+        }
+    }
+
+    Ok(ObligationFarmUserStates {
+        reserves,
+        initialized,
+    })
+}
+
+/// Farm user states keep the owner they were initialized with, so the owner of an obligation
+/// can't change while one of its reserves has a farm user state for it. The remaining accounts
+/// must cover every deposit and borrow reserve of the obligation.
+pub fn check_obligation_farm_user_states_not_initialized(
+    obligation_key: &Pubkey,
+    obligation: &ObligationAccount,
+    remaining_accounts: &[AccountInfo],
+) -> Result<()> {
+    let farm_user_states = get_obligation_farm_user_states(
+        obligation_key,
+        &obligation.lending_market,
+        remaining_accounts,
+    )?;
+
+    let position_reserves = obligation
+        .deposits()
+        .map(|deposit| deposit.deposit_reserve)
+        .chain(obligation.borrows().map(|borrow| borrow.borrow_reserve))
+        .filter(|reserve| *reserve != Pubkey::default());
+    for reserve in position_reserves {
+        if !farm_user_states.reserves.contains(&reserve) {
+            msg!("Reserve {} of the obligation is not passed", reserve);
+            return err!(LendingError::ObligationFarmUserStatesMissing);
+        }
+    }
+
+    if let Some(farm_user_state) = farm_user_states.initialized.first() {
+        msg!(
+            "Farm user state {} keeps the current owner of the obligation",
+            farm_user_state.key
+        );
+        return err!(LendingError::ObligationHasFarmUserStates);
+    }
+
+    Ok(())
+}
+
+/// Separates the owner withdrawal caps of `reserve_key` and `owner` from the other remaining
+/// accounts. They are passed last, after the withdraw queue and the obligation permission, and
+/// are required by withdrawals and borrows from reserves capping owners.
+pub fn split_owner_withdrawal_caps<'a, 'info>(
+    reserve_key: Pubkey,
+    owner: Pubkey,
+    remaining_accounts: &'a [AccountInfo<'info>],
+) -> Result<(
+    &'a [AccountInfo<'info>],
+    Option<FatAccountLoader<'info, OwnerWithdrawalCaps>>,
+)> {
+    let Some((owner_withdrawal_caps_info, other_accounts)) = remaining_accounts.split_last() else {
+        return Ok((remaining_accounts, None));
+    };
+    if owner_withdrawal_caps_info.owner != &crate::ID
+        || !owner_withdrawal_caps_info
+            .try_borrow_data()?
+            .starts_with(&OwnerWithdrawalCaps::discriminator())
+    {
+        return Ok((remaining_accounts, None));
+    }
+
+    let owner_withdrawal_caps_loader =
+        FatAccountLoader::<OwnerWithdrawalCaps>::try_from(owner_withdrawal_caps_info)?;
+    {
+        let owner_withdrawal_caps = owner_withdrawal_caps_loader.load()?;
+        let owner_withdrawal_caps_valid_pda = Pubkey::create_program_address(
+            &[
+                BASE_SEED_OWNER_WITHDRAWAL_CAPS,
+                reserve_key.as_ref(),
+                owner.as_ref(),
+                &[owner_withdrawal_caps.bump.try_into().unwrap()],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| error!(LendingError::InvalidAccountInput))?;
+        require_keys_eq!(
+            owner_withdrawal_caps_info.key(),
+            owner_withdrawal_caps_valid_pda,
+            LendingError::InvalidAccountInput
+        );
+    }
+
+    Ok((other_accounts, Some(owner_withdrawal_caps_loader)))
+}
@@ -12,32 +12,38 @@ use self::utils::{
     calculate_obligation_collateral_market_value, calculate_obligation_liquidity_market_value,
     check_elevation_group_borrowing_enabled, check_non_elevation_group_borrowing_enabled,
     check_obligation_collateral_deposit_reserve, check_obligation_fully_refreshed_and_not_null,
-    check_obligation_liquidity_borrow_reserve, check_same_elevation_group, get_elevation_group,
-    get_max_ltv_and_liquidation_threshold, post_borrow_obligation_invariants,
-    post_deposit_obligation_invariants, post_repay_obligation_invariants,
-    post_withdraw_obligation_invariants, update_elevation_group_debt_trackers_on_repay,
-    validate_obligation_asset_tiers,
+    check_obligation_liquidity_borrow_reserve, check_obligation_reserves_limit,
+    check_same_elevation_group, get_elevation_group, get_max_ltv_and_liquidation_threshold,
+    post_borrow_obligation_invariants, post_deposit_obligation_invariants,
+    post_repay_obligation_invariants, post_withdraw_obligation_invariants,
+    update_elevation_group_debt_trackers_on_repay, validate_obligation_asset_tiers,
 };
 use super::{
     validate_referrer_token_state,
-    withdrawal_cap_operations::utils::{add_to_withdrawal_accum, sub_from_withdrawal_accum},
+    withdrawal_cap_operations::utils::{
+        add_to_owner_withdrawal_accum, add_to_withdrawal_accum, remaining_withdrawal_capacity,
+        sub_from_owner_withdrawal_accum, sub_from_withdrawal_accum,
+    },
 };
 use crate::{
     approximate_compounded_interest,
     fraction::FractionExtra,
     liquidation_operations,
     state::{
-        obligation::Obligation, CalculateBorrowResult, CalculateLiquidationResult,
-        CalculateRepayResult, Reserve,
+        obligation::{Obligation, ObligationMut},
+        CalculateBorrowResult, CalculateLiquidationResult, CalculateRepayResult,
+        OwnerWithdrawalCaps, Reserve,
     },
     utils::{
         borrow_rate_curve::BorrowRateCurve, consts::NO_DELEVERAGING_MARKER, AnyAccountLoader,
-        BigFraction, Fraction, GetPriceResult, ELEVATION_GROUP_NONE, PROGRAM_VERSION,
+        BigFraction, Fraction, GetPriceResult, ELEVATION_GROUP_NONE, FULL_BPS, PROGRAM_VERSION,
+        SLOTS_PER_SECOND,
     },
-    xmsg, AssetTier, ElevationGroup, LendingError, LendingMarket, LiquidateAndRedeemResult,
+    xmsg, AdaptiveRateConfig, AssetTier, ElevationGroup, FixedRateAccrual, FixedRateBorrowConfig,
+    FixedRateBorrowTerms, LendingError, LendingMarket, LiquidateAndRedeemResult,
     LiquidateObligationResult, LtvMaxWithdrawalCheck, MaxReservesAsCollateralCheck,
     ObligationCollateral, PriceStatusFlags, ReferrerTokenState, RefreshObligationBorrowsResult,
-    RefreshObligationDepositsResult, ReserveConfig, ReserveStatus, UpdateConfigMode,
+    RefreshObligationDepositsResult, ReserveConfig, ReserveStatus, UpdateConfigMode, WithdrawQueue,
     WithdrawResult,
 };
 use crate::{utils::zip_and_validate_same_length, DepositLiquidityResult};
@@ -51,6 +57,7 @@ pub fn refresh_reserve(
     let slot = clock.slot;
 
     reserve.accrue_interest(slot, referral_fee_bps)?;
+    reserve.record_rate_history_sample(slot)?;
 
     let price_status = if let Some(GetPriceResult {
         price,
@@ -58,6 +65,17 @@ pub fn refresh_reserve(
         timestamp,
     }) = price
     {
+        roll_price_circuit_breaker_window(reserve, price, timestamp);
+        if is_price_circuit_breaker_triggered(reserve, price) {
+            msg!(
+                "Price circuit breaker tripped token=[{}] window_start_price={} new_price={}",
+                reserve.config.token_info.symbol(),
+                Fraction::from_bits(reserve.liquidity.price_circuit_breaker_anchor_price_sf),
+                price,
+            );
+            reserve.liquidity.price_circuit_breaker_tripped_ts = timestamp;
+        }
+
         reserve.liquidity.market_price_sf = price.to_bits();
         reserve.liquidity.market_price_last_updated_ts = timestamp;
 
@@ -76,6 +94,83 @@ pub fn refresh_reserve(
     Ok(())
 }
 
+/// Moves are measured against the price at the start of the window, so a move split across
+/// several refreshes is caught as well. The window restarts once it expires, from the previous
+/// price if it is recent enough, otherwise from the new price.
+fn roll_price_circuit_breaker_window(reserve: &mut Reserve, price: Fraction, timestamp: u64) {
+    let token_info = &reserve.config.token_info;
+    if !token_info.is_price_circuit_breaker_enabled() {
+        return;
+    }
+    let window_seconds = token_info.price_circuit_breaker_window_seconds;
+    let liquidity = &mut reserve.liquidity;
+
+    let anchor_ts = liquidity.price_circuit_breaker_anchor_ts;
+    if anchor_ts > 0
+        && liquidity.price_circuit_breaker_anchor_price_sf > 0
+        && timestamp.saturating_sub(anchor_ts) <= window_seconds
+    {
+        return;
+    }
+
+    let previous_price_ts = liquidity.market_price_last_updated_ts;
+    if liquidity.market_price_sf > 0
+        && previous_price_ts > 0
+        && timestamp.saturating_sub(previous_price_ts) <= window_seconds
+    {
+        liquidity.price_circuit_breaker_anchor_price_sf = liquidity.market_price_sf;
+        liquidity.price_circuit_breaker_anchor_ts = previous_price_ts;
+    } else {
+        liquidity.price_circuit_breaker_anchor_price_sf = price.to_bits();
+        liquidity.price_circuit_breaker_anchor_ts = timestamp;
+    }
+}
+
+fn is_price_circuit_breaker_triggered(reserve: &Reserve, price: Fraction) -> bool {
+    let token_info = &reserve.config.token_info;
+    if !token_info.is_price_circuit_breaker_enabled() {
+        return false;
+    }
+
+    let anchor_price = Fraction::from_bits(reserve.liquidity.price_circuit_breaker_anchor_price_sf);
+    if anchor_price == Fraction::ZERO {
+        return false;
+    }
+
+    let price_change_bps_scaled = Fraction::abs_diff(price, anchor_price) * u128::from(FULL_BPS);
+    let max_change_scaled =
+        anchor_price * u128::from(token_info.price_circuit_breaker_max_change_bps);
+    price_change_bps_scaled > max_change_scaled
+}
+
+fn check_price_circuit_breaker_not_tripped(
+    reserve: &Reserve,
+    obligation: &Obligation,
+) -> Result<()> {
+    if reserve.liquidity.is_price_circuit_breaker_tripped()
+        || obligation.is_price_circuit_breaker_tripped()
+    {
+        msg!(
+            "Price circuit breaker tripped on token=[{}] or another reserve of the obligation",
+            reserve.config.token_info.symbol()
+        );
+        return err!(LendingError::PriceCircuitBreakerTripped);
+    }
+    Ok(())
+}
+
+pub fn clear_price_circuit_breaker(reserve: &mut Reserve) {
+    msg!(
+        "Prv circuit breaker trip timestamp is {:?}",
+        reserve.liquidity.price_circuit_breaker_tripped_ts
+    );
+    reserve.liquidity.price_circuit_breaker_tripped_ts = 0;
+    // Forces a fresh price on the next refresh, outside of the window of the tripping price
+    reserve.liquidity.market_price_last_updated_ts = 0;
+    reserve.liquidity.price_circuit_breaker_anchor_ts = 0;
+    reserve.last_update.mark_stale();
+}
+
 pub fn is_saved_price_age_valid(reserve: &Reserve, current_ts: clock::UnixTimestamp) -> bool {
     let current_ts: u64 = current_ts.try_into().expect("Negative timestamp");
     let price_last_updated_ts = reserve.liquidity.market_price_last_updated_ts;
@@ -161,12 +256,13 @@ pub fn deposit_reserve_liquidity(
 pub fn borrow_obligation_liquidity<'info, T>(
     lending_market: &LendingMarket,
     borrow_reserve: &mut Reserve,
-    obligation: &mut Obligation,
+    obligation: &mut ObligationMut,
     liquidity_amount: u64,
     clock: &Clock,
     borrow_reserve_pk: Pubkey,
     referrer_token_state: Option<RefMut<ReferrerTokenState>>,
     deposit_reserves_iter: impl Iterator<Item = T>,
+    owner_withdrawal_caps: Option<&mut OwnerWithdrawalCaps>,
 ) -> Result<CalculateBorrowResult>
 where
     T: AnyAccountLoader<'info, Reserve>,
@@ -194,6 +290,14 @@ where
 
     obligation.check_not_marked_for_deleveraging()?;
 
+    if matches!(
+        obligation.find_liquidity_in_borrows(borrow_reserve_pk),
+        Ok((liquidity, _)) if liquidity.is_fixed_rate()
+    ) {
+        msg!("Cannot add to a fixed-rate borrow, repay it or borrow from another reserve");
+        return err!(LendingError::InvalidFixedRateBorrow);
+    }
+
     let current_utilization = borrow_reserve.liquidity.utilization_rate();
     let reserve_liquidity_borrowed_f = borrow_reserve.liquidity.total_borrow();
     let liquidity_amount_f = Fraction::from(liquidity_amount);
@@ -209,6 +313,7 @@ where
         return err!(LendingError::BorrowLimitExceeded);
     }
     check_obligation_fully_refreshed_and_not_null(obligation, clock.slot)?;
+    check_price_circuit_breaker_not_tripped(borrow_reserve, obligation)?;
 
     let remaining_borrow_value = obligation.remaining_borrow_value();
     if remaining_borrow_value == Fraction::ZERO {
@@ -245,6 +350,8 @@ where
     let borrow_amount = borrow_amount_f.to_ceil();
     msg!("Requested {}, allowed {}", liquidity_amount, borrow_amount);
 
+    check_withdraw_queue_priority(borrow_reserve, borrow_amount)?;
+
     add_to_withdrawal_accum(
         &mut borrow_reserve.config.debt_withdrawal_cap,
         borrow_amount,
@@ -271,8 +378,16 @@ where
 
         obligation_liquidity.borrow(borrow_amount_f);
 
+        add_to_owner_withdrawal_accum(
+            owner_withdrawal_caps.map(|caps| &mut caps.debt_withdrawal_cap),
+            &borrow_reserve.config.obligation_debt_withdrawal_cap,
+            borrow_amount,
+            u64::try_from(clock.unix_timestamp).unwrap(),
+        )?;
+
         borrow_index
     };
+    check_obligation_reserves_limit(obligation)?;
 
     if let Some(mut referrer_token_state) = referrer_token_state {
         if lending_market.referral_fee_bps > 0 {
@@ -320,7 +435,7 @@ where
         borrow_amount_f,
         obligation,
         borrow_reserve,
-        Fraction::from_bits(obligation.borrows[borrow_index].market_value_sf),
+        Fraction::from_bits(obligation.liquidity(borrow_index).market_value_sf),
         Fraction::from_bits(lending_market.min_net_value_in_obligation_sf),
     )?;
 
@@ -332,14 +447,136 @@ where
     })
 }
 
+#[allow(clippy::too_many_arguments)]
+pub fn borrow_obligation_liquidity_fixed_rate<'info, T>(
+    lending_market: &LendingMarket,
+    borrow_reserve: &mut Reserve,
+    obligation: &mut ObligationMut,
+    liquidity_amount: u64,
+    terms: FixedRateBorrowTerms,
+    clock: &Clock,
+    borrow_reserve_pk: Pubkey,
+    referrer_token_state: Option<RefMut<ReferrerTokenState>>,
+    deposit_reserves_iter: impl Iterator<Item = T>,
+    owner_withdrawal_caps: Option<&mut OwnerWithdrawalCaps>,
+) -> Result<CalculateBorrowResult>
+where
+    T: AnyAccountLoader<'info, Reserve>,
+{
+    let fixed_rate_borrow = borrow_reserve.config.fixed_rate_borrow;
+    if !fixed_rate_borrow.is_enabled() {
+        msg!("Fixed-rate borrowing is disabled for this reserve");
+        return err!(LendingError::InvalidFixedRateBorrow);
+    }
+    if terms.term_seconds == 0 || terms.term_seconds > u64::from(fixed_rate_borrow.max_term_seconds)
+    {
+        msg!(
+            "Fixed-rate term must be in range [1, {}] seconds",
+            fixed_rate_borrow.max_term_seconds
+        );
+        return err!(LendingError::InvalidFixedRateBorrow);
+    }
+    if obligation
+        .find_liquidity_in_borrows(borrow_reserve_pk)
+        .is_ok()
+    {
+        msg!("Fixed-rate borrows must open a new position in the reserve");
+        return err!(LendingError::InvalidFixedRateBorrow);
+    }
+
+    let borrow_result = borrow_obligation_liquidity(
+        lending_market,
+        borrow_reserve,
+        obligation,
+        liquidity_amount,
+        clock,
+        borrow_reserve_pk,
+        referrer_token_state,
+        deposit_reserves_iter,
+        owner_withdrawal_caps,
+    )?;
+
+    // The host fixed rate is part of the floating rate paid by borrowers, the locked rate keeps
+    // it so the referral fees accrued on top of it stay covered
+    let fixed_rate = borrow_reserve.current_borrow_rate()?
+        + Fraction::from_bps(borrow_reserve.config.host_fixed_interest_rate_bps)
+        + Fraction::from_bps(fixed_rate_borrow.premium_bps);
+    let fixed_rate_bps: u64 = fixed_rate
+        .to_bps()
+        .ok_or_else(|| error!(LendingError::MathOverflow))?;
+    if fixed_rate_bps > terms.max_fixed_rate_bps {
+        msg!(
+            "Fixed rate {} bps is above the requested maximum {} bps",
+            fixed_rate_bps,
+            terms.max_fixed_rate_bps
+        );
+        return err!(LendingError::FixedRateAboveMaximum);
+    }
+
+    let (liquidity, _) = obligation.find_liquidity_in_borrows_mut(borrow_reserve_pk)?;
+    liquidity.fixed_borrow_rate_bps = fixed_rate_bps;
+    liquidity.fixed_rate_maturity_slot = clock.slot + terms.term_seconds * SLOTS_PER_SECOND;
+
+    msg!(
+        "Locked borrow rate {} bps until slot {}",
+        fixed_rate_bps,
+        liquidity.fixed_rate_maturity_slot
+    );
+
+    Ok(borrow_result)
+}
+
+pub fn check_delegate_borrow_within_ltv_ceiling(
+    obligation: &Obligation,
+    borrow_reserve: &Reserve,
+    borrow_amount_f: Fraction,
+) -> Result<()> {
+    let borrow_factor_adjusted_value =
+        utils::calculate_market_value_from_liquidity_amount(borrow_reserve, borrow_amount_f)
+            * borrow_reserve.borrow_factor_f(obligation.elevation_group != ELEVATION_GROUP_NONE);
+    check_delegate_ltv_ceiling(obligation, borrow_factor_adjusted_value, Fraction::ZERO)
+}
+
+pub fn check_delegate_withdraw_within_ltv_ceiling(
+    obligation: &Obligation,
+    collateral_before_withdraw: &ObligationCollateral,
+    withdraw_amount: u64,
+) -> Result<()> {
+    let withdraw_value = Fraction::from_bits(collateral_before_withdraw.market_value_sf)
+        * u128::from(withdraw_amount)
+        / u128::from(collateral_before_withdraw.deposited_amount);
+    check_delegate_ltv_ceiling(obligation, Fraction::ZERO, withdraw_value)
+}
+
+fn check_delegate_ltv_ceiling(
+    obligation: &Obligation,
+    added_debt_value: Fraction,
+    removed_deposited_value: Fraction,
+) -> Result<()> {
+    let debt_value =
+        Fraction::from_bits(obligation.borrow_factor_adjusted_debt_value_sf) + added_debt_value;
+    let deposited_value =
+        Fraction::from_bits(obligation.deposited_value_sf).saturating_sub(removed_deposited_value);
+    if debt_value > deposited_value * Fraction::from_percent(obligation.delegate_max_ltv_pct) {
+        msg!(
+            "Delegate action would take the obligation LTV above {}%",
+            obligation.delegate_max_ltv_pct
+        );
+        return err!(LendingError::DelegateLtvCeilingExceeded);
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn deposit_obligation_collateral(
     lending_market: &LendingMarket,
     deposit_reserve: &mut Reserve,
-    obligation: &mut Obligation,
-    slot: Slot,
+    obligation: &mut ObligationMut,
+    clock: &Clock,
     collateral_amount: u64,
     deposit_reserve_pk: Pubkey,
     max_reserves_as_collateral_check: MaxReservesAsCollateralCheck,
+    owner_withdrawal_caps: Option<&mut OwnerWithdrawalCaps>,
 ) -> Result<()> {
     if collateral_amount == 0 {
         msg!("Collateral amount provided cannot be zero");
@@ -348,7 +585,7 @@ pub fn deposit_obligation_collateral(
 
     if deposit_reserve
         .last_update
-        .is_stale(slot, PriceStatusFlags::NONE)?
+        .is_stale(clock.slot, PriceStatusFlags::NONE)?
     {
         msg!("Deposit reserve is stale and must be refreshed in the current slot");
         return err!(LendingError::ReserveStale);
@@ -356,7 +593,7 @@ pub fn deposit_obligation_collateral(
 
     if obligation
         .last_update
-        .is_stale(slot, PriceStatusFlags::NONE)?
+        .is_stale(clock.slot, PriceStatusFlags::NONE)?
     {
         msg!("Obligation is stale and must be refreshed in the current slot");
         return err!(LendingError::ObligationStale);
@@ -382,6 +619,12 @@ pub fn deposit_obligation_collateral(
     let pre_deposit_count = obligation.deposits_count();
     let total_borrowed_amount = obligation.get_borrowed_amount_if_single_token();
     let asset_tier = deposit_reserve.config.get_asset_tier();
+    let check_reserves_limit =
+        max_reserves_as_collateral_check == MaxReservesAsCollateralCheck::Perform;
+    let liquidity_amount = deposit_reserve
+        .collateral_exchange_rate()
+        .collateral_to_liquidity(collateral_amount);
+    let obligation_withdrawal_cap = deposit_reserve.config.obligation_deposit_withdrawal_cap;
 
     let new_deposit_initializer = |obligation_collateral: &mut ObligationCollateral| -> Result<()> {
         utils::update_elevation_group_debt_trackers_on_new_deposit(
@@ -403,10 +646,20 @@ pub fn deposit_obligation_collateral(
         )?;
 
         obligation_collateral.deposit(collateral_amount)?;
+        sub_from_owner_withdrawal_accum(
+            owner_withdrawal_caps.map(|caps| &mut caps.deposit_withdrawal_cap),
+            &obligation_withdrawal_cap,
+            liquidity_amount,
+            u64::try_from(clock.unix_timestamp).unwrap(),
+        )?;
 
         Fraction::from_bits(obligation_collateral.market_value_sf)
     };
 
+    if check_reserves_limit {
+        check_obligation_reserves_limit(obligation)?;
+    }
+
     obligation.last_update.mark_stale();
 
     deposit_reserve.last_update.mark_stale();
@@ -425,14 +678,16 @@ pub fn deposit_obligation_collateral(
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn withdraw_obligation_collateral(
     lending_market: &LendingMarket,
     withdraw_reserve: &mut Reserve,
-    obligation: &mut Obligation,
+    obligation: &mut ObligationMut,
     collateral_amount: u64,
-    slot: Slot,
+    clock: &Clock,
     withdraw_reserve_pk: Pubkey,
     ltv_max_withdrawal_check: LtvMaxWithdrawalCheck,
+    owner_withdrawal_caps: Option<&mut OwnerWithdrawalCaps>,
 ) -> Result<u64> {
     if collateral_amount == 0 {
         return err!(LendingError::InvalidAmount);
@@ -448,7 +703,7 @@ pub fn withdraw_obligation_collateral(
 
     if withdraw_reserve
         .last_update
-        .is_stale(slot, required_price_status)?
+        .is_stale(clock.slot, required_price_status)?
     {
         msg!(
             "Withdraw reserve is stale and must be refreshed in the current slot, price status: {:08b}",
@@ -459,7 +714,7 @@ pub fn withdraw_obligation_collateral(
 
     if obligation
         .last_update
-        .is_stale(slot, required_price_status)?
+        .is_stale(clock.slot, required_price_status)?
     {
         msg!(
             "Obligation is stale and must be refreshed in the current slot, price status: {:08b}",
@@ -469,12 +724,13 @@ pub fn withdraw_obligation_collateral(
     }
 
     let collateral_index = obligation.position_of_collateral_in_deposits(withdraw_reserve_pk)?;
-    let collateral = &obligation.deposits[collateral_index];
+    let collateral = obligation.collateral(collateral_index);
     if collateral.deposited_amount == 0 {
         return err!(LendingError::ObligationCollateralEmpty);
     }
 
     if !is_borrows_empty {
+        check_price_circuit_breaker_not_tripped(withdraw_reserve, obligation)?;
         check_elevation_group_borrowing_enabled(lending_market, obligation)?;
     }
 
@@ -555,6 +811,14 @@ pub fn withdraw_obligation_collateral(
 
     let previous_debt_in_elevation_group =
         collateral.borrowed_amount_against_this_collateral_in_elevation_group;
+    add_to_owner_withdrawal_accum(
+        owner_withdrawal_caps.map(|caps| &mut caps.deposit_withdrawal_cap),
+        &withdraw_reserve.config.obligation_deposit_withdrawal_cap,
+        withdraw_reserve
+            .collateral_exchange_rate()
+            .collateral_to_liquidity(withdraw_amount),
+        u64::try_from(clock.unix_timestamp).unwrap(),
+    )?;
     let is_full_withdrawal = obligation.withdraw(withdraw_amount, collateral_index)?;
     obligation.last_update.mark_stale();
 
@@ -573,7 +837,7 @@ pub fn withdraw_obligation_collateral(
         obligation,
         withdraw_reserve,
         reserve_liq_threshold_ltv_pct,
-        Fraction::from_bits(obligation.deposits[collateral_index].market_value_sf),
+        Fraction::from_bits(obligation.collateral(collateral_index).market_value_sf),
         Fraction::from_bits(lending_market.min_net_value_in_obligation_sf),
     )?;
 
@@ -599,6 +863,15 @@ pub fn redeem_reserve_collateral(
         return err!(LendingError::ReserveStale);
     }
 
+    if add_amount_to_withdrawal_caps {
+        check_withdraw_queue_priority(
+            reserve,
+            reserve
+                .collateral_exchange_rate()
+                .collateral_to_liquidity(collateral_amount),
+        )?;
+    }
+
     let liquidity_amount = reserve.redeem_collateral(collateral_amount)?;
     let timestamp = u64::try_from(clock.unix_timestamp).unwrap();
     refresh_reserve_limit_timestamps(reserve, timestamp);
@@ -615,6 +888,194 @@ pub fn redeem_reserve_collateral(
     Ok(liquidity_amount)
 }
 
+pub fn enqueue_withdrawal(
+    lending_market: &LendingMarket,
+    reserve: &mut Reserve,
+    withdraw_queue: &mut WithdrawQueue,
+    owner: Pubkey,
+    collateral_amount: u64,
+    clock: &Clock,
+) -> Result<u64> {
+    if collateral_amount == 0 {
+        msg!("Collateral amount provided cannot be zero");
+        return err!(LendingError::InvalidAmount);
+    }
+
+    if reserve
+        .last_update
+        .is_stale(clock.slot, PriceStatusFlags::NONE)?
+    {
+        msg!("Reserve is stale and must be refreshed in the current slot");
+        return err!(LendingError::ReserveStale);
+    }
+
+    let liquidity_amount = reserve
+        .collateral_exchange_rate()
+        .collateral_to_liquidity(collateral_amount);
+    if liquidity_amount < lending_market.min_initial_deposit_amount {
+        msg!(
+            "Withdraw ticket of {} liquidity is below the minimum of {}",
+            liquidity_amount,
+            lending_market.min_initial_deposit_amount
+        );
+        return err!(LendingError::WithdrawTicketTooSmall);
+    }
+
+    let unreserved_liquidity = reserve.unreserved_available_liquidity();
+    if liquidity_amount <= unreserved_liquidity {
+        msg!(
+            "Reserve has {} liquidity available for a withdrawal of {}, redeem directly",
+            unreserved_liquidity,
+            liquidity_amount
+        );
+        return err!(LendingError::WithdrawQueueNotRequired);
+    }
+
+    let ticket_id = withdraw_queue.enqueue(
+        owner,
+        collateral_amount,
+        u64::try_from(clock.unix_timestamp).unwrap(),
+    )?;
+    reserve.withdraw_queue_collateral_amount += collateral_amount;
+
+    Ok(ticket_id)
+}
+
+/// Fills the tickets in FIFO order with the available liquidity, within the reserve
+/// withdrawal cap. The reserve has to be refreshed earlier in the instruction.
+pub fn fill_withdraw_queue(
+    reserve: &mut Reserve,
+    withdraw_queue: &mut WithdrawQueue,
+    timestamp: u64,
+) -> Result<u64> {
+    let mut filled_liquidity_amount = 0;
+    for ticket in withdraw_queue.tickets_mut() {
+        if ticket.collateral_amount == 0 {
+            continue;
+        }
+
+        let withdrawal_capacity =
+            remaining_withdrawal_capacity(&mut reserve.config.deposit_withdrawal_cap, timestamp)?;
+        let fillable_collateral_amount = reserve
+            .collateral_exchange_rate()
+            .liquidity_to_collateral(min(reserve.liquidity.available_amount, withdrawal_capacity));
+        let collateral_amount = min(ticket.collateral_amount, fillable_collateral_amount);
+        if collateral_amount == 0 {
+            break;
+        }
+
+        let liquidity_amount = reserve.redeem_collateral(collateral_amount)?;
+        add_to_withdrawal_accum(
+            &mut reserve.config.deposit_withdrawal_cap,
+            liquidity_amount,
+            timestamp,
+        )?;
+        reserve.withdraw_queue_collateral_amount -= collateral_amount;
+        ticket.collateral_amount -= collateral_amount;
+        ticket.filled_liquidity_amount += liquidity_amount;
+        filled_liquidity_amount += liquidity_amount;
+
+        xmsg!(
+            "Filled withdraw ticket {} with {} collateral for {} liquidity",
+            ticket.id,
+            collateral_amount,
+            liquidity_amount
+        );
+    }
+
+    if filled_liquidity_amount > 0 {
+        withdraw_queue.queued_collateral_amount = reserve.withdraw_queue_collateral_amount;
+        withdraw_queue.filled_liquidity_amount += filled_liquidity_amount;
+        reserve.last_update.mark_stale();
+    }
+
+    Ok(filled_liquidity_amount)
+}
+
+pub fn cancel_withdrawal(
+    reserve: &mut Reserve,
+    withdraw_queue: &mut WithdrawQueue,
+    owner: Pubkey,
+    ticket_id: u64,
+) -> Result<u64> {
+    let ticket_index = withdraw_queue.position_of_ticket(owner, ticket_id)?;
+    let ticket = &mut withdraw_queue.tickets[ticket_index];
+    let collateral_amount = ticket.collateral_amount;
+    if collateral_amount == 0 {
+        msg!(
+            "Withdraw ticket {} has no collateral left to cancel",
+            ticket_id
+        );
+        return err!(LendingError::WithdrawTicketAlreadyFilled);
+    }
+
+    ticket.collateral_amount = 0;
+    withdraw_queue.queued_collateral_amount -= collateral_amount;
+    reserve.withdraw_queue_collateral_amount -= collateral_amount;
+    withdraw_queue.remove_settled_tickets();
+
+    Ok(collateral_amount)
+}
+
+pub fn claim_withdrawals(
+    reserve: &mut Reserve,
+    withdraw_queue: &mut WithdrawQueue,
+    owner: Pubkey,
+    clock: &Clock,
+) -> Result<u64> {
+    if reserve
+        .last_update
+        .is_stale(clock.slot, PriceStatusFlags::NONE)?
+    {
+        msg!("Reserve is stale and must be refreshed in the current slot");
+        return err!(LendingError::ReserveStale);
+    }
+
+    fill_withdraw_queue(
+        reserve,
+        withdraw_queue,
+        u64::try_from(clock.unix_timestamp).unwrap(),
+    )?;
+
+    let mut claimed_liquidity_amount = 0;
+    for ticket in withdraw_queue
+        .tickets_mut()
+        .iter_mut()
+        .filter(|ticket| ticket.owner == owner)
+    {
+        claimed_liquidity_amount += ticket.filled_liquidity_amount;
+        ticket.filled_liquidity_amount = 0;
+    }
+
+    if claimed_liquidity_amount == 0 {
+        msg!("No filled withdraw ticket for {}", owner);
+        return err!(LendingError::WithdrawTicketNotFilled);
+    }
+
+    withdraw_queue.filled_liquidity_amount -= claimed_liquidity_amount;
+    withdraw_queue.remove_settled_tickets();
+
+    Ok(claimed_liquidity_amount)
+}
+
+fn check_withdraw_queue_priority(reserve: &Reserve, liquidity_amount: u64) -> Result<()> {
+    if reserve.withdraw_queue_collateral_amount == 0 {
+        return Ok(());
+    }
+
+    let unreserved_liquidity = reserve.unreserved_available_liquidity();
+    if liquidity_amount > unreserved_liquidity {
+        msg!(
+            "Only {} liquidity is available outside the withdraw queue, requested {}",
+            unreserved_liquidity,
+            liquidity_amount
+        );
+        return err!(LendingError::LiquidityReservedForWithdrawQueue);
+    }
+
+    Ok(())
+}
+
 pub fn redeem_fees(reserve: &mut Reserve, slot: Slot) -> Result<u64> {
     if reserve.last_update.is_stale(slot, PriceStatusFlags::NONE)? {
         msg!(
@@ -636,14 +1097,16 @@ pub fn redeem_fees(reserve: &mut Reserve, slot: Slot) -> Result<u64> {
     Ok(withdraw_amount)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn repay_obligation_liquidity<'info, T>(
     repay_reserve: &mut Reserve,
-    obligation: &mut Obligation,
+    obligation: &mut ObligationMut,
     clock: &Clock,
     liquidity_amount: u64,
     repay_reserve_pk: Pubkey,
     lending_market: &LendingMarket,
     deposit_reserves_iter: impl Iterator<Item = T>,
+    owner_withdrawal_caps: Option<&mut OwnerWithdrawalCaps>,
 ) -> Result<u64>
 where
     T: AnyAccountLoader<'info, Reserve>,
@@ -667,6 +1130,7 @@ where
         msg!("Obligation is stale and must be refreshed in the current slot");
         return err!(LendingError::ObligationStale);
     }
+    let obligation_slots_elapsed = obligation.last_update.slots_elapsed(clock.slot)?;
 
     let (liquidity, liquidity_index) =
         obligation.find_liquidity_in_borrows_mut(repay_reserve_pk)?;
@@ -675,9 +1139,12 @@ where
         return err!(LendingError::ObligationLiquidityEmpty);
     }
 
-    let cumulative_borrow_rate =
-        BigFraction::from(repay_reserve.liquidity.cumulative_borrow_rate_bsf);
-    liquidity.accrue_interest(cumulative_borrow_rate)?;
+    utils::accrue_borrow_interest(
+        liquidity,
+        repay_reserve,
+        clock.slot,
+        obligation_slots_elapsed,
+    )?;
 
     let CalculateRepayResult {
         settle_amount_f: settle_amount,
@@ -692,11 +1159,27 @@ where
         return err!(LendingError::RepayTooSmall);
     }
 
+    // Repaying a fixed-rate borrow before maturity has no penalty: the interest accrued at the
+    // locked rate so far is owed and the remaining debt keeps the locked rate until maturity
+    if liquidity.is_fixed_rate() {
+        msg!(
+            "Early repay of a fixed-rate borrow, the remaining debt stays locked at {} bps until slot {}",
+            liquidity.fixed_borrow_rate_bps,
+            liquidity.fixed_rate_maturity_slot
+        );
+    }
+
     sub_from_withdrawal_accum(
         &mut repay_reserve.config.debt_withdrawal_cap,
         repay_amount,
         u64::try_from(clock.unix_timestamp).unwrap(),
     )?;
+    sub_from_owner_withdrawal_accum(
+        owner_withdrawal_caps.map(|caps| &mut caps.debt_withdrawal_cap),
+        &repay_reserve.config.obligation_debt_withdrawal_cap,
+        repay_amount,
+        u64::try_from(clock.unix_timestamp).unwrap(),
+    )?;
 
     update_elevation_group_debt_trackers_on_repay(
         repay_amount,
@@ -717,7 +1200,7 @@ where
         settle_amount,
         obligation,
         repay_reserve,
-        Fraction::from_bits(obligation.borrows[liquidity_index].market_value_sf),
+        Fraction::from_bits(obligation.liquidity(liquidity_index).market_value_sf),
         Fraction::from_bits(lending_market.min_net_value_in_obligation_sf),
     )?;
 
@@ -727,7 +1210,7 @@ where
 #[allow(clippy::too_many_arguments)]
 pub fn request_elevation_group<'info, T, U>(
     program_id: &Pubkey,
-    obligation: &mut Obligation,
+    obligation: &mut ObligationMut,
     lending_market: &LendingMarket,
     slot: Slot,
     new_elevation_group: u8,
@@ -862,7 +1345,7 @@ fn check_ltv_not_worse_if_marked_for_deleveraging(
 }
 
 fn reset_elevation_group_debts<'info, T>(
-    obligation: &mut Obligation,
+    obligation: &mut ObligationMut,
     elevation_group: Option<&ElevationGroup>,
     deposit_reserves_iter: impl Iterator<Item = T> + Clone,
     borrow_reserves_iter: impl Iterator<Item = T> + Clone,
@@ -874,8 +1357,7 @@ where
         let elevation_group_index = elevation_group.get_index();
         let deposits_and_reserves = zip_and_validate_same_length(
             obligation
-                .deposits
-                .iter_mut()
+                .deposits_mut()
                 .filter(|deposit| deposit.deposit_reserve != Pubkey::default()),
             deposit_reserves_iter,
         );
@@ -900,8 +1382,7 @@ where
     } else {
         let borrows_and_reserves = zip_and_validate_same_length(
             obligation
-                .borrows
-                .iter_mut()
+                .borrows_mut()
                 .filter(|borrow| borrow.borrow_reserve != Pubkey::default()),
             borrow_reserves_iter,
         );
@@ -921,7 +1402,7 @@ where
 }
 
 pub fn refresh_obligation_deposits<'info, T>(
-    obligation: &mut Obligation,
+    obligation: &mut ObligationMut,
     lending_market: &LendingMarket,
     slot: Slot,
     max_reserves_as_collateral_check: MaxReservesAsCollateralCheck,
@@ -939,6 +1420,7 @@ where
     let mut unhealthy_borrow_value = Fraction::ZERO;
     let mut num_of_obsolete_reserves = 0;
     let mut prices_state = PriceStatusFlags::all();
+    let mut price_circuit_breaker_tripped = false;
     let mut borrowing_disabled = false;
     let mut collaterals_count = 0;
 
@@ -953,11 +1435,10 @@ where
         }
     };
 
-    for (index, deposit) in obligation
-        .deposits
-        .iter_mut()
+    for (index, (deposit, deposit_asset_tier)) in obligation
+        .deposits_with_asset_tiers_mut()
         .enumerate()
-        .filter(|(_, deposit)| deposit.deposit_reserve != Pubkey::default())
+        .filter(|(_, (deposit, _))| deposit.deposit_reserve != Pubkey::default())
     {
         let deposit_reserve = reserves_iter
             .next()
@@ -1037,9 +1518,11 @@ where
         unhealthy_borrow_value +=
             market_value_f * Fraction::from_percent(coll_liquidation_threshold_pct);
 
-        obligation.deposits_asset_tiers[index] = deposit_reserve.config.asset_tier;
+        *deposit_asset_tier = deposit_reserve.config.asset_tier;
 
         prices_state &= deposit_reserve.last_update.get_price_status();
+        price_circuit_breaker_tripped |=
+            deposit_reserve.liquidity.is_price_circuit_breaker_tripped();
 
         xmsg!(
             "Deposit: {} amount: {} value: {}",
@@ -1053,6 +1536,7 @@ where
     }
 
     if max_reserves_as_collateral_check == MaxReservesAsCollateralCheck::Perform {
+        check_obligation_reserves_limit(obligation)?;
         if let Some(elevation_group) = elevation_group {
             require_gte!(
                 elevation_group.max_reserves_as_collateral,
@@ -1070,13 +1554,14 @@ where
         allowed_borrow_value_f: allowed_borrow_value,
         unhealthy_borrow_value_f: unhealthy_borrow_value,
         prices_state,
+        price_circuit_breaker_tripped,
         borrowing_disabled,
     })
 }
 
 pub fn refresh_obligation_borrows<'info, T, U>(
     program_id: &Pubkey,
-    obligation: &mut Obligation,
+    obligation: &mut ObligationMut,
     lending_market: &LendingMarket,
     slot: u64,
     elevation_group: Option<&ElevationGroup>,
@@ -1090,17 +1575,19 @@ where
     let mut borrowed_assets_market_value = Fraction::ZERO;
     let mut borrow_factor_adjusted_debt_value = Fraction::ZERO;
     let mut prices_state = PriceStatusFlags::all();
+    let mut price_circuit_breaker_tripped = false;
     let mut highest_borrow_factor_f = Fraction::ONE;
 
     let obligation_has_referrer = obligation.has_referrer();
+    let obligation_referrer = obligation.referrer;
+    let slots_elapsed = obligation.last_update.slots_elapsed(slot)?;
     let mut borrowed_amounts_accumulator_for_elevation_group = 0_u64;
     let mut num_borrow_reserves = 0;
 
-    for (index, borrow) in obligation
-        .borrows
-        .iter_mut()
+    for (index, (borrow, borrow_asset_tier)) in obligation
+        .borrows_with_asset_tiers_mut()
         .enumerate()
-        .filter(|(_, borrow)| borrow.borrow_reserve != Pubkey::default())
+        .filter(|(_, (borrow, _))| borrow.borrow_reserve != Pubkey::default())
     {
         num_borrow_reserves += 1;
         let borrow_reserve = reserves_iter
@@ -1121,12 +1608,9 @@ where
             slot,
         )?;
 
-        let cumulative_borrow_rate_bf =
-            BigFraction::from(borrow_reserve.liquidity.cumulative_borrow_rate_bsf);
-
         let previous_borrowed_amount_f = Fraction::from_bits(borrow.borrowed_amount_sf);
 
-        borrow.accrue_interest(cumulative_borrow_rate_bf)?;
+        utils::accrue_borrow_interest(borrow, borrow_reserve, slot, slots_elapsed)?;
 
         let borrowed_amount_f = Fraction::from_bits(borrow.borrowed_amount_sf);
         let borrowed_amount = borrowed_amount_f.to_ceil::<u64>();
@@ -1158,9 +1642,9 @@ where
             program_id,
             borrow_reserve_info_key,
             borrow_reserve,
-            &obligation.referrer,
+            &obligation_referrer,
             lending_market.referral_fee_bps,
-            obligation.last_update.slots_elapsed(slot)?,
+            slots_elapsed,
             borrowed_amount_f,
             previous_borrowed_amount_f,
             obligation_has_referrer,
@@ -1185,11 +1669,11 @@ where
 
         borrow_factor_adjusted_debt_value += borrow_factor_adjusted_market_value;
 
-        obligation.borrows_asset_tiers[index] = borrow_reserve.config.asset_tier;
-
-        obligation.has_debt = 1;
+        *borrow_asset_tier = borrow_reserve.config.asset_tier;
 
         prices_state &= borrow_reserve.last_update.get_price_status();
+        price_circuit_breaker_tripped |=
+            borrow_reserve.liquidity.is_price_circuit_breaker_tripped();
 
         xmsg!(
             "Borrow: {} amount: {} value: {} value_bf: {}",
@@ -1200,6 +1684,10 @@ where
         );
     }
 
+    if num_borrow_reserves > 0 {
+        obligation.has_debt = 1;
+    }
+
     let borrowed_amount_in_elevation_group = if elevation_group.is_some() {
         require!(
             num_borrow_reserves <= 1,
@@ -1215,6 +1703,7 @@ where
         borrow_factor_adjusted_debt_value_f: borrow_factor_adjusted_debt_value,
         borrowed_amount_in_elevation_group,
         prices_state,
+        price_circuit_breaker_tripped,
         highest_borrow_factor_pct: highest_borrow_factor_f.to_percent::<u64>().unwrap(),
     })
 }
@@ -1222,7 +1711,7 @@ where
 #[allow(clippy::too_many_arguments)]
 pub fn refresh_obligation<'info, T, U>(
     program_id: &Pubkey,
-    obligation: &mut Obligation,
+    obligation: &mut ObligationMut,
     lending_market: &LendingMarket,
     slot: Slot,
     max_reserves_as_collateral_check: MaxReservesAsCollateralCheck,
@@ -1240,6 +1729,7 @@ where
         borrow_factor_adjusted_debt_value_f,
         borrowed_assets_market_value_f,
         prices_state: borrows_prices_state,
+        price_circuit_breaker_tripped: borrows_price_circuit_breaker_tripped,
         borrowed_amount_in_elevation_group,
         highest_borrow_factor_pct,
     } = refresh_obligation_borrows(
@@ -1260,6 +1750,7 @@ where
         allowed_borrow_value_f: allowed_borrow_value,
         unhealthy_borrow_value_f: unhealthy_borrow_value,
         prices_state: deposits_prices_state,
+        price_circuit_breaker_tripped: deposits_price_circuit_breaker_tripped,
         borrowing_disabled,
     } = refresh_obligation_deposits(
         obligation,
@@ -1293,6 +1784,8 @@ where
 
     obligation.borrowing_disabled = borrowing_disabled.into();
     obligation.highest_borrow_factor_pct = highest_borrow_factor_pct;
+    obligation.price_circuit_breaker_tripped =
+        (deposits_price_circuit_breaker_tripped || borrows_price_circuit_breaker_tripped).into();
 
     let prices_state = deposits_prices_state.intersection(borrows_prices_state);
     obligation.last_update.update_slot(slot, Some(prices_state));
@@ -1305,7 +1798,7 @@ pub fn liquidate_and_redeem<'info, T>(
     lending_market: &LendingMarket,
     repay_reserve: &dyn AnyAccountLoader<Reserve>,
     withdraw_reserve: &dyn AnyAccountLoader<Reserve>,
-    obligation: &mut Obligation,
+    obligation: &mut ObligationMut,
     clock: &Clock,
     liquidity_amount: u64,
     min_acceptable_received_liquidity_amount: u64,
@@ -1349,6 +1842,7 @@ where
         withdraw_amount,
         total_withdraw_liquidity_amount,
         withdraw_collateral_amount,
+        liquidation_bonus_rate,
     })
 }
 
@@ -1357,7 +1851,7 @@ pub fn liquidate_obligation<'info, T>(
     lending_market: &LendingMarket,
     repay_reserve: &dyn AnyAccountLoader<Reserve>,
     withdraw_reserve: &dyn AnyAccountLoader<Reserve>,
-    obligation: &mut Obligation,
+    obligation: &mut ObligationMut,
     clock: &Clock,
     liquidity_amount: u64,
     max_allowed_ltv_override_pct_opt: Option<u64>,
@@ -1371,6 +1865,16 @@ where
         lending_market.liquidation_max_debt_close_factor_pct,
         lending_market.max_liquidatable_debt_market_value_at_once
     );
+    let obligation_slots_elapsed = obligation.last_update.slots_elapsed(clock.slot)?;
+    if let Ok((liquidity, _)) = obligation.find_liquidity_in_borrows_mut(repay_reserve.get_pubkey())
+    {
+        utils::accrue_borrow_interest(
+            liquidity,
+            &mut *repay_reserve.get_mut()?,
+            clock.slot,
+            obligation_slots_elapsed,
+        )?;
+    }
     let repay_reserve_ref = repay_reserve.get()?;
     let withdraw_reserve_ref = withdraw_reserve.get()?;
 
@@ -1402,7 +1906,7 @@ where
 
     let collateral_index =
         obligation.position_of_collateral_in_deposits(withdraw_reserve.get_pubkey())?;
-    let collateral = &obligation.deposits[collateral_index];
+    let collateral = obligation.collateral(collateral_index);
     if collateral.market_value_sf == 0 {
         msg!("Obligation deposit value is zero");
         return err!(LendingError::ObligationCollateralEmpty);
@@ -1450,8 +1954,8 @@ where
             deposit_reserves_iter,
         )?;
 
-        previous_borrowed_amount_against_this_collateral_in_elevation_group = obligation.deposits
-            [collateral_index]
+        previous_borrowed_amount_against_this_collateral_in_elevation_group = obligation
+            .collateral(collateral_index)
             .borrowed_amount_against_this_collateral_in_elevation_group;
 
         utils::repay_and_withdraw_from_obligation_post_liquidation(
@@ -1605,7 +2109,7 @@ where
 pub fn socialize_loss<'info, T>(
     reserve: &mut Reserve,
     reserve_pk: &Pubkey,
-    obligation: &mut Obligation,
+    obligation: &mut ObligationMut,
     liquidity_amount: u64,
     slot: u64,
     deposit_reserves_iter: impl Iterator<Item = T>,
@@ -1835,7 +2339,14 @@ pub fn withdraw_referrer_fees(
     Ok(withdraw_amount)
 }
 
-pub fn update_reserve_config(reserve: &mut Reserve, mode: UpdateConfigMode, value: &[u8]) {
+pub fn update_reserve_config(
+    reserve: &mut Reserve,
+    mode: UpdateConfigMode,
+    value: &[u8],
+    risk_reducing_only: bool,
+) -> Result<()> {
+    let previous_config = reserve.config;
+
     match mode {
         UpdateConfigMode::UpdateLoanToValuePct => {
             let new = value[0];
@@ -2040,6 +2551,28 @@ pub fn update_reserve_config(reserve: &mut Reserve, mode: UpdateConfigMode, valu
             msg!("Prv Value is {:?}", prv);
             msg!("New Value is {:?}", new);
         }
+        UpdateConfigMode::UpdateAdaptiveRateConfig => {
+            let new: AdaptiveRateConfig = BorshDeserialize::deserialize(&mut &value[..]).unwrap();
+            let prv = reserve.config.adaptive_rate;
+            reserve.config.adaptive_rate = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateFixedRateBorrowConfig => {
+            let new: FixedRateBorrowConfig =
+                BorshDeserialize::deserialize(&mut &value[..]).unwrap();
+            let prv = reserve.config.fixed_rate_borrow;
+            reserve.config.fixed_rate_borrow = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateRateHistorySampleInterval => {
+            let new = u64::from_le_bytes(value[..8].try_into().unwrap());
+            let prv = reserve.config.rate_history_sample_interval_slots;
+            reserve.config.rate_history_sample_interval_slots = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
         UpdateConfigMode::UpdateEntireReserveConfig => {
             let new: ReserveConfig = BorshDeserialize::deserialize(&mut &value[..]).unwrap();
             reserve.config = new;
@@ -2099,6 +2632,46 @@ pub fn update_reserve_config(reserve: &mut Reserve, mode: UpdateConfigMode, valu
                 interval_length_seconds
             );
         }
+        UpdateConfigMode::UpdateObligationDepositWithdrawalCap => {
+            let capacity = u64::from_le_bytes(value[..8].try_into().unwrap());
+            let interval_length_seconds = u64::from_le_bytes(value[8..16].try_into().unwrap());
+
+            let prv = reserve.config.obligation_deposit_withdrawal_cap;
+            reserve
+                .config
+                .obligation_deposit_withdrawal_cap
+                .config_capacity = capacity.try_into().unwrap();
+            reserve
+                .config
+                .obligation_deposit_withdrawal_cap
+                .config_interval_length_seconds = interval_length_seconds;
+
+            msg!("Prv Value is {:?}", prv);
+            msg!(
+                "New Value is {:?}",
+                reserve.config.obligation_deposit_withdrawal_cap
+            );
+        }
+        UpdateConfigMode::UpdateObligationDebtWithdrawalCap => {
+            let capacity = u64::from_le_bytes(value[..8].try_into().unwrap());
+            let interval_length_seconds = u64::from_le_bytes(value[8..16].try_into().unwrap());
+
+            let prv = reserve.config.obligation_debt_withdrawal_cap;
+            reserve
+                .config
+                .obligation_debt_withdrawal_cap
+                .config_capacity = capacity.try_into().unwrap();
+            reserve
+                .config
+                .obligation_debt_withdrawal_cap
+                .config_interval_length_seconds = interval_length_seconds;
+
+            msg!("Prv Value is {:?}", prv);
+            msg!(
+                "New Value is {:?}",
+                reserve.config.obligation_debt_withdrawal_cap
+            );
+        }
         UpdateConfigMode::UpdateDebtWithdrawalCapCurrentTotal => {
             let new = u64::from_le_bytes(value[..8].try_into().unwrap());
             let prv = reserve.config.debt_withdrawal_cap.current_total;
@@ -2258,9 +2831,139 @@ pub fn update_reserve_config(reserve: &mut Reserve, mode: UpdateConfigMode, valu
             msg!("Prv Value is {:?}", prv);
             msg!("New Value is {:?}", new);
         }
+        UpdateConfigMode::UpdateTokenInfoPriceAggregationMode => {
+            let new = value[0];
+            let prv = reserve.config.token_info.price_aggregation_mode;
+            reserve.config.token_info.price_aggregation_mode = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateTokenInfoPrimaryPriceSource => {
+            let new = value[0];
+            let prv = reserve.config.token_info.primary_price_source;
+            reserve.config.token_info.primary_price_source = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateTokenInfoMaxPriceSourcesDivergence => {
+            let new = u64::from_le_bytes(value[..8].try_into().unwrap());
+            let prv = reserve.config.token_info.max_price_sources_divergence_bps;
+            reserve.config.token_info.max_price_sources_divergence_bps = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateTokenInfoMaxConfidenceBps => {
+            let new = u64::from_le_bytes(value[..8].try_into().unwrap());
+            let prv = reserve.config.token_info.max_confidence_bps;
+            reserve.config.token_info.max_confidence_bps = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateTokenInfoRequireConfidence => {
+            let new = value[0];
+            let prv = reserve.config.token_info.require_confidence;
+            reserve.config.token_info.require_confidence = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateTokenInfoPythMinVerificationSignatures => {
+            let new = value[0];
+            let prv = reserve.config.token_info.pyth_min_verification_signatures;
+            reserve.config.token_info.pyth_min_verification_signatures = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateTokenInfoPythMaxPostedSlotLag => {
+            let new = u64::from_le_bytes(value[..8].try_into().unwrap());
+            let prv = reserve.config.token_info.pyth_max_posted_slot_lag;
+            reserve.config.token_info.pyth_max_posted_slot_lag = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdatePriceCircuitBreakerMaxChangeBps => {
+            let new = u64::from_le_bytes(value[..8].try_into().unwrap());
+            let prv = reserve
+                .config
+                .token_info
+                .price_circuit_breaker_max_change_bps;
+            reserve
+                .config
+                .token_info
+                .price_circuit_breaker_max_change_bps = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdatePriceCircuitBreakerWindowSeconds => {
+            let new = u64::from_le_bytes(value[..8].try_into().unwrap());
+            let prv = reserve
+                .config
+                .token_info
+                .price_circuit_breaker_window_seconds;
+            reserve
+                .config
+                .token_info
+                .price_circuit_breaker_window_seconds = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateTokenInfoFixedPriceValue => {
+            let new = u64::from_le_bytes(value[..8].try_into().unwrap());
+            let prv = reserve.config.token_info.fixed_price_configuration.value;
+            reserve.config.token_info.fixed_price_configuration.value = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateTokenInfoFixedPriceExp => {
+            let new = u64::from_le_bytes(value[..8].try_into().unwrap());
+            let prv = reserve.config.token_info.fixed_price_configuration.exp;
+            reserve.config.token_info.fixed_price_configuration.exp = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateTokenInfoDerivedPriceBaseReserve => {
+            let new: [u8; 32] = value[0..32].try_into().unwrap();
+            let new = Pubkey::new_from_array(new);
+            let prv = reserve
+                .config
+                .token_info
+                .derived_price_configuration
+                .base_reserve;
+            reserve
+                .config
+                .token_info
+                .derived_price_configuration
+                .base_reserve = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+        UpdateConfigMode::UpdateTokenInfoDerivedPriceStakePool => {
+            let new: [u8; 32] = value[0..32].try_into().unwrap();
+            let new = Pubkey::new_from_array(new);
+            let prv = reserve
+                .config
+                .token_info
+                .derived_price_configuration
+                .stake_pool;
+            reserve
+                .config
+                .token_info
+                .derived_price_configuration
+                .stake_pool = new;
+            msg!("Prv Value is {:?}", prv);
+            msg!("New Value is {:?}", new);
+        }
+    }
+
+    if risk_reducing_only
+        && !utils::is_reserve_config_change_risk_reducing(mode, &previous_config, &reserve.config)
+    {
+        msg!("Mode {:?} with this value does not reduce risk", mode);
+        return err!(LendingError::ConfigChangeNotRiskReducing);
     }
 
     reserve.last_update.mark_stale();
+
+    Ok(())
 }
 
 pub mod utils {
@@ -2272,12 +2975,40 @@ pub mod utils {
     use crate::{
         fraction::FRACTION_ONE_SCALED,
         state::ReserveConfig,
-        utils::{ten_pow, ELEVATION_GROUP_NONE, FULL_BPS, MAX_NUM_ELEVATION_GROUPS},
+        utils::{
+            ten_pow, ELEVATION_GROUP_NONE, FULL_BPS, MAX_NUM_ELEVATION_GROUPS,
+            MAX_OBLIGATION_RESERVES, MAX_RATE_HISTORY_SAMPLE_INTERVAL_SLOTS,
+        },
         ElevationGroup, ObligationCollateral, ObligationLiquidity,
     };
 
+    /// Accrues the interest of a borrow up to the cumulative borrow rate of its reserve. Fixed-rate
+    /// debt accrues at the locked rate instead, and the reserve is settled back to it.
+    pub(crate) fn accrue_borrow_interest(
+        borrow: &mut ObligationLiquidity,
+        borrow_reserve: &mut Reserve,
+        slot: Slot,
+        slots_elapsed: u64,
+    ) -> Result<()> {
+        let cumulative_borrow_rate_bf =
+            BigFraction::from(borrow_reserve.liquidity.cumulative_borrow_rate_bsf);
+        if !borrow.is_fixed_rate() {
+            return borrow.accrue_interest(cumulative_borrow_rate_bf);
+        }
+
+        let FixedRateAccrual {
+            floating_borrowed_amount_f,
+            fixed_borrowed_amount_f,
+        } = borrow.accrue_fixed_rate_interest(cumulative_borrow_rate_bf, slot, slots_elapsed)?;
+        borrow_reserve.liquidity.settle_fixed_rate_interest(
+            floating_borrowed_amount_f,
+            fixed_borrowed_amount_f,
+            Fraction::from_percent(borrow_reserve.config.protocol_take_rate_pct),
+        )
+    }
+
     pub(crate) fn repay_and_withdraw_from_obligation_post_liquidation(
-        obligation: &mut Obligation,
+        obligation: &mut ObligationMut,
         repay_reserve: &mut Reserve,
         settle_amount_f: Fraction,
         withdraw_amount: u64,
@@ -2439,7 +3170,7 @@ pub mod utils {
     }
 
     pub fn check_elevation_group_borrow_limit_constraints<'info, T>(
-        obligation: &Obligation,
+        obligation: &ObligationMut,
         elevation_group: Option<&ElevationGroup>,
         deposit_reserves_iter: impl Iterator<Item = T>,
         borrow_reserves_iter: impl Iterator<Item = T>,
@@ -2450,8 +3181,7 @@ pub mod utils {
         {
             let borrows_and_reserves = zip_and_validate_same_length(
                 obligation
-                    .borrows
-                    .iter()
+                    .borrows()
                     .filter(|borrow| borrow.borrow_reserve != Pubkey::default()),
                 borrow_reserves_iter,
             );
@@ -2492,8 +3222,7 @@ pub mod utils {
         {
             let deposits_and_reserves = zip_and_validate_same_length(
                 obligation
-                    .deposits
-                    .iter()
+                    .deposits()
                     .filter(|deposit| deposit.deposit_reserve != Pubkey::default()),
                 deposit_reserves_iter,
             );
@@ -2545,7 +3274,7 @@ pub mod utils {
 
     pub fn update_elevation_group_debt_trackers_on_borrow<'info, T>(
         new_borrowed_amount: u64,
-        obligation: &mut Obligation,
+        obligation: &mut ObligationMut,
         obligation_borrow_index: usize,
         elevation_group: Option<&ElevationGroup>,
         borrow_reserve_pk: &Pubkey,
@@ -2564,8 +3293,7 @@ pub mod utils {
                 LendingError::ElevationGroupHasAnotherDebtReserve
             );
             for obligation_deposit in obligation
-                .deposits
-                .iter_mut()
+                .deposits_mut()
                 .filter(|d| d.deposit_reserve != Pubkey::default())
             {
                 let deposit_reserve = deposit_reserves_iter
@@ -2623,15 +3351,16 @@ pub mod utils {
             );
 
             borrow_reserve.borrowed_amount_outside_elevation_group = new_total_borrow_amount;
-            obligation.borrows[obligation_borrow_index].borrowed_amount_outside_elevation_groups +=
-                new_borrowed_amount;
+            obligation
+                .liquidity_mut(obligation_borrow_index)
+                .borrowed_amount_outside_elevation_groups += new_borrowed_amount;
         }
         Ok(())
     }
 
     pub fn update_elevation_group_debt_trackers_on_repay<'info, T>(
         repay_amount: u64,
-        obligation: &mut Obligation,
+        obligation: &mut ObligationMut,
         obligation_borrow_index: usize,
         borrow_reserve: &mut Reserve,
         mut deposit_reserves_iter: impl Iterator<Item = T>,
@@ -2642,8 +3371,7 @@ pub mod utils {
         if obligation.elevation_group != ELEVATION_GROUP_NONE {
             let elevation_group_index = obligation.elevation_group as usize - 1;
             for obligation_deposit in obligation
-                .deposits
-                .iter_mut()
+                .deposits_mut()
                 .filter(|d| d.deposit_reserve != Pubkey::default())
             {
                 let deposit_reserve = deposit_reserves_iter
@@ -2683,10 +3411,10 @@ pub mod utils {
             );
 
             borrow_reserve.borrowed_amount_outside_elevation_group = new_total_borrow_amount;
-            obligation.borrows[obligation_borrow_index].borrowed_amount_outside_elevation_groups =
-                obligation.borrows[obligation_borrow_index]
-                    .borrowed_amount_outside_elevation_groups
-                    .saturating_sub(repay_amount);
+            let obligation_liquidity = obligation.liquidity_mut(obligation_borrow_index);
+            obligation_liquidity.borrowed_amount_outside_elevation_groups = obligation_liquidity
+                .borrowed_amount_outside_elevation_groups
+                .saturating_sub(repay_amount);
         }
         Ok(())
     }
@@ -2923,7 +3651,7 @@ pub mod utils {
         Ok(())
     }
     pub fn post_deposit_and_withdraw_obligation_enforcements(
-        obligation: &Obligation,
+        obligation: &ObligationMut,
         withdraw_reserve: &Reserve,
         lending_market: &LendingMarket,
         initial_ltv: Fraction,
@@ -3119,8 +3847,21 @@ pub mod utils {
         }
     }
 
+    pub fn check_obligation_reserves_limit(obligation: &ObligationMut) -> Result<()> {
+        let reserves_count = obligation.deposits_count() + obligation.borrows_count();
+        if reserves_count > MAX_OBLIGATION_RESERVES as usize {
+            msg!(
+                "Obligation uses {} reserves, above the limit of {}",
+                reserves_count,
+                MAX_OBLIGATION_RESERVES
+            );
+            return err!(LendingError::ObligationReserveLimit);
+        }
+        Ok(())
+    }
+
     pub fn check_obligation_fully_refreshed_and_not_null(
-        obligation: &Obligation,
+        obligation: &ObligationMut,
         slot: Slot,
     ) -> Result<()> {
         if obligation
@@ -3202,6 +3943,58 @@ pub mod utils {
         Ok(())
     }
 
+    /// Whether a config change only lowers the risk taken by the reserve. Such changes skip the
+    /// market config timelock and are the only ones the risk council can make.
+    pub fn is_reserve_config_change_risk_reducing(
+        mode: UpdateConfigMode,
+        previous: &ReserveConfig,
+        new: &ReserveConfig,
+    ) -> bool {
+        match mode {
+            UpdateConfigMode::UpdateLoanToValuePct => {
+                new.loan_to_value_pct <= previous.loan_to_value_pct
+            }
+            UpdateConfigMode::UpdateDepositLimit => new.deposit_limit <= previous.deposit_limit,
+            UpdateConfigMode::UpdateBorrowLimit => new.borrow_limit <= previous.borrow_limit,
+            UpdateConfigMode::UpdateBorrowLimitOutsideElevationGroup => {
+                new.borrow_limit_outside_elevation_group
+                    <= previous.borrow_limit_outside_elevation_group
+            }
+            UpdateConfigMode::UpdateBorrowLimitsInElevationGroupAgainstThisReserve => new
+                .borrow_limit_against_this_collateral_in_elevation_group
+                .iter()
+                .zip(
+                    previous
+                        .borrow_limit_against_this_collateral_in_elevation_group
+                        .iter(),
+                )
+                .all(|(new_limit, previous_limit)| new_limit <= previous_limit),
+            UpdateConfigMode::UpdateReserveStatus => {
+                new.status == ReserveStatus::Obsolete as u8 || new.status == previous.status
+            }
+            UpdateConfigMode::UpdateBlockBorrowingAboveUtilizationPct => {
+                new.utilization_limit_block_borrowing_above_pct != 0
+                    && (previous.utilization_limit_block_borrowing_above_pct == 0
+                        || new.utilization_limit_block_borrowing_above_pct
+                            <= previous.utilization_limit_block_borrowing_above_pct)
+            }
+            UpdateConfigMode::UpdateDisableUsageAsCollateralOutsideEmode => {
+                new.disable_usage_as_coll_outside_emode
+                    >= previous.disable_usage_as_coll_outside_emode
+            }
+            UpdateConfigMode::UpdateObligationDepositWithdrawalCap => new
+                .obligation_deposit_withdrawal_cap
+                .is_tighter_than(&previous.obligation_deposit_withdrawal_cap),
+            UpdateConfigMode::UpdateObligationDebtWithdrawalCap => new
+                .obligation_debt_withdrawal_cap
+                .is_tighter_than(&previous.obligation_debt_withdrawal_cap),
+            UpdateConfigMode::UpdateTokenInfoRequireConfidence => {
+                new.token_info.is_confidence_required()
+            }
+            _ => false,
+        }
+    }
+
     pub fn validate_reserve_config(
         config: &ReserveConfig,
         market: &LendingMarket,
@@ -3245,6 +4038,26 @@ pub mod utils {
             msg!("Invalid reserve token twap config");
             return err!(LendingError::InvalidTwapConfig);
         }
+        if !config.token_info.is_price_aggregation_config_valid() {
+            msg!("Invalid reserve token price aggregation config");
+            return err!(LendingError::InvalidPriceAggregationConfig);
+        }
+        if !config.adaptive_rate.is_valid(&config.borrow_rate_curve) {
+            msg!("Invalid adaptive rate config");
+            return err!(LendingError::InvalidConfig);
+        }
+        if config.fixed_rate_borrow.premium_bps > FULL_BPS as u32 {
+            msg!("Fixed rate borrow premium must be at most 100%");
+            return err!(LendingError::InvalidConfig);
+        }
+        if !config.token_info.is_price_circuit_breaker_config_valid() {
+            msg!("Price circuit breaker requires a non-zero window");
+            return err!(LendingError::InvalidConfig);
+        }
+        if config.rate_history_sample_interval_slots > MAX_RATE_HISTORY_SAMPLE_INTERVAL_SLOTS {
+            msg!("Rate history sample interval must be at most {MAX_RATE_HISTORY_SAMPLE_INTERVAL_SLOTS} slots");
+            return err!(LendingError::InvalidConfig);
+        }
 
         if config.bad_debt_liquidation_bonus_bps >= 100 {
             msg!("Invalid bad debt liquidation bonus, cannot be more than 1%");
@@ -3341,7 +4154,7 @@ pub mod utils {
         Ok(())
     }
 
-    pub fn validate_obligation_asset_tiers(obligation: &Obligation) -> Result<()> {
+    pub fn validate_obligation_asset_tiers(obligation: &ObligationMut) -> Result<()> {
         let deposit_tiers = obligation.get_deposit_asset_tiers();
 
         let borrow_tiers = obligation.get_borrows_asset_tiers();
@@ -4,7 +4,7 @@ pub mod utils {
     #[cfg(target_arch = "bpf")]
     use anchor_lang::prelude::msg;
 
-    use crate::{dbg_msg, LendingError, WithdrawalCaps};
+    use crate::{dbg_msg, LendingError, ObligationWithdrawalCapConfig, WithdrawalCaps};
 
     #[derive(PartialEq)]
     enum WithdrawalCapAction {
@@ -167,6 +167,65 @@ pub mod utils {
         )
     }
 
+    /// Amount that can still be added to the accumulator in the current interval
+    pub fn remaining_withdrawal_capacity(
+        caps: &mut WithdrawalCaps,
+        curr_timestamp: u64,
+    ) -> Result<u64, LendingError> {
+        if caps.config_interval_length_seconds == 0 {
+            return Ok(u64::MAX);
+        }
+        if check_last_interval_elapsed(caps, curr_timestamp)? {
+            reset_current_interval_and_counter(caps, curr_timestamp);
+        }
+        caps.config_capacity
+            .saturating_sub(caps.current_total)
+            .max(0)
+            .try_into()
+            .map_err(|_| dbg_msg!(LendingError::MathOverflow))
+    }
+
+    /// Adds to the accumulator of the owner, `caps` is `None` when the owner withdrawal caps were
+    /// not passed, which is only allowed while the reserve doesn't cap owners
+    pub fn add_to_owner_withdrawal_accum(
+        caps: Option<&mut WithdrawalCaps>,
+        config: &ObligationWithdrawalCapConfig,
+        requested_amount: u64,
+        curr_timestamp: u64,
+    ) -> Result<(), LendingError> {
+        if !config.is_enabled() {
+            return Ok(());
+        }
+        let caps = caps.ok_or(LendingError::OwnerWithdrawalCapsMissing)?;
+        sync_obligation_caps_config(caps, config);
+        add_to_withdrawal_accum(caps, requested_amount, curr_timestamp).map_err(|err| match err {
+            LendingError::WithdrawalCapReached => LendingError::ObligationWithdrawalCapReached,
+            err => err,
+        })
+    }
+
+    /// Deposits and repays only offset the accumulator of the owner when it is passed
+    pub fn sub_from_owner_withdrawal_accum(
+        caps: Option<&mut WithdrawalCaps>,
+        config: &ObligationWithdrawalCapConfig,
+        requested_amount: u64,
+        curr_timestamp: u64,
+    ) -> Result<(), LendingError> {
+        let Some(caps) = caps.filter(|_| config.is_enabled()) else {
+            return Ok(());
+        };
+        sync_obligation_caps_config(caps, config);
+        sub_from_withdrawal_accum(caps, requested_amount, curr_timestamp)
+    }
+
+    fn sync_obligation_caps_config(
+        caps: &mut WithdrawalCaps,
+        config: &ObligationWithdrawalCapConfig,
+    ) {
+        caps.config_capacity = config.config_capacity;
+        caps.config_interval_length_seconds = config.config_interval_length_seconds;
+    }
+
     fn check_and_update_withdrawal_caps(
         caps: &mut WithdrawalCaps,
         requested_amount: u64,
@@ -2,6 +2,7 @@
 #![allow(deprecated)]
 use anchor_lang::prelude::*;
 
+pub mod events;
 mod handlers;
 pub mod lending_market;
 pub mod state;
@@ -53,6 +54,17 @@ pub mod kamino_lending {
         handler_update_lending_market_owner::process(ctx)
     }
 
+    pub fn init_obligation_permission(ctx: Context<InitObligationPermission>) -> Result<()> {
+        handler_init_obligation_permission::process(ctx)
+    }
+
+    pub fn update_obligation_permission(
+        ctx: Context<UpdateObligationPermission>,
+        revoked: bool,
+    ) -> Result<()> {
+        handler_update_obligation_permission::process(ctx, revoked)
+    }
+
     pub fn init_reserve<'info>(ctx: Context<'_, '_, '_, 'info, InitReserve<'info>>) -> Result<()> {
         handler_init_reserve::process(ctx)
     }
@@ -70,6 +82,22 @@ pub mod kamino_lending {
         handler_update_reserve_config::process(ctx, mode, &value, skip_validation)
     }
 
+    pub fn queue_config_change(
+        ctx: Context<QueueConfigChange>,
+        mode: u64,
+        value: Vec<u8>,
+    ) -> Result<()> {
+        handler_queue_config_change::process(ctx, mode, &value)
+    }
+
+    pub fn execute_config_change(ctx: Context<ExecuteConfigChange>) -> Result<()> {
+        handler_execute_config_change::process(ctx)
+    }
+
+    pub fn cancel_config_change(ctx: Context<CancelConfigChange>) -> Result<()> {
+        handler_cancel_config_change::process(ctx)
+    }
+
     pub fn redeem_fees(ctx: Context<RedeemFees>) -> Result<()> {
         handler_redeem_fees::process(ctx)
     }
@@ -97,6 +125,10 @@ pub mod kamino_lending {
         handler_mark_obligation_for_deleveraging::process(ctx, autodeleverage_target_ltv_pct)
     }
 
+    pub fn clear_price_circuit_breaker(ctx: Context<ClearPriceCircuitBreaker>) -> Result<()> {
+        handler_clear_price_circuit_breaker::process(ctx)
+    }
+
     #[access_control(emergency_mode_disabled(&ctx.accounts.lending_market))]
     pub fn refresh_reserve(ctx: Context<RefreshReserve>) -> Result<()> {
         handler_refresh_reserve::process(ctx)
@@ -125,10 +157,71 @@ pub mod kamino_lending {
         handler_redeem_reserve_collateral::process(ctx, collateral_amount)
     }
 
+    pub fn init_withdraw_queue(ctx: Context<InitWithdrawQueue>) -> Result<()> {
+        handler_init_withdraw_queue::process(ctx)
+    }
+
+    pub fn init_owner_withdrawal_caps(ctx: Context<InitOwnerWithdrawalCaps>) -> Result<()> {
+        handler_init_owner_withdrawal_caps::process(ctx)
+    }
+
+    #[access_control(emergency_mode_disabled(&ctx.accounts.lending_market))]
+    pub fn enqueue_withdrawal(
+        ctx: Context<EnqueueWithdrawal>,
+        collateral_amount: u64,
+    ) -> Result<()> {
+        handler_enqueue_withdrawal::process(ctx, collateral_amount)
+    }
+
+    #[access_control(emergency_mode_disabled(&ctx.accounts.lending_market))]
+    pub fn cancel_withdrawal(ctx: Context<CancelWithdrawal>, ticket_id: u64) -> Result<()> {
+        handler_cancel_withdrawal::process(ctx, ticket_id)
+    }
+
+    #[access_control(emergency_mode_disabled(&ctx.accounts.lending_market))]
+    pub fn claim_withdrawal(ctx: Context<ClaimWithdrawal>) -> Result<()> {
+        handler_claim_withdrawal::process(ctx)
+    }
+
     pub fn init_obligation(ctx: Context<InitObligation>, args: InitObligationArgs) -> Result<()> {
         handler_init_obligation::process(ctx, args)
     }
 
+    #[access_control(emergency_mode_disabled(&ctx.accounts.lending_market))]
+    pub fn close_obligation<'info>(
+        ctx: Context<'_, '_, '_, 'info, CloseObligation<'info>>,
+    ) -> Result<()> {
+        handler_close_obligation::process(ctx)
+    }
+
+    #[access_control(emergency_mode_disabled(&ctx.accounts.lending_market))]
+    pub fn extend_obligation(ctx: Context<ExtendObligation>) -> Result<()> {
+        handler_extend_obligation::process(ctx)
+    }
+
+    #[access_control(emergency_mode_disabled(&ctx.accounts.lending_market))]
+    pub fn propose_obligation_owner(
+        ctx: Context<ProposeObligationOwner>,
+        new_owner: Pubkey,
+    ) -> Result<()> {
+        handler_propose_obligation_owner::process(ctx, new_owner)
+    }
+
+    #[access_control(emergency_mode_disabled(&ctx.accounts.lending_market))]
+    pub fn update_obligation_owner(ctx: Context<UpdateObligationOwner>) -> Result<()> {
+        handler_update_obligation_owner::process(ctx)
+    }
+
+    #[access_control(emergency_mode_disabled(&ctx.accounts.lending_market))]
+    pub fn update_obligation_delegate(
+        ctx: Context<UpdateObligationDelegate>,
+        delegate: Pubkey,
+        permissions: u8,
+        max_ltv_pct: u8,
+    ) -> Result<()> {
+        handler_update_obligation_delegate::process(ctx, delegate, permissions, max_ltv_pct)
+    }
+
     pub fn init_obligation_farms_for_reserve(
         ctx: Context<InitObligationFarmsForReserve>,
         mode: u8,
@@ -211,6 +304,40 @@ pub mod kamino_lending {
         handler_borrow_obligation_liquidity::process_v2(ctx, liquidity_amount)
     }
 
+    #[access_control(emergency_mode_disabled(&ctx.accounts.lending_market))]
+    pub fn borrow_obligation_liquidity_fixed_rate<'info>(
+        ctx: Context<'_, '_, '_, 'info, BorrowObligationLiquidity<'info>>,
+        liquidity_amount: u64,
+        term_seconds: u64,
+        max_fixed_rate_bps: u64,
+    ) -> Result<()> {
+        handler_borrow_obligation_liquidity::process_fixed_rate_v1(
+            ctx,
+            liquidity_amount,
+            FixedRateBorrowTerms {
+                term_seconds,
+                max_fixed_rate_bps,
+            },
+        )
+    }
+
+    #[access_control(emergency_mode_disabled(&ctx.accounts.borrow_accounts.lending_market))]
+    pub fn borrow_obligation_liquidity_fixed_rate_v2<'info>(
+        ctx: Context<'_, '_, '_, 'info, BorrowObligationLiquidityV2<'info>>,
+        liquidity_amount: u64,
+        term_seconds: u64,
+        max_fixed_rate_bps: u64,
+    ) -> Result<()> {
+        handler_borrow_obligation_liquidity::process_fixed_rate_v2(
+            ctx,
+            liquidity_amount,
+            FixedRateBorrowTerms {
+                term_seconds,
+                max_fixed_rate_bps,
+            },
+        )
+    }
+
     #[deprecated(
         since = "1.8.0",
         note = "Please use `_v2` variant of the handler instead"
@@ -658,6 +785,68 @@ pub enum LendingError {
     RepayTooSmallForFullLiquidation,
     #[msg("Liquidator provided repay amount lower than required by liquidation rules")]
     InsufficientRepayAmount,
+    #[msg("Price aggregation mode requires at least two enabled price sources, a divergence tolerance and a valid primary source")]
+    InvalidPriceAggregationConfig,
+    #[msg("Derived price base reserve or stake pool account does not match the reserve config")]
+    InvalidDerivedPriceAccount,
+    #[msg("Fixed-rate borrow is not allowed with these terms")]
+    InvalidFixedRateBorrow,
+    #[msg("Fixed borrow rate is above the maximum accepted by the borrower")]
+    FixedRateAboveMaximum,
+    #[msg("Obligation still has deposits or borrows")]
+    ObligationNotEmpty,
+    #[msg("Obligation farm still has stake")]
+    ObligationFarmStakeNotZero,
+    #[msg("Delegated action would take the obligation above the delegate LTV ceiling")]
+    DelegateLtvCeilingExceeded,
+    #[msg("Invalid obligation delegate permissions or LTV ceiling")]
+    InvalidDelegatePermissions,
+    #[msg("Obligation already uses the extended layout")]
+    ObligationAlreadyExtended,
+    #[msg("Lending market requires an obligation permission for the obligation owner")]
+    ObligationPermissionMissing,
+    #[msg("Obligation permission account does not match the market and owner")]
+    InvalidObligationPermission,
+    #[msg("Obligation permission has been revoked")]
+    ObligationPermissionRevoked,
+    #[msg("Config change must be queued while the market config timelock is enabled")]
+    ConfigChangeTimelocked,
+    #[msg("Queued config change cannot be executed before its execution timestamp")]
+    ConfigChangeNotExecutable,
+    #[msg("Config change does not reduce risk and requires the lending market owner")]
+    ConfigChangeNotRiskReducing,
+    #[msg("Withdrawal cap of the obligation owner reached for this reserve")]
+    ObligationWithdrawalCapReached,
+    #[msg("Withdraw queue has no free ticket slot")]
+    WithdrawQueueFull,
+    #[msg("Withdraw ticket not found for this owner")]
+    WithdrawTicketNotFound,
+    #[msg("Withdraw ticket is already filled")]
+    WithdrawTicketAlreadyFilled,
+    #[msg("No filled withdraw ticket to claim")]
+    WithdrawTicketNotFilled,
+    #[msg("Reserve liquidity is reserved for the withdraw queue")]
+    LiquidityReservedForWithdrawQueue,
+    #[msg("Price circuit breaker is tripped for a reserve of the obligation")]
+    PriceCircuitBreakerTripped,
+    #[msg("Obligation has farm user states, its owner cannot be transferred")]
+    ObligationHasFarmUserStates,
+    #[msg("Queued config change expired, it has to be cancelled and queued again")]
+    ConfigChangeExpired,
+    #[msg("Withdraw ticket is below the minimum withdrawal amount")]
+    WithdrawTicketTooSmall,
+    #[msg("Reserve has enough available liquidity, redeem the collateral directly")]
+    WithdrawQueueNotRequired,
+    #[msg("Price source has no confidence interval and the reserve requires one")]
+    PriceConfidenceNotAvailable,
+    #[msg("Every farm user state of the obligation must be passed")]
+    ObligationFarmUserStatesMissing,
+    #[msg("Reserve has queued withdrawals, its withdraw queue must be passed")]
+    WithdrawQueueMissing,
+    #[msg("Owner reached the maximum number of withdraw tickets in the queue")]
+    WithdrawTicketsPerOwnerExceeded,
+    #[msg("Reserve caps withdrawals per owner, the owner withdrawal caps must be passed")]
+    OwnerWithdrawalCapsMissing,
 }
 
 pub type LendingResult<T = ()> = std::result::Result<T, LendingError>;
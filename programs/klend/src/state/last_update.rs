@@ -26,12 +26,14 @@ pub struct PriceStatusFlags(pub u8);
 #[rustfmt::skip]
 bitflags! {
     impl PriceStatusFlags: u8 {
-        const PRICE_LOADED =        0b_0000_0001;
-        const PRICE_AGE_CHECKED =   0b_0000_0010;
-        const TWAP_CHECKED =        0b_0000_0100;
-        const TWAP_AGE_CHECKED =    0b_0000_1000;
-        const HEURISTIC_CHECKED =   0b_0001_0000;
-        const PRICE_USAGE_ALLOWED = 0b_0010_0000;
+        const PRICE_LOADED =             0b_0000_0001;
+        const PRICE_AGE_CHECKED =        0b_0000_0010;
+        const TWAP_CHECKED =             0b_0000_0100;
+        const TWAP_AGE_CHECKED =         0b_0000_1000;
+        const HEURISTIC_CHECKED =        0b_0001_0000;
+        const PRICE_USAGE_ALLOWED =      0b_0010_0000;
+        const PRICE_SOURCES_AGREED =     0b_0100_0000;
+        const PRICE_CONFIDENCE_CHECKED = 0b_1000_0000;
     }
 }
 
@@ -97,17 +97,29 @@ pub struct LendingMarket {
 
     pub min_initial_deposit_amount: u64,
 
+    #[cfg_attr(feature = "serde", serde(with = "serde_bool_u8"))]
+    pub obligation_permission_required: u8,
+    #[cfg_attr(
+        feature = "serde",
+        serde(skip_deserializing, skip_serializing, default)
+    )]
+    #[derivative(Debug = "ignore")]
+    pub reserved2: [u8; 7],
+
+    /// Delay before a queued config change can be executed, 0 disables the timelock
+    pub config_timelock_secs: u64,
+
     #[cfg_attr(
         feature = "serde",
-        serde(skip_deserializing, skip_serializing, default = "default_padding_170")
+        serde(skip_deserializing, skip_serializing, default = "default_padding_168")
     )]
     #[derivative(Debug = "ignore")]
-    pub padding1: [u64; 170],
+    pub padding1: [u64; 168],
 }
 
 #[cfg(feature = "serde")]
-fn default_padding_170() -> [u64; 170] {
-    [0; 170]
+fn default_padding_168() -> [u64; 168] {
+    [0; 168]
 }
 
 #[cfg(feature = "serde")]
@@ -144,7 +156,10 @@ impl Default for LendingMarket {
             name: [0; 32],
             individual_autodeleverage_margin_call_period_secs: 0,
             min_initial_deposit_amount: DEFAULT_MIN_DEPOSIT_AMOUNT,
-            padding1: [0; 170],
+            obligation_permission_required: 0,
+            reserved2: [0; 7],
+            config_timelock_secs: 0,
+            padding1: [0; 168],
         }
     }
 }
@@ -190,6 +205,19 @@ impl LendingMarket {
     pub fn is_autodeleverage_enabled(&self) -> bool {
         self.autodeleverage_enabled != false as u8
     }
+
+    pub fn is_obligation_permission_required(&self) -> bool {
+        self.obligation_permission_required != false as u8
+    }
+
+    pub fn is_config_timelock_enabled(&self) -> bool {
+        self.config_timelock_secs > 0
+    }
+
+    pub fn is_owner_or_risk_council(&self, authority: &Pubkey) -> bool {
+        *authority == self.lending_market_owner
+            || (self.risk_council != Pubkey::default() && *authority == self.risk_council)
+    }
 }
 
 pub struct InitLendingMarketParams {
@@ -252,7 +252,7 @@ pub fn check_liquidate_obligation(
     None
 }
 
-fn get_emode_max_liquidation_bonus(
+pub fn get_emode_max_liquidation_bonus(
     lending_market: &LendingMarket,
     collateral_reserve: &ReserveConfig,
     debt_reserve: &ReserveConfig,
@@ -418,15 +418,10 @@ fn check_individual_autodeleverage_obligation(
         secs_since_margin_call_started,
     )?;
     let days_since_deleveraging_started = secs::to_days_fractional(secs_since_deleveraging_started);
-    let selected_reserve_config = [&collateral_reserve.config, &debt_reserve.config]
-        .into_iter()
-        .max_by_key(|reserve| {
-            (
-                reserve.max_liquidation_bonus_bps,
-                reserve.deleveraging_bonus_increase_bps_per_day,
-            )
-        })
-        .expect("must exist for a statically-constructed non-empty array");
+    let selected_reserve_config = select_individual_autodeleverage_reserve_config(
+        &collateral_reserve.config,
+        &debt_reserve.config,
+    );
     let liquidation_bonus_rate = calculate_autodeleverage_bonus(
         selected_reserve_config.deleveraging_bonus_increase_bps_per_day,
         selected_reserve_config.max_liquidation_bonus_bps,
@@ -446,6 +441,21 @@ fn check_individual_autodeleverage_obligation(
     })
 }
 
+pub fn select_individual_autodeleverage_reserve_config<'a>(
+    collateral_reserve_config: &'a ReserveConfig,
+    debt_reserve_config: &'a ReserveConfig,
+) -> &'a ReserveConfig {
+    [collateral_reserve_config, debt_reserve_config]
+        .into_iter()
+        .max_by_key(|reserve| {
+            (
+                reserve.max_liquidation_bonus_bps,
+                reserve.deleveraging_bonus_increase_bps_per_day,
+            )
+        })
+        .expect("must exist for a statically-constructed non-empty array")
+}
+
 fn check_market_wide_autodeleverage_obligation(
     lending_market: &LendingMarket,
     collateral_reserve: &Reserve,
@@ -585,7 +595,7 @@ fn get_secs_since_deleveraging_started(
     secs_since_deleveraging_started
 }
 
-fn calculate_autodeleverage_threshold(
+pub fn calculate_autodeleverage_threshold(
     autodeleverage_reserve: &Reserve,
     days_since_deleveraging_started: Fraction,
 ) -> Fraction {
@@ -598,7 +608,7 @@ fn calculate_autodeleverage_threshold(
     Fraction::ONE.saturating_sub(ltv_threshold_reduction)
 }
 
-fn calculate_autodeleverage_bonus(
+pub fn calculate_autodeleverage_bonus(
     deleveraging_bonus_increase_bps_per_day: u64,
     reserve_max_bonus_bps: u16,
     emode_max_liquidation_bonus_bps: u16,
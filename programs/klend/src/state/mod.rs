@@ -3,10 +3,14 @@ pub mod lending_market;
 pub mod liquidation_operations;
 pub mod nested_accounts;
 pub mod obligation;
+pub mod obligation_permission;
+pub mod owner_withdrawal_caps;
+pub mod pending_config_change;
 pub mod referral;
 pub mod reserve;
 pub mod token_info;
 pub mod types;
+pub mod withdraw_queue;
 
 use anchor_lang::prelude::*;
 pub use last_update::*;
@@ -14,6 +18,9 @@ pub use lending_market::*;
 pub use nested_accounts::*;
 use num_enum::TryFromPrimitive;
 pub use obligation::*;
+pub use obligation_permission::*;
+pub use owner_withdrawal_caps::*;
+pub use pending_config_change::*;
 pub use referral::*;
 pub use reserve::*;
 #[cfg(feature = "serde")]
@@ -21,6 +28,7 @@ use strum::EnumIter;
 use strum::EnumString;
 pub use token_info::*;
 pub use types::*;
+pub use withdraw_queue::*;
 
 use crate::utils::{borrow_rate_curve::BorrowRateCurve, RESERVE_CONFIG_SIZE};
 
@@ -134,6 +142,72 @@ pub enum UpdateConfigMode {
     UpdateHostFixedInterestRateBps = 47,
     UpdateAutodeleverageEnabled = 48,
     UpdateDeleveragingBonusIncreaseBpsPerDay = 49,
+    UpdateTokenInfoPriceAggregationMode = 50,
+    UpdateTokenInfoPrimaryPriceSource = 51,
+    UpdateTokenInfoMaxPriceSourcesDivergence = 52,
+    UpdateTokenInfoMaxConfidenceBps = 53,
+    UpdateTokenInfoPythMinVerificationSignatures = 54,
+    UpdateTokenInfoPythMaxPostedSlotLag = 55,
+    UpdatePriceCircuitBreakerMaxChangeBps = 56,
+    UpdatePriceCircuitBreakerWindowSeconds = 57,
+    UpdateTokenInfoFixedPriceValue = 58,
+    UpdateTokenInfoFixedPriceExp = 59,
+    UpdateTokenInfoDerivedPriceBaseReserve = 60,
+    UpdateTokenInfoDerivedPriceStakePool = 61,
+    UpdateAdaptiveRateConfig = 62,
+    UpdateFixedRateBorrowConfig = 63,
+    UpdateRateHistorySampleInterval = 64,
+    UpdateObligationDepositWithdrawalCap = 65,
+    UpdateObligationDebtWithdrawalCap = 66,
+    UpdateTokenInfoRequireConfidence = 67,
+}
+
+impl UpdateConfigMode {
+    /// Reserve modes touching risk parameters, limits or the oracle feeds and price safety
+    /// checks, queued as a pending config change while the market timelock is enabled unless
+    /// `is_reserve_config_change_risk_reducing` holds for the change
+    pub fn is_timelocked(self) -> bool {
+        matches!(
+            self,
+            UpdateConfigMode::UpdateLoanToValuePct
+                | UpdateConfigMode::UpdateMaxLiquidationBonusBps
+                | UpdateConfigMode::UpdateLiquidationThresholdPct
+                | UpdateConfigMode::UpdateDepositLimit
+                | UpdateConfigMode::UpdateBorrowLimit
+                | UpdateConfigMode::UpdateTokenInfoLowerHeuristic
+                | UpdateConfigMode::UpdateTokenInfoUpperHeuristic
+                | UpdateConfigMode::UpdateTokenInfoExpHeuristic
+                | UpdateConfigMode::UpdateTokenInfoTwapDivergence
+                | UpdateConfigMode::UpdateTokenInfoScopeTwap
+                | UpdateConfigMode::UpdateTokenInfoScopeChain
+                | UpdateConfigMode::UpdateTokenInfoPriceMaxAge
+                | UpdateConfigMode::UpdateTokenInfoTwapMaxAge
+                | UpdateConfigMode::UpdateScopePriceFeed
+                | UpdateConfigMode::UpdatePythPrice
+                | UpdateConfigMode::UpdateSwitchboardFeed
+                | UpdateConfigMode::UpdateSwitchboardTwapFeed
+                | UpdateConfigMode::UpdateEntireReserveConfig
+                | UpdateConfigMode::UpdateBorrowFactor
+                | UpdateConfigMode::UpdateAssetTier
+                | UpdateConfigMode::UpdateElevationGroup
+                | UpdateConfigMode::UpdateBlockPriceUsage
+                | UpdateConfigMode::UpdateBorrowLimitOutsideElevationGroup
+                | UpdateConfigMode::UpdateBorrowLimitsInElevationGroupAgainstThisReserve
+                | UpdateConfigMode::UpdateTokenInfoPriceAggregationMode
+                | UpdateConfigMode::UpdateTokenInfoPrimaryPriceSource
+                | UpdateConfigMode::UpdateTokenInfoMaxPriceSourcesDivergence
+                | UpdateConfigMode::UpdateTokenInfoMaxConfidenceBps
+                | UpdateConfigMode::UpdateTokenInfoRequireConfidence
+                | UpdateConfigMode::UpdateTokenInfoPythMinVerificationSignatures
+                | UpdateConfigMode::UpdateTokenInfoPythMaxPostedSlotLag
+                | UpdateConfigMode::UpdatePriceCircuitBreakerMaxChangeBps
+                | UpdateConfigMode::UpdatePriceCircuitBreakerWindowSeconds
+                | UpdateConfigMode::UpdateTokenInfoFixedPriceValue
+                | UpdateConfigMode::UpdateTokenInfoFixedPriceExp
+                | UpdateConfigMode::UpdateTokenInfoDerivedPriceBaseReserve
+                | UpdateConfigMode::UpdateTokenInfoDerivedPriceStakePool
+        )
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, PartialEq, Eq, Clone, Debug)]
@@ -221,6 +295,25 @@ pub enum UpdateLendingMarketMode {
     UpdateName = 19,
     UpdateIndividualAutodeleverageMarginCallPeriodSecs = 20,
     UpdateInitialDepositAmount = 21,
+    UpdateObligationPermissionRequired = 22,
+    UpdateConfigTimelockSecs = 23,
+}
+
+impl UpdateLendingMarketMode {
+    /// Market modes changing who controls the market or how liquidations and elevation groups
+    /// behave, including the timelock duration itself, so lowering the timelock also waits for it
+    pub fn is_timelocked(self) -> bool {
+        matches!(
+            self,
+            UpdateLendingMarketMode::UpdateOwner
+                | UpdateLendingMarketMode::UpdateLiquidationCloseFactor
+                | UpdateLendingMarketMode::UpdateLiquidationMaxValue
+                | UpdateLendingMarketMode::UpdateRiskCouncil
+                | UpdateLendingMarketMode::UpdateInsolvencyRiskLtv
+                | UpdateLendingMarketMode::UpdateElevationGroup
+                | UpdateLendingMarketMode::UpdateConfigTimelockSecs
+        )
+    }
 }
 
 #[cfg(feature = "serde")]
@@ -1,14 +1,22 @@
 use std::{
+    cell::{Ref, RefMut},
     cmp::Ordering,
     fmt::{self, Display, Formatter},
+    ops::{Deref, DerefMut},
 };
 
-use anchor_lang::{account, err, prelude::*, solana_program::clock::Slot, Result};
+use anchor_lang::{
+    account, err, error::ErrorCode, prelude::*, solana_program::clock::Slot, Discriminator, Result,
+};
+use bitflags::bitflags;
 use derivative::Derivative;
 
-use super::{LastUpdate, LtvMaxWithdrawalCheck};
+use super::{approximate_compounded_interest, LastUpdate, LtvMaxWithdrawalCheck};
 use crate::{
-    utils::{BigFraction, Fraction, FractionExtra, ELEVATION_GROUP_NONE, OBLIGATION_SIZE, U256},
+    utils::{
+        BigFraction, Fraction, FractionExtra, ELEVATION_GROUP_NONE, OBLIGATION_EXTENSION_BORROWS,
+        OBLIGATION_EXTENSION_DEPOSITS, OBLIGATION_EXTENSION_SIZE, OBLIGATION_SIZE, U256,
+    },
     xmsg, AssetTier, BigFractionBytes, LendingError,
 };
 
@@ -50,15 +58,31 @@ pub struct Obligation {
 
     pub lowest_reserve_deposit_max_ltv_pct: u8,
 
+    /// Set on refresh when a deposit or borrow reserve has a tripped price circuit breaker
+    pub price_circuit_breaker_tripped: u8,
+
     #[derivative(Debug = "ignore")]
-    pub reserved: [u8; 5],
+    pub reserved: [u8; 4],
 
     pub highest_borrow_factor_pct: u64,
 
     pub autodeleverage_margin_call_started_timestamp: u64,
 
+    pub owner_cached: Pubkey,
+
+    pub delegate: Pubkey,
+    pub delegate_permissions: u8,
+    pub delegate_max_ltv_pct: u8,
+    pub delegate_reserved: [u8; 6],
+
+    pub layout_version: u8,
+    /// Number of farm user states initialized with the obligation as delegatee
+    pub num_of_farm_user_states: u8,
+    #[derivative(Debug = "ignore")]
+    pub layout_reserved: [u8; 6],
+
     #[derivative(Debug = "ignore")]
-    pub padding_3: [u64; 125],
+    pub padding_3: [u64; 115],
 }
 
 impl Default for Obligation {
@@ -84,8 +108,17 @@ impl Default for Obligation {
             borrowing_disabled: 0,
             highest_borrow_factor_pct: 0,
             lowest_reserve_deposit_max_ltv_pct: 0,
-            reserved: [0; 5],
-            padding_3: [0; 125],
+            price_circuit_breaker_tripped: 0,
+            reserved: [0; 4],
+            owner_cached: Pubkey::default(),
+            delegate: Pubkey::default(),
+            delegate_permissions: 0,
+            delegate_max_ltv_pct: 0,
+            delegate_reserved: [0; 6],
+            layout_version: ObligationLayout::Standard.into(),
+            num_of_farm_user_states: 0,
+            layout_reserved: [0; 6],
+            padding_3: [0; 115],
             referrer: Pubkey::default(),
             autodeleverage_target_ltv_pct: 0,
             autodeleverage_margin_call_started_timestamp: 0,
@@ -173,30 +206,12 @@ impl Obligation {
             / Fraction::from_bits(self.deposited_value_sf)
     }
 
-    pub fn repay(&mut self, settle_amount: Fraction, liquidity_index: usize) {
-        let liquidity = &mut self.borrows[liquidity_index];
-        if settle_amount == Fraction::from_bits(liquidity.borrowed_amount_sf) {
-            self.borrows[liquidity_index] = ObligationLiquidity::default();
-            self.borrows_asset_tiers[liquidity_index] = u8::MAX;
-        } else {
-            liquidity.repay(settle_amount);
-        }
+    pub fn layout(&self) -> ObligationLayout {
+        ObligationLayout::try_from(self.layout_version).unwrap()
     }
 
-    pub fn withdraw(
-        &mut self,
-        withdraw_amount: u64,
-        collateral_index: usize,
-    ) -> Result<WithdrawResult> {
-        let collateral = &mut self.deposits[collateral_index];
-        if withdraw_amount == collateral.deposited_amount {
-            self.deposits[collateral_index] = ObligationCollateral::default();
-            self.deposits_asset_tiers[collateral_index] = u8::MAX;
-            Ok(WithdrawResult::Full)
-        } else {
-            collateral.withdraw(withdraw_amount)?;
-            Ok(WithdrawResult::Partial)
-        }
+    pub fn is_extended(&self) -> bool {
+        self.layout() == ObligationLayout::Extended
     }
 
     pub fn max_withdraw_value(
@@ -241,50 +256,223 @@ impl Obligation {
         )
     }
 
-    pub fn find_collateral_in_deposits(
-        &self,
-        deposit_reserve: Pubkey,
-    ) -> Result<&ObligationCollateral> {
-        if self.deposits_empty() {
-            xmsg!("Obligation has no deposits");
-            return err!(LendingError::ObligationDepositsEmpty);
+    pub fn get_bf_adjusted_debt_value(&self) -> Fraction {
+        Fraction::from_bits(self.borrow_factor_adjusted_debt_value_sf)
+    }
+
+    pub fn get_allowed_borrow_value(&self) -> Fraction {
+        Fraction::from_bits(self.allowed_borrow_value_sf)
+    }
+
+    pub fn get_unhealthy_borrow_value(&self) -> Fraction {
+        Fraction::from_bits(self.unhealthy_borrow_value_sf)
+    }
+
+    pub fn has_referrer(&self) -> bool {
+        self.referrer != Pubkey::default()
+    }
+
+    pub fn is_marked_for_deleveraging(&self) -> bool {
+        self.autodeleverage_margin_call_started_timestamp != 0
+    }
+
+    pub fn mark_for_deleveraging(&mut self, current_timestamp: u64, target_ltv_pct: u8) {
+        if current_timestamp == 0 {
+            panic!("value reserved for non-marked state");
+        }
+        self.autodeleverage_margin_call_started_timestamp = current_timestamp;
+        self.autodeleverage_target_ltv_pct = target_ltv_pct;
+    }
+
+    pub fn unmark_for_deleveraging(&mut self) {
+        self.autodeleverage_margin_call_started_timestamp = 0;
+        self.autodeleverage_target_ltv_pct = 0;
+    }
+
+    pub fn is_price_circuit_breaker_tripped(&self) -> bool {
+        self.price_circuit_breaker_tripped > 0
+    }
+
+    pub fn has_delegate(&self) -> bool {
+        self.delegate != Pubkey::default()
+    }
+
+    pub fn clear_delegate(&mut self) {
+        self.delegate = Pubkey::default();
+        self.delegate_permissions = 0;
+        self.delegate_max_ltv_pct = 0;
+    }
+
+    pub fn is_authorized(&self, signer: Pubkey, permission: ObligationDelegatePermissions) -> bool {
+        signer == self.owner
+            || (self.has_delegate()
+                && signer == self.delegate
+                && ObligationDelegatePermissions::from_bits_truncate(self.delegate_permissions)
+                    .contains(permission))
+    }
+
+    pub fn check_not_marked_for_deleveraging(&self) -> Result<()> {
+        if self.is_marked_for_deleveraging() {
+            msg!(
+                "Obligation marked for deleveraging since {}",
+                self.autodeleverage_margin_call_started_timestamp
+            );
+            return err!(LendingError::ObligationCurrentlyMarkedForDeleveraging);
+        }
+        Ok(())
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, num_enum::IntoPrimitive, num_enum::TryFromPrimitive,
+)]
+#[repr(u8)]
+pub enum ObligationLayout {
+    Standard = 0,
+    Extended = 1,
+}
+
+static_assertions::const_assert_eq!(
+    OBLIGATION_EXTENSION_SIZE,
+    std::mem::size_of::<ObligationExtension>()
+);
+static_assertions::const_assert_eq!(0, std::mem::size_of::<ObligationExtension>() % 8);
+
+/// Extra position slots stored right after the `Obligation` in an extended obligation account.
+#[derive(PartialEq, Derivative)]
+#[derivative(Debug)]
+#[zero_copy]
+#[repr(C)]
+pub struct ObligationExtension {
+    pub deposits: [ObligationCollateral; 8],
+    pub borrows: [ObligationLiquidity; 5],
+
+    pub deposits_asset_tiers: [u8; 8],
+    pub borrows_asset_tiers: [u8; 5],
+
+    #[derivative(Debug = "ignore")]
+    pub padding: [u8; 3],
+    #[derivative(Debug = "ignore")]
+    pub padding_2: [u64; 64],
+}
+
+impl Default for ObligationExtension {
+    fn default() -> Self {
+        Self {
+            deposits: [ObligationCollateral::default(); OBLIGATION_EXTENSION_DEPOSITS],
+            borrows: [ObligationLiquidity::default(); OBLIGATION_EXTENSION_BORROWS],
+            deposits_asset_tiers: [u8::MAX; OBLIGATION_EXTENSION_DEPOSITS],
+            borrows_asset_tiers: [u8::MAX; OBLIGATION_EXTENSION_BORROWS],
+            padding: [0; 3],
+            padding_2: [0; 64],
         }
-        let collateral = self
+    }
+}
+
+/// Positions of an obligation across both layouts.
+///
+/// Position indexes cover the `Obligation` slots first, then the `ObligationExtension` slots.
+pub struct ObligationView<O, E> {
+    obligation: O,
+    extension: Option<E>,
+}
+
+pub type ObligationRef<'a> = ObligationView<&'a Obligation, &'a ObligationExtension>;
+pub type ObligationMut<'a> = ObligationView<&'a mut Obligation, &'a mut ObligationExtension>;
+pub type ObligationAccount<'a> = ObligationView<Ref<'a, Obligation>, Ref<'a, ObligationExtension>>;
+pub type ObligationAccountMut<'a> =
+    ObligationView<RefMut<'a, Obligation>, RefMut<'a, ObligationExtension>>;
+
+impl<O, E> Deref for ObligationView<O, E>
+where
+    O: Deref<Target = Obligation>,
+{
+    type Target = Obligation;
+
+    fn deref(&self) -> &Obligation {
+        &self.obligation
+    }
+}
+
+impl<O, E> DerefMut for ObligationView<O, E>
+where
+    O: DerefMut<Target = Obligation>,
+{
+    fn deref_mut(&mut self) -> &mut Obligation {
+        &mut self.obligation
+    }
+}
+
+impl<O, E> ObligationView<O, E>
+where
+    O: Deref<Target = Obligation>,
+    E: Deref<Target = ObligationExtension>,
+{
+    pub fn new(obligation: O, extension: Option<E>) -> Self {
+        debug_assert_eq!(obligation.is_extended(), extension.is_some());
+        Self {
+            obligation,
+            extension,
+        }
+    }
+
+    pub fn deposits(&self) -> impl Iterator<Item = &ObligationCollateral> {
+        self.obligation.deposits.iter().chain(
+            self.extension
+                .as_deref()
+                .into_iter()
+                .flat_map(|extension| extension.deposits.iter()),
+        )
+    }
+
+    pub fn borrows(&self) -> impl Iterator<Item = &ObligationLiquidity> {
+        self.obligation.borrows.iter().chain(
+            self.extension
+                .as_deref()
+                .into_iter()
+                .flat_map(|extension| extension.borrows.iter()),
+        )
+    }
+
+    fn deposits_with_asset_tiers(&self) -> impl Iterator<Item = (&ObligationCollateral, u8)> {
+        self.obligation
             .deposits
             .iter()
-            .find(|collateral| collateral.deposit_reserve == deposit_reserve)
-            .ok_or(LendingError::InvalidObligationCollateral)?;
-        Ok(collateral)
+            .zip(self.obligation.deposits_asset_tiers)
+            .chain(self.extension.as_deref().into_iter().flat_map(|extension| {
+                extension
+                    .deposits
+                    .iter()
+                    .zip(extension.deposits_asset_tiers)
+            }))
     }
 
-    pub fn find_or_add_collateral_to_deposits(
-        &mut self,
-        deposit_reserve: Pubkey,
-        deposit_reserve_asset_tier: AssetTier,
-        init_function: impl FnOnce(&mut ObligationCollateral) -> Result<()>,
-    ) -> Result<&mut ObligationCollateral> {
-        if let Some(collateral_index) = self
-            .deposits
-            .iter_mut()
-            .position(|collateral| collateral.deposit_reserve == deposit_reserve)
-        {
-            Ok(&mut self.deposits[collateral_index])
-        } else if let Some(collateral_index) = self
-            .deposits
+    fn borrows_with_asset_tiers(&self) -> impl Iterator<Item = (&ObligationLiquidity, u8)> {
+        self.obligation
+            .borrows
             .iter()
-            .position(|c| c.deposit_reserve == Pubkey::default())
-        {
-            let collateral = &mut self.deposits[collateral_index];
-            *collateral = ObligationCollateral::new(deposit_reserve);
-            self.deposits_asset_tiers[collateral_index] = deposit_reserve_asset_tier.into();
+            .zip(self.obligation.borrows_asset_tiers)
+            .chain(
+                self.extension.as_deref().into_iter().flat_map(|extension| {
+                    extension.borrows.iter().zip(extension.borrows_asset_tiers)
+                }),
+            )
+    }
 
-            init_function(collateral)?;
+    pub fn collateral(&self, collateral_index: usize) -> &ObligationCollateral {
+        self.deposits().nth(collateral_index).unwrap()
+    }
 
-            Ok(collateral)
-        } else {
-            xmsg!("Obligation has no empty deposits");
-            err!(LendingError::ObligationReserveLimit)
-        }
+    pub fn liquidity(&self, liquidity_index: usize) -> &ObligationLiquidity {
+        self.borrows().nth(liquidity_index).unwrap()
+    }
+
+    pub fn find_collateral_in_deposits(
+        &self,
+        deposit_reserve: Pubkey,
+    ) -> Result<&ObligationCollateral> {
+        let collateral_index = self.position_of_collateral_in_deposits(deposit_reserve)?;
+        Ok(self.collateral(collateral_index))
     }
 
     pub fn position_of_collateral_in_deposits(&self, deposit_reserve: Pubkey) -> Result<usize> {
@@ -292,8 +480,7 @@ impl Obligation {
             xmsg!("Obligation has no deposits");
             return err!(LendingError::ObligationDepositsEmpty);
         }
-        self.deposits
-            .iter()
+        self.deposits()
             .position(|collateral| collateral.deposit_reserve == deposit_reserve)
             .ok_or(error!(LendingError::InvalidObligationCollateral))
     }
@@ -309,86 +496,41 @@ impl Obligation {
         let liquidity_index = self
             .find_liquidity_index_in_borrows(borrow_reserve)
             .ok_or_else(|| error!(LendingError::InvalidObligationLiquidity))?;
-        Ok((&self.borrows[liquidity_index], liquidity_index))
-    }
-
-    pub fn find_liquidity_in_borrows_mut(
-        &mut self,
-        borrow_reserve: Pubkey,
-    ) -> Result<(&mut ObligationLiquidity, usize)> {
-        if self.borrows_empty() {
-            xmsg!("Obligation has no borrows");
-            return err!(LendingError::ObligationBorrowsEmpty);
-        }
-        let liquidity_index = self
-            .find_liquidity_index_in_borrows(borrow_reserve)
-            .ok_or_else(|| error!(LendingError::InvalidObligationLiquidity))?;
-        Ok((&mut self.borrows[liquidity_index], liquidity_index))
-    }
-
-    pub fn find_or_add_liquidity_to_borrows(
-        &mut self,
-        borrow_reserve: Pubkey,
-        cumulative_borrow_rate: BigFraction,
-        borrow_reserve_asset_tier: AssetTier,
-    ) -> Result<(&mut ObligationLiquidity, usize)> {
-        if let Some(liquidity_index) = self.find_liquidity_index_in_borrows(borrow_reserve) {
-            Ok((&mut self.borrows[liquidity_index], liquidity_index))
-        } else if let Some((index, liquidity)) = self
-            .borrows
-            .iter_mut()
-            .enumerate()
-            .find(|c| c.1.borrow_reserve == Pubkey::default())
-        {
-            *liquidity = ObligationLiquidity::new(borrow_reserve, cumulative_borrow_rate);
-            self.borrows_asset_tiers[index] = borrow_reserve_asset_tier.into();
-
-            Ok((liquidity, index))
-        } else {
-            xmsg!("Obligation has no empty borrows");
-            err!(LendingError::ObligationReserveLimit)
-        }
+        Ok((self.liquidity(liquidity_index), liquidity_index))
     }
 
     fn find_liquidity_index_in_borrows(&self, borrow_reserve: Pubkey) -> Option<usize> {
-        self.borrows
-            .iter()
+        self.borrows()
             .position(|liquidity| liquidity.borrow_reserve == borrow_reserve)
     }
 
     pub fn deposits_empty(&self) -> bool {
-        self.deposits
-            .iter()
+        self.deposits()
             .all(|c| c.deposit_reserve == Pubkey::default())
     }
 
     pub fn borrows_empty(&self) -> bool {
-        self.borrows
-            .iter()
+        self.borrows()
             .all(|l| l.borrow_reserve == Pubkey::default())
     }
 
     pub fn deposits_count(&self) -> usize {
-        self.deposits
-            .iter()
+        self.deposits()
             .filter(|c| c.deposit_reserve != Pubkey::default())
             .count()
     }
 
     pub fn borrows_count(&self) -> usize {
-        self.borrows
-            .iter()
+        self.borrows()
             .filter(|l| l.borrow_reserve != Pubkey::default())
             .count()
     }
 
     pub fn get_deposit_asset_tiers(&self) -> Vec<AssetTier> {
-        self.deposits
-            .iter()
-            .enumerate()
-            .filter_map(|(index, deposit)| {
+        self.deposits_with_asset_tiers()
+            .filter_map(|(deposit, asset_tier)| {
                 if deposit.deposit_reserve != Pubkey::default() && deposit.deposited_amount > 0 {
-                    Some(AssetTier::try_from(self.deposits_asset_tiers[index]).unwrap())
+                    Some(AssetTier::try_from(asset_tier).unwrap())
                 } else {
                     None
                 }
@@ -397,12 +539,10 @@ impl Obligation {
     }
 
     pub fn get_borrows_asset_tiers(&self) -> Vec<AssetTier> {
-        self.borrows
-            .iter()
-            .enumerate()
-            .filter_map(|(index, borrow)| {
+        self.borrows_with_asset_tiers()
+            .filter_map(|(borrow, asset_tier)| {
                 if borrow.borrow_reserve != Pubkey::default() && borrow.borrowed_amount_sf > 0 {
-                    Some(AssetTier::try_from(self.borrows_asset_tiers[index]).unwrap())
+                    Some(AssetTier::try_from(asset_tier).unwrap())
                 } else {
                     None
                 }
@@ -415,62 +555,281 @@ impl Obligation {
             None
         } else {
             Some(
-                Fraction::from_bits(self.borrows.iter().map(|l| l.borrowed_amount_sf).sum())
+                Fraction::from_bits(self.borrows().map(|l| l.borrowed_amount_sf).sum())
                     .to_ceil::<u64>(),
             )
         }
     }
+}
 
-    pub fn get_bf_adjusted_debt_value(&self) -> Fraction {
-        Fraction::from_bits(self.borrow_factor_adjusted_debt_value_sf)
+impl<O, E> ObligationView<O, E>
+where
+    O: DerefMut<Target = Obligation>,
+    E: DerefMut<Target = ObligationExtension>,
+{
+    pub fn get_mut(&mut self) -> ObligationMut<'_> {
+        ObligationView::new(&mut *self.obligation, self.extension.as_deref_mut())
+    }
+
+    pub fn deposits_mut(&mut self) -> impl Iterator<Item = &mut ObligationCollateral> {
+        self.obligation.deposits.iter_mut().chain(
+            self.extension
+                .as_deref_mut()
+                .into_iter()
+                .flat_map(|extension| extension.deposits.iter_mut()),
+        )
     }
 
-    pub fn get_allowed_borrow_value(&self) -> Fraction {
-        Fraction::from_bits(self.allowed_borrow_value_sf)
+    pub fn borrows_mut(&mut self) -> impl Iterator<Item = &mut ObligationLiquidity> {
+        self.obligation.borrows.iter_mut().chain(
+            self.extension
+                .as_deref_mut()
+                .into_iter()
+                .flat_map(|extension| extension.borrows.iter_mut()),
+        )
     }
 
-    pub fn get_unhealthy_borrow_value(&self) -> Fraction {
-        Fraction::from_bits(self.unhealthy_borrow_value_sf)
+    pub fn deposits_with_asset_tiers_mut(
+        &mut self,
+    ) -> impl Iterator<Item = (&mut ObligationCollateral, &mut u8)> {
+        let obligation = &mut *self.obligation;
+        obligation
+            .deposits
+            .iter_mut()
+            .zip(obligation.deposits_asset_tiers.iter_mut())
+            .chain(
+                self.extension
+                    .as_deref_mut()
+                    .into_iter()
+                    .flat_map(|extension| {
+                        extension
+                            .deposits
+                            .iter_mut()
+                            .zip(extension.deposits_asset_tiers.iter_mut())
+                    }),
+            )
     }
 
-    pub fn has_referrer(&self) -> bool {
-        self.referrer != Pubkey::default()
+    pub fn borrows_with_asset_tiers_mut(
+        &mut self,
+    ) -> impl Iterator<Item = (&mut ObligationLiquidity, &mut u8)> {
+        let obligation = &mut *self.obligation;
+        obligation
+            .borrows
+            .iter_mut()
+            .zip(obligation.borrows_asset_tiers.iter_mut())
+            .chain(
+                self.extension
+                    .as_deref_mut()
+                    .into_iter()
+                    .flat_map(|extension| {
+                        extension
+                            .borrows
+                            .iter_mut()
+                            .zip(extension.borrows_asset_tiers.iter_mut())
+                    }),
+            )
     }
 
-    pub fn update_has_debt(&mut self) {
-        if self.borrows_empty() {
-            self.has_debt = 0;
+    pub fn collateral_mut(&mut self, collateral_index: usize) -> &mut ObligationCollateral {
+        self.deposits_mut().nth(collateral_index).unwrap()
+    }
+
+    pub fn liquidity_mut(&mut self, liquidity_index: usize) -> &mut ObligationLiquidity {
+        self.borrows_mut().nth(liquidity_index).unwrap()
+    }
+
+    pub fn repay(&mut self, settle_amount: Fraction, liquidity_index: usize) {
+        let (liquidity, asset_tier) = self
+            .borrows_with_asset_tiers_mut()
+            .nth(liquidity_index)
+            .unwrap();
+        if settle_amount == Fraction::from_bits(liquidity.borrowed_amount_sf) {
+            *liquidity = ObligationLiquidity::default();
+            *asset_tier = u8::MAX;
         } else {
-            self.has_debt = 1;
+            liquidity.repay(settle_amount);
         }
     }
 
-    pub fn is_marked_for_deleveraging(&self) -> bool {
-        self.autodeleverage_margin_call_started_timestamp != 0
+    pub fn withdraw(
+        &mut self,
+        withdraw_amount: u64,
+        collateral_index: usize,
+    ) -> Result<WithdrawResult> {
+        let (collateral, asset_tier) = self
+            .deposits_with_asset_tiers_mut()
+            .nth(collateral_index)
+            .unwrap();
+        if withdraw_amount == collateral.deposited_amount {
+            *collateral = ObligationCollateral::default();
+            *asset_tier = u8::MAX;
+            Ok(WithdrawResult::Full)
+        } else {
+            collateral.withdraw(withdraw_amount)?;
+            Ok(WithdrawResult::Partial)
+        }
     }
 
-    pub fn mark_for_deleveraging(&mut self, current_timestamp: u64, target_ltv_pct: u8) {
-        if current_timestamp == 0 {
-            panic!("value reserved for non-marked state");
+    pub fn find_or_add_collateral_to_deposits(
+        &mut self,
+        deposit_reserve: Pubkey,
+        deposit_reserve_asset_tier: AssetTier,
+        init_function: impl FnOnce(&mut ObligationCollateral) -> Result<()>,
+    ) -> Result<&mut ObligationCollateral> {
+        let collateral_index = self
+            .deposits()
+            .position(|collateral| collateral.deposit_reserve == deposit_reserve);
+        if let Some(collateral_index) = collateral_index {
+            Ok(self.collateral_mut(collateral_index))
+        } else if let Some((collateral, asset_tier)) = self
+            .deposits_with_asset_tiers_mut()
+            .find(|(c, _)| c.deposit_reserve == Pubkey::default())
+        {
+            *collateral = ObligationCollateral::new(deposit_reserve);
+            *asset_tier = deposit_reserve_asset_tier.into();
+
+            init_function(collateral)?;
+
+            Ok(collateral)
+        } else {
+            xmsg!("Obligation has no empty deposits");
+            err!(LendingError::ObligationReserveLimit)
         }
-        self.autodeleverage_margin_call_started_timestamp = current_timestamp;
-        self.autodeleverage_target_ltv_pct = target_ltv_pct;
     }
 
-    pub fn unmark_for_deleveraging(&mut self) {
-        self.autodeleverage_margin_call_started_timestamp = 0;
-        self.autodeleverage_target_ltv_pct = 0;
+    pub fn find_liquidity_in_borrows_mut(
+        &mut self,
+        borrow_reserve: Pubkey,
+    ) -> Result<(&mut ObligationLiquidity, usize)> {
+        if self.borrows_empty() {
+            xmsg!("Obligation has no borrows");
+            return err!(LendingError::ObligationBorrowsEmpty);
+        }
+        let liquidity_index = self
+            .find_liquidity_index_in_borrows(borrow_reserve)
+            .ok_or_else(|| error!(LendingError::InvalidObligationLiquidity))?;
+        Ok((self.liquidity_mut(liquidity_index), liquidity_index))
     }
 
-    pub fn check_not_marked_for_deleveraging(&self) -> Result<()> {
-        if self.is_marked_for_deleveraging() {
-            msg!(
-                "Obligation marked for deleveraging since {}",
-                self.autodeleverage_margin_call_started_timestamp
+    pub fn find_or_add_liquidity_to_borrows(
+        &mut self,
+        borrow_reserve: Pubkey,
+        cumulative_borrow_rate: BigFraction,
+        borrow_reserve_asset_tier: AssetTier,
+    ) -> Result<(&mut ObligationLiquidity, usize)> {
+        if let Some(liquidity_index) = self.find_liquidity_index_in_borrows(borrow_reserve) {
+            Ok((self.liquidity_mut(liquidity_index), liquidity_index))
+        } else if let Some((index, (liquidity, asset_tier))) = self
+            .borrows_with_asset_tiers_mut()
+            .enumerate()
+            .find(|(_, (l, _))| l.borrow_reserve == Pubkey::default())
+        {
+            *liquidity = ObligationLiquidity::new(borrow_reserve, cumulative_borrow_rate);
+            *asset_tier = borrow_reserve_asset_tier.into();
+
+            Ok((liquidity, index))
+        } else {
+            xmsg!("Obligation has no empty borrows");
+            err!(LendingError::ObligationReserveLimit)
+        }
+    }
+
+    pub fn update_has_debt(&mut self) {
+        if self.borrows_empty() {
+            self.obligation.has_debt = 0;
+        } else {
+            self.obligation.has_debt = 1;
+        }
+    }
+}
+
+fn check_obligation_data(data: &[u8]) -> Result<()> {
+    if data.len() < OBLIGATION_SIZE + 8 {
+        return Err(ErrorCode::AccountDidNotDeserialize.into());
+    }
+    if data[0..8] != Obligation::discriminator() {
+        return Err(ErrorCode::AccountDiscriminatorMismatch.into());
+    }
+    Ok(())
+}
+
+impl<'a> ObligationAccount<'a> {
+    pub fn load(account_info: &'a AccountInfo) -> Result<Self> {
+        if account_info.owner != &crate::ID {
+            return Err(ErrorCode::AccountOwnedByWrongProgram.into());
+        }
+
+        let data = account_info.try_borrow_data()?;
+        check_obligation_data(&data)?;
+
+        let (obligation, extension_data) = Ref::map_split(data, |data| {
+            let (obligation, extension) = data[8..].split_at(OBLIGATION_SIZE);
+            (bytemuck::from_bytes::<Obligation>(obligation), extension)
+        });
+
+        let extension = if obligation.is_extended() {
+            require_gte!(
+                extension_data.len(),
+                OBLIGATION_EXTENSION_SIZE,
+                LendingError::InvalidAccountInput
             );
-            return err!(LendingError::ObligationCurrentlyMarkedForDeleveraging);
+            Some(Ref::map(extension_data, |data| {
+                bytemuck::from_bytes(&data[..OBLIGATION_EXTENSION_SIZE])
+            }))
+        } else {
+            None
+        };
+
+        Ok(Self::new(obligation, extension))
+    }
+}
+
+impl<'a> ObligationAccountMut<'a> {
+    pub fn load(account_info: &'a AccountInfo) -> Result<Self> {
+        if account_info.owner != &crate::ID {
+            return Err(ErrorCode::AccountOwnedByWrongProgram.into());
         }
-        Ok(())
+        if !account_info.is_writable {
+            return Err(ErrorCode::AccountNotMutable.into());
+        }
+
+        let data = account_info.try_borrow_mut_data()?;
+        check_obligation_data(&data)?;
+
+        let (obligation, extension_data) = RefMut::map_split(data, |data| {
+            let (obligation, extension) = data[8..].split_at_mut(OBLIGATION_SIZE);
+            (
+                bytemuck::from_bytes_mut::<Obligation>(obligation),
+                extension,
+            )
+        });
+
+        let extension = if obligation.is_extended() {
+            require_gte!(
+                extension_data.len(),
+                OBLIGATION_EXTENSION_SIZE,
+                LendingError::InvalidAccountInput
+            );
+            Some(RefMut::map(extension_data, |data| {
+                bytemuck::from_bytes_mut(&mut data[..OBLIGATION_EXTENSION_SIZE])
+            }))
+        } else {
+            None
+        };
+
+        Ok(Self::new(obligation, extension))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObligationDelegatePermissions(u8);
+
+#[rustfmt::skip]
+bitflags! {
+    impl ObligationDelegatePermissions: u8 {
+        const DEPOSIT =   0b_0000_0010;
+        const REBALANCE = 0b_0000_0100;
     }
 }
 
@@ -542,7 +901,15 @@ pub struct ObligationLiquidity {
 
     pub borrowed_amount_outside_elevation_groups: u64,
 
-    pub padding2: [u64; 7],
+    pub fixed_borrow_rate_bps: u64,
+    pub fixed_rate_maturity_slot: u64,
+
+    pub padding2: [u64; 5],
+}
+
+pub struct FixedRateAccrual {
+    pub floating_borrowed_amount_f: Fraction,
+    pub fixed_borrowed_amount_f: Fraction,
 }
 
 impl ObligationLiquidity {
@@ -555,7 +922,9 @@ impl ObligationLiquidity {
             market_value_sf: 0,
             borrow_factor_adjusted_market_value_sf: 0,
             borrowed_amount_outside_elevation_groups: 0,
-            padding2: [0; 7],
+            fixed_borrow_rate_bps: 0,
+            fixed_rate_maturity_slot: 0,
+            padding2: [0; 5],
         }
     }
 
@@ -569,6 +938,54 @@ impl ObligationLiquidity {
             (Fraction::from_bits(self.borrowed_amount_sf) + borrow_amount).to_bits();
     }
 
+    pub fn is_fixed_rate(&self) -> bool {
+        self.fixed_rate_maturity_slot > 0
+    }
+
+    /// Accrues the locked rate until maturity. The slots of the period past maturity grow with
+    /// the reserve cumulative borrow rate, pro rata of the growth over the whole period.
+    pub fn accrue_fixed_rate_interest(
+        &mut self,
+        new_cumulative_borrow_rate: BigFraction,
+        current_slot: Slot,
+        slots_elapsed: u64,
+    ) -> Result<FixedRateAccrual> {
+        let previous_borrowed_amount_f = Fraction::from_bits(self.borrowed_amount_sf);
+
+        self.accrue_interest(new_cumulative_borrow_rate)?;
+        let floating_borrowed_amount_f = Fraction::from_bits(self.borrowed_amount_sf);
+
+        let last_accrual_slot = current_slot.saturating_sub(slots_elapsed);
+        let fixed_rate_slots = current_slot
+            .min(self.fixed_rate_maturity_slot)
+            .saturating_sub(last_accrual_slot);
+        let floating_rate_slots = slots_elapsed - fixed_rate_slots;
+
+        let floating_interest_f = if floating_rate_slots > 0 {
+            (floating_borrowed_amount_f - previous_borrowed_amount_f)
+                * Fraction::from(floating_rate_slots)
+                / Fraction::from(slots_elapsed)
+        } else {
+            Fraction::ZERO
+        };
+        let fixed_borrowed_amount_f = (previous_borrowed_amount_f + floating_interest_f)
+            * approximate_compounded_interest(
+                Fraction::from_bps(self.fixed_borrow_rate_bps),
+                fixed_rate_slots,
+            );
+        self.borrowed_amount_sf = fixed_borrowed_amount_f.to_bits();
+
+        if current_slot >= self.fixed_rate_maturity_slot {
+            self.fixed_borrow_rate_bps = 0;
+            self.fixed_rate_maturity_slot = 0;
+        }
+
+        Ok(FixedRateAccrual {
+            floating_borrowed_amount_f,
+            fixed_borrowed_amount_f,
+        })
+    }
+
     pub fn accrue_interest(&mut self, new_cumulative_borrow_rate: BigFraction) -> Result<()> {
         let former_cumulative_borrow_rate_bsf: U256 = U256(self.cumulative_borrow_rate_bsf.value);
 
@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+use derivative::Derivative;
+
+use crate::utils::OBLIGATION_PERMISSION_SIZE;
+
+static_assertions::const_assert_eq!(
+    OBLIGATION_PERMISSION_SIZE,
+    std::mem::size_of::<ObligationPermission>()
+);
+static_assertions::const_assert_eq!(0, std::mem::size_of::<ObligationPermission>() % 8);
+/// Allows `owner` to open obligations and borrow in a permissioned lending market.
+#[derive(PartialEq, Derivative, Default)]
+#[derivative(Debug)]
+#[account(zero_copy)]
+#[repr(C)]
+pub struct ObligationPermission {
+    pub lending_market: Pubkey,
+    pub owner: Pubkey,
+    /// Market owner or risk council that last issued or revoked the permission
+    pub authority: Pubkey,
+    pub issued_slot: u64,
+    /// Non-zero once revoked: new obligations and borrows are refused, repayments are not
+    pub revoked_slot: u64,
+    pub bump: u64,
+
+    #[derivative(Debug = "ignore")]
+    pub padding: [u64; 32],
+}
+
+impl ObligationPermission {
+    pub fn is_revoked(&self) -> bool {
+        self.revoked_slot != 0
+    }
+}
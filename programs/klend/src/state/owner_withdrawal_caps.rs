@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+use derivative::Derivative;
+
+use crate::{utils::OWNER_WITHDRAWAL_CAPS_SIZE, WithdrawalCaps};
+
+static_assertions::const_assert_eq!(
+    OWNER_WITHDRAWAL_CAPS_SIZE,
+    std::mem::size_of::<OwnerWithdrawalCaps>()
+);
+static_assertions::const_assert_eq!(0, std::mem::size_of::<OwnerWithdrawalCaps>() % 8);
+/// Accumulators of the withdrawal caps a reserve applies to each owner. They are shared by every
+/// obligation of `owner` and outlive its positions, so neither clearing a position nor opening
+/// another obligation resets them.
+#[derive(PartialEq, Derivative, Default)]
+#[derivative(Debug)]
+#[account(zero_copy)]
+#[repr(C)]
+pub struct OwnerWithdrawalCaps {
+    pub lending_market: Pubkey,
+    pub reserve: Pubkey,
+    pub owner: Pubkey,
+    /// Collateral withdrawn, in liquidity, capped by `obligation_deposit_withdrawal_cap`
+    pub deposit_withdrawal_cap: WithdrawalCaps,
+    /// Liquidity borrowed, capped by `obligation_debt_withdrawal_cap`
+    pub debt_withdrawal_cap: WithdrawalCaps,
+    pub bump: u64,
+
+    #[derivative(Debug = "ignore")]
+    pub padding: [u64; 32],
+}
@@ -0,0 +1,95 @@
+use anchor_lang::prelude::*;
+use derivative::Derivative;
+use num_enum::TryFromPrimitive;
+
+use super::VALUE_BYTE_ARRAY_LEN_RESERVE;
+use crate::{utils::PENDING_CONFIG_CHANGE_SIZE, LendingError};
+
+static_assertions::const_assert_eq!(
+    PENDING_CONFIG_CHANGE_SIZE,
+    std::mem::size_of::<PendingConfigChange>()
+);
+static_assertions::const_assert_eq!(0, std::mem::size_of::<PendingConfigChange>() % 8);
+
+#[derive(TryFromPrimitive, PartialEq, Eq, Clone, Copy, Debug)]
+#[repr(u8)]
+pub enum PendingConfigChangeKind {
+    Reserve = 0,
+    LendingMarket = 1,
+}
+
+/// Config change queued behind the lending market timelock, executable by anyone between
+/// `execution_timestamp` and `expiration_timestamp`.
+#[derive(PartialEq, Derivative)]
+#[derivative(Debug)]
+#[account(zero_copy)]
+#[repr(C)]
+pub struct PendingConfigChange {
+    pub lending_market: Pubkey,
+    /// Reserve for reserve config changes, the lending market itself otherwise
+    pub target: Pubkey,
+    /// Receives the rent when the change is executed or cancelled
+    pub payer: Pubkey,
+    /// `UpdateConfigMode` or `UpdateLendingMarketMode` depending on `kind`
+    pub mode: u64,
+    pub kind: u8,
+    #[derivative(Debug = "ignore")]
+    pub reserved: [u8; 7],
+    pub queued_timestamp: u64,
+    pub execution_timestamp: u64,
+    pub expiration_timestamp: u64,
+    pub value_len: u64,
+    #[derivative(Debug = "ignore")]
+    pub value: [u8; VALUE_BYTE_ARRAY_LEN_RESERVE],
+    pub bump: u64,
+
+    #[derivative(Debug = "ignore")]
+    pub padding: [u64; 15],
+}
+
+impl Default for PendingConfigChange {
+    fn default() -> Self {
+        Self {
+            lending_market: Pubkey::default(),
+            target: Pubkey::default(),
+            payer: Pubkey::default(),
+            mode: 0,
+            kind: 0,
+            reserved: [0; 7],
+            queued_timestamp: 0,
+            execution_timestamp: 0,
+            expiration_timestamp: 0,
+            value_len: 0,
+            value: [0; VALUE_BYTE_ARRAY_LEN_RESERVE],
+            bump: 0,
+            padding: [0; 15],
+        }
+    }
+}
+
+impl PendingConfigChange {
+    pub fn kind(&self) -> Result<PendingConfigChangeKind> {
+        PendingConfigChangeKind::try_from(self.kind)
+            .map_err(|_| error!(LendingError::InvalidAccountInput))
+    }
+
+    pub fn value(&self) -> &[u8] {
+        &self.value[..self.value_len as usize]
+    }
+
+    pub fn set_value(&mut self, value: &[u8]) -> Result<()> {
+        require_gte!(self.value.len(), value.len(), LendingError::InvalidConfig);
+        self.value.fill(0);
+        self.value[..value.len()].copy_from_slice(value);
+        self.value_len = value.len() as u64;
+        Ok(())
+    }
+
+    pub fn is_executable(&self, timestamp: u64) -> bool {
+        timestamp >= self.execution_timestamp
+    }
+
+    pub fn is_expired(&self, timestamp: u64) -> bool {
+        timestamp > self.expiration_timestamp
+    }
+}
@@ -21,8 +21,9 @@ use super::{DepositLiquidityResult, LastUpdate, TokenInfo};
 use crate::{
     fraction::FractionExtra,
     utils::{
-        borrow_rate_curve::BorrowRateCurve, BigFraction, Fraction, INITIAL_COLLATERAL_RATE,
-        PROGRAM_VERSION, RESERVE_CONFIG_SIZE, RESERVE_SIZE, SLOTS_PER_YEAR, U256,
+        borrow_rate_curve::{BorrowRateCurve, MAX_UTILIZATION_RATE_BPS},
+        BigFraction, Fraction, INITIAL_COLLATERAL_RATE, PROGRAM_VERSION, RESERVE_CONFIG_SIZE,
+        RESERVE_SIZE, SLOTS_PER_DAY, SLOTS_PER_YEAR, U256,
     },
     CalculateBorrowResult, CalculateRepayResult, LendingError, LendingResult, ReferrerTokenState,
 };
@@ -50,6 +51,57 @@ impl From<BigFractionBytes> for BigFraction {
     }
 }
 
+pub const RATE_HISTORY_CAPACITY: usize = 24;
+
+#[derive(PartialEq, Eq, Default, Debug)]
+#[zero_copy]
+#[repr(C)]
+pub struct RateHistorySample {
+    pub slot: u64,
+    pub utilization_rate_bps: u32,
+    pub borrow_rate_bps: u32,
+    pub cumulative_borrow_rate_bsf: [u64; 4],
+}
+
+#[derive(PartialEq, Eq, Debug)]
+#[zero_copy]
+#[repr(C)]
+pub struct ReserveRateHistory {
+    pub next_index: u64,
+    pub samples: [RateHistorySample; RATE_HISTORY_CAPACITY],
+}
+
+impl Default for ReserveRateHistory {
+    fn default() -> Self {
+        Self {
+            next_index: 0,
+            samples: [RateHistorySample::default(); RATE_HISTORY_CAPACITY],
+        }
+    }
+}
+
+impl ReserveRateHistory {
+    pub fn last_sample(&self) -> Option<&RateHistorySample> {
+        let index = (self.next_index as usize + RATE_HISTORY_CAPACITY - 1) % RATE_HISTORY_CAPACITY;
+        let sample = &self.samples[index];
+        (sample.slot > 0).then_some(sample)
+    }
+
+    pub fn push(&mut self, sample: RateHistorySample) {
+        self.samples[self.next_index as usize] = sample;
+        self.next_index = (self.next_index + 1) % RATE_HISTORY_CAPACITY as u64;
+    }
+
+    /// Recorded samples, oldest first.
+    pub fn samples(&self) -> impl Iterator<Item = &RateHistorySample> {
+        let (newest, oldest) = self.samples.split_at(self.next_index as usize);
+        oldest
+            .iter()
+            .chain(newest.iter())
+            .filter(|sample| sample.slot > 0)
+    }
+}
+
 static_assertions::const_assert_eq!(RESERVE_SIZE, std::mem::size_of::<Reserve>());
 static_assertions::const_assert_eq!(0, std::mem::size_of::<Reserve>() % 8);
 #[derive(PartialEq, Derivative)]
@@ -79,14 +131,22 @@ pub struct Reserve {
     pub config: ReserveConfig,
 
     #[derivative(Debug = "ignore")]
-    pub config_padding: [u64; 116],
+    pub config_padding: [u64; 108],
 
     pub borrowed_amount_outside_elevation_group: u64,
 
     pub borrowed_amounts_against_this_reserve_in_elevation_groups: [u64; 32],
 
+    pub adaptive_rate_at_target_sf: u128,
+
+    #[derivative(Debug = "ignore")]
+    pub rate_history: ReserveRateHistory,
+
+    /// Collateral locked in the withdraw queue, redeemed first when liquidity comes back
+    pub withdraw_queue_collateral_amount: u64,
+
     #[derivative(Debug = "ignore")]
-    pub padding: [u64; 207],
+    pub padding: [u64; 59],
 }
 
 impl Default for Reserve {
@@ -102,10 +162,13 @@ impl Default for Reserve {
             farm_debt: Pubkey::default(),
             reserve_liquidity_padding: [0; 150],
             reserve_collateral_padding: [0; 150],
-            config_padding: [0; 116],
+            config_padding: [0; 108],
             borrowed_amount_outside_elevation_group: 0,
             borrowed_amounts_against_this_reserve_in_elevation_groups: [0; 32],
-            padding: [0; 207],
+            adaptive_rate_at_target_sf: 0,
+            rate_history: ReserveRateHistory::default(),
+            withdraw_queue_collateral_amount: 0,
+            padding: [0; 59],
         }
     }
 }
@@ -142,9 +205,107 @@ impl Reserve {
     pub fn current_borrow_rate(&self) -> Result<Fraction> {
         let utilization_rate = self.liquidity.utilization_rate();
 
-        self.config
+        let curve_rate = self
+            .config
+            .borrow_rate_curve
+            .get_borrow_rate(utilization_rate)?;
+
+        let Some(rate_at_target) = self.adaptive_rate_at_target()? else {
+            return Ok(curve_rate);
+        };
+
+        // The whole curve is scaled so that it goes through the adaptive rate at target, without
+        // exceeding the max rate of the curve
+        let curve_rate_at_target =
+            self.config
+                .borrow_rate_curve
+                .get_borrow_rate(Fraction::from_bps(
+                    self.config.adaptive_rate.target_utilization_bps,
+                ))?;
+        if curve_rate_at_target == Fraction::ZERO {
+            return Ok(curve_rate);
+        }
+        let curve_max_rate = self
+            .config
             .borrow_rate_curve
-            .get_borrow_rate(utilization_rate)
+            .get_borrow_rate(Fraction::ONE)?;
+        Ok((curve_rate * rate_at_target / curve_rate_at_target).min(curve_max_rate))
+    }
+
+    pub fn record_rate_history_sample(&mut self, slot: Slot) -> Result<()> {
+        let interval = self.config.rate_history_sample_interval_slots;
+        if interval == 0 {
+            return Ok(());
+        }
+        if let Some(last_sample) = self.rate_history.last_sample() {
+            if slot < last_sample.slot.saturating_add(interval) {
+                return Ok(());
+            }
+        }
+
+        // Saturated so that an extreme rate cannot prevent the reserve from refreshing
+        let sample = RateHistorySample {
+            slot,
+            utilization_rate_bps: self
+                .liquidity
+                .utilization_rate()
+                .to_bps()
+                .unwrap_or(u32::MAX),
+            borrow_rate_bps: self.current_borrow_rate()?.to_bps().unwrap_or(u32::MAX),
+            cumulative_borrow_rate_bsf: self.liquidity.cumulative_borrow_rate_bsf.value,
+        };
+        self.rate_history.push(sample);
+
+        Ok(())
+    }
+
+    pub fn adaptive_rate_at_target(&self) -> Result<Option<Fraction>> {
+        let adaptive_rate = &self.config.adaptive_rate;
+        if !adaptive_rate.is_enabled() {
+            return Ok(None);
+        }
+
+        let rate_at_target = if self.adaptive_rate_at_target_sf == 0 {
+            self.config
+                .borrow_rate_curve
+                .get_borrow_rate(Fraction::from_bps(adaptive_rate.target_utilization_bps))?
+        } else {
+            Fraction::from_bits(self.adaptive_rate_at_target_sf)
+        };
+
+        Ok(Some(adaptive_rate.bound_rate_at_target(rate_at_target)))
+    }
+
+    fn update_adaptive_rate_at_target(
+        &mut self,
+        utilization_rate: Fraction,
+        slots_elapsed: u64,
+    ) -> Result<()> {
+        let Some(rate_at_target) = self.adaptive_rate_at_target()? else {
+            self.adaptive_rate_at_target_sf = 0;
+            return Ok(());
+        };
+
+        let adaptive_rate = &self.config.adaptive_rate;
+        let target_utilization = Fraction::from_bps(adaptive_rate.target_utilization_bps);
+        let max_change = Fraction::from_bps(adaptive_rate.adjustment_speed_bps_per_day)
+            * u128::from(slots_elapsed)
+            / u128::from(SLOTS_PER_DAY);
+
+        // The rate drifts proportionally to the distance from target, normalized to [0, 1] on each side
+        let new_rate_at_target = if utilization_rate > target_utilization {
+            let distance =
+                (utilization_rate - target_utilization) / (Fraction::ONE - target_utilization);
+            rate_at_target + max_change * distance
+        } else {
+            let distance = (target_utilization - utilization_rate) / target_utilization;
+            rate_at_target.saturating_sub(max_change * distance)
+        };
+
+        self.adaptive_rate_at_target_sf = adaptive_rate
+            .bound_rate_at_target(new_rate_at_target)
+            .to_bits();
+        Ok(())
     }
 
     pub fn borrow_factor_f(&self, is_in_elevation_group: bool) -> Fraction {
@@ -219,6 +380,16 @@ impl Reserve {
         Ok(liquidity_amount)
     }
 
+    /// Available liquidity that is not owed to the withdraw queue
+    pub fn unreserved_available_liquidity(&self) -> u64 {
+        let reserved_liquidity = self
+            .collateral_exchange_rate()
+            .collateral_to_liquidity_ceil(self.withdraw_queue_collateral_amount);
+        self.liquidity
+            .available_amount
+            .saturating_sub(reserved_liquidity)
+    }
+
     pub fn collateral_exchange_rate(&self) -> CollateralExchangeRate {
         let total_liquidity = self.liquidity.total_supply();
         self.collateral.exchange_rate(total_liquidity)
@@ -227,6 +398,7 @@ impl Reserve {
     pub fn accrue_interest(&mut self, current_slot: Slot, referral_fee_bps: u16) -> Result<()> {
         let slots_elapsed = self.last_update.slots_elapsed(current_slot)?;
         if slots_elapsed > 0 {
+            let utilization_rate = self.liquidity.utilization_rate();
             let current_borrow_rate = self.current_borrow_rate()?;
             let protocol_take_rate = Fraction::from_percent(self.config.protocol_take_rate_pct);
             let referral_rate = Fraction::from_bps(referral_fee_bps);
@@ -240,6 +412,8 @@ impl Reserve {
                 protocol_take_rate,
                 referral_rate,
             )?;
+
+            self.update_adaptive_rate_at_target(utilization_rate, slots_elapsed)?;
         }
 
         Ok(())
@@ -349,9 +523,10 @@ impl Reserve {
         }
     }
 
+    /// Fees are only redeemed from the liquidity that is not owed to the withdraw queue
     pub fn calculate_redeem_fees(&self) -> u64 {
         min(
-            self.liquidity.available_amount,
+            self.unreserved_available_liquidity(),
             Fraction::from_bits(self.liquidity.accumulated_protocol_fees_sf).to_floor(),
         )
     }
@@ -370,7 +545,7 @@ impl Reserve {
             self.liquidity.accumulated_referrer_fees_sf,
         );
         let available_unclaimed: u64 = Fraction::from_bits(available_unclaimed_sf).to_floor();
-        min(available_unclaimed, self.liquidity.available_amount)
+        min(available_unclaimed, self.unreserved_available_liquidity())
     }
 }
 
@@ -405,7 +580,14 @@ pub struct ReserveLiquidity {
     pub absolute_referral_rate_sf: u128,
     pub token_program: Pubkey,
 
-    pub padding2: [u64; 51],
+    pub price_circuit_breaker_tripped_ts: u64,
+
+    pub fixed_rate_interest_spread_sf: i128,
+
+    pub price_circuit_breaker_anchor_price_sf: u128,
+    pub price_circuit_breaker_anchor_ts: u64,
+
+    pub padding2: [u64; 45],
     pub padding3: [u128; 32],
 }
 
@@ -428,7 +610,11 @@ impl Default for ReserveLiquidity {
             absolute_referral_rate_sf: 0,
             market_price_last_updated_ts: 0,
             token_program: Pubkey::default(),
-            padding2: [0; 51],
+            price_circuit_breaker_tripped_ts: 0,
+            fixed_rate_interest_spread_sf: 0,
+            price_circuit_breaker_anchor_price_sf: 0,
+            price_circuit_breaker_anchor_ts: 0,
+            padding2: [0; 45],
             padding3: [0; 32],
         }
     }
@@ -463,7 +649,11 @@ impl ReserveLiquidity {
             absolute_referral_rate_sf: 0,
             market_price_last_updated_ts: 0,
             token_program: mint_token_program,
-            padding2: [0; 51],
+            price_circuit_breaker_tripped_ts: 0,
+            fixed_rate_interest_spread_sf: 0,
+            price_circuit_breaker_anchor_price_sf: 0,
+            price_circuit_breaker_anchor_ts: 0,
+            padding2: [0; 45],
             padding3: [0; 32],
         }
     }
@@ -573,6 +763,51 @@ impl ReserveLiquidity {
         Fraction::from_bits(self.borrowed_amount_sf) / total_supply
     }
 
+    /// Brings the debt accrued at the floating rate back to the fixed rate. The fees taken on the
+    /// floating interest are adjusted by the same take rates, the fees already withdrawn cannot be
+    /// clawed back.
+    pub fn settle_fixed_rate_interest(
+        &mut self,
+        floating_borrowed_amount: Fraction,
+        fixed_borrowed_amount: Fraction,
+        protocol_take_rate: Fraction,
+    ) -> Result<()> {
+        let borrowed_amount_f = Fraction::from_bits(self.borrowed_amount_sf);
+        self.borrowed_amount_sf = (borrowed_amount_f + fixed_borrowed_amount)
+            .checked_sub(floating_borrowed_amount)
+            .ok_or_else(|| error!(LendingError::MathOverflow))?
+            .to_bits();
+
+        let absolute_referral_rate = Fraction::from_bits(self.absolute_referral_rate_sf);
+        let protocol_rate = protocol_take_rate.saturating_sub(absolute_referral_rate);
+        let acc_protocol_fees_f = Fraction::from_bits(self.accumulated_protocol_fees_sf);
+        let pending_referrer_fees_f = Fraction::from_bits(self.pending_referrer_fees_sf);
+        if fixed_borrowed_amount >= floating_borrowed_amount {
+            let spread_f = fixed_borrowed_amount - floating_borrowed_amount;
+            self.accumulated_protocol_fees_sf =
+                (acc_protocol_fees_f + spread_f * protocol_rate).to_bits();
+            self.pending_referrer_fees_sf =
+                (pending_referrer_fees_f + spread_f * absolute_referral_rate).to_bits();
+        } else {
+            let spread_f = floating_borrowed_amount - fixed_borrowed_amount;
+            self.accumulated_protocol_fees_sf = acc_protocol_fees_f
+                .saturating_sub(spread_f * protocol_rate)
+                .to_bits();
+            self.pending_referrer_fees_sf = pending_referrer_fees_f
+                .saturating_sub(spread_f * absolute_referral_rate)
+                .to_bits();
+        }
+
+        let spread_sf = i128::try_from(fixed_borrowed_amount.to_bits())
+            .ok()
+            .zip(i128::try_from(floating_borrowed_amount.to_bits()).ok())
+            .map(|(fixed, floating)| fixed - floating)
+            .ok_or_else(|| error!(LendingError::MathOverflow))?;
+        self.fixed_rate_interest_spread_sf += spread_sf;
+
+        Ok(())
+    }
+
     fn compound_interest(
         &mut self,
         current_borrow_rate: Fraction,
@@ -674,6 +909,10 @@ impl ReserveLiquidity {
     pub fn get_market_price_f(&self) -> Fraction {
         Fraction::from_bits(self.market_price_sf)
     }
+
+    pub fn is_price_circuit_breaker_tripped(&self) -> bool {
+        self.price_circuit_breaker_tripped_ts > 0
+    }
 }
 
 pub struct NewReserveLiquidityParams {
@@ -901,6 +1140,21 @@ pub struct ReserveConfig {
     pub borrow_limit_against_this_collateral_in_elevation_group: [u64; 32],
 
     pub deleveraging_bonus_increase_bps_per_day: u64,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub adaptive_rate: AdaptiveRateConfig,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fixed_rate_borrow: FixedRateBorrowConfig,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub rate_history_sample_interval_slots: u64,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub obligation_deposit_withdrawal_cap: ObligationWithdrawalCapConfig,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub obligation_debt_withdrawal_cap: ObligationWithdrawalCapConfig,
 }
 
 impl ReserveConfig {
@@ -942,6 +1196,89 @@ pub enum ReserveStatus {
     Hidden = 2,
 }
 
+#[derive(BorshDeserialize, BorshSerialize, PartialEq, Eq, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+#[zero_copy]
+#[repr(C)]
+pub struct AdaptiveRateConfig {
+    pub target_utilization_bps: u32,
+    pub adjustment_speed_bps_per_day: u32,
+    pub min_rate_at_target_bps: u32,
+    pub max_rate_at_target_bps: u32,
+}
+
+impl AdaptiveRateConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.adjustment_speed_bps_per_day > 0
+    }
+
+    pub fn is_valid(&self, borrow_rate_curve: &BorrowRateCurve) -> bool {
+        if !self.is_enabled() {
+            return true;
+        }
+
+        self.target_utilization_bps > 0
+            && self.target_utilization_bps < MAX_UTILIZATION_RATE_BPS
+            && self.min_rate_at_target_bps <= self.max_rate_at_target_bps
+            && matches!(
+                borrow_rate_curve.get_borrow_rate(Fraction::from_bps(self.target_utilization_bps)),
+                Ok(rate) if rate > Fraction::ZERO
+            )
+            && matches!(
+                borrow_rate_curve.get_borrow_rate(Fraction::ONE),
+                Ok(max_rate) if Fraction::from_bps(self.max_rate_at_target_bps) <= max_rate
+            )
+    }
+
+    pub fn bound_rate_at_target(&self, rate_at_target: Fraction) -> Fraction {
+        rate_at_target
+            .max(Fraction::from_bps(self.min_rate_at_target_bps))
+            .min(Fraction::from_bps(self.max_rate_at_target_bps))
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize, PartialEq, Eq, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+#[zero_copy]
+#[repr(C)]
+pub struct FixedRateBorrowConfig {
+    pub premium_bps: u32,
+    pub max_term_seconds: u32,
+}
+
+impl FixedRateBorrowConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.max_term_seconds > 0
+    }
+}
+
+/// Withdrawal cap applied to each owner separately, per reserve, across all of their obligations
+#[derive(BorshDeserialize, BorshSerialize, PartialEq, Eq, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+#[zero_copy]
+#[repr(C)]
+pub struct ObligationWithdrawalCapConfig {
+    pub config_capacity: i64,
+    pub config_interval_length_seconds: u64,
+}
+
+impl ObligationWithdrawalCapConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.config_interval_length_seconds > 0
+    }
+
+    pub fn is_tighter_than(&self, previous: &ObligationWithdrawalCapConfig) -> bool {
+        self.is_enabled()
+            && (!previous.is_enabled()
+                || (self.config_capacity <= previous.config_capacity
+                    && self.config_interval_length_seconds
+                        >= previous.config_interval_length_seconds))
+    }
+}
+
 #[derive(BorshDeserialize, BorshSerialize, PartialEq, Eq, Default, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[zero_copy]
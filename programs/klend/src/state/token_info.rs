@@ -2,13 +2,14 @@ use std::fmt::Formatter;
 
 use anchor_lang::prelude::*;
 use borsh::{BorshDeserialize, BorshSerialize};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
 #[cfg(feature = "serde")]
 use serde;
 
 #[cfg(feature = "serde")]
 use super::serde_string;
 use crate::{
-    utils::{NULL_PUBKEY, TOKEN_INFO_SIZE},
+    utils::{DEFAULT_MAX_CONFIDENCE_BPS, FULL_BPS, NULL_PUBKEY, TOKEN_INFO_SIZE},
     LendingError,
 };
 
@@ -41,11 +42,44 @@ pub struct TokenInfo {
 
     pub block_price_usage: u8,
 
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub price_aggregation_mode: u8,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub primary_price_source: u8,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub pyth_min_verification_signatures: u8,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub require_confidence: u8,
+
     #[cfg_attr(feature = "serde", serde(skip_serializing, default))]
-    pub reserved: [u8; 7],
+    pub reserved: [u8; 3],
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_price_sources_divergence_bps: u64,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_confidence_bps: u64,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub pyth_max_posted_slot_lag: u64,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub price_circuit_breaker_max_change_bps: u64,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub price_circuit_breaker_window_seconds: u64,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fixed_price_configuration: FixedPriceConfiguration,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub derived_price_configuration: DerivedPriceConfiguration,
 
     #[cfg_attr(feature = "serde", serde(skip_serializing, default))]
-    pub _padding: [u64; 19],
+    pub _padding: [u64; 4],
 }
 
 impl std::fmt::Debug for TokenInfo {
@@ -61,6 +95,32 @@ impl std::fmt::Debug for TokenInfo {
             .field("switchboard_configuration", &self.switchboard_configuration)
             .field("pyth_configuration", &self.pyth_configuration)
             .field("block_price_usage", &self.block_price_usage)
+            .field("price_aggregation_mode", &self.price_aggregation_mode)
+            .field("primary_price_source", &self.primary_price_source)
+            .field(
+                "max_price_sources_divergence_bps",
+                &self.max_price_sources_divergence_bps,
+            )
+            .field("max_confidence_bps", &self.max_confidence_bps)
+            .field("require_confidence", &self.require_confidence)
+            .field(
+                "pyth_min_verification_signatures",
+                &self.pyth_min_verification_signatures,
+            )
+            .field("pyth_max_posted_slot_lag", &self.pyth_max_posted_slot_lag)
+            .field(
+                "price_circuit_breaker_max_change_bps",
+                &self.price_circuit_breaker_max_change_bps,
+            )
+            .field(
+                "price_circuit_breaker_window_seconds",
+                &self.price_circuit_breaker_window_seconds,
+            )
+            .field("fixed_price_configuration", &self.fixed_price_configuration)
+            .field(
+                "derived_price_configuration",
+                &self.derived_price_configuration,
+            )
             .finish()
     }
 }
@@ -72,9 +132,15 @@ impl TokenInfo {
         switchboard_price_info: Option<&AccountInfo>,
         switchboard_twap_info: Option<&AccountInfo>,
         scope_prices_info: Option<&AccountInfo>,
+        derived_price_base_reserve_info: Option<&AccountInfo>,
+        derived_price_stake_pool_info: Option<&AccountInfo>,
     ) -> Result<()> {
         require!(self.is_valid(), LendingError::InvalidOracleConfig);
         require!(self.is_twap_config_valid(), LendingError::InvalidTwapConfig);
+        require!(
+            self.is_price_aggregation_config_valid(),
+            LendingError::InvalidPriceAggregationConfig
+        );
         require!(
             self.check_pyth_acc_matches(pyth_info),
             LendingError::InvalidPythPriceAccount
@@ -87,15 +153,50 @@ impl TokenInfo {
             self.check_scope_acc_matches(scope_prices_info),
             LendingError::InvalidScopePriceAccount
         );
+        require!(
+            self.check_derived_price_acc_matches(
+                derived_price_base_reserve_info,
+                derived_price_stake_pool_info
+            ),
+            LendingError::InvalidDerivedPriceAccount
+        );
         Ok(())
     }
 
     #[inline]
     pub fn is_valid(&self) -> bool {
         self.scope_configuration.is_valid()
-            && (self.scope_configuration.is_enabled()
-                || self.switchboard_configuration.is_enabled()
-                || self.pyth_configuration.is_enabled())
+            && self.derived_price_configuration.is_valid()
+            && self.max_confidence_bps <= u64::from(FULL_BPS)
+            && self.require_confidence <= 1
+            && if self.is_oracle_free() {
+                self.is_oracle_free_config_valid()
+            } else {
+                self.scope_configuration.is_enabled()
+                    || self.switchboard_configuration.is_enabled()
+                    || self.pyth_configuration.is_enabled()
+            }
+    }
+
+    #[inline]
+    pub fn is_oracle_free(&self) -> bool {
+        self.fixed_price_configuration.is_enabled() || self.derived_price_configuration.is_enabled()
+    }
+
+    pub fn is_oracle_free_config_valid(&self) -> bool {
+        if self.fixed_price_configuration.is_enabled()
+            && self.derived_price_configuration.is_enabled()
+        {
+            return false;
+        }
+
+        let has_oracle = self.scope_configuration.is_enabled()
+            || self.switchboard_configuration.is_enabled()
+            || self.pyth_configuration.is_enabled();
+
+        !has_oracle
+            && !self.is_twap_enabled()
+            && self.price_aggregation_mode == u8::from(PriceAggregationMode::MostRecent)
     }
 
     #[inline]
@@ -125,6 +226,79 @@ impl TokenInfo {
         true
     }
 
+    pub fn get_max_confidence_bps(&self) -> u64 {
+        if self.max_confidence_bps == 0 {
+            DEFAULT_MAX_CONFIDENCE_BPS
+        } else {
+            self.max_confidence_bps
+        }
+    }
+
+    #[inline]
+    pub fn is_price_circuit_breaker_enabled(&self) -> bool {
+        self.price_circuit_breaker_max_change_bps > 0
+    }
+
+    #[inline]
+    pub fn is_price_circuit_breaker_config_valid(&self) -> bool {
+        !self.is_price_circuit_breaker_enabled() || self.price_circuit_breaker_window_seconds > 0
+    }
+
+    /// Sources without a confidence interval, like Scope, fail the confidence check only when
+    /// the reserve opted in, zero keeps the behaviour of the reserves created before the flag
+    pub fn is_confidence_required(&self) -> bool {
+        self.require_confidence != 0
+    }
+
+    pub fn get_price_aggregation_mode(&self) -> PriceAggregationMode {
+        PriceAggregationMode::try_from(self.price_aggregation_mode).unwrap()
+    }
+
+    pub fn get_primary_price_source(&self) -> PriceSource {
+        PriceSource::try_from(self.primary_price_source).unwrap()
+    }
+
+    pub fn is_price_source_enabled(&self, source: PriceSource) -> bool {
+        match source {
+            PriceSource::Pyth => self.pyth_configuration.is_enabled(),
+            PriceSource::Switchboard => self.switchboard_configuration.is_enabled(),
+            PriceSource::Scope => self.scope_configuration.is_enabled(),
+        }
+    }
+
+    pub fn is_price_aggregation_config_valid(&self) -> bool {
+        let Ok(mode) = PriceAggregationMode::try_from(self.price_aggregation_mode) else {
+            return false;
+        };
+        if mode == PriceAggregationMode::MostRecent {
+            return true;
+        }
+
+        let enabled_sources = [
+            PriceSource::Pyth,
+            PriceSource::Switchboard,
+            PriceSource::Scope,
+        ]
+        .into_iter()
+        .filter(|source| self.is_price_source_enabled(*source))
+        .count();
+        if enabled_sources < 2
+            || self.max_price_sources_divergence_bps == 0
+            || self.max_price_sources_divergence_bps > u64::from(FULL_BPS)
+        {
+            return false;
+        }
+
+        if mode == PriceAggregationMode::PrimaryWithDeviationGuard {
+            return matches!(
+                PriceSource::try_from(self.primary_price_source),
+                Ok(source) if self.is_price_source_enabled(source)
+            );
+        }
+
+        true
+    }
+
     #[inline]
     pub fn check_pyth_acc_matches(&self, pyth_info: Option<&AccountInfo>) -> bool {
         if self.pyth_configuration.is_enabled() {
@@ -163,6 +337,21 @@ impl TokenInfo {
         }
     }
 
+    #[inline]
+    pub fn check_derived_price_acc_matches(
+        &self,
+        base_reserve_info: Option<&AccountInfo>,
+        stake_pool_info: Option<&AccountInfo>,
+    ) -> bool {
+        let conf = &self.derived_price_configuration;
+        if conf.is_enabled() {
+            matches!(base_reserve_info, Some(a) if *a.key == conf.base_reserve)
+                && matches!(stake_pool_info, Some(a) if *a.key == conf.stake_pool)
+        } else {
+            base_reserve_info.is_none() && stake_pool_info.is_none()
+        }
+    }
+
     pub fn symbol(&self) -> &str {
         std::str::from_utf8(&self.name)
             .unwrap_or("InvalidTokenName")
@@ -170,6 +359,42 @@ impl TokenInfo {
     }
 }
 
+#[derive(
+    AnchorSerialize,
+    AnchorDeserialize,
+    TryFromPrimitive,
+    IntoPrimitive,
+    PartialEq,
+    Eq,
+    Debug,
+    Clone,
+    Copy,
+)]
+#[repr(u8)]
+pub enum PriceAggregationMode {
+    MostRecent = 0,
+    Median = 1,
+    PrimaryWithDeviationGuard = 2,
+}
+
+#[derive(
+    AnchorSerialize,
+    AnchorDeserialize,
+    TryFromPrimitive,
+    IntoPrimitive,
+    PartialEq,
+    Eq,
+    Debug,
+    Clone,
+    Copy,
+)]
+#[repr(u8)]
+pub enum PriceSource {
+    Pyth = 0,
+    Switchboard = 1,
+    Scope = 2,
+}
+
 #[derive(BorshDeserialize, BorshSerialize, Debug, PartialEq, Eq, Default)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
@@ -257,6 +482,45 @@ impl PythConfiguration {
     }
 }
 
+#[derive(BorshDeserialize, BorshSerialize, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+#[zero_copy]
+#[repr(C)]
+pub struct FixedPriceConfiguration {
+    pub value: u64,
+    pub exp: u64,
+}
+
+impl FixedPriceConfiguration {
+    pub fn is_enabled(&self) -> bool {
+        self.value > 0
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+#[zero_copy]
+#[repr(C)]
+pub struct DerivedPriceConfiguration {
+    #[cfg_attr(feature = "serde", serde(with = "serde_string", default))]
+    pub base_reserve: Pubkey,
+    #[cfg_attr(feature = "serde", serde(with = "serde_string", default))]
+    pub stake_pool: Pubkey,
+}
+
+impl DerivedPriceConfiguration {
+    pub fn is_enabled(&self) -> bool {
+        self.base_reserve != Pubkey::default() && self.base_reserve != NULL_PUBKEY
+    }
+
+    pub fn is_valid(&self) -> bool {
+        !self.is_enabled()
+            || (self.stake_pool != Pubkey::default() && self.stake_pool != NULL_PUBKEY)
+    }
+}
+
 #[cfg(feature = "serde")]
 mod serde_name {
     use serde::{Deserialize, Deserializer, Serializer};
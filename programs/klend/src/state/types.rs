@@ -13,6 +13,12 @@ pub struct CalculateBorrowResult {
     pub referrer_fee: u64,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedRateBorrowTerms {
+    pub term_seconds: u64,
+    pub max_fixed_rate_bps: u64,
+}
+
 #[derive(Debug)]
 pub struct CalculateRepayResult {
     pub settle_amount_f: Fraction,
@@ -41,6 +47,7 @@ pub struct LiquidateAndRedeemResult {
     pub withdraw_amount: u64,
     pub withdraw_collateral_amount: u64,
     pub total_withdraw_liquidity_amount: Option<(u64, u64)>,
+    pub liquidation_bonus_rate: Fraction,
 }
 
 pub struct LiquidationParams {
@@ -56,6 +63,7 @@ pub struct RefreshObligationDepositsResult {
     pub allowed_borrow_value_f: Fraction,
     pub unhealthy_borrow_value_f: Fraction,
     pub prices_state: PriceStatusFlags,
+    pub price_circuit_breaker_tripped: bool,
     pub borrowing_disabled: bool,
 }
 
@@ -63,6 +71,7 @@ pub struct RefreshObligationBorrowsResult {
     pub borrow_factor_adjusted_debt_value_f: Fraction,
     pub borrowed_assets_market_value_f: Fraction,
     pub prices_state: PriceStatusFlags,
+    pub price_circuit_breaker_tripped: bool,
     pub highest_borrow_factor_pct: u64,
     pub borrowed_amount_in_elevation_group: Option<u64>,
 }
@@ -0,0 +1,133 @@
+use anchor_lang::prelude::*;
+use derivative::Derivative;
+
+use crate::{
+    utils::{MAX_WITHDRAW_QUEUE_TICKETS, MAX_WITHDRAW_TICKETS_PER_OWNER, WITHDRAW_QUEUE_SIZE},
+    LendingError,
+};
+
+static_assertions::const_assert_eq!(WITHDRAW_QUEUE_SIZE, std::mem::size_of::<WithdrawQueue>());
+static_assertions::const_assert_eq!(0, std::mem::size_of::<WithdrawQueue>() % 8);
+
+/// FIFO queue of collateral redemptions waiting for liquidity to come back into a reserve.
+#[derive(PartialEq, Derivative)]
+#[derivative(Debug)]
+#[account(zero_copy)]
+#[repr(C)]
+pub struct WithdrawQueue {
+    pub lending_market: Pubkey,
+    pub reserve: Pubkey,
+    pub next_ticket_id: u64,
+    /// Collateral locked in tickets that is not filled yet
+    pub queued_collateral_amount: u64,
+    /// Liquidity set aside for filled tickets that is not claimed yet
+    pub filled_liquidity_amount: u64,
+    pub tickets_count: u64,
+    /// Tickets in FIFO order, only the first `tickets_count` are in use
+    pub tickets: [WithdrawTicket; MAX_WITHDRAW_QUEUE_TICKETS],
+    pub bump: u64,
+
+    #[derivative(Debug = "ignore")]
+    pub padding: [u64; 31],
+}
+
+impl Default for WithdrawQueue {
+    fn default() -> Self {
+        Self {
+            lending_market: Pubkey::default(),
+            reserve: Pubkey::default(),
+            next_ticket_id: 0,
+            queued_collateral_amount: 0,
+            filled_liquidity_amount: 0,
+            tickets_count: 0,
+            tickets: [WithdrawTicket::default(); MAX_WITHDRAW_QUEUE_TICKETS],
+            bump: 0,
+            padding: [0; 31],
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+#[zero_copy]
+#[repr(C)]
+pub struct WithdrawTicket {
+    pub owner: Pubkey,
+    pub id: u64,
+    /// Collateral still waiting to be filled
+    pub collateral_amount: u64,
+    /// Liquidity the owner can claim
+    pub filled_liquidity_amount: u64,
+    pub enqueued_timestamp: u64,
+}
+
+impl WithdrawQueue {
+    pub fn tickets(&self) -> &[WithdrawTicket] {
+        &self.tickets[..self.tickets_count as usize]
+    }
+
+    pub fn tickets_mut(&mut self) -> &mut [WithdrawTicket] {
+        &mut self.tickets[..self.tickets_count as usize]
+    }
+
+    pub fn enqueue(
+        &mut self,
+        owner: Pubkey,
+        collateral_amount: u64,
+        timestamp: u64,
+    ) -> Result<u64> {
+        let index = self.tickets_count as usize;
+        if index >= MAX_WITHDRAW_QUEUE_TICKETS {
+            return err!(LendingError::WithdrawQueueFull);
+        }
+        let owner_tickets_count = self
+            .tickets()
+            .iter()
+            .filter(|ticket| ticket.owner == owner)
+            .count();
+        if owner_tickets_count >= MAX_WITHDRAW_TICKETS_PER_OWNER {
+            msg!(
+                "Owner {} already has {} withdraw tickets",
+                owner,
+                owner_tickets_count
+            );
+            return err!(LendingError::WithdrawTicketsPerOwnerExceeded);
+        }
+
+        let id = self.next_ticket_id;
+        self.tickets[index] = WithdrawTicket {
+            owner,
+            id,
+            collateral_amount,
+            filled_liquidity_amount: 0,
+            enqueued_timestamp: timestamp,
+        };
+        self.tickets_count += 1;
+        self.next_ticket_id += 1;
+        self.queued_collateral_amount += collateral_amount;
+
+        Ok(id)
+    }
+
+    pub fn position_of_ticket(&self, owner: Pubkey, id: u64) -> Result<usize> {
+        self.tickets()
+            .iter()
+            .position(|ticket| ticket.id == id && ticket.owner == owner)
+            .ok_or_else(|| error!(LendingError::WithdrawTicketNotFound))
+    }
+
+    /// Drops the tickets that have nothing left to fill or claim, keeping the FIFO order
+    pub fn remove_settled_tickets(&mut self) {
+        let mut kept = 0;
+        for index in 0..self.tickets_count as usize {
+            let ticket = self.tickets[index];
+            if ticket.collateral_amount > 0 || ticket.filled_liquidity_amount > 0 {
+                self.tickets[kept] = ticket;
+                kept += 1;
+            }
+        }
+        for ticket in &mut self.tickets[kept..self.tickets_count as usize] {
+            *ticket = WithdrawTicket::default();
+        }
+        self.tickets_count = kept as u64;
+    }
+}
@@ -37,6 +37,19 @@ pub const NO_DELEVERAGING_MARKER: u8 = u8::MAX;
 
 pub const MAX_OBLIGATION_RESERVES: u64 = 20;
 
+pub const MAX_RATE_HISTORY_SAMPLE_INTERVAL_SLOTS: u64 = SLOTS_PER_DAY * 30;
+
+pub const MAX_CONFIG_TIMELOCK_SECS: u64 = SECONDS_PER_DAY * 30;
+/// Queued config changes not executed within this window after their execution timestamp expire
+pub const CONFIG_CHANGE_EXPIRY_SECS: u64 = SECONDS_PER_DAY * 7;
+pub const MAX_WITHDRAW_QUEUE_TICKETS: usize = 64;
+/// Keeps a single owner from taking up the whole withdraw queue
+pub const MAX_WITHDRAW_TICKETS_PER_OWNER: usize = 4;
+
+pub const OBLIGATION_EXTENSION_DEPOSITS: usize = 8;
+
+pub const OBLIGATION_EXTENSION_BORROWS: usize = 5;
+
 pub const CLOSE_TO_INSOLVENCY_RISKY_LTV: u8 = 95;
 
 pub const MIN_INITIAL_DEPOSIT_AMOUNT: u64 = 1000;
@@ -52,11 +65,16 @@ pub const NULL_PUBKEY: solana_program::pubkey::Pubkey =
 pub const LENDING_MARKET_SIZE: usize = 4656;
 pub const RESERVE_SIZE: usize = 8616;
 pub const OBLIGATION_SIZE: usize = 3336;
-pub const RESERVE_CONFIG_SIZE: usize = 920;
+pub const OBLIGATION_EXTENSION_SIZE: usize = 2616;
+pub const RESERVE_CONFIG_SIZE: usize = 984;
 pub const REFERRER_TOKEN_STATE_SIZE: usize = 352;
 pub const USER_METADATA_SIZE: usize = 1024;
 pub const REFERRER_STATE_SIZE: usize = 64;
 pub const SHORT_URL_SIZE: usize = 68;
+pub const OBLIGATION_PERMISSION_SIZE: usize = 376;
+pub const PENDING_CONFIG_CHANGE_SIZE: usize = 1256;
+pub const WITHDRAW_QUEUE_SIZE: usize = 4448;
+pub const OWNER_WITHDRAWAL_CAPS_SIZE: usize = 424;
 pub const TOKEN_INFO_SIZE: usize = 384;
 
 pub const GLOBAL_ALLOWED_BORROW_VALUE: u64 = 45_000_000;
@@ -179,3 +197,5 @@ impl CpiWhitelistedAccount {
         }
     }
 }
+
+pub const SPL_STAKE_POOL_PROGRAM_ID: Pubkey = pubkey!("SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy");
@@ -0,0 +1,177 @@
+use anchor_lang::prelude::*;
+
+use super::{
+    checks::is_within_tolerance,
+    types::{Price, TimestampedPrice, TimestampedPriceWithTwap},
+};
+use crate::{utils::Fraction, LendingError, PriceAggregationMode, PriceSource, TokenInfo};
+
+pub(super) struct AggregatedPrice {
+    pub price: TimestampedPriceWithTwap,
+    pub sources_agreed: bool,
+}
+
+struct LoadedPrice {
+    source: PriceSource,
+    price: Fraction,
+    timestamp: u64,
+    confidence: Option<Price<u128>>,
+    twap: Option<TimestampedPrice>,
+}
+
+impl LoadedPrice {
+    fn into_price_with_twap(self) -> TimestampedPriceWithTwap {
+        let LoadedPrice {
+            price,
+            timestamp,
+            confidence,
+            twap,
+            ..
+        } = self;
+        TimestampedPriceWithTwap {
+            price: TimestampedPrice {
+                price_load: Box::new(move || Ok(price)),
+                timestamp,
+                confidence,
+            },
+            twap,
+        }
+    }
+}
+
+pub(super) fn aggregate_prices(
+    token_info: &TokenInfo,
+    prices: Vec<(PriceSource, TimestampedPriceWithTwap)>,
+) -> Result<Option<AggregatedPrice>> {
+    if prices.is_empty() {
+        msg!("No price feed available");
+        return err!(LendingError::PriceNotValid);
+    }
+
+    let tolerance_bps = token_info.max_price_sources_divergence_bps;
+    let aggregated_price = match token_info.get_price_aggregation_mode() {
+        PriceAggregationMode::MostRecent => {
+            let price = most_recent(prices, |(_, p)| p.price.timestamp)
+                .map(|(_, price)| price)
+                .unwrap();
+            Some(AggregatedPrice {
+                price,
+                sources_agreed: true,
+            })
+        }
+        PriceAggregationMode::Median => get_median_price(load_prices(prices), tolerance_bps),
+        PriceAggregationMode::PrimaryWithDeviationGuard => get_primary_price(
+            load_prices(prices),
+            token_info.get_primary_price_source(),
+            tolerance_bps,
+        ),
+    };
+
+    if let Some(AggregatedPrice {
+        sources_agreed: false,
+        ..
+    }) = aggregated_price
+    {
+        msg!(
+            "Price sources do not agree within {tolerance_bps} bps token=[{}]",
+            token_info.symbol()
+        );
+    }
+
+    Ok(aggregated_price)
+}
+
+fn load_prices(prices: Vec<(PriceSource, TimestampedPriceWithTwap)>) -> Vec<LoadedPrice> {
+    prices
+        .into_iter()
+        .filter_map(|(source, TimestampedPriceWithTwap { price, twap })| {
+            match (price.price_load)() {
+                Ok(price_dec) => Some(LoadedPrice {
+                    source,
+                    price: price_dec,
+                    timestamp: price.timestamp,
+                    confidence: price.confidence,
+                    twap,
+                }),
+                Err(e) => {
+                    msg!("Price from {source:?} is not available, {e:?}",);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+fn most_recent<T>(prices: impl IntoIterator<Item = T>, timestamp: impl Fn(&T) -> u64) -> Option<T> {
+    prices.into_iter().reduce(|current, candidate| {
+        if timestamp(&candidate) > timestamp(&current) {
+            candidate
+        } else {
+            current
+        }
+    })
+}
+
+fn get_median_price(mut prices: Vec<LoadedPrice>, tolerance_bps: u64) -> Option<AggregatedPrice> {
+    if prices.is_empty() {
+        return None;
+    }
+    prices.sort_by_key(|p| p.price);
+
+    let mid = prices.len() / 2;
+    let median = if prices.len() % 2 == 0 {
+        (prices[mid - 1].price + prices[mid].price) / 2
+    } else {
+        prices[mid].price
+    };
+
+    let agreeing_timestamps: Vec<u64> = prices
+        .iter()
+        .filter(|p| is_within_tolerance(median, p.price, tolerance_bps))
+        .map(|p| p.timestamp)
+        .collect();
+    let sources_agreed = agreeing_timestamps.len() >= 2;
+    let timestamp = if agreeing_timestamps.is_empty() {
+        prices.iter().map(|p| p.timestamp).min()
+    } else {
+        agreeing_timestamps.into_iter().min()
+    }
+    .unwrap();
+
+    let mut closest = prices
+        .into_iter()
+        .min_by_key(|p| Fraction::abs_diff(p.price, median))
+        .unwrap();
+    closest.price = median;
+    closest.timestamp = timestamp;
+
+    Some(AggregatedPrice {
+        price: closest.into_price_with_twap(),
+        sources_agreed,
+    })
+}
+
+fn get_primary_price(
+    mut prices: Vec<LoadedPrice>,
+    primary_source: PriceSource,
+    tolerance_bps: u64,
+) -> Option<AggregatedPrice> {
+    let Some(primary_index) = prices.iter().position(|p| p.source == primary_source) else {
+        msg!("Primary price source {primary_source:?} is not available",);
+        // Keep a usable price for liquidations, the missing guard blocks everything else
+        return most_recent(prices, |p| p.timestamp).map(|p| AggregatedPrice {
+            price: p.into_price_with_twap(),
+            sources_agreed: false,
+        });
+    };
+
+    let primary = prices.swap_remove(primary_index);
+    let sources_agreed = prices
+        .iter()
+        .any(|p| is_within_tolerance(primary.price, p.price, tolerance_bps));
+
+    Some(AggregatedPrice {
+        price: primary.into_price_with_twap(),
+        sources_agreed,
+    })
+}
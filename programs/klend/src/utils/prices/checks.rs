@@ -53,9 +53,10 @@ pub(super) fn get_validated_price(
                 }
             }
 
-            match (twap.price_load)()
-                .and_then(|twap_dec| check_twap_in_tolerance(price_dec, twap_dec, token_info))
-            {
+            match (twap.price_load)().and_then(|twap_dec| {
+                check_twap_in_tolerance(price_dec, twap_dec, token_info)?;
+                check_price_confidence(twap_dec, twap.confidence, token_info)
+            }) {
                 Ok(()) => {
                     price_status.set(PriceStatusFlags::TWAP_CHECKED, true);
                 }
@@ -71,6 +72,11 @@ pub(super) fn get_validated_price(
         price_status.set(PriceStatusFlags::TWAP_AGE_CHECKED, true);
     }
 
+    match check_price_confidence(price_dec, price.confidence, token_info) {
+        Ok(()) => price_status.set(PriceStatusFlags::PRICE_CONFIDENCE_CHECKED, true),
+        Err(e) => msg!("Price confidence check failed token=[{price_label}]: {e:?}",),
+    }
+
     match check_price_heuristics(price_dec, &token_info.heuristic) {
         Ok(()) => price_status.set(PriceStatusFlags::HEURISTIC_CHECKED, true),
         Err(e) => msg!("Price heuristic check failed token=[{price_label}]: {e:?}",),
@@ -101,7 +107,11 @@ fn check_price_age(
     }
 }
 
-fn is_within_tolerance(px: Fraction, twap: Fraction, acceptable_tolerance_bps: u64) -> bool {
+pub(super) fn is_within_tolerance(
+    px: Fraction,
+    twap: Fraction,
+    acceptable_tolerance_bps: u64,
+) -> bool {
     let abs_diff = Fraction::abs_diff(px, twap);
 
     let diff_bps_scaled = abs_diff * u128::from(FULL_BPS);
@@ -126,6 +136,35 @@ fn check_twap_in_tolerance(price: Fraction, twap: Fraction, token_info: &TokenIn
     Ok(())
 }
 
+fn check_price_confidence(
+    price: Fraction,
+    confidence: Option<Price<u128>>,
+    token_info: &TokenInfo,
+) -> Result<()> {
+    let Some(confidence) = confidence else {
+        if token_info.is_confidence_required() {
+            xmsg!("Price source does not provide a confidence interval");
+            return err!(LendingError::PriceConfidenceNotAvailable);
+        }
+        return Ok(());
+    };
+    let confidence = price_to_fraction(confidence);
+    let max_confidence_bps = token_info.get_max_confidence_bps();
+
+    let confidence_bps_scaled = confidence * u128::from(FULL_BPS);
+    let max_confidence_scaled = price * u128::from(max_confidence_bps);
+    if confidence_bps_scaled > max_confidence_scaled {
+        msg!(
+            "Price confidence interval is too wide \
+              price={price} \
+              confidence={confidence} \
+              max_confidence_bps={max_confidence_bps}",
+        );
+        return err!(LendingError::PriceConfidenceTooWide);
+    }
+    Ok(())
+}
+
 fn check_price_heuristics(token_price: Fraction, heuristic: &PriceHeuristic) -> Result<()> {
     if heuristic.lower > 0 {
         let lower_heuristic = Price {
@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+
+use super::types::{Price, TimestampedPrice, TimestampedPriceWithTwap};
+use crate::{
+    utils::{FatAccountLoader, Fraction, SPL_STAKE_POOL_PROGRAM_ID},
+    DerivedPriceConfiguration, LendingError, PriceStatusFlags, Reserve,
+};
+
+// SPL stake pool account layout, only the fields needed to compute the exchange rate
+const STAKE_POOL_ACCOUNT_TYPE: u8 = 1;
+const STAKE_POOL_TOTAL_LAMPORTS_OFFSET: usize = 258;
+const STAKE_POOL_POOL_TOKEN_SUPPLY_OFFSET: usize = 266;
+const STAKE_POOL_LAST_UPDATE_EPOCH_OFFSET: usize = 274;
+
+pub(super) struct DerivedPrice {
+    pub price: TimestampedPriceWithTwap,
+    pub base_price_status: PriceStatusFlags,
+}
+
+pub(super) fn get_derived_price_and_status(
+    config: &DerivedPriceConfiguration,
+    reserve: &Pubkey,
+    lending_market: &Pubkey,
+    base_reserve_info: &AccountInfo,
+    stake_pool_info: &AccountInfo,
+    clock: &Clock,
+) -> Result<DerivedPrice> {
+    if *base_reserve_info.key != config.base_reserve || *stake_pool_info.key != config.stake_pool {
+        msg!("Derived price accounts do not match the reserve config");
+        return err!(LendingError::InvalidDerivedPriceAccount);
+    }
+    if base_reserve_info.key == reserve {
+        msg!("Derived price base reserve can't be the reserve itself");
+        return err!(LendingError::InvalidDerivedPriceAccount);
+    }
+
+    let base_reserve_loader = FatAccountLoader::<Reserve>::try_from(base_reserve_info)?;
+    let base_reserve = base_reserve_loader.load()?;
+
+    if base_reserve.lending_market != *lending_market {
+        msg!("Derived price base reserve belongs to another lending market");
+        return err!(LendingError::InvalidDerivedPriceAccount);
+    }
+
+    if base_reserve
+        .last_update
+        .is_stale(clock.slot, PriceStatusFlags::NONE)?
+    {
+        msg!("Derived price base reserve is stale and must be refreshed in the current slot");
+        return err!(LendingError::ReserveStale);
+    }
+
+    let ratio = get_stake_pool_exchange_rate(stake_pool_info, clock.epoch)?;
+    let price_dec = base_reserve.liquidity.get_market_price_f() * ratio;
+
+    Ok(DerivedPrice {
+        price: TimestampedPriceWithTwap {
+            price: TimestampedPrice {
+                price_load: Box::new(move || Ok(price_dec)),
+                timestamp: base_reserve.liquidity.market_price_last_updated_ts,
+                // The confidence of the base price is carried by its status
+                confidence: Some(Price { value: 0, exp: 0 }),
+            },
+            twap: None,
+        },
+        base_price_status: base_reserve.last_update.get_price_status(),
+    })
+}
+
+fn get_stake_pool_exchange_rate(stake_pool_info: &AccountInfo, epoch: u64) -> Result<Fraction> {
+    if *stake_pool_info.owner != SPL_STAKE_POOL_PROGRAM_ID {
+        msg!("Derived price stake pool account is not owned by the stake pool program");
+        return err!(LendingError::InvalidDerivedPriceAccount);
+    }
+
+    let data = stake_pool_info.try_borrow_data()?;
+    let read_u64 = |offset: usize| -> Result<u64> {
+        data.get(offset..offset + 8)
+            .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+            .ok_or_else(|| error!(LendingError::PriceNotValid))
+    };
+
+    if data.first() != Some(&STAKE_POOL_ACCOUNT_TYPE) {
+        msg!("Derived price stake pool account is not a stake pool");
+        return err!(LendingError::PriceNotValid);
+    }
+
+    let total_lamports = read_u64(STAKE_POOL_TOTAL_LAMPORTS_OFFSET)?;
+    let pool_token_supply = read_u64(STAKE_POOL_POOL_TOKEN_SUPPLY_OFFSET)?;
+    let last_update_epoch = read_u64(STAKE_POOL_LAST_UPDATE_EPOCH_OFFSET)?;
+
+    if last_update_epoch != epoch {
+        msg!(
+            "Derived price stake pool was last updated in epoch {last_update_epoch}, current epoch {epoch}",
+        );
+        return err!(LendingError::PriceTooOld);
+    }
+    if pool_token_supply == 0 {
+        msg!("Derived price stake pool has no pool token supply");
+        return err!(LendingError::PriceNotValid);
+    }
+
+    Ok(Fraction::from_num(total_lamports) / Fraction::from_num(pool_token_supply))
+}
@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+
+use super::{
+    types::{Price, TimestampedPrice, TimestampedPriceWithTwap},
+    utils::price_to_fraction,
+};
+use crate::{FixedPriceConfiguration, LendingError};
+
+pub(super) fn get_fixed_price(
+    conf: &FixedPriceConfiguration,
+    clock: &Clock,
+) -> Result<TimestampedPriceWithTwap> {
+    let price = Price {
+        value: conf.value,
+        exp: u32::try_from(conf.exp).map_err(|_| error!(LendingError::PriceNotValid))?,
+    };
+    let price_dec = price_to_fraction(price);
+
+    Ok(TimestampedPriceWithTwap {
+        price: TimestampedPrice {
+            price_load: Box::new(move || Ok(price_dec)),
+            timestamp: u64::try_from(clock.unix_timestamp).unwrap(),
+            // A fixed price is exact
+            confidence: Some(Price { value: 0, exp: 0 }),
+        },
+        twap: None,
+    })
+}
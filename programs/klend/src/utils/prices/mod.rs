@@ -1,4 +1,7 @@
+mod aggregation;
 mod checks;
+mod derived;
+mod fixed;
 mod pyth;
 mod scope;
 mod switchboard;
@@ -9,14 +12,18 @@ use anchor_lang::prelude::*;
 use types::{Price, TimestampedPrice};
 
 use self::{
-    checks::get_validated_price, pyth::get_pyth_price_and_twap, scope::get_scope_price_and_twap,
-    switchboard::get_switchboard_price_and_twap, types::TimestampedPriceWithTwap,
+    aggregation::{aggregate_prices, AggregatedPrice},
+    checks::get_validated_price,
+    derived::{get_derived_price_and_status, DerivedPrice},
+    fixed::get_fixed_price,
+    pyth::get_pyth_price_and_twap,
+    scope::get_scope_price_and_twap,
+    switchboard::get_switchboard_price_and_twap,
+    types::TimestampedPriceWithTwap,
 };
-use crate::{utils::Fraction, LendingError, PriceStatusFlags, TokenInfo};
+use crate::{utils::Fraction, PriceSource, PriceStatusFlags, TokenInfo};
 
-const MAX_CONFIDENCE_PERCENTAGE: u64 = 2u64;
-
-const CONFIDENCE_FACTOR: u64 = 100 / MAX_CONFIDENCE_PERCENTAGE;
+pub const DEFAULT_MAX_CONFIDENCE_BPS: u64 = 200;
 
 #[derive(Debug, Clone)]
 pub struct GetPriceResult {
@@ -33,28 +40,88 @@ pub fn get_price(
     scope_prices_info: Option<&AccountInfo>,
     clock: &Clock,
 ) -> Result<Option<GetPriceResult>> {
-    let price = get_most_recent_price_and_twap(
+    if token_info.fixed_price_configuration.is_enabled() {
+        let price = get_fixed_price(&token_info.fixed_price_configuration, clock)?;
+        return Ok(get_validated_single_source_price(price, token_info, clock));
+    }
+
+    let prices = get_available_prices_and_twaps(
         token_info,
         pyth_price_account_info,
         switchboard_price_feed_info,
         switchboard_price_twap_info,
         scope_prices_info,
         clock,
+    );
+
+    let Some(AggregatedPrice {
+        price,
+        sources_agreed,
+    }) = aggregate_prices(token_info, prices)?
+    else {
+        return Ok(None);
+    };
+
+    Ok(
+        get_validated_price(price, token_info, clock.unix_timestamp).map(|mut result| {
+            result
+                .status
+                .set(PriceStatusFlags::PRICE_SOURCES_AGREED, sources_agreed);
+            result
+        }),
+    )
+}
+
+pub fn get_derived_price(
+    token_info: &TokenInfo,
+    reserve: &Pubkey,
+    lending_market: &Pubkey,
+    base_reserve_info: &AccountInfo,
+    stake_pool_info: &AccountInfo,
+    clock: &Clock,
+) -> Result<Option<GetPriceResult>> {
+    let DerivedPrice {
+        price,
+        base_price_status,
+    } = get_derived_price_and_status(
+        &token_info.derived_price_configuration,
+        reserve,
+        lending_market,
+        base_reserve_info,
+        stake_pool_info,
+        clock,
     )?;
 
-    Ok(get_validated_price(price, token_info, clock.unix_timestamp))
+    // A derived price can't be more trustworthy than the price it is derived from
+    Ok(
+        get_validated_single_source_price(price, token_info, clock).map(|mut result| {
+            result.status &= base_price_status;
+            result
+        }),
+    )
+}
+
+fn get_validated_single_source_price(
+    price: TimestampedPriceWithTwap,
+    token_info: &TokenInfo,
+    clock: &Clock,
+) -> Option<GetPriceResult> {
+    get_validated_price(price, token_info, clock.unix_timestamp).map(|mut result| {
+        result.status.insert(PriceStatusFlags::PRICE_SOURCES_AGREED);
+        result
+    })
 }
 
-fn get_most_recent_price_and_twap(
+fn get_available_prices_and_twaps(
     token_info: &TokenInfo,
     pyth_price_account_info: Option<&AccountInfo>,
     switchboard_price_feed_info: Option<&AccountInfo>,
     switchboard_price_twap_info: Option<&AccountInfo>,
     scope_prices_info: Option<&AccountInfo>,
     clock: &Clock,
-) -> Result<TimestampedPriceWithTwap> {
+) -> Vec<(PriceSource, TimestampedPriceWithTwap)> {
     let pyth_price = if token_info.pyth_configuration.is_enabled() {
-        pyth_price_account_info.and_then(|a| get_pyth_price_and_twap(a).ok())
+        pyth_price_account_info.and_then(|a| get_pyth_price_and_twap(a, token_info, clock).ok())
     } else {
         None
     };
@@ -80,19 +147,12 @@ fn get_most_recent_price_and_twap(
         None
     };
 
-    let most_recent_price = [pyth_price, switchboard_price, scope_price]
-        .into_iter()
-        .flatten()
-        .reduce(|current, candidate| {
-            if candidate.price.timestamp > current.price.timestamp {
-                candidate
-            } else {
-                current
-            }
-        });
-
-    most_recent_price.ok_or_else(|| {
-        msg!("No price feed available");
-        error!(LendingError::PriceNotValid)
-    })
+    [
+        (PriceSource::Pyth, pyth_price),
+        (PriceSource::Switchboard, switchboard_price),
+        (PriceSource::Scope, scope_price),
+    ]
+    .into_iter()
+    .filter_map(|(source, price)| price.map(|price| (source, price)))
+    .collect()
 }
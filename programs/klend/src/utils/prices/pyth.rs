@@ -9,17 +9,19 @@ use super::{
     types::{Price, TimestampedPriceWithTwap},
     utils, TimestampedPrice,
 };
-use crate::LendingError;
+use crate::{LendingError, TokenInfo};
 
 pub(super) fn get_pyth_price_and_twap(
     pyth_price_info: &AccountInfo,
+    token_info: &TokenInfo,
+    clock: &Clock,
 ) -> Result<TimestampedPriceWithTwap> {
-    let price_feed = load_price_feed_from_account_info(pyth_price_info)?;
+    let price_feed = load_price_feed_from_account_info(pyth_price_info, token_info, clock)?;
 
     let (price, twap) = into_pyth_price_and_twap(price_feed);
 
-    validate_pyth_confidence(&price, super::CONFIDENCE_FACTOR)?;
-    validate_pyth_confidence(&twap, super::CONFIDENCE_FACTOR)?;
+    validate_pyth_price(&price)?;
+    validate_pyth_price(&twap)?;
 
     Ok(TimestampedPriceWithTwap {
         price: price.into(),
@@ -27,20 +29,48 @@ pub(super) fn get_pyth_price_and_twap(
     })
 }
 
-fn load_price_feed_from_account_info(pyth_price_info: &AccountInfo) -> Result<PriceFeedMessage> {
+fn load_price_feed_from_account_info(
+    pyth_price_info: &AccountInfo,
+    token_info: &TokenInfo,
+    clock: &Clock,
+) -> Result<PriceFeedMessage> {
     let price_update_data = pyth_price_info.data.borrow();
     let PriceUpdateV2 {
         write_authority: _,
         verification_level,
         price_message,
-        posted_slot: _,
+        posted_slot,
     } = PriceUpdateV2::try_deserialize(&mut price_update_data.as_ref())?;
-    if !verification_level.gte(VerificationLevel::Full) {
+
+    let min_verification_level = min_verification_level(token_info);
+    if !verification_level.gte(min_verification_level) {
+        msg!(
+            "Pyth price update verification level {verification_level:?} \
+             is below the required {min_verification_level:?}",
+        );
         return err!(LendingError::PriceNotValid);
     }
+
+    let max_posted_slot_lag = token_info.pyth_max_posted_slot_lag;
+    let posted_slot_lag = clock.slot.saturating_sub(posted_slot);
+    if max_posted_slot_lag > 0 && posted_slot_lag > max_posted_slot_lag {
+        msg!(
+            "Pyth price update was posted too long ago \
+             posted_slot={posted_slot} lag={posted_slot_lag} max_lag={max_posted_slot_lag}",
+        );
+        return err!(LendingError::PriceTooOld);
+    }
+
     Ok(price_message)
 }
 
+fn min_verification_level(token_info: &TokenInfo) -> VerificationLevel {
+    match token_info.pyth_min_verification_signatures {
+        0 => VerificationLevel::Full,
+        num_signatures => VerificationLevel::Partial { num_signatures },
+    }
+}
+
 fn into_pyth_price_and_twap(price_feed: PriceFeedMessage) -> (PythPrice, PythPrice) {
     let PriceFeedMessage {
         feed_id: _,
@@ -68,25 +98,11 @@ fn into_pyth_price_and_twap(price_feed: PriceFeedMessage) -> (PythPrice, PythPri
     )
 }
 
-pub(super) fn validate_pyth_confidence(
-    pyth_price: &PythPrice,
-    oracle_confidence_factor: u64,
-) -> Result<()> {
-    let price = u64::try_from(pyth_price.price).unwrap();
-    if price == 0 {
+fn validate_pyth_price(pyth_price: &PythPrice) -> Result<()> {
+    if pyth_price.price <= 0 {
+        msg!("Pyth price is zero or negative which is not allowed");
         return err!(LendingError::PriceIsZero);
     }
-    let conf: u64 = pyth_price.conf;
-    let scaled_conf: u64 = conf.checked_mul(oracle_confidence_factor).unwrap();
-    if scaled_conf > price {
-        msg!(
-            "Confidence interval check failed on pyth account {} {} {}",
-            conf,
-            price,
-            oracle_confidence_factor,
-        );
-        return err!(LendingError::PriceConfidenceTooWide);
-    };
     Ok(())
 }
 
@@ -97,6 +113,11 @@ impl From<PythPrice> for TimestampedPrice {
 
         let price = Price { value, exp };
 
+        let confidence = Price {
+            value: u128::from(pyth_price.conf),
+            exp,
+        };
+
         let timestamp = pyth_price.publish_time.try_into().unwrap();
 
         let price_load = Box::new(move || Ok(utils::price_to_fraction(price)));
@@ -104,6 +125,7 @@ impl From<PythPrice> for TimestampedPrice {
         TimestampedPrice {
             price_load,
             timestamp,
+            confidence: Some(confidence),
         }
     }
 }
@@ -89,6 +89,7 @@ fn get_price_usd(
         return Ok(TimestampedPrice {
             price_load,
             timestamp: price.1,
+            confidence: None,
         });
     }
 
@@ -131,6 +132,7 @@ fn get_price_usd(
     Ok(TimestampedPrice {
         price_load,
         timestamp: oldest_timestamp,
+        confidence: None,
     })
 }
 
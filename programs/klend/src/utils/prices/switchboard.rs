@@ -11,7 +11,6 @@ use crate::{
         prices::{
             types::{TimestampedPrice, TimestampedPriceWithTwap},
             utils::price_to_fraction,
-            CONFIDENCE_FACTOR,
         },
         FatAccountLoader, NULL_PUBKEY,
     },
@@ -73,14 +72,6 @@ fn get_switchboard_price(
     let stdev_scale = stdev.scale();
 
     let price_load = Box::new(move || {
-        validate_switchboard_confidence(
-            price_switchboard_desc_mantissa,
-            price_switchboard_desc_scale,
-            stdev_mantissa,
-            stdev_scale,
-            CONFIDENCE_FACTOR,
-        )?;
-
         let base_price = super::Price {
             value: price_switchboard_desc_mantissa,
             exp: price_switchboard_desc_scale,
@@ -92,46 +83,9 @@ fn get_switchboard_price(
     Ok(TimestampedPrice {
         price_load,
         timestamp,
+        confidence: Some(super::Price {
+            value: stdev_mantissa,
+            exp: stdev_scale,
+        }),
     })
 }
-
-fn validate_switchboard_confidence(
-    price_mantissa: u128,
-    price_scale: u32,
-    stdev_mantissa: u128,
-    stdev_scale: u32,
-    oracle_confidence_factor: u64,
-) -> Result<()> {
-    let (scale_op, scale_diff): (&dyn Fn(u128, u128) -> Option<u128>, _) =
-        if price_scale >= stdev_scale {
-            (
-                &u128::checked_mul,
-                price_scale.checked_sub(stdev_scale).unwrap(),
-            )
-        } else {
-            (
-                &u128::checked_div,
-                stdev_scale.checked_sub(price_scale).unwrap(),
-            )
-        };
-
-    let scaling_factor = 10_u128
-        .checked_pow(scale_diff)
-        .ok_or_else(|| error!(LendingError::MathOverflow))?;
-
-    let stdev_x_confidence_factor_scaled = stdev_mantissa
-        .checked_mul(oracle_confidence_factor.into())
-        .and_then(|a| scale_op(a, scaling_factor))
-        .ok_or_else(|| error!(LendingError::MathOverflow))?;
-
-    if stdev_x_confidence_factor_scaled >= price_mantissa {
-        msg!(
-            "Validation of confidence interval for switchboard v2 feed failed.\n\
-             Price mantissa: {price_mantissa}, Price scale: {price_scale}\n\
-             stdev mantissa: {stdev_mantissa}, stdev_scale: {stdev_scale}",
-        );
-        err!(LendingError::PriceConfidenceTooWide)
-    } else {
-        Ok(())
-    }
-}
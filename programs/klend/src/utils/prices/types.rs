@@ -69,6 +69,7 @@ where
 pub(super) struct TimestampedPrice {
     pub price_load: Box<dyn FnOnce() -> Result<Fraction>>,
     pub timestamp: u64,
+    pub confidence: Option<Price<u128>>,
 }
 
 pub(super) struct TimestampedPriceWithTwap {
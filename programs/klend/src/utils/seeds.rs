@@ -7,6 +7,12 @@ pub const BASE_SEED_REFERRER_TOKEN_STATE: &[u8] = b"referrer_acc";
 pub const BASE_SEED_USER_METADATA: &[u8] = b"user_meta";
 pub const BASE_SEED_REFERRER_STATE: &[u8] = b"ref_state";
 pub const BASE_SEED_SHORT_URL: &[u8] = b"short_url";
+pub const BASE_SEED_OBLIGATION_PERMISSION: &[u8] = b"obligation_perm";
+pub const BASE_SEED_PENDING_CONFIG_CHANGE: &[u8] = b"pending_config";
+pub const BASE_SEED_WITHDRAW_QUEUE: &[u8] = b"withdraw_queue";
+pub const BASE_SEED_OWNER_WITHDRAWAL_CAPS: &[u8] = b"owner_withdrawal_caps";
+/// Seed of the farms program for user states, an obligation is the delegatee of its user states.
+pub const BASE_SEED_FARM_USER_STATE: &[u8] = b"user";
 
 pub mod pda {
     use anchor_lang::prelude::Pubkey;
@@ -77,4 +83,57 @@ pub mod pda {
             &crate::ID,
         )
     }
+
+    pub fn obligation_permission(lending_market: Pubkey, owner: Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                BASE_SEED_OBLIGATION_PERMISSION,
+                lending_market.as_ref(),
+                owner.as_ref(),
+            ],
+            &crate::ID,
+        )
+    }
+
+    pub fn pending_config_change(
+        lending_market: Pubkey,
+        target: Pubkey,
+        mode: u64,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                BASE_SEED_PENDING_CONFIG_CHANGE,
+                lending_market.as_ref(),
+                target.as_ref(),
+                &mode.to_le_bytes(),
+            ],
+            &crate::ID,
+        )
+    }
+
+    pub fn withdraw_queue(reserve: Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[BASE_SEED_WITHDRAW_QUEUE, reserve.as_ref()], &crate::ID)
+    }
+
+    pub fn owner_withdrawal_caps(reserve: Pubkey, owner: Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                BASE_SEED_OWNER_WITHDRAWAL_CAPS,
+                reserve.as_ref(),
+                owner.as_ref(),
+            ],
+            &crate::ID,
+        )
+    }
+
+    pub fn obligation_farm_user_state(farm_state: Pubkey, obligation: Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                BASE_SEED_FARM_USER_STATE,
+                farm_state.as_ref(),
+                obligation.as_ref(),
+            ],
+            &farms::ID,
+        )
+    }
 }
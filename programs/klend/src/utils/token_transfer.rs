@@ -377,3 +377,64 @@ pub fn withdraw_fees_from_reserve<'a>(
 
     Ok(())
 }
+
+pub fn enqueue_withdrawal_transfer<'a>(
+    collateral_token_program: AccountInfo<'a>,
+    reserve_collateral_mint: AccountInfo<'a>,
+    user_source_collateral: AccountInfo<'a>,
+    user_authority: AccountInfo<'a>,
+    collateral_amount: u64,
+) -> Result<()> {
+    spltoken::burn(
+        reserve_collateral_mint,
+        user_source_collateral,
+        user_authority,
+        collateral_token_program,
+        collateral_amount,
+    )
+}
+
+pub fn cancel_withdrawal_transfer<'a>(
+    collateral_token_program: AccountInfo<'a>,
+    reserve_collateral_mint: AccountInfo<'a>,
+    lending_market_authority: AccountInfo<'a>,
+    user_destination_collateral: AccountInfo<'a>,
+    authority_signer_seeds: &[&[u8]],
+    collateral_amount: u64,
+) -> Result<()> {
+    spltoken::mint(
+        collateral_token_program,
+        reserve_collateral_mint,
+        lending_market_authority,
+        user_destination_collateral,
+        authority_signer_seeds,
+        collateral_amount,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn claim_withdrawal_transfer<'a>(
+    liquidity_token_program: AccountInfo<'a>,
+    reserve_liquidity_mint: AccountInfo<'a>,
+    reserve_liquidity_supply: AccountInfo<'a>,
+    user_destination_liquidity: AccountInfo<'a>,
+    lending_market_authority: AccountInfo<'a>,
+    authority_signer_seeds: &[&[u8]],
+    liquidity_amount: u64,
+    liquidity_decimals: u8,
+) -> Result<()> {
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            liquidity_token_program,
+            token_interface::TransferChecked {
+                from: reserve_liquidity_supply,
+                to: user_destination_liquidity,
+                authority: lending_market_authority,
+                mint: reserve_liquidity_mint,
+            },
+            &[authority_signer_seeds],
+        ),
+        liquidity_amount,
+        liquidity_decimals,
+    )
+}
@@ -0,0 +1,729 @@
+#![allow(dead_code)]
+
+use std::{
+    cell::{Ref, RefCell, RefMut},
+    collections::HashMap,
+    rc::Rc,
+};
+
+use anchor_lang::{
+    prelude::{Clock, ProgramError, Pubkey},
+    Result,
+};
+use kamino_lending::{
+    fraction::{Fraction, FractionExtra},
+    lending_market::{lending_checks, lending_operations},
+    utils::{
+        borrow_rate_curve::{BorrowRateCurve, CurvePoint},
+        AnyAccountLoader, GetPriceResult, NULL_PUBKEY,
+    },
+    CalculateBorrowResult, DepositLiquidityResult, FixedRateBorrowTerms, InitLendingMarketParams,
+    InitObligationParams, InitReserveParams, LendingAction, LendingMarket,
+    LiquidateAndRedeemResult, LtvMaxWithdrawalCheck, MaxReservesAsCollateralCheck,
+    NewReserveCollateralParams, NewReserveLiquidityParams, Obligation, ObligationAccount,
+    ObligationAccountMut, ObligationCollateral, ObligationExtension, ObligationLayout,
+    ObligationLiquidity, ObligationView, OwnerWithdrawalCaps, PriceStatusFlags, ReferrerTokenState,
+    Reserve, ReserveCollateral, ReserveConfig, ReserveLiquidity, ReserveStatus,
+};
+
+pub const MS_PER_SLOT: u64 = 400;
+
+#[derive(Debug)]
+pub struct TestAccount<T> {
+    pub key: Pubkey,
+    data: Rc<RefCell<T>>,
+}
+
+impl<T> TestAccount<T> {
+    pub fn new(key: Pubkey, value: T) -> Self {
+        Self {
+            key,
+            data: Rc::new(RefCell::new(value)),
+        }
+    }
+
+    pub fn borrow(&self) -> Ref<T> {
+        self.data.borrow()
+    }
+
+    pub fn borrow_mut(&self) -> RefMut<T> {
+        self.data.borrow_mut()
+    }
+}
+
+impl<T: Copy> TestAccount<T> {
+    pub fn deep_clone(&self) -> Self {
+        Self::new(self.key, *self.borrow())
+    }
+}
+
+impl<T> Clone for TestAccount<T> {
+    fn clone(&self) -> Self {
+        Self {
+            key: self.key,
+            data: Rc::clone(&self.data),
+        }
+    }
+}
+
+impl<'info, T> AnyAccountLoader<'info, T> for TestAccount<T> {
+    fn get_mut(&self) -> Result<RefMut<T>> {
+        self.data
+            .try_borrow_mut()
+            .map_err(|_| ProgramError::AccountBorrowFailed.into())
+    }
+
+    fn get(&self) -> Result<Ref<T>> {
+        self.data
+            .try_borrow()
+            .map_err(|_| ProgramError::AccountBorrowFailed.into())
+    }
+
+    fn get_pubkey(&self) -> Pubkey {
+        self.key
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ReserveSpec {
+    pub decimals: u8,
+    pub price: Fraction,
+    pub loan_to_value_pct: u8,
+    pub liquidation_threshold_pct: u8,
+    pub borrow_factor_pct: u64,
+}
+
+impl ReserveSpec {
+    pub fn to_reserve_config(&self) -> ReserveConfig {
+        let mut config = ReserveConfig {
+            status: ReserveStatus::Active.into(),
+            loan_to_value_pct: self.loan_to_value_pct,
+            liquidation_threshold_pct: self.liquidation_threshold_pct,
+            min_liquidation_bonus_bps: 200,
+            max_liquidation_bonus_bps: 500,
+            bad_debt_liquidation_bonus_bps: 10,
+            protocol_take_rate_pct: 10,
+            protocol_liquidation_fee_pct: 10,
+            borrow_factor_pct: self.borrow_factor_pct,
+            borrow_rate_curve: BorrowRateCurve::from_points(&[
+                CurvePoint::new(0, 100),
+                CurvePoint::new(8_000, 1_500),
+                CurvePoint::new(10_000, 30_000),
+            ])
+            .unwrap(),
+            deposit_limit: u64::MAX,
+            borrow_limit: u64::MAX,
+            borrow_limit_outside_elevation_group: u64::MAX,
+            ..Default::default()
+        };
+        config.fees.borrow_fee_sf = Fraction::from_bps(10).to_bits() as u64;
+        config.fees.flash_loan_fee_sf = Fraction::from_bps(9).to_bits() as u64;
+        config.token_info.max_age_price_seconds = 60;
+        config.token_info.max_age_twap_seconds = 120;
+        config.token_info.scope_configuration.price_feed = NULL_PUBKEY;
+        config
+    }
+}
+
+#[derive(Debug)]
+pub struct TestReserve {
+    pub account: TestAccount<Reserve>,
+    pub price: Fraction,
+    pub liquidity_vault: u64,
+    pub collateral_vault: u64,
+    pub collateral_supply: u64,
+}
+
+impl TestReserve {
+    fn deep_clone(&self) -> Self {
+        Self {
+            account: self.account.deep_clone(),
+            ..*self
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct LendingTestState {
+    pub lending_market: LendingMarket,
+    pub lending_market_pk: Pubkey,
+    pub reserves: Vec<TestReserve>,
+    pub obligations: Vec<TestAccount<Obligation>>,
+    pub obligation_extensions: Vec<Option<TestAccount<ObligationExtension>>>,
+    pub owner_withdrawal_caps: HashMap<(Pubkey, Pubkey), OwnerWithdrawalCaps>,
+    pub clock: Clock,
+}
+
+impl LendingTestState {
+    pub fn new(reserve_specs: &[ReserveSpec], num_obligations: usize) -> Self {
+        let clock = Clock {
+            slot: 250_000_000,
+            unix_timestamp: 1_700_000_000,
+            ..Default::default()
+        };
+
+        let lending_market_pk = Pubkey::new_unique();
+        let mut lending_market = LendingMarket::default();
+        lending_market.init(InitLendingMarketParams {
+            bump_seed: 255,
+            lending_market_owner: Pubkey::new_unique(),
+            quote_currency: [0; 32],
+        });
+        let initial_deposit = lending_market.min_initial_deposit_amount;
+
+        let reserves = reserve_specs
+            .iter()
+            .map(|spec| {
+                let mut reserve = Reserve::default();
+                reserve.init(InitReserveParams {
+                    current_slot: clock.slot,
+                    lending_market: lending_market_pk,
+                    liquidity: Box::new(ReserveLiquidity::new(NewReserveLiquidityParams {
+                        mint_pubkey: Pubkey::new_unique(),
+                        mint_decimals: spec.decimals,
+                        mint_token_program: Pubkey::new_unique(),
+                        supply_vault: Pubkey::new_unique(),
+                        fee_vault: Pubkey::new_unique(),
+                        market_price_sf: spec.price.to_bits(),
+                        initial_amount_deposited_in_reserve: initial_deposit,
+                    })),
+                    collateral: Box::new(ReserveCollateral::new(NewReserveCollateralParams {
+                        mint_pubkey: Pubkey::new_unique(),
+                        supply_vault: Pubkey::new_unique(),
+                        initial_collateral_supply: initial_deposit,
+                    })),
+                    config: Box::new(spec.to_reserve_config()),
+                });
+                TestReserve {
+                    account: TestAccount::new(Pubkey::new_unique(), reserve),
+                    price: spec.price,
+                    liquidity_vault: initial_deposit,
+                    collateral_vault: 0,
+                    collateral_supply: initial_deposit,
+                }
+            })
+            .collect();
+
+        let obligations = (0..num_obligations)
+            .map(|_| {
+                let mut obligation = Obligation::default();
+                obligation.init(InitObligationParams {
+                    current_slot: clock.slot,
+                    lending_market: lending_market_pk,
+                    owner: Pubkey::new_unique(),
+                    deposits: [ObligationCollateral::default(); 8],
+                    borrows: [ObligationLiquidity::default(); 5],
+                    tag: 0,
+                    referrer: Pubkey::default(),
+                });
+                TestAccount::new(Pubkey::new_unique(), obligation)
+            })
+            .collect();
+
+        Self {
+            lending_market,
+            lending_market_pk,
+            reserves,
+            obligations,
+            obligation_extensions: vec![None; num_obligations],
+            owner_withdrawal_caps: HashMap::new(),
+            clock,
+        }
+    }
+
+    pub fn deep_clone(&self) -> Self {
+        Self {
+            lending_market: self.lending_market,
+            lending_market_pk: self.lending_market_pk,
+            reserves: self.reserves.iter().map(TestReserve::deep_clone).collect(),
+            obligations: self
+                .obligations
+                .iter()
+                .map(TestAccount::deep_clone)
+                .collect(),
+            obligation_extensions: self
+                .obligation_extensions
+                .iter()
+                .map(|extension| extension.as_ref().map(TestAccount::deep_clone))
+                .collect(),
+            owner_withdrawal_caps: self.owner_withdrawal_caps.clone(),
+            clock: self.clock.clone(),
+        }
+    }
+
+    pub fn advance_slots(&mut self, slots: u64) {
+        self.clock.slot += slots;
+        self.clock.unix_timestamp += i64::try_from(slots * MS_PER_SLOT / 1000).unwrap();
+    }
+
+    pub fn set_price(&mut self, reserve: usize, price: Fraction) {
+        self.reserves[reserve].price = price;
+    }
+
+    pub fn refresh_reserves(&mut self) -> Result<()> {
+        for reserve in &self.reserves {
+            lending_operations::refresh_reserve(
+                &mut reserve.account.borrow_mut(),
+                &self.clock,
+                Some(GetPriceResult {
+                    price: reserve.price,
+                    timestamp: u64::try_from(self.clock.unix_timestamp).unwrap(),
+                    status: PriceStatusFlags::ALL_CHECKS,
+                }),
+                self.lending_market.referral_fee_bps,
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn extend_obligation(&mut self, obligation: usize) {
+        let mut account = self.obligations[obligation].borrow_mut();
+        assert!(!account.is_extended());
+        account.layout_version = ObligationLayout::Extended.into();
+        self.obligation_extensions[obligation] = Some(TestAccount::new(
+            self.obligations[obligation].key,
+            ObligationExtension::default(),
+        ));
+    }
+
+    pub fn obligation(&self, obligation: usize) -> ObligationAccount {
+        ObligationView::new(
+            self.obligations[obligation].borrow(),
+            self.obligation_extensions[obligation]
+                .as_ref()
+                .map(TestAccount::borrow),
+        )
+    }
+
+    pub fn refresh_obligation(&self, obligation: usize) -> Result<()> {
+        let mut obligation_account = self.obligation_mut(obligation);
+        let obligation = &mut obligation_account.get_mut();
+        let deposit_reserves: Vec<_> = obligation
+            .deposits()
+            .filter(|deposit| deposit.deposit_reserve != Pubkey::default())
+            .map(|deposit| self.reserve_account(&deposit.deposit_reserve))
+            .collect();
+        let borrow_reserves: Vec<_> = obligation
+            .borrows()
+            .filter(|borrow| borrow.borrow_reserve != Pubkey::default())
+            .map(|borrow| self.reserve_account(&borrow.borrow_reserve))
+            .collect();
+
+        lending_operations::refresh_obligation(
+            &kamino_lending::ID,
+            obligation,
+            &self.lending_market,
+            self.clock.slot,
+            MaxReservesAsCollateralCheck::Perform,
+            deposit_reserves.into_iter(),
+            borrow_reserves.into_iter(),
+            std::iter::empty::<TestAccount<ReferrerTokenState>>(),
+        )
+    }
+
+    pub fn refresh_all(&mut self) -> Result<()> {
+        self.refresh_reserves()?;
+        for obligation in 0..self.obligations.len() {
+            self.refresh_obligation(obligation)?;
+        }
+        Ok(())
+    }
+
+    pub fn deposit(&mut self, reserve: usize, obligation: usize, amount: u64) -> Result<()> {
+        self.refresh_reserves()?;
+        self.refresh_obligation(obligation)?;
+
+        let test_reserve = &mut self.reserves[reserve];
+        let reserve_pk = test_reserve.account.key;
+        let reserve = &mut test_reserve.account.borrow_mut();
+        let initial_vault = test_reserve.liquidity_vault;
+        let initial_available = reserve.liquidity.available_amount;
+
+        let DepositLiquidityResult {
+            liquidity_amount,
+            collateral_amount,
+        } = lending_operations::deposit_reserve_liquidity(reserve, &self.clock, amount)?;
+        lending_operations::refresh_reserve(
+            reserve,
+            &self.clock,
+            None,
+            self.lending_market.referral_fee_bps,
+        )?;
+        let owner_withdrawal_caps = owner_withdrawal_caps(
+            &mut self.owner_withdrawal_caps,
+            self.lending_market_pk,
+            reserve_pk,
+            self.obligations[obligation].borrow().owner,
+        );
+        lending_operations::deposit_obligation_collateral(
+            &self.lending_market,
+            reserve,
+            &mut load_obligation_mut(&self.obligations, &self.obligation_extensions, obligation)
+                .get_mut(),
+            &self.clock,
+            collateral_amount,
+            reserve_pk,
+            MaxReservesAsCollateralCheck::Perform,
+            Some(owner_withdrawal_caps),
+        )?;
+
+        test_reserve.liquidity_vault += liquidity_amount;
+        test_reserve.collateral_supply += collateral_amount;
+        test_reserve.collateral_vault += collateral_amount;
+
+        lending_checks::post_transfer_vault_balance_liquidity_reserve_checks(
+            test_reserve.liquidity_vault,
+            reserve.liquidity.available_amount,
+            initial_vault,
+            initial_available,
+            LendingAction::Additive(liquidity_amount),
+        )
+    }
+
+    pub fn withdraw(
+        &mut self,
+        reserve: usize,
+        obligation: usize,
+        collateral_amount: u64,
+    ) -> Result<()> {
+        self.refresh_reserves()?;
+        self.refresh_obligation(obligation)?;
+
+        let test_reserve = &mut self.reserves[reserve];
+        let reserve_pk = test_reserve.account.key;
+        let reserve = &mut test_reserve.account.borrow_mut();
+        let initial_vault = test_reserve.liquidity_vault;
+        let initial_available = reserve.liquidity.available_amount;
+
+        let owner_withdrawal_caps = owner_withdrawal_caps(
+            &mut self.owner_withdrawal_caps,
+            self.lending_market_pk,
+            reserve_pk,
+            self.obligations[obligation].borrow().owner,
+        );
+        let withdraw_amount = lending_operations::withdraw_obligation_collateral(
+            &self.lending_market,
+            reserve,
+            &mut load_obligation_mut(&self.obligations, &self.obligation_extensions, obligation)
+                .get_mut(),
+            collateral_amount,
+            &self.clock,
+            reserve_pk,
+            LtvMaxWithdrawalCheck::MaxLtv,
+            Some(owner_withdrawal_caps),
+        )?;
+        let liquidity_amount = lending_operations::redeem_reserve_collateral(
+            reserve,
+            withdraw_amount,
+            &self.clock,
+            true,
+        )?;
+
+        test_reserve.collateral_vault -= withdraw_amount;
+        test_reserve.collateral_supply -= withdraw_amount;
+        test_reserve.liquidity_vault -= liquidity_amount;
+
+        lending_checks::post_transfer_vault_balance_liquidity_reserve_checks(
+            test_reserve.liquidity_vault,
+            reserve.liquidity.available_amount,
+            initial_vault,
+            initial_available,
+            LendingAction::Subtractive(liquidity_amount),
+        )
+    }
+
+    pub fn borrow(&mut self, reserve: usize, obligation: usize, amount: u64) -> Result<()> {
+        self.borrow_impl(reserve, obligation, amount, None)
+    }
+
+    pub fn borrow_fixed_rate(
+        &mut self,
+        reserve: usize,
+        obligation: usize,
+        amount: u64,
+        terms: FixedRateBorrowTerms,
+    ) -> Result<()> {
+        self.borrow_impl(reserve, obligation, amount, Some(terms))
+    }
+
+    fn borrow_impl(
+        &mut self,
+        reserve: usize,
+        obligation: usize,
+        amount: u64,
+        fixed_rate_terms: Option<FixedRateBorrowTerms>,
+    ) -> Result<()> {
+        self.refresh_reserves()?;
+        self.refresh_obligation(obligation)?;
+
+        let deposit_reserves = self.deposit_reserves(obligation);
+        let test_reserve = &mut self.reserves[reserve];
+        let reserve_pk = test_reserve.account.key;
+        let reserve = &mut test_reserve.account.borrow_mut();
+        let initial_vault = test_reserve.liquidity_vault;
+        let initial_available = reserve.liquidity.available_amount;
+        let owner_withdrawal_caps = owner_withdrawal_caps(
+            &mut self.owner_withdrawal_caps,
+            self.lending_market_pk,
+            reserve_pk,
+            self.obligations[obligation].borrow().owner,
+        );
+
+        let CalculateBorrowResult {
+            receive_amount,
+            borrow_fee,
+            ..
+        } = match fixed_rate_terms {
+            Some(terms) => lending_operations::borrow_obligation_liquidity_fixed_rate(
+                &self.lending_market,
+                reserve,
+                &mut load_obligation_mut(
+                    &self.obligations,
+                    &self.obligation_extensions,
+                    obligation,
+                )
+                .get_mut(),
+                amount,
+                terms,
+                &self.clock,
+                reserve_pk,
+                None,
+                deposit_reserves.into_iter(),
+                Some(owner_withdrawal_caps),
+            )?,
+            None => lending_operations::borrow_obligation_liquidity(
+                &self.lending_market,
+                reserve,
+                &mut load_obligation_mut(
+                    &self.obligations,
+                    &self.obligation_extensions,
+                    obligation,
+                )
+                .get_mut(),
+                amount,
+                &self.clock,
+                reserve_pk,
+                None,
+                deposit_reserves.into_iter(),
+                Some(owner_withdrawal_caps),
+            )?,
+        };
+
+        test_reserve.liquidity_vault -= borrow_fee + receive_amount;
+
+        lending_checks::post_transfer_vault_balance_liquidity_reserve_checks(
+            test_reserve.liquidity_vault,
+            reserve.liquidity.available_amount,
+            initial_vault,
+            initial_available,
+            LendingAction::Subtractive(borrow_fee + receive_amount),
+        )
+    }
+
+    pub fn repay(&mut self, reserve: usize, obligation: usize, amount: u64) -> Result<()> {
+        self.refresh_reserves()?;
+        self.refresh_obligation(obligation)?;
+
+        let deposit_reserves = self.deposit_reserves(obligation);
+        let test_reserve = &mut self.reserves[reserve];
+        let reserve_pk = test_reserve.account.key;
+        let reserve = &mut test_reserve.account.borrow_mut();
+        let initial_vault = test_reserve.liquidity_vault;
+        let initial_available = reserve.liquidity.available_amount;
+
+        let owner_withdrawal_caps = owner_withdrawal_caps(
+            &mut self.owner_withdrawal_caps,
+            self.lending_market_pk,
+            reserve_pk,
+            self.obligations[obligation].borrow().owner,
+        );
+        let repay_amount = lending_operations::repay_obligation_liquidity(
+            reserve,
+            &mut load_obligation_mut(&self.obligations, &self.obligation_extensions, obligation)
+                .get_mut(),
+            &self.clock,
+            amount,
+            reserve_pk,
+            &self.lending_market,
+            deposit_reserves.into_iter(),
+            Some(owner_withdrawal_caps),
+        )?;
+
+        test_reserve.liquidity_vault += repay_amount;
+
+        lending_checks::post_transfer_vault_balance_liquidity_reserve_checks(
+            test_reserve.liquidity_vault,
+            reserve.liquidity.available_amount,
+            initial_vault,
+            initial_available,
+            LendingAction::Additive(repay_amount),
+        )
+    }
+
+    pub fn liquidate(
+        &mut self,
+        repay_reserve: usize,
+        withdraw_reserve: usize,
+        obligation: usize,
+        amount: u64,
+    ) -> Result<()> {
+        self.refresh_reserves()?;
+        self.refresh_obligation(obligation)?;
+
+        let deposit_reserves = self.deposit_reserves(obligation);
+        let repay_account = self.reserves[repay_reserve].account.clone();
+        let withdraw_account = self.reserves[withdraw_reserve].account.clone();
+        let initial_withdraw_vault = self.reserves[withdraw_reserve].liquidity_vault;
+        let initial_withdraw_available = withdraw_account.borrow().liquidity.available_amount;
+
+        let LiquidateAndRedeemResult {
+            repay_amount,
+            withdraw_amount,
+            total_withdraw_liquidity_amount,
+            withdraw_collateral_amount,
+            ..
+        } = lending_operations::liquidate_and_redeem(
+            &self.lending_market,
+            &repay_account,
+            &withdraw_account,
+            &mut load_obligation_mut(&self.obligations, &self.obligation_extensions, obligation)
+                .get_mut(),
+            &self.clock,
+            amount,
+            0,
+            None,
+            deposit_reserves.into_iter(),
+        )?;
+
+        self.reserves[repay_reserve].liquidity_vault += repay_amount;
+        self.reserves[withdraw_reserve].collateral_vault -= withdraw_amount;
+
+        if let Some((withdraw_liquidity_amount, _protocol_fee)) = total_withdraw_liquidity_amount {
+            let test_reserve = &mut self.reserves[withdraw_reserve];
+            test_reserve.collateral_supply -= withdraw_collateral_amount;
+            test_reserve.liquidity_vault -= withdraw_liquidity_amount;
+
+            let net_withdrawal_amount = if repay_reserve == withdraw_reserve {
+                withdraw_liquidity_amount as i64 - repay_amount as i64
+            } else {
+                withdraw_liquidity_amount as i64
+            };
+            lending_checks::post_transfer_vault_balance_liquidity_reserve_checks(
+                test_reserve.liquidity_vault,
+                withdraw_account.borrow().liquidity.available_amount,
+                initial_withdraw_vault,
+                initial_withdraw_available,
+                LendingAction::SubstractiveSigned(net_withdrawal_amount),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn flash_loan(&mut self, reserve: usize, amount: u64) -> Result<()> {
+        self.refresh_reserves()?;
+
+        let test_reserve = &mut self.reserves[reserve];
+        let reserve = &mut test_reserve.account.borrow_mut();
+        let initial_vault = test_reserve.liquidity_vault;
+        let initial_available = reserve.liquidity.available_amount;
+
+        lending_operations::flash_borrow_reserve_liquidity(reserve, amount)?;
+        test_reserve.liquidity_vault -= amount;
+
+        let (amount_with_referral_fee, _protocol_fee) =
+            lending_operations::flash_repay_reserve_liquidity(
+                &self.lending_market,
+                reserve,
+                amount,
+                u64::try_from(self.clock.unix_timestamp).unwrap(),
+                None::<&TestAccount<ReferrerTokenState>>,
+            )?;
+        test_reserve.liquidity_vault += amount_with_referral_fee;
+
+        lending_checks::post_transfer_vault_balance_liquidity_reserve_checks(
+            test_reserve.liquidity_vault,
+            reserve.liquidity.available_amount,
+            initial_vault,
+            initial_available,
+            LendingAction::Additive(amount_with_referral_fee - amount),
+        )
+    }
+
+    pub fn is_healthy(&self, obligation: usize) -> bool {
+        let obligation = self.obligations[obligation].borrow();
+        let debt = obligation.get_bf_adjusted_debt_value();
+        debt == Fraction::ZERO || debt < obligation.get_unhealthy_borrow_value()
+    }
+
+    pub fn deposit_reserve_indices(&self, obligation: usize) -> Vec<usize> {
+        let obligation = self.obligation(obligation);
+        obligation
+            .deposits()
+            .filter_map(|deposit| self.reserve_index(&deposit.deposit_reserve))
+            .collect()
+    }
+
+    pub fn borrow_reserve_indices(&self, obligation: usize) -> Vec<usize> {
+        let obligation = self.obligation(obligation);
+        obligation
+            .borrows()
+            .filter_map(|borrow| self.reserve_index(&borrow.borrow_reserve))
+            .collect()
+    }
+
+    fn reserve_index(&self, reserve_pk: &Pubkey) -> Option<usize> {
+        self.reserves
+            .iter()
+            .position(|reserve| reserve.account.key == *reserve_pk)
+    }
+
+    fn reserve_account(&self, reserve_pk: &Pubkey) -> TestAccount<Reserve> {
+        self.reserves
+            .iter()
+            .find(|reserve| reserve.account.key == *reserve_pk)
+            .map(|reserve| reserve.account.clone())
+            .unwrap()
+    }
+
+    fn obligation_mut(&self, obligation: usize) -> ObligationAccountMut {
+        load_obligation_mut(&self.obligations, &self.obligation_extensions, obligation)
+    }
+
+    fn deposit_reserves(&self, obligation: usize) -> Vec<TestAccount<Reserve>> {
+        self.obligation(obligation)
+            .deposits()
+            .filter(|deposit| deposit.deposit_reserve != Pubkey::default())
+            .map(|deposit| self.reserve_account(&deposit.deposit_reserve))
+            .collect()
+    }
+}
+
+fn load_obligation_mut<'a>(
+    obligations: &'a [TestAccount<Obligation>],
+    obligation_extensions: &'a [Option<TestAccount<ObligationExtension>>],
+    obligation: usize,
+) -> ObligationAccountMut<'a> {
+    ObligationView::new(
+        obligations[obligation].borrow_mut(),
+        obligation_extensions[obligation]
+            .as_ref()
+            .map(TestAccount::borrow_mut),
+    )
+}
+
+fn owner_withdrawal_caps(
+    owner_withdrawal_caps: &mut HashMap<(Pubkey, Pubkey), OwnerWithdrawalCaps>,
+    lending_market: Pubkey,
+    reserve: Pubkey,
+    owner: Pubkey,
+) -> &mut OwnerWithdrawalCaps {
+    owner_withdrawal_caps
+        .entry((reserve, owner))
+        .or_insert_with(|| OwnerWithdrawalCaps {
+            lending_market,
+            reserve,
+            owner,
+            ..Default::default()
+        })
+}
@@ -0,0 +1,160 @@
+mod common;
+
+use common::{LendingTestState, ReserveSpec};
+use kamino_lending::{
+    fraction::{Fraction, FractionExtra},
+    utils::SLOTS_PER_DAY,
+    AdaptiveRateConfig,
+};
+
+const TARGET_UTILIZATION_BPS: u32 = 8_000;
+const CURVE_RATE_AT_TARGET_BPS: u32 = 1_500;
+const ADJUSTMENT_SPEED_BPS_PER_DAY: u32 = 1_000;
+
+fn setup(adaptive_rate: AdaptiveRateConfig, borrow_amount: u64) -> LendingTestState {
+    let spec = ReserveSpec {
+        decimals: 6,
+        price: Fraction::ONE,
+        loan_to_value_pct: 80,
+        liquidation_threshold_pct: 85,
+        borrow_factor_pct: 100,
+    };
+    let mut state = LendingTestState::new(&[spec.clone(), spec], 2);
+    state.reserves[0].account.borrow_mut().config.adaptive_rate = adaptive_rate;
+
+    state.deposit(0, 0, 1_000_000_000).unwrap();
+    state.deposit(1, 1, 2_000_000_000).unwrap();
+    state.borrow(0, 1, borrow_amount).unwrap();
+    state
+}
+
+fn adaptive_rate_config() -> AdaptiveRateConfig {
+    AdaptiveRateConfig {
+        target_utilization_bps: TARGET_UTILIZATION_BPS,
+        adjustment_speed_bps_per_day: ADJUSTMENT_SPEED_BPS_PER_DAY,
+        min_rate_at_target_bps: 500,
+        max_rate_at_target_bps: 5_000,
+    }
+}
+
+fn rate_at_target_bps(state: &LendingTestState) -> u64 {
+    let reserve = state.reserves[0].account.borrow();
+    reserve
+        .adaptive_rate_at_target()
+        .unwrap()
+        .unwrap()
+        .to_bps()
+        .unwrap()
+}
+
+#[test]
+fn test_rate_at_target_drifts_up_above_target() {
+    let mut state = setup(adaptive_rate_config(), 900_000_000);
+    assert_eq!(
+        rate_at_target_bps(&state),
+        u64::from(CURVE_RATE_AT_TARGET_BPS)
+    );
+
+    let utilization = state.reserves[0]
+        .account
+        .borrow()
+        .liquidity
+        .utilization_rate();
+    state.advance_slots(SLOTS_PER_DAY);
+    state.refresh_reserves().unwrap();
+
+    let target = Fraction::from_bps(TARGET_UTILIZATION_BPS);
+    let expected = Fraction::from_bps(CURVE_RATE_AT_TARGET_BPS)
+        + Fraction::from_bps(ADJUSTMENT_SPEED_BPS_PER_DAY) * (utilization - target)
+            / (Fraction::ONE - target);
+    assert_eq!(
+        rate_at_target_bps(&state),
+        expected.to_bps::<u64>().unwrap()
+    );
+
+    // The whole curve is scaled along with the rate at target
+    let reserve = state.reserves[0].account.borrow();
+    let curve = &reserve.config.borrow_rate_curve;
+    let curve_rate = curve
+        .get_borrow_rate(reserve.liquidity.utilization_rate())
+        .unwrap();
+    let curve_rate_at_target = curve.get_borrow_rate(target).unwrap();
+    let scaled_rate =
+        curve_rate * Fraction::from_bits(reserve.adaptive_rate_at_target_sf) / curve_rate_at_target;
+    assert_eq!(reserve.current_borrow_rate().unwrap(), scaled_rate);
+}
+
+#[test]
+fn test_rate_at_target_is_bounded() {
+    let mut state = setup(adaptive_rate_config(), 950_000_000);
+    state.advance_slots(30 * SLOTS_PER_DAY);
+    state.refresh_reserves().unwrap();
+    assert_eq!(rate_at_target_bps(&state), 5_000);
+
+    let mut state = setup(adaptive_rate_config(), 10_000_000);
+    state.advance_slots(30 * SLOTS_PER_DAY);
+    state.refresh_reserves().unwrap();
+    assert_eq!(rate_at_target_bps(&state), 500);
+}
+
+#[test]
+fn test_scaled_rate_is_capped_by_curve_max_rate() {
+    let mut state = setup(adaptive_rate_config(), 950_000_000);
+    state.reserves[0]
+        .account
+        .borrow_mut()
+        .config
+        .rate_history_sample_interval_slots = SLOTS_PER_DAY;
+    state.advance_slots(30 * SLOTS_PER_DAY);
+    state.refresh_reserves().unwrap();
+
+    let reserve = state.reserves[0].account.borrow();
+    let curve_max_rate = reserve
+        .config
+        .borrow_rate_curve
+        .get_borrow_rate(Fraction::ONE)
+        .unwrap();
+    assert_eq!(reserve.current_borrow_rate().unwrap(), curve_max_rate);
+    assert_eq!(
+        reserve.rate_history.last_sample().unwrap().borrow_rate_bps,
+        curve_max_rate.to_bps::<u32>().unwrap()
+    );
+}
+
+#[test]
+fn test_max_rate_at_target_cannot_exceed_curve_max_rate() {
+    let state = setup(adaptive_rate_config(), 900_000_000);
+    let curve = state.reserves[0].account.borrow().config.borrow_rate_curve;
+    let curve_max_rate_bps = curve.points[10].borrow_rate_bps;
+
+    let config = AdaptiveRateConfig {
+        max_rate_at_target_bps: curve_max_rate_bps,
+        ..adaptive_rate_config()
+    };
+    assert!(config.is_valid(&curve));
+
+    let config = AdaptiveRateConfig {
+        max_rate_at_target_bps: curve_max_rate_bps + 1,
+        ..adaptive_rate_config()
+    };
+    assert!(!config.is_valid(&curve));
+}
+
+#[test]
+fn test_disabled_adaptive_rate_uses_static_curve() {
+    let mut state = setup(AdaptiveRateConfig::default(), 900_000_000);
+    state.advance_slots(SLOTS_PER_DAY);
+    state.refresh_reserves().unwrap();
+
+    let reserve = state.reserves[0].account.borrow();
+    assert_eq!(reserve.adaptive_rate_at_target_sf, 0);
+    assert!(reserve.adaptive_rate_at_target().unwrap().is_none());
+    assert_eq!(
+        reserve.current_borrow_rate().unwrap(),
+        reserve
+            .config
+            .borrow_rate_curve
+            .get_borrow_rate(reserve.liquidity.utilization_rate())
+            .unwrap()
+    );
+}
@@ -0,0 +1,142 @@
+use kamino_lending::{
+    lending_market::lending_operations::utils::is_reserve_config_change_risk_reducing,
+    state::PendingConfigChange, utils::CONFIG_CHANGE_EXPIRY_SECS, ReserveConfig, UpdateConfigMode,
+    UpdateLendingMarketMode,
+};
+
+fn config() -> ReserveConfig {
+    ReserveConfig {
+        loan_to_value_pct: 70,
+        liquidation_threshold_pct: 80,
+        deposit_limit: 1_000_000,
+        borrow_limit: 500_000,
+        borrow_limit_against_this_collateral_in_elevation_group: [100; 32],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_lowering_limits_is_risk_reducing() {
+    let previous = config();
+
+    let lower_ltv = ReserveConfig {
+        loan_to_value_pct: 60,
+        ..previous
+    };
+    assert!(is_reserve_config_change_risk_reducing(
+        UpdateConfigMode::UpdateLoanToValuePct,
+        &previous,
+        &lower_ltv
+    ));
+
+    let lower_deposit_limit = ReserveConfig {
+        deposit_limit: 0,
+        ..previous
+    };
+    assert!(is_reserve_config_change_risk_reducing(
+        UpdateConfigMode::UpdateDepositLimit,
+        &previous,
+        &lower_deposit_limit
+    ));
+
+    let mut lower_group_limits = previous;
+    lower_group_limits.borrow_limit_against_this_collateral_in_elevation_group[3] = 50;
+    assert!(is_reserve_config_change_risk_reducing(
+        UpdateConfigMode::UpdateBorrowLimitsInElevationGroupAgainstThisReserve,
+        &previous,
+        &lower_group_limits
+    ));
+}
+
+#[test]
+fn test_raising_limits_is_not_risk_reducing() {
+    let previous = config();
+
+    let higher_ltv = ReserveConfig {
+        loan_to_value_pct: 75,
+        ..previous
+    };
+    assert!(!is_reserve_config_change_risk_reducing(
+        UpdateConfigMode::UpdateLoanToValuePct,
+        &previous,
+        &higher_ltv
+    ));
+
+    let higher_borrow_limit = ReserveConfig {
+        borrow_limit: 600_000,
+        ..previous
+    };
+    assert!(!is_reserve_config_change_risk_reducing(
+        UpdateConfigMode::UpdateBorrowLimit,
+        &previous,
+        &higher_borrow_limit
+    ));
+
+    let mut mixed_group_limits = previous;
+    mixed_group_limits.borrow_limit_against_this_collateral_in_elevation_group[0] = 50;
+    mixed_group_limits.borrow_limit_against_this_collateral_in_elevation_group[1] = 150;
+    assert!(!is_reserve_config_change_risk_reducing(
+        UpdateConfigMode::UpdateBorrowLimitsInElevationGroupAgainstThisReserve,
+        &previous,
+        &mixed_group_limits
+    ));
+
+    // Modes without a direction are never considered risk reducing
+    let lower_threshold = ReserveConfig {
+        liquidation_threshold_pct: 75,
+        ..previous
+    };
+    assert!(!is_reserve_config_change_risk_reducing(
+        UpdateConfigMode::UpdateLiquidationThresholdPct,
+        &previous,
+        &lower_threshold
+    ));
+}
+
+#[test]
+fn test_timelocked_modes() {
+    assert!(UpdateConfigMode::UpdateLoanToValuePct.is_timelocked());
+    assert!(UpdateConfigMode::UpdateScopePriceFeed.is_timelocked());
+    assert!(UpdateConfigMode::UpdateEntireReserveConfig.is_timelocked());
+    assert!(!UpdateConfigMode::UpdateReserveStatus.is_timelocked());
+    assert!(!UpdateConfigMode::UpdateFeesBorrowFee.is_timelocked());
+
+    assert!(UpdateLendingMarketMode::UpdateConfigTimelockSecs.is_timelocked());
+    assert!(UpdateLendingMarketMode::UpdateRiskCouncil.is_timelocked());
+    assert!(!UpdateLendingMarketMode::UpdateEmergencyMode.is_timelocked());
+}
+
+#[test]
+fn test_oracle_modes_are_timelocked() {
+    for mode in [
+        UpdateConfigMode::UpdateTokenInfoExpHeuristic,
+        UpdateConfigMode::UpdateTokenInfoTwapDivergence,
+        UpdateConfigMode::UpdateTokenInfoScopeTwap,
+        UpdateConfigMode::UpdateTokenInfoScopeChain,
+        UpdateConfigMode::UpdateTokenInfoPriceMaxAge,
+        UpdateConfigMode::UpdateTokenInfoTwapMaxAge,
+        UpdateConfigMode::UpdateTokenInfoMaxPriceSourcesDivergence,
+        UpdateConfigMode::UpdateTokenInfoMaxConfidenceBps,
+        UpdateConfigMode::UpdateTokenInfoPythMinVerificationSignatures,
+        UpdateConfigMode::UpdateTokenInfoPythMaxPostedSlotLag,
+        UpdateConfigMode::UpdatePriceCircuitBreakerMaxChangeBps,
+        UpdateConfigMode::UpdatePriceCircuitBreakerWindowSeconds,
+    ] {
+        assert!(mode.is_timelocked(), "{mode:?}");
+    }
+    assert!(!UpdateConfigMode::UpdateTokenInfoName.is_timelocked());
+}
+
+#[test]
+fn test_pending_config_change_expiry() {
+    let pending_config_change = PendingConfigChange {
+        execution_timestamp: 1_000,
+        expiration_timestamp: 1_000 + CONFIG_CHANGE_EXPIRY_SECS,
+        ..Default::default()
+    };
+
+    assert!(!pending_config_change.is_executable(999));
+    assert!(pending_config_change.is_executable(1_000));
+    assert!(!pending_config_change.is_expired(1_000 + CONFIG_CHANGE_EXPIRY_SECS));
+    assert!(pending_config_change.is_expired(1_001 + CONFIG_CHANGE_EXPIRY_SECS));
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use common::{LendingTestState, ReserveSpec};
+use kamino_lending::{
+    events::{changed_bytes, ReserveInterestSnapshot},
+    fraction::Fraction,
+    utils::SLOTS_PER_DAY,
+};
+
+#[test]
+fn test_changed_bytes() {
+    assert_eq!(changed_bytes(&[1, 2, 3], &[1, 2, 3]), (0, vec![], vec![]));
+    assert_eq!(
+        changed_bytes(&[1, 2, 3, 4, 5], &[1, 9, 3, 8, 5]),
+        (1, vec![2, 3, 4], vec![9, 3, 8])
+    );
+    assert_eq!(changed_bytes(&[1, 2], &[3, 2]), (0, vec![1], vec![3]));
+}
+
+#[test]
+fn test_interest_accrued_matches_reserve() {
+    let spec = ReserveSpec {
+        decimals: 6,
+        price: Fraction::ONE,
+        loan_to_value_pct: 80,
+        liquidation_threshold_pct: 85,
+        borrow_factor_pct: 100,
+    };
+    let mut state = LendingTestState::new(&[spec], 1);
+    state.deposit(0, 0, 1_000_000_000_000).unwrap();
+    state.borrow(0, 0, 500_000_000_000).unwrap();
+    state.refresh_reserves().unwrap();
+
+    let reserve = state.reserves[0].account.clone();
+    let snapshot = ReserveInterestSnapshot::new(&reserve.borrow());
+    assert!(snapshot
+        .accrued(reserve.key, &reserve.borrow(), state.clock.slot)
+        .is_none());
+
+    let borrowed_before = reserve.borrow().liquidity.borrowed_amount_sf;
+    state.advance_slots(SLOTS_PER_DAY);
+    state.refresh_reserves().unwrap();
+
+    let event = snapshot
+        .accrued(reserve.key, &reserve.borrow(), state.clock.slot)
+        .unwrap();
+    assert_eq!(event.slots_elapsed, SLOTS_PER_DAY);
+    assert_eq!(
+        event.borrowed_amount_sf,
+        borrowed_before + event.interest_sf
+    );
+    assert!(event.protocol_fees_sf > 0);
+    assert!(event.protocol_fees_sf < event.interest_sf);
+}
@@ -0,0 +1,101 @@
+mod common;
+
+use common::{LendingTestState, ReserveSpec};
+use kamino_lending::{fraction::Fraction, utils::MAX_OBLIGATION_RESERVES, AssetTier};
+
+const NUM_RESERVES: usize = 21;
+
+fn setup() -> LendingTestState {
+    let spec = ReserveSpec {
+        decimals: 6,
+        price: Fraction::ONE,
+        loan_to_value_pct: 80,
+        liquidation_threshold_pct: 85,
+        borrow_factor_pct: 100,
+    };
+    let mut state = LendingTestState::new(&vec![spec; NUM_RESERVES], 2);
+    state.extend_obligation(0);
+    for reserve in 10..NUM_RESERVES {
+        state.deposit(reserve, 0, 1_000_000_000).unwrap();
+    }
+    state
+}
+
+#[test]
+fn test_standard_obligation_deposit_slots() {
+    let mut state = setup();
+    for reserve in 0..8 {
+        state.deposit(reserve, 1, 1_000_000).unwrap();
+    }
+    assert!(state.deep_clone().deposit(8, 1, 1_000_000).is_err());
+
+    state.extend_obligation(1);
+    state.deposit(8, 1, 1_000_000).unwrap();
+    assert_eq!(state.obligation(1).deposits_count(), 9);
+    assert_eq!(state.deposit_reserve_indices(1), (0..9).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_extended_obligation_positions() {
+    let mut state = setup();
+    state.extend_obligation(1);
+    for reserve in 0..10 {
+        state.deposit(reserve, 1, 10_000_000).unwrap();
+    }
+    for reserve in 10..17 {
+        state.borrow(reserve, 1, 1_000_000).unwrap();
+    }
+    state.refresh_all().unwrap();
+
+    let obligation = state.obligation(1);
+    assert_eq!(obligation.deposits_count(), 10);
+    assert_eq!(obligation.borrows_count(), 7);
+    let deposit_tiers = obligation.get_deposit_asset_tiers();
+    assert_eq!(deposit_tiers.len(), 10);
+    assert!(deposit_tiers.iter().all(|tier| *tier == AssetTier::Regular));
+    let borrow_tiers = obligation.get_borrows_asset_tiers();
+    assert_eq!(borrow_tiers.len(), 7);
+    assert!(borrow_tiers.iter().all(|tier| *tier == AssetTier::Regular));
+    assert_eq!(
+        obligation.deposited_value_sf,
+        Fraction::from_num(100).to_bits()
+    );
+    assert!(Fraction::from_bits(obligation.borrow_factor_adjusted_debt_value_sf) >= 7);
+    drop(obligation);
+
+    state.repay(16, 1, u64::MAX).unwrap();
+    state.withdraw(9, 1, u64::MAX).unwrap();
+    state.refresh_all().unwrap();
+    assert_eq!(
+        state.borrow_reserve_indices(1),
+        (10..16).collect::<Vec<_>>()
+    );
+    assert_eq!(state.deposit_reserve_indices(1), (0..9).collect::<Vec<_>>());
+    assert!(state.is_healthy(1));
+}
+
+#[test]
+fn test_extended_obligation_reserves_limit() {
+    let mut state = setup();
+    state.extend_obligation(1);
+    for reserve in 0..16 {
+        state.deposit(reserve, 1, 10_000_000).unwrap();
+    }
+    assert!(state.deep_clone().deposit(16, 1, 1_000_000).is_err());
+
+    let max_borrows = MAX_OBLIGATION_RESERVES as usize - 16;
+    for reserve in 16..16 + max_borrows {
+        state.borrow(reserve, 1, 1_000_000).unwrap();
+    }
+    assert!(state
+        .deep_clone()
+        .borrow(16 + max_borrows, 1, 1_000_000)
+        .is_err());
+
+    state.repay(16, 1, u64::MAX).unwrap();
+    state.borrow(16 + max_borrows, 1, 1_000_000).unwrap();
+    assert_eq!(
+        state.obligation(1).borrows_count() + state.obligation(1).deposits_count(),
+        MAX_OBLIGATION_RESERVES as usize
+    );
+}
@@ -0,0 +1,269 @@
+mod common;
+
+use common::{LendingTestState, ReserveSpec};
+use kamino_lending::{
+    approximate_compounded_interest,
+    fraction::{BigFraction, Fraction, FractionExtra},
+    utils::{SLOTS_PER_DAY, SLOTS_PER_SECOND},
+    FixedRateBorrowConfig, FixedRateBorrowTerms,
+};
+
+const PREMIUM_BPS: u32 = 200;
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+fn setup(fixed_rate_borrow: FixedRateBorrowConfig) -> LendingTestState {
+    let spec = ReserveSpec {
+        decimals: 6,
+        price: Fraction::ONE,
+        loan_to_value_pct: 80,
+        liquidation_threshold_pct: 85,
+        borrow_factor_pct: 100,
+    };
+    let mut state = LendingTestState::new(&[spec.clone(), spec], 2);
+    state.reserves[0]
+        .account
+        .borrow_mut()
+        .config
+        .fixed_rate_borrow = fixed_rate_borrow;
+
+    state.deposit(0, 0, 1_000_000_000).unwrap();
+    state.deposit(1, 1, 2_000_000_000).unwrap();
+    state
+}
+
+fn fixed_rate_borrow_config() -> FixedRateBorrowConfig {
+    FixedRateBorrowConfig {
+        premium_bps: PREMIUM_BPS,
+        max_term_seconds: u32::try_from(30 * SECONDS_PER_DAY).unwrap(),
+    }
+}
+
+fn terms(term_days: u64) -> FixedRateBorrowTerms {
+    FixedRateBorrowTerms {
+        term_seconds: term_days * SECONDS_PER_DAY,
+        max_fixed_rate_bps: 10_000,
+    }
+}
+
+fn borrowed_amount(state: &LendingTestState) -> Fraction {
+    Fraction::from_bits(state.obligations[1].borrow().borrows[0].borrowed_amount_sf)
+}
+
+#[test]
+fn test_fixed_rate_borrow_accrues_at_locked_rate() {
+    let mut state = setup(fixed_rate_borrow_config());
+    let start_slot = state.clock.slot;
+    state
+        .borrow_fixed_rate(0, 1, 500_000_000, terms(10))
+        .unwrap();
+
+    let expected_rate_bps: u64 = {
+        let reserve = state.reserves[0].account.borrow();
+        (reserve.current_borrow_rate().unwrap()
+            + Fraction::from_bps(reserve.config.host_fixed_interest_rate_bps)
+            + Fraction::from_bps(PREMIUM_BPS))
+        .to_bps()
+        .unwrap()
+    };
+    {
+        let obligation = state.obligations[1].borrow();
+        let borrow = &obligation.borrows[0];
+        assert!(borrow.is_fixed_rate());
+        assert_eq!(borrow.fixed_borrow_rate_bps, expected_rate_bps);
+        assert_eq!(
+            borrow.fixed_rate_maturity_slot,
+            start_slot + 10 * SECONDS_PER_DAY * SLOTS_PER_SECOND
+        );
+    }
+
+    let borrowed_before = borrowed_amount(&state);
+    state.advance_slots(SLOTS_PER_DAY);
+    state.refresh_reserves().unwrap();
+    state.refresh_obligation(1).unwrap();
+
+    let expected = borrowed_before
+        * approximate_compounded_interest(Fraction::from_bps(expected_rate_bps), SLOTS_PER_DAY);
+    assert_eq!(borrowed_amount(&state), expected);
+
+    // The reserve tracks the locked-rate debt and books the premium over floating as spread
+    let reserve = state.reserves[0].account.borrow();
+    assert!(reserve.liquidity.fixed_rate_interest_spread_sf > 0);
+    let reserve_borrowed = Fraction::from_bits(reserve.liquidity.borrowed_amount_sf);
+    assert!(reserve_borrowed.abs_diff(expected) < Fraction::ONE);
+}
+
+#[test]
+fn test_fixed_rate_borrow_protocol_fees_follow_locked_rate() {
+    let mut state = setup(fixed_rate_borrow_config());
+    state
+        .borrow_fixed_rate(0, 1, 500_000_000, terms(10))
+        .unwrap();
+
+    let (fees_before, protocol_take_rate) = {
+        let reserve = state.reserves[0].account.borrow();
+        (
+            Fraction::from_bits(reserve.liquidity.accumulated_protocol_fees_sf),
+            Fraction::from_percent(reserve.config.protocol_take_rate_pct),
+        )
+    };
+    let borrowed_before = borrowed_amount(&state);
+    state.advance_slots(SLOTS_PER_DAY);
+    state.refresh_reserves().unwrap();
+    state.refresh_obligation(1).unwrap();
+
+    // The only debt of the reserve is fixed-rate, the protocol takes its share of the locked
+    // interest rather than of the floating one
+    let interest = borrowed_amount(&state) - borrowed_before;
+    let reserve = state.reserves[0].account.borrow();
+    let fees = Fraction::from_bits(reserve.liquidity.accumulated_protocol_fees_sf) - fees_before;
+    assert!(reserve.liquidity.fixed_rate_interest_spread_sf > 0);
+    assert!(fees.abs_diff(interest * protocol_take_rate) < Fraction::from_num(0.001));
+}
+
+#[test]
+fn test_fixed_rate_borrow_converts_to_floating_at_maturity() {
+    let mut state = setup(fixed_rate_borrow_config());
+    state
+        .borrow_fixed_rate(0, 1, 500_000_000, terms(1))
+        .unwrap();
+
+    let (fixed_rate_bps, maturity_slot, mut floating_borrow) = {
+        let obligation = state.obligations[1].borrow();
+        let borrow = obligation.borrows[0];
+        (
+            borrow.fixed_borrow_rate_bps,
+            borrow.fixed_rate_maturity_slot,
+            borrow,
+        )
+    };
+    let start_slot = state.clock.slot;
+    let borrowed_before = borrowed_amount(&state);
+
+    state.advance_slots(2 * SLOTS_PER_DAY);
+    state.refresh_reserves().unwrap();
+    state.refresh_obligation(1).unwrap();
+
+    // Past maturity the debt follows the reserve cumulative borrow rate
+    floating_borrow
+        .accrue_interest(BigFraction::from(
+            state.reserves[0]
+                .account
+                .borrow()
+                .liquidity
+                .cumulative_borrow_rate_bsf,
+        ))
+        .unwrap();
+    let floating_interest = (Fraction::from_bits(floating_borrow.borrowed_amount_sf)
+        - borrowed_before)
+        * Fraction::from(state.clock.slot - maturity_slot)
+        / Fraction::from(state.clock.slot - start_slot);
+    let expected = (borrowed_before + floating_interest)
+        * approximate_compounded_interest(
+            Fraction::from_bps(fixed_rate_bps),
+            maturity_slot - start_slot,
+        );
+    assert_eq!(borrowed_amount(&state), expected);
+
+    let obligation = state.obligations[1].borrow();
+    let borrow = &obligation.borrows[0];
+    assert!(!borrow.is_fixed_rate());
+    assert_eq!(borrow.fixed_borrow_rate_bps, 0);
+    drop(obligation);
+
+    // Once floating, the position can be increased like any other borrow
+    state.borrow(0, 1, 1_000_000).unwrap();
+}
+
+#[test]
+fn test_fixed_rate_borrow_early_repay() {
+    let mut state = setup(fixed_rate_borrow_config());
+    state
+        .borrow_fixed_rate(0, 1, 500_000_000, terms(10))
+        .unwrap();
+
+    state.advance_slots(SLOTS_PER_DAY);
+    let fixed_rate_bps = state.obligations[1].borrow().borrows[0].fixed_borrow_rate_bps;
+    state.repay(0, 1, 100_000_000).unwrap();
+    {
+        // A partial early repay keeps the locked rate on the remaining debt
+        let obligation = state.obligations[1].borrow();
+        assert!(obligation.borrows[0].is_fixed_rate());
+        assert_eq!(obligation.borrows[0].fixed_borrow_rate_bps, fixed_rate_bps);
+    }
+
+    state.advance_slots(SLOTS_PER_DAY);
+    state.repay(0, 1, u64::MAX).unwrap();
+    assert_eq!(
+        state.obligations[1].borrow().borrows[0].borrowed_amount_sf,
+        0
+    );
+    assert!(!state.obligations[1].borrow().borrows[0].is_fixed_rate());
+
+    state
+        .borrow_fixed_rate(0, 1, 500_000_000, terms(10))
+        .unwrap();
+}
+
+#[test]
+fn test_fixed_rate_borrow_rejections() {
+    let state = setup(FixedRateBorrowConfig::default());
+    assert!(state
+        .deep_clone()
+        .borrow_fixed_rate(0, 1, 500_000_000, terms(10))
+        .is_err());
+
+    let state = setup(fixed_rate_borrow_config());
+    assert!(state
+        .deep_clone()
+        .borrow_fixed_rate(0, 1, 500_000_000, terms(31))
+        .is_err());
+    assert!(state
+        .deep_clone()
+        .borrow_fixed_rate(
+            0,
+            1,
+            500_000_000,
+            FixedRateBorrowTerms {
+                max_fixed_rate_bps: 1,
+                ..terms(10)
+            }
+        )
+        .is_err());
+
+    let mut floating = state.deep_clone();
+    floating.borrow(0, 1, 1_000_000).unwrap();
+    assert!(floating
+        .borrow_fixed_rate(0, 1, 500_000_000, terms(10))
+        .is_err());
+
+    let mut fixed = state.deep_clone();
+    fixed
+        .borrow_fixed_rate(0, 1, 500_000_000, terms(10))
+        .unwrap();
+    assert!(fixed.borrow(0, 1, 1_000_000).is_err());
+}
+
+#[test]
+fn test_fixed_rate_borrow_with_host_fixed_rate_and_referral_fees() {
+    let mut state = setup(fixed_rate_borrow_config());
+    state.lending_market.referral_fee_bps = 2_000;
+    state.reserves[0]
+        .account
+        .borrow_mut()
+        .config
+        .host_fixed_interest_rate_bps = 1_000;
+    state.refresh_all().unwrap();
+
+    state
+        .borrow_fixed_rate(0, 1, 500_000_000, terms(10))
+        .unwrap();
+    let fixed_rate_bps = state.obligations[1].borrow().borrows[0].fixed_borrow_rate_bps;
+    assert!(fixed_rate_bps >= 1_000 + u64::from(PREMIUM_BPS));
+
+    // The locked rate covers the host fixed rate, referral accrual does not fail the refresh
+    state.advance_slots(SLOTS_PER_DAY);
+    state.refresh_all().unwrap();
+    state.advance_slots(10 * SLOTS_PER_DAY);
+    state.refresh_all().unwrap();
+    assert!(!state.obligations[1].borrow().borrows[0].is_fixed_rate());
+}
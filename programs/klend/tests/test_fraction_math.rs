@@ -0,0 +1,161 @@
+use kamino_lending::{
+    fraction::{pow_fraction, BigFraction, Fraction, FractionExtra, EPSILON, U256},
+    prop_assert_fuzzy_eq, prop_assert_gte,
+};
+use proptest::prelude::*;
+
+const FRAC_ONE_BITS: u128 = 1 << Fraction::FRAC_NBITS;
+
+fn fraction(max_int: u64) -> impl Strategy<Value = Fraction> {
+    (0..=max_int, 0..FRAC_ONE_BITS).prop_map(|(int, frac)| {
+        Fraction::from_bits((u128::from(int) << Fraction::FRAC_NBITS) | frac)
+    })
+}
+
+fn fraction_in(min: Fraction, max: Fraction) -> impl Strategy<Value = Fraction> {
+    (min.to_bits()..=max.to_bits()).prop_map(Fraction::from_bits)
+}
+
+proptest! {
+    #[test]
+    fn test_floor_and_ceil_bracket_value(x in fraction(u64::MAX)) {
+        let floor: u128 = x.to_floor();
+        let ceil: u128 = x.to_ceil();
+        let round: u128 = x.to_round();
+
+        prop_assert!(Fraction::from_num(floor) <= x);
+        prop_assert!(Fraction::from_num(ceil) >= x);
+        prop_assert!(floor <= round && round <= ceil);
+        if x.frac() == Fraction::ZERO {
+            prop_assert_eq!(floor, ceil);
+        } else {
+            prop_assert_eq!(floor + 1, ceil);
+        }
+    }
+
+    #[test]
+    fn test_try_to_floor_overflow(int in u64::MAX as u128 + 1..1 << 68) {
+        let x = Fraction::from_num(int);
+        prop_assert!(x.try_to_floor::<u64>().is_none());
+        prop_assert!(x.try_to_ceil::<u64>().is_none());
+        prop_assert_eq!(x.try_to_floor::<u128>(), Some(int));
+    }
+
+    #[test]
+    fn test_div_ceil_rounds_up(
+        num in fraction(u32::MAX.into()),
+        denom in fraction_in(Fraction::ONE, Fraction::from_num(u32::MAX)),
+    ) {
+        let floor = num / denom;
+        let ceil = num.div_ceil(&denom);
+
+        prop_assert_gte!(ceil, floor);
+        prop_assert_fuzzy_eq!(ceil.to_bits(), floor.to_bits(), 1);
+        prop_assert_gte!(ceil * denom, num);
+    }
+
+    #[test]
+    fn test_bps_and_percent_round_trip(bps in 0..=10_000_u64, pct in 0..=100_u8) {
+        prop_assert_eq!(Fraction::from_bps(bps).to_bps::<u64>(), Some(bps));
+        prop_assert_eq!(Fraction::from_percent(pct).to_percent::<u8>(), Some(pct));
+        prop_assert!(Fraction::from_bps(bps) <= Fraction::ONE);
+    }
+
+    #[test]
+    fn test_full_mul_int_ratio_identity(x in fraction(u64::MAX), n in 1..u128::MAX) {
+        prop_assert_eq!(x.full_mul_int_ratio(n, n), x);
+    }
+
+    #[test]
+    fn test_pow_fraction_matches_repeated_mul(
+        x in fraction_in(Fraction::ONE, Fraction::from_num(2)),
+        power in 0..64_u32,
+    ) {
+        let mut expected = Fraction::ONE;
+        for _ in 0..power {
+            expected *= x;
+        }
+        let actual = pow_fraction(x, power).unwrap();
+
+        let diff = if actual > expected { actual - expected } else { expected - actual };
+        let tolerance = Fraction::from_bits(expected.to_bits() >> 40) + EPSILON;
+        prop_assert!(
+            diff <= tolerance,
+            "x: {}, power: {}, actual: {}, expected: {}",
+            x,
+            power,
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_pow_fraction_below_one_stays_below_one(
+        x in fraction_in(Fraction::ZERO, Fraction::ONE),
+        power in 1..1_000_u32,
+    ) {
+        let res = pow_fraction(x, power).unwrap();
+        prop_assert!(res <= x);
+    }
+
+    #[test]
+    fn test_big_fraction_round_trip(x in fraction(u64::MAX)) {
+        prop_assert_eq!(Fraction::try_from(BigFraction::from(x)).unwrap(), x);
+    }
+
+    #[test]
+    fn test_big_fraction_from_num(int in any::<u64>()) {
+        prop_assert_eq!(BigFraction::from_num(int), BigFraction::from(Fraction::from_num(int)));
+    }
+
+    #[test]
+    fn test_big_fraction_overflow(excess in 1..u128::MAX) {
+        let big = BigFraction(U256::from(u128::MAX) + U256::from(excess));
+        prop_assert!(Fraction::try_from(big).is_err());
+    }
+
+    #[test]
+    fn test_big_fraction_mul_matches_fraction(
+        a in fraction(u32::MAX.into()),
+        b in fraction(u32::MAX.into()),
+    ) {
+        let big: Fraction = (BigFraction::from(a) * BigFraction::from(b)).try_into().unwrap();
+        prop_assert_fuzzy_eq!(big.to_bits(), (a * b).to_bits(), 1);
+    }
+
+    #[test]
+    fn test_big_fraction_div_matches_fraction(
+        a in fraction(u32::MAX.into()),
+        b in fraction_in(Fraction::ONE, Fraction::from_num(u32::MAX)),
+    ) {
+        let big: Fraction = (BigFraction::from(a) / BigFraction::from(b)).try_into().unwrap();
+        prop_assert_fuzzy_eq!(big.to_bits(), (a / b).to_bits(), 1);
+    }
+
+    #[test]
+    fn test_big_fraction_handles_intermediate_overflow(
+        a in fraction_in(Fraction::from_num(1_u64 << 40), Fraction::from_num(1_u64 << 60)),
+        b in fraction_in(Fraction::from_num(1_u64 << 40), Fraction::from_num(1_u64 << 60)),
+    ) {
+        prop_assert!(a.checked_mul(b).is_none());
+        let res: Fraction = (BigFraction::from(a) * BigFraction::from(b) / BigFraction::from(b))
+            .try_into()
+            .unwrap();
+        prop_assert_fuzzy_eq!(res.to_bits(), a.to_bits(), 1);
+    }
+}
+
+#[test]
+fn test_pow_fraction_overflow_boundary() {
+    let two = Fraction::from_num(2);
+    for power in 0..Fraction::INT_NBITS {
+        assert_eq!(
+            pow_fraction(two, power),
+            Some(Fraction::from_num(1_u128 << power))
+        );
+    }
+    assert_eq!(pow_fraction(two, Fraction::INT_NBITS), None);
+    assert_eq!(Fraction::MAX.checked_pow(2), None);
+    assert_eq!(Fraction::MAX.checked_pow(1), Some(Fraction::MAX));
+    assert_eq!(Fraction::ZERO.checked_pow(0), Some(Fraction::ONE));
+}
@@ -0,0 +1,156 @@
+use kamino_lending::{
+    fraction::{pow_fraction, Fraction, FractionExtra, EPSILON},
+    prop_assert_gte,
+    state::{approximate_compounded_interest, CollateralExchangeRate},
+    utils::{SLOTS_PER_DAY, SLOTS_PER_YEAR},
+};
+use proptest::prelude::*;
+
+const MAX_TEST_AMOUNT: u64 = 1_000_000_000_000_000;
+
+fn borrow_rate(max_bps: u32) -> impl Strategy<Value = Fraction> {
+    (0..=max_bps).prop_map(Fraction::from_bps)
+}
+
+fn exchange_rate() -> impl Strategy<Value = CollateralExchangeRate> {
+    (1..=MAX_TEST_AMOUNT, 5_000..=1_000_000_u64).prop_map(|(collateral_supply, rate_bps)| {
+        let liquidity = Fraction::from_num(collateral_supply) * Fraction::from_bps(rate_bps);
+        CollateralExchangeRate::from_supply_and_liquidity(collateral_supply, liquidity)
+    })
+}
+
+proptest! {
+    #[test]
+    fn test_compounded_interest_never_below_one(
+        rate in borrow_rate(100_000),
+        slots in 0..10 * SLOTS_PER_YEAR,
+    ) {
+        prop_assert_gte!(approximate_compounded_interest(rate, slots), Fraction::ONE);
+    }
+
+    #[test]
+    fn test_compounded_interest_monotonic_in_slots(
+        rate in borrow_rate(100_000),
+        slots in 0..10 * SLOTS_PER_YEAR,
+        extra_slots in 1..SLOTS_PER_DAY,
+    ) {
+        let lower = approximate_compounded_interest(rate, slots);
+        let upper = approximate_compounded_interest(rate, slots + extra_slots);
+        prop_assert_gte!(upper, lower);
+    }
+
+    #[test]
+    fn test_compounded_interest_small_slot_counts_monotonic(
+        rate in borrow_rate(100_000),
+        slots in 0..16_u64,
+    ) {
+        let lower = approximate_compounded_interest(rate, slots);
+        let upper = approximate_compounded_interest(rate, slots + 1);
+        prop_assert_gte!(upper, lower);
+    }
+
+    #[test]
+    fn test_compounded_interest_monotonic_in_rate(
+        rate in borrow_rate(100_000),
+        extra_bps in 1..10_000_u32,
+        slots in 0..10 * SLOTS_PER_YEAR,
+    ) {
+        let lower = approximate_compounded_interest(rate, slots);
+        let upper = approximate_compounded_interest(rate + Fraction::from_bps(extra_bps), slots);
+        prop_assert_gte!(upper, lower);
+    }
+
+    #[test]
+    fn test_compounded_interest_does_not_exceed_exact(
+        rate in borrow_rate(10_000),
+        slots in 0..SLOTS_PER_YEAR,
+    ) {
+        let approx = approximate_compounded_interest(rate, slots);
+        let base = Fraction::ONE + rate / u128::from(SLOTS_PER_YEAR);
+        let exact = pow_fraction(base, u32::try_from(slots).unwrap()).unwrap();
+        prop_assert!(
+            approx <= exact + EPSILON,
+            "rate: {}, slots: {}, approx: {}, exact: {}",
+            rate,
+            slots,
+            approx,
+            exact
+        );
+    }
+
+    #[test]
+    fn test_deposit_then_redeem_never_creates_liquidity(
+        rate in exchange_rate(),
+        liquidity_amount in 0..=MAX_TEST_AMOUNT,
+    ) {
+        let collateral = rate.liquidity_to_collateral(liquidity_amount);
+        let redeemed = rate.collateral_to_liquidity(collateral);
+        prop_assert!(
+            redeemed <= liquidity_amount,
+            "rate: {:?}, deposited: {}, collateral: {}, redeemed: {}",
+            rate,
+            liquidity_amount,
+            collateral,
+            redeemed
+        );
+    }
+
+    #[test]
+    fn test_redeem_then_deposit_never_creates_collateral(
+        rate in exchange_rate(),
+        collateral_amount in 0..=MAX_TEST_AMOUNT,
+    ) {
+        let liquidity = rate.collateral_to_liquidity(collateral_amount);
+        let collateral = rate.liquidity_to_collateral(liquidity);
+        prop_assert!(
+            collateral <= collateral_amount,
+            "rate: {:?}, redeemed: {}, liquidity: {}, collateral: {}",
+            rate,
+            collateral_amount,
+            liquidity,
+            collateral
+        );
+    }
+
+    #[test]
+    fn test_exchange_rate_ceil_variants_round_up(
+        rate in exchange_rate(),
+        amount in 0..=MAX_TEST_AMOUNT,
+    ) {
+        let collateral_floor = rate.liquidity_to_collateral(amount);
+        let collateral_ceil = rate.liquidity_to_collateral_ceil(amount);
+        prop_assert!(collateral_floor <= collateral_ceil && collateral_ceil - collateral_floor <= 1);
+
+        let liquidity_floor = rate.collateral_to_liquidity(amount);
+        let liquidity_ceil = rate.collateral_to_liquidity_ceil(amount);
+        prop_assert!(liquidity_floor <= liquidity_ceil && liquidity_ceil - liquidity_floor <= 1);
+    }
+
+    #[test]
+    fn test_exchange_rate_monotonic(
+        rate in exchange_rate(),
+        amount in 0..MAX_TEST_AMOUNT,
+    ) {
+        prop_assert_gte!(rate.liquidity_to_collateral(amount + 1), rate.liquidity_to_collateral(amount));
+        prop_assert_gte!(rate.collateral_to_liquidity(amount + 1), rate.collateral_to_liquidity(amount));
+    }
+
+    #[test]
+    fn test_unit_exchange_rate_is_identity(amount in any::<u64>()) {
+        let rate = CollateralExchangeRate::ONE;
+        prop_assert_eq!(rate.liquidity_to_collateral(amount), amount);
+        prop_assert_eq!(rate.collateral_to_liquidity(amount), amount);
+    }
+}
+
+#[test]
+fn test_compounded_interest_zero_rate_or_slots() {
+    assert_eq!(
+        approximate_compounded_interest(Fraction::ZERO, SLOTS_PER_YEAR),
+        Fraction::ONE
+    );
+    assert_eq!(
+        approximate_compounded_interest(Fraction::from_percent(50), 0),
+        Fraction::ONE
+    );
+}
@@ -0,0 +1,309 @@
+mod common;
+
+use common::{LendingTestState, ReserveSpec};
+use kamino_lending::{
+    fraction::{Fraction, FractionExtra},
+    utils::SLOTS_PER_DAY,
+};
+use proptest::{prelude::*, sample::Index};
+
+const NUM_OBLIGATIONS: usize = 3;
+const MAX_AMOUNT: u64 = 1_000_000_000_000;
+
+fn reserve_specs() -> Vec<ReserveSpec> {
+    vec![
+        ReserveSpec {
+            decimals: 6,
+            price: Fraction::ONE,
+            loan_to_value_pct: 80,
+            liquidation_threshold_pct: 85,
+            borrow_factor_pct: 100,
+        },
+        ReserveSpec {
+            decimals: 9,
+            price: Fraction::from_num(150),
+            loan_to_value_pct: 65,
+            liquidation_threshold_pct: 75,
+            borrow_factor_pct: 100,
+        },
+        ReserveSpec {
+            decimals: 6,
+            price: Fraction::from_num(3),
+            loan_to_value_pct: 40,
+            liquidation_threshold_pct: 55,
+            borrow_factor_pct: 150,
+        },
+    ]
+}
+
+#[derive(Debug, Clone)]
+enum Action {
+    Deposit {
+        reserve: usize,
+        obligation: usize,
+        amount: u64,
+    },
+    Withdraw {
+        deposit: Index,
+        obligation: usize,
+        collateral_amount: u64,
+    },
+    Borrow {
+        reserve: usize,
+        obligation: usize,
+        amount: u64,
+    },
+    Repay {
+        borrow: Index,
+        obligation: usize,
+        amount: u64,
+    },
+    Liquidate {
+        borrow: Index,
+        deposit: Index,
+        obligation: Index,
+        amount: u64,
+    },
+    FlashLoan {
+        reserve: usize,
+        amount: u64,
+    },
+    SetPrice {
+        reserve: usize,
+        price_bps: u64,
+    },
+    AdvanceSlots(u64),
+}
+
+impl Action {
+    fn is_user_action(&self) -> bool {
+        !matches!(self, Action::SetPrice { .. } | Action::AdvanceSlots(_))
+    }
+}
+
+fn amount() -> impl Strategy<Value = u64> {
+    prop_oneof![
+        1 => 1..=1_000_000_u64,
+        2 => 1..=MAX_AMOUNT,
+        2 => Just(u64::MAX),
+    ]
+}
+
+fn action() -> impl Strategy<Value = Action> {
+    let reserve = 0..reserve_specs().len();
+    let obligation = 0..NUM_OBLIGATIONS;
+    prop_oneof![
+        4 => (reserve.clone(), obligation.clone(), 1..=MAX_AMOUNT)
+            .prop_map(|(reserve, obligation, amount)| Action::Deposit { reserve, obligation, amount }),
+        2 => (any::<Index>(), obligation.clone(), amount())
+            .prop_map(|(deposit, obligation, collateral_amount)| Action::Withdraw {
+                deposit,
+                obligation,
+                collateral_amount,
+            }),
+        4 => (reserve.clone(), obligation.clone(), amount())
+            .prop_map(|(reserve, obligation, amount)| Action::Borrow { reserve, obligation, amount }),
+        2 => (any::<Index>(), obligation.clone(), amount())
+            .prop_map(|(borrow, obligation, amount)| Action::Repay { borrow, obligation, amount }),
+        3 => (any::<Index>(), any::<Index>(), any::<Index>(), amount())
+            .prop_map(|(borrow, deposit, obligation, amount)| Action::Liquidate {
+                borrow,
+                deposit,
+                obligation,
+                amount,
+            }),
+        1 => (reserve.clone(), 1..=MAX_AMOUNT)
+            .prop_map(|(reserve, amount)| Action::FlashLoan { reserve, amount }),
+        3 => (reserve, 5_000..=15_000_u64)
+            .prop_map(|(reserve, price_bps)| Action::SetPrice { reserve, price_bps }),
+        1 => (1..=30 * SLOTS_PER_DAY).prop_map(Action::AdvanceSlots),
+    ]
+}
+
+fn pick(candidates: Vec<usize>, index: &Index) -> Option<usize> {
+    (!candidates.is_empty()).then(|| candidates[index.index(candidates.len())])
+}
+
+fn execute(state: &mut LendingTestState, action: &Action) {
+    let mut next = state.deep_clone();
+    let res = match *action {
+        Action::Deposit {
+            reserve,
+            obligation,
+            amount,
+        } => next.deposit(reserve, obligation, amount),
+        Action::Withdraw {
+            ref deposit,
+            obligation,
+            collateral_amount,
+        } => match pick(next.deposit_reserve_indices(obligation), deposit) {
+            Some(reserve) => next.withdraw(reserve, obligation, collateral_amount),
+            None => return,
+        },
+        Action::Borrow {
+            reserve,
+            obligation,
+            amount,
+        } => next.borrow(reserve, obligation, amount),
+        Action::Repay {
+            ref borrow,
+            obligation,
+            amount,
+        } => match pick(next.borrow_reserve_indices(obligation), borrow) {
+            Some(reserve) => next.repay(reserve, obligation, amount),
+            None => return,
+        },
+        Action::Liquidate {
+            ref borrow,
+            ref deposit,
+            ref obligation,
+            amount,
+        } => {
+            let unhealthy: Vec<usize> = (0..NUM_OBLIGATIONS)
+                .filter(|obligation| !next.is_healthy(*obligation))
+                .collect();
+            let obligation =
+                pick(unhealthy, obligation).unwrap_or_else(|| obligation.index(NUM_OBLIGATIONS));
+            match (
+                pick(next.borrow_reserve_indices(obligation), borrow),
+                pick(next.deposit_reserve_indices(obligation), deposit),
+            ) {
+                (Some(repay_reserve), Some(withdraw_reserve)) => {
+                    next.liquidate(repay_reserve, withdraw_reserve, obligation, amount)
+                }
+                _ => return,
+            }
+        }
+        Action::FlashLoan { reserve, amount } => next.flash_loan(reserve, amount),
+        Action::SetPrice { reserve, price_bps } => {
+            let price = reserve_specs()[reserve].price * Fraction::from_bps(price_bps);
+            next.set_price(reserve, price);
+            Ok(())
+        }
+        Action::AdvanceSlots(slots) => {
+            next.advance_slots(slots);
+            Ok(())
+        }
+    };
+
+    // A failed instruction reverts every account it touched.
+    if res.is_ok() {
+        *state = next;
+    }
+}
+
+fn check_vault_balances(state: &LendingTestState) -> Result<(), TestCaseError> {
+    for (index, test_reserve) in state.reserves.iter().enumerate() {
+        let reserve = test_reserve.account.borrow();
+        prop_assert!(
+            test_reserve.liquidity_vault >= reserve.liquidity.available_amount,
+            "reserve {}: vault {} < available {}",
+            index,
+            test_reserve.liquidity_vault,
+            reserve.liquidity.available_amount
+        );
+        prop_assert_eq!(
+            test_reserve.collateral_supply,
+            reserve.collateral.mint_total_supply
+        );
+
+        let deposited: u64 = state
+            .obligations
+            .iter()
+            .flat_map(|obligation| obligation.borrow().deposits)
+            .filter(|deposit| deposit.deposit_reserve == test_reserve.account.key)
+            .map(|deposit| deposit.deposited_amount)
+            .sum();
+        prop_assert_eq!(test_reserve.collateral_vault, deposited);
+    }
+    Ok(())
+}
+
+fn check_borrowed_amounts(state: &LendingTestState) -> Result<(), TestCaseError> {
+    for (index, test_reserve) in state.reserves.iter().enumerate() {
+        let reserve_borrowed =
+            Fraction::from_bits(test_reserve.account.borrow().liquidity.borrowed_amount_sf);
+        let obligations_borrowed = state
+            .obligations
+            .iter()
+            .flat_map(|obligation| obligation.borrow().borrows)
+            .filter(|borrow| borrow.borrow_reserve == test_reserve.account.key)
+            .map(|borrow| Fraction::from_bits(borrow.borrowed_amount_sf))
+            .fold(Fraction::ZERO, |acc, borrowed| acc + borrowed);
+
+        let diff = if reserve_borrowed > obligations_borrowed {
+            reserve_borrowed - obligations_borrowed
+        } else {
+            obligations_borrowed - reserve_borrowed
+        };
+        let tolerance = Fraction::from_num(NUM_OBLIGATIONS) + reserve_borrowed / 1_000_000;
+        prop_assert!(
+            diff <= tolerance,
+            "reserve {}: borrowed {} != sum of obligation borrows {}",
+            index,
+            reserve_borrowed,
+            obligations_borrowed
+        );
+    }
+    Ok(())
+}
+
+fn healthy_obligations(state: &LendingTestState) -> Vec<bool> {
+    (0..state.obligations.len())
+        .map(|obligation| state.is_healthy(obligation))
+        .collect()
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(256))]
+
+    #[test]
+    fn test_lending_operations_invariants(actions in prop::collection::vec(action(), 1..100)) {
+        let mut state = LendingTestState::new(&reserve_specs(), NUM_OBLIGATIONS);
+
+        for action in &actions {
+            state.refresh_all().unwrap();
+            let healthy_before = healthy_obligations(&state);
+
+            execute(&mut state, action);
+
+            state.refresh_all().unwrap();
+            check_vault_balances(&state)?;
+            check_borrowed_amounts(&state)?;
+
+            if action.is_user_action() {
+                for (obligation, healthy_after) in healthy_obligations(&state).into_iter().enumerate() {
+                    prop_assert!(
+                        !healthy_before[obligation] || healthy_after,
+                        "obligation {} became unhealthy after {:?}",
+                        obligation,
+                        action
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_borrow_and_repay_keep_reserve_balanced() {
+    let mut state = LendingTestState::new(&reserve_specs(), 2);
+
+    state.deposit(0, 0, 1_000_000_000_000).unwrap();
+    state.deposit(1, 1, 100_000_000_000).unwrap();
+    state.borrow(0, 1, u64::MAX).unwrap();
+    assert!(state.borrow(0, 1, 1_000_000).is_err());
+
+    state.set_price(1, Fraction::from_num(120));
+    state.refresh_all().unwrap();
+    assert!(!state.is_healthy(1));
+
+    state.liquidate(0, 1, 1, u64::MAX).unwrap();
+    state.repay(0, 1, u64::MAX).unwrap();
+    state.withdraw(1, 1, u64::MAX).unwrap();
+    state.refresh_all().unwrap();
+
+    let obligation = state.obligation(1);
+    assert!(obligation.borrows_empty() && obligation.deposits_empty());
+    assert_eq!(state.reserves[1].collateral_vault, 0);
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::{LendingTestState, ReserveSpec};
+use kamino_lending::{
+    fraction::Fraction, lending_market::lending_operations, ObligationDelegatePermissions,
+};
+
+fn setup() -> (LendingTestState, Pubkey) {
+    let spec = ReserveSpec {
+        decimals: 6,
+        price: Fraction::ONE,
+        loan_to_value_pct: 80,
+        liquidation_threshold_pct: 85,
+        borrow_factor_pct: 100,
+    };
+    let mut state = LendingTestState::new(&[spec.clone(), spec], 2);
+    state.deposit(0, 0, 1_000_000_000).unwrap();
+    state.deposit(1, 1, 1_000_000_000).unwrap();
+
+    let delegate = Pubkey::new_unique();
+    {
+        let mut obligation = state.obligations[1].borrow_mut();
+        obligation.delegate = delegate;
+        obligation.delegate_permissions = ObligationDelegatePermissions::REBALANCE.bits();
+        obligation.delegate_max_ltv_pct = 50;
+    }
+    (state, delegate)
+}
+
+#[test]
+fn test_delegate_permissions() {
+    let (state, delegate) = setup();
+    let mut obligation = state.obligations[1].borrow_mut();
+    let owner = obligation.owner;
+
+    assert!(obligation.is_authorized(owner, ObligationDelegatePermissions::DEPOSIT));
+    assert!(obligation.is_authorized(delegate, ObligationDelegatePermissions::REBALANCE));
+    assert!(!obligation.is_authorized(delegate, ObligationDelegatePermissions::DEPOSIT));
+    assert!(!obligation.is_authorized(
+        Pubkey::new_unique(),
+        ObligationDelegatePermissions::REBALANCE
+    ));
+
+    obligation.clear_delegate();
+    assert!(!obligation.is_authorized(delegate, ObligationDelegatePermissions::REBALANCE));
+    assert!(!obligation.is_authorized(Pubkey::default(), ObligationDelegatePermissions::empty()));
+}
+
+#[test]
+fn test_delegate_borrow_ltv_ceiling() {
+    let (mut state, _) = setup();
+    state.refresh_reserves().unwrap();
+    state.refresh_obligation(1).unwrap();
+    let obligation = state.obligations[1].borrow();
+    let reserve = state.reserves[0].account.borrow();
+
+    lending_operations::check_delegate_borrow_within_ltv_ceiling(
+        &obligation,
+        &reserve,
+        Fraction::from_num(500_000_000),
+    )
+    .unwrap();
+    assert!(
+        lending_operations::check_delegate_borrow_within_ltv_ceiling(
+            &obligation,
+            &reserve,
+            Fraction::from_num(600_000_000),
+        )
+        .is_err()
+    );
+}
+
+#[test]
+fn test_delegate_withdraw_ltv_ceiling() {
+    let (mut state, _) = setup();
+    state.borrow(0, 1, 400_000_000).unwrap();
+    state.refresh_reserves().unwrap();
+    state.refresh_obligation(1).unwrap();
+    let obligation = state.obligations[1].borrow();
+    let collateral = obligation.deposits[0];
+
+    lending_operations::check_delegate_withdraw_within_ltv_ceiling(
+        &obligation,
+        &collateral,
+        100_000_000,
+    )
+    .unwrap();
+    assert!(
+        lending_operations::check_delegate_withdraw_within_ltv_ceiling(
+            &obligation,
+            &collateral,
+            300_000_000,
+        )
+        .is_err()
+    );
+}
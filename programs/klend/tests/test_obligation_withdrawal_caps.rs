@@ -0,0 +1,197 @@
+mod common;
+
+use common::{LendingTestState, ReserveSpec, MS_PER_SLOT};
+use kamino_lending::{fraction::Fraction, ObligationWithdrawalCapConfig};
+
+const INTERVAL_SECONDS: u64 = 60 * 60;
+const INTERVAL_SLOTS: u64 = INTERVAL_SECONDS * 1000 / MS_PER_SLOT;
+
+fn setup() -> LendingTestState {
+    let spec = ReserveSpec {
+        decimals: 6,
+        price: Fraction::ONE,
+        loan_to_value_pct: 80,
+        liquidation_threshold_pct: 85,
+        borrow_factor_pct: 100,
+    };
+    let mut state = LendingTestState::new(&[spec.clone(), spec], 3);
+    state.deposit(0, 0, 1_000_000_000).unwrap();
+    state.deposit(1, 1, 2_000_000_000).unwrap();
+    state.deposit(0, 2, 500_000_000).unwrap();
+    state
+}
+
+fn cap(capacity: i64) -> ObligationWithdrawalCapConfig {
+    ObligationWithdrawalCapConfig {
+        config_capacity: capacity,
+        config_interval_length_seconds: INTERVAL_SECONDS,
+    }
+}
+
+#[test]
+fn test_obligation_withdrawal_cap_limits_withdrawals_per_interval() {
+    let mut state = setup();
+    state.reserves[0]
+        .account
+        .borrow_mut()
+        .config
+        .obligation_deposit_withdrawal_cap = cap(100_000_000);
+
+    state.withdraw(0, 0, 60_000_000).unwrap();
+    assert!(state.withdraw(0, 0, 50_000_000).is_err());
+    state.withdraw(0, 0, 40_000_000).unwrap();
+
+    // Other owners have their own allowance in the same reserve
+    state.withdraw(0, 2, 100_000_000).unwrap();
+
+    state.advance_slots(INTERVAL_SLOTS);
+    state.withdraw(0, 0, 100_000_000).unwrap();
+}
+
+#[test]
+fn test_obligation_withdrawal_cap_is_shared_by_obligations_of_owner() {
+    let mut state = setup();
+    let owner = state.obligations[0].borrow().owner;
+    state.obligations[2].borrow_mut().owner = owner;
+    state.reserves[0]
+        .account
+        .borrow_mut()
+        .config
+        .obligation_deposit_withdrawal_cap = cap(100_000_000);
+
+    state.withdraw(0, 0, 60_000_000).unwrap();
+    assert!(state.withdraw(0, 2, 50_000_000).is_err());
+    state.withdraw(0, 2, 40_000_000).unwrap();
+    assert!(state.withdraw(0, 0, 1).is_err());
+}
+
+#[test]
+fn test_obligation_withdrawal_cap_survives_cleared_position() {
+    let mut state = setup();
+    let reserve_pk = state.reserves[0].account.key;
+    let owner = state.obligations[2].borrow().owner;
+    state.reserves[0]
+        .account
+        .borrow_mut()
+        .config
+        .obligation_deposit_withdrawal_cap = cap(600_000_000);
+
+    state.withdraw(0, 2, 500_000_000).unwrap();
+    assert!(state
+        .obligation(2)
+        .find_collateral_in_deposits(reserve_pk)
+        .is_err());
+
+    state.deposit(0, 2, 50_000_000).unwrap();
+    assert_eq!(
+        state.owner_withdrawal_caps[&(reserve_pk, owner)]
+            .deposit_withdrawal_cap
+            .current_total,
+        450_000_000
+    );
+    state.withdraw(0, 2, 50_000_000).unwrap();
+    assert_eq!(
+        state.owner_withdrawal_caps[&(reserve_pk, owner)]
+            .deposit_withdrawal_cap
+            .current_total,
+        500_000_000
+    );
+}
+
+#[test]
+fn test_obligation_debt_withdrawal_cap_survives_repaid_position() {
+    let mut state = setup();
+    let reserve_pk = state.reserves[0].account.key;
+    state.reserves[0]
+        .account
+        .borrow_mut()
+        .config
+        .obligation_debt_withdrawal_cap = cap(100_000_000);
+
+    state.borrow(0, 1, 50_000_000).unwrap();
+    state.advance_slots(INTERVAL_SLOTS);
+    state.borrow(0, 1, 80_000_000).unwrap();
+    state.repay(0, 1, u64::MAX).unwrap();
+    assert!(state
+        .obligation(1)
+        .find_liquidity_in_borrows(reserve_pk)
+        .is_err());
+
+    // Repaying the debt borrowed before the interval still offsets it, but not past the
+    // borrows of the interval
+    state.borrow(0, 1, 99_000_000).unwrap();
+    assert!(state.borrow(0, 1, 90_000_000).is_err());
+}
+
+#[test]
+fn test_obligation_withdrawal_cap_is_offset_by_deposits() {
+    let mut state = setup();
+    state.reserves[0]
+        .account
+        .borrow_mut()
+        .config
+        .obligation_deposit_withdrawal_cap = cap(100_000_000);
+
+    state.withdraw(0, 0, 100_000_000).unwrap();
+    assert!(state.withdraw(0, 0, 10_000_000).is_err());
+
+    state.deposit(0, 0, 30_000_000).unwrap();
+    state.withdraw(0, 0, 30_000_000).unwrap();
+    assert!(state.withdraw(0, 0, 10_000_000).is_err());
+}
+
+#[test]
+fn test_obligation_debt_withdrawal_cap_limits_borrows_per_interval() {
+    let mut state = setup();
+    state.reserves[0]
+        .account
+        .borrow_mut()
+        .config
+        .obligation_debt_withdrawal_cap = cap(100_000_000);
+
+    state.borrow(0, 1, 80_000_000).unwrap();
+    assert!(state.borrow(0, 1, 30_000_000).is_err());
+
+    state.repay(0, 1, 20_000_000).unwrap();
+    state.borrow(0, 1, 30_000_000).unwrap();
+    assert!(state.borrow(0, 1, 20_000_000).is_err());
+
+    // The borrow fee counts towards the cap
+    state.advance_slots(INTERVAL_SLOTS);
+    assert!(state.borrow(0, 1, 100_000_000).is_err());
+    state.borrow(0, 1, 99_000_000).unwrap();
+}
+
+#[test]
+fn test_obligation_withdrawal_cap_disabled_without_interval() {
+    let mut state = setup();
+    state.reserves[0]
+        .account
+        .borrow_mut()
+        .config
+        .obligation_debt_withdrawal_cap = ObligationWithdrawalCapConfig {
+        config_capacity: 0,
+        config_interval_length_seconds: 0,
+    };
+
+    state.borrow(0, 1, 500_000_000).unwrap();
+    state.withdraw(0, 0, 400_000_000).unwrap();
+}
+
+#[test]
+fn test_obligation_withdrawal_cap_tightening() {
+    let disabled = ObligationWithdrawalCapConfig::default();
+    let base = cap(100);
+
+    assert!(base.is_tighter_than(&disabled));
+    assert!(cap(50).is_tighter_than(&base));
+    assert!(base.is_tighter_than(&base));
+    assert!(!cap(150).is_tighter_than(&base));
+    assert!(!disabled.is_tighter_than(&base));
+
+    let shorter_interval = ObligationWithdrawalCapConfig {
+        config_capacity: 100,
+        config_interval_length_seconds: INTERVAL_SECONDS / 2,
+    };
+    assert!(!shorter_interval.is_tighter_than(&base));
+}
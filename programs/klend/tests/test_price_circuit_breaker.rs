@@ -0,0 +1,167 @@
+mod common;
+
+use common::{LendingTestState, ReserveSpec};
+use kamino_lending::{
+    fraction::Fraction, lending_market::lending_operations, LendingError, PriceStatusFlags,
+};
+
+const WINDOW_SECONDS: u64 = 3_600;
+const SLOTS_PER_WINDOW: u64 = WINDOW_SECONDS * 1000 / common::MS_PER_SLOT;
+
+fn setup() -> LendingTestState {
+    let spec = ReserveSpec {
+        decimals: 6,
+        price: Fraction::ONE,
+        loan_to_value_pct: 80,
+        liquidation_threshold_pct: 85,
+        borrow_factor_pct: 100,
+    };
+    let mut state = LendingTestState::new(&[spec.clone(), spec], 2);
+    {
+        let token_info = &mut state.reserves[0].account.borrow_mut().config.token_info;
+        token_info.price_circuit_breaker_max_change_bps = 1_000;
+        token_info.price_circuit_breaker_window_seconds = WINDOW_SECONDS;
+    }
+
+    state.deposit(0, 0, 1_000_000_000).unwrap();
+    state.deposit(1, 1, 1_000_000_000).unwrap();
+    state.borrow(1, 0, 100_000_000).unwrap();
+    state
+}
+
+fn price_status(state: &LendingTestState, reserve: usize) -> PriceStatusFlags {
+    state.reserves[reserve]
+        .account
+        .borrow()
+        .last_update
+        .get_price_status()
+}
+
+fn is_tripped(state: &LendingTestState, reserve: usize) -> bool {
+    state.reserves[reserve]
+        .account
+        .borrow()
+        .liquidity
+        .is_price_circuit_breaker_tripped()
+}
+
+fn trip(state: &mut LendingTestState) {
+    state.advance_slots(100);
+    state.set_price(0, Fraction::from_num(1.2));
+    state.refresh_reserves().unwrap();
+    assert!(is_tripped(state, 0));
+}
+
+#[test]
+fn test_circuit_breaker_blocks_borrows_and_withdrawals() {
+    let mut state = setup();
+
+    state.advance_slots(100);
+    state.set_price(0, Fraction::from_num(1.05));
+    state.refresh_reserves().unwrap();
+    assert_eq!(price_status(&state, 0), PriceStatusFlags::ALL_CHECKS);
+
+    trip(&mut state);
+    // A tripped breaker does not degrade the price status of the reserve
+    assert_eq!(price_status(&state, 0), PriceStatusFlags::ALL_CHECKS);
+
+    // Borrowing against the tripped collateral and withdrawing it are both rejected
+    assert_eq!(
+        state.deep_clone().borrow(1, 0, 1_000_000).unwrap_err(),
+        LendingError::PriceCircuitBreakerTripped.into()
+    );
+    assert_eq!(
+        state.deep_clone().withdraw(0, 0, 1_000_000).unwrap_err(),
+        LendingError::PriceCircuitBreakerTripped.into()
+    );
+
+    // The breaker stays tripped on later refreshes, even once the price settles
+    state.advance_slots(SLOTS_PER_WINDOW * 2);
+    state.refresh_reserves().unwrap();
+    assert!(state.deep_clone().borrow(1, 0, 1_000_000).is_err());
+
+    lending_operations::clear_price_circuit_breaker(&mut state.reserves[0].account.borrow_mut());
+    assert_eq!(
+        state.reserves[0]
+            .account
+            .borrow()
+            .liquidity
+            .price_circuit_breaker_tripped_ts,
+        0
+    );
+    state.borrow(1, 0, 1_000_000).unwrap();
+    state.withdraw(0, 0, 1_000_000).unwrap();
+    assert_eq!(price_status(&state, 0), PriceStatusFlags::ALL_CHECKS);
+}
+
+#[test]
+fn test_circuit_breaker_ignores_moves_outside_window() {
+    let mut state = setup();
+
+    state.advance_slots(SLOTS_PER_WINDOW + 10);
+    state.set_price(0, Fraction::from_num(1.5));
+    state.refresh_reserves().unwrap();
+    assert_eq!(price_status(&state, 0), PriceStatusFlags::ALL_CHECKS);
+
+    state.advance_slots(1);
+    state.set_price(0, Fraction::from_num(1.3));
+    state.refresh_reserves().unwrap();
+    assert!(is_tripped(&state, 0));
+    assert!(!is_tripped(&state, 1));
+}
+
+#[test]
+fn test_circuit_breaker_catches_move_split_across_refreshes() {
+    let mut state = setup();
+
+    // Each step stays under the 10% limit, the move since the start of the window does not
+    for price in [1.04, 1.08, 1.12] {
+        state.advance_slots(100);
+        state.set_price(0, Fraction::from_num(price));
+        state.refresh_reserves().unwrap();
+    }
+    assert!(is_tripped(&state, 0));
+    assert_eq!(
+        state.deep_clone().borrow(1, 0, 1_000_000).unwrap_err(),
+        LendingError::PriceCircuitBreakerTripped.into()
+    );
+}
+
+#[test]
+fn test_circuit_breaker_window_restarts_once_expired() {
+    let mut state = setup();
+
+    state.advance_slots(100);
+    state.set_price(0, Fraction::from_num(1.08));
+    state.refresh_reserves().unwrap();
+
+    // Once expired, the window restarts from the last price and the move since setup is no
+    // longer measured
+    state.advance_slots(SLOTS_PER_WINDOW);
+    state.set_price(0, Fraction::from_num(1.16));
+    state.refresh_reserves().unwrap();
+    state.advance_slots(100);
+    state.set_price(0, Fraction::from_num(1.18));
+    state.refresh_reserves().unwrap();
+    assert!(!is_tripped(&state, 0));
+
+    // Measured against the price starting the new window, not the last refresh
+    state.advance_slots(100);
+    state.set_price(0, Fraction::from_num(1.28));
+    state.refresh_reserves().unwrap();
+    assert!(is_tripped(&state, 0));
+}
+
+#[test]
+fn test_circuit_breaker_allows_deposits_and_repays() {
+    let mut state = setup();
+    trip(&mut state);
+
+    state.repay(1, 0, 10_000_000).unwrap();
+    state.deposit(0, 0, 10_000_000).unwrap();
+    state.deposit(1, 1, 10_000_000).unwrap();
+
+    // Obligations that do not touch the tripped reserve are unaffected
+    state.borrow(1, 1, 1_000_000).unwrap();
+    state.withdraw(1, 1, 1_000_000).unwrap();
+}
@@ -0,0 +1,92 @@
+use kamino_lending::{
+    lending_market::lending_operations::update_reserve_config, state::ReserveStatus, Reserve,
+    UpdateConfigMode,
+};
+
+fn test_reserve() -> Reserve {
+    let mut reserve = Reserve::default();
+    reserve.config.loan_to_value_pct = 70;
+    reserve.config.deposit_limit = 1_000_000;
+    reserve.config.borrow_limit = 500_000;
+    reserve.config.utilization_limit_block_borrowing_above_pct = 90;
+    reserve
+}
+
+#[test]
+fn test_risk_reducing_updates() {
+    let mut reserve = test_reserve();
+
+    update_reserve_config(
+        &mut reserve,
+        UpdateConfigMode::UpdateLoanToValuePct,
+        &[60],
+        true,
+    )
+    .unwrap();
+    update_reserve_config(
+        &mut reserve,
+        UpdateConfigMode::UpdateDepositLimit,
+        &500_000_u64.to_le_bytes(),
+        true,
+    )
+    .unwrap();
+    update_reserve_config(
+        &mut reserve,
+        UpdateConfigMode::UpdateBorrowLimit,
+        &0_u64.to_le_bytes(),
+        true,
+    )
+    .unwrap();
+    update_reserve_config(
+        &mut reserve,
+        UpdateConfigMode::UpdateBlockBorrowingAboveUtilizationPct,
+        &[80],
+        true,
+    )
+    .unwrap();
+    update_reserve_config(
+        &mut reserve,
+        UpdateConfigMode::UpdateReserveStatus,
+        &[ReserveStatus::Obsolete as u8],
+        true,
+    )
+    .unwrap();
+
+    assert_eq!(reserve.config.loan_to_value_pct, 60);
+    assert_eq!(reserve.config.deposit_limit, 500_000);
+    assert_eq!(reserve.config.borrow_limit, 0);
+    assert_eq!(
+        reserve.config.utilization_limit_block_borrowing_above_pct,
+        80
+    );
+    assert_eq!(reserve.config.status(), ReserveStatus::Obsolete);
+}
+
+#[test]
+fn test_risk_increasing_updates_are_refused() {
+    let cases: [(UpdateConfigMode, Vec<u8>); 5] = [
+        (UpdateConfigMode::UpdateLoanToValuePct, vec![75]),
+        (
+            UpdateConfigMode::UpdateBorrowLimit,
+            600_000_u64.to_le_bytes().to_vec(),
+        ),
+        // Zero disables the utilization limit
+        (
+            UpdateConfigMode::UpdateBlockBorrowingAboveUtilizationPct,
+            vec![0],
+        ),
+        (
+            UpdateConfigMode::UpdateReserveStatus,
+            vec![ReserveStatus::Hidden as u8],
+        ),
+        (UpdateConfigMode::UpdateProtocolTakeRate, vec![5]),
+    ];
+
+    for (mode, value) in cases {
+        let mut reserve = test_reserve();
+        assert!(update_reserve_config(&mut reserve, mode, &value, true).is_err());
+
+        let mut reserve = test_reserve();
+        update_reserve_config(&mut reserve, mode, &value, false).unwrap();
+    }
+}
@@ -0,0 +1,363 @@
+mod common;
+
+use anchor_lang::{prelude::Pubkey, Result};
+use common::{LendingTestState, ReserveSpec};
+use kamino_lending::{
+    fraction::Fraction,
+    lending_market::lending_operations,
+    utils::{MAX_WITHDRAW_QUEUE_TICKETS, MAX_WITHDRAW_TICKETS_PER_OWNER},
+    DepositLiquidityResult, LendingError, WithdrawQueue,
+};
+
+fn setup() -> LendingTestState {
+    let spec = ReserveSpec {
+        decimals: 6,
+        price: Fraction::ONE,
+        loan_to_value_pct: 80,
+        liquidation_threshold_pct: 85,
+        borrow_factor_pct: 100,
+    };
+    let mut state = LendingTestState::new(&[spec.clone(), spec], 1);
+    state.deposit(1, 0, 2_000_000_000).unwrap();
+    state
+}
+
+fn deposit_liquidity(state: &mut LendingTestState, amount: u64) -> u64 {
+    state.refresh_reserves().unwrap();
+    let DepositLiquidityResult {
+        collateral_amount, ..
+    } = lending_operations::deposit_reserve_liquidity(
+        &mut state.reserves[0].account.borrow_mut(),
+        &state.clock,
+        amount,
+    )
+    .unwrap();
+    state.reserves[0].liquidity_vault += amount;
+    collateral_amount
+}
+
+fn enqueue(
+    state: &mut LendingTestState,
+    withdraw_queue: &mut WithdrawQueue,
+    owner: Pubkey,
+    collateral_amount: u64,
+) -> Result<u64> {
+    state.refresh_reserves()?;
+    lending_operations::enqueue_withdrawal(
+        &state.lending_market,
+        &mut state.reserves[0].account.borrow_mut(),
+        withdraw_queue,
+        owner,
+        collateral_amount,
+        &state.clock,
+    )
+}
+
+fn claim(
+    state: &mut LendingTestState,
+    withdraw_queue: &mut WithdrawQueue,
+    owner: Pubkey,
+) -> Result<u64> {
+    state.refresh_reserves()?;
+    let claimed = lending_operations::claim_withdrawals(
+        &mut state.reserves[0].account.borrow_mut(),
+        withdraw_queue,
+        owner,
+        &state.clock,
+    )?;
+    state.reserves[0].liquidity_vault -= claimed;
+    Ok(claimed)
+}
+
+fn fill(state: &LendingTestState, withdraw_queue: &mut WithdrawQueue) -> u64 {
+    lending_operations::fill_withdraw_queue(
+        &mut state.reserves[0].account.borrow_mut(),
+        withdraw_queue,
+        u64::try_from(state.clock.unix_timestamp).unwrap(),
+    )
+    .unwrap()
+}
+
+fn available_amount(state: &LendingTestState) -> u64 {
+    state.reserves[0]
+        .account
+        .borrow()
+        .liquidity
+        .available_amount
+}
+
+#[test]
+fn test_withdraw_queue_fills_tickets_in_fifo_order() {
+    let mut state = setup();
+    let first = Pubkey::new_unique();
+    let second = Pubkey::new_unique();
+    let mut withdraw_queue = WithdrawQueue::default();
+
+    deposit_liquidity(&mut state, 1_000_000_000);
+    deposit_liquidity(&mut state, 200_000_000);
+    state.borrow(0, 0, 1_000_000_000).unwrap();
+    let initially_available = available_amount(&state);
+    assert!(initially_available < 300_000_000);
+
+    assert_eq!(
+        enqueue(&mut state, &mut withdraw_queue, first, 600_000_000).unwrap(),
+        0
+    );
+    assert_eq!(
+        enqueue(&mut state, &mut withdraw_queue, second, 100_000_000).unwrap(),
+        1
+    );
+    assert_eq!(
+        state.reserves[0]
+            .account
+            .borrow()
+            .withdraw_queue_collateral_amount,
+        700_000_000
+    );
+
+    // Queued withdrawals have priority over new borrows
+    assert_eq!(
+        state.reserves[0]
+            .account
+            .borrow()
+            .unreserved_available_liquidity(),
+        0
+    );
+    assert!(state.borrow(0, 0, 1_000_000).is_err());
+
+    let claimed = claim(&mut state, &mut withdraw_queue, first).unwrap();
+    assert_eq!(claimed, initially_available);
+    assert_eq!(available_amount(&state), 0);
+    assert_eq!(withdraw_queue.tickets()[0].owner, first);
+    assert!(withdraw_queue.tickets()[0].collateral_amount > 0);
+    assert_eq!(withdraw_queue.tickets()[1].collateral_amount, 100_000_000);
+
+    // Repaid liquidity fills the head of the queue before the second ticket
+    state.repay(0, 0, 700_000_000).unwrap();
+    let filled = fill(&state, &mut withdraw_queue);
+    assert!(filled > 0);
+    assert_eq!(withdraw_queue.tickets()[0].collateral_amount, 0);
+    assert!(withdraw_queue.tickets()[1].filled_liquidity_amount >= 100_000_000);
+    let claimed = claim(&mut state, &mut withdraw_queue, second).unwrap();
+    assert!(claimed >= 100_000_000);
+    assert_eq!(withdraw_queue.tickets_count, 1);
+    assert_eq!(withdraw_queue.tickets()[0].owner, first);
+    assert_eq!(withdraw_queue.tickets()[0].collateral_amount, 0);
+
+    let first_filled = withdraw_queue.tickets()[0].filled_liquidity_amount;
+    assert_eq!(withdraw_queue.filled_liquidity_amount, first_filled);
+    assert_eq!(
+        claim(&mut state, &mut withdraw_queue, first).unwrap(),
+        first_filled
+    );
+    assert_eq!(withdraw_queue.tickets_count, 0);
+    assert_eq!(withdraw_queue.queued_collateral_amount, 0);
+    assert_eq!(
+        state.reserves[0]
+            .account
+            .borrow()
+            .withdraw_queue_collateral_amount,
+        0
+    );
+
+    // Once the queue is drained the remaining liquidity is free again
+    state.borrow(0, 0, 1_000_000).unwrap();
+}
+
+#[test]
+fn test_withdraw_queue_cancel_returns_unfilled_collateral() {
+    let mut state = setup();
+    let owner = Pubkey::new_unique();
+    let mut withdraw_queue = WithdrawQueue::default();
+
+    deposit_liquidity(&mut state, 1_000_000_000);
+    state.borrow(0, 0, 800_000_000).unwrap();
+
+    let ticket_id = enqueue(&mut state, &mut withdraw_queue, owner, 500_000_000).unwrap();
+    assert!(lending_operations::cancel_withdrawal(
+        &mut state.reserves[0].account.borrow_mut(),
+        &mut withdraw_queue,
+        Pubkey::new_unique(),
+        ticket_id,
+    )
+    .is_err());
+
+    claim(&mut state, &mut withdraw_queue, owner).unwrap();
+    let unfilled = withdraw_queue.tickets()[0].collateral_amount;
+    assert!(unfilled > 0 && unfilled < 500_000_000);
+
+    let cancelled = lending_operations::cancel_withdrawal(
+        &mut state.reserves[0].account.borrow_mut(),
+        &mut withdraw_queue,
+        owner,
+        ticket_id,
+    )
+    .unwrap();
+    assert_eq!(cancelled, unfilled);
+    assert_eq!(withdraw_queue.tickets_count, 0);
+    assert_eq!(
+        state.reserves[0]
+            .account
+            .borrow()
+            .withdraw_queue_collateral_amount,
+        0
+    );
+    assert!(claim(&mut state, &mut withdraw_queue, owner).is_err());
+}
+
+#[test]
+fn test_withdraw_queue_is_bounded() {
+    let mut state = setup();
+    let owner = Pubkey::new_unique();
+    let mut withdraw_queue = WithdrawQueue::default();
+    deposit_liquidity(&mut state, 1_000_000_000);
+    state.borrow(0, 0, 800_000_000).unwrap();
+
+    enqueue(&mut state, &mut withdraw_queue, owner, 300_000_000).unwrap();
+    for _ in 1..MAX_WITHDRAW_QUEUE_TICKETS {
+        enqueue(
+            &mut state,
+            &mut withdraw_queue,
+            Pubkey::new_unique(),
+            1_000_000,
+        )
+        .unwrap();
+    }
+    assert_eq!(
+        enqueue(
+            &mut state,
+            &mut withdraw_queue,
+            Pubkey::new_unique(),
+            1_000_000
+        )
+        .unwrap_err(),
+        LendingError::WithdrawQueueFull.into()
+    );
+    assert!(enqueue(&mut state, &mut WithdrawQueue::default(), owner, 0).is_err());
+}
+
+#[test]
+fn test_withdraw_queue_limits_tickets_per_owner() {
+    let mut state = setup();
+    let owner = Pubkey::new_unique();
+    let mut withdraw_queue = WithdrawQueue::default();
+    deposit_liquidity(&mut state, 1_000_000_000);
+    state.borrow(0, 0, 800_000_000).unwrap();
+
+    enqueue(&mut state, &mut withdraw_queue, owner, 300_000_000).unwrap();
+    for _ in 1..MAX_WITHDRAW_TICKETS_PER_OWNER {
+        enqueue(&mut state, &mut withdraw_queue, owner, 1_000_000).unwrap();
+    }
+    assert_eq!(
+        enqueue(&mut state, &mut withdraw_queue, owner, 1_000_000).unwrap_err(),
+        LendingError::WithdrawTicketsPerOwnerExceeded.into()
+    );
+
+    // Other owners still get a slot, and a cancelled ticket frees one for its owner
+    enqueue(
+        &mut state,
+        &mut withdraw_queue,
+        Pubkey::new_unique(),
+        1_000_000,
+    )
+    .unwrap();
+    let ticket_id = withdraw_queue.tickets()[1].id;
+    lending_operations::cancel_withdrawal(
+        &mut state.reserves[0].account.borrow_mut(),
+        &mut withdraw_queue,
+        owner,
+        ticket_id,
+    )
+    .unwrap();
+    enqueue(&mut state, &mut withdraw_queue, owner, 1_000_000).unwrap();
+}
+
+#[test]
+fn test_withdraw_queue_liquidity_is_not_redeemed_as_fees() {
+    let mut state = setup();
+    let owner = Pubkey::new_unique();
+    let mut withdraw_queue = WithdrawQueue::default();
+    deposit_liquidity(&mut state, 1_000_000_000);
+    state.borrow(0, 0, 900_000_000).unwrap();
+    enqueue(&mut state, &mut withdraw_queue, owner, 600_000_000).unwrap();
+
+    let mut reserve = state.reserves[0].account.borrow_mut();
+    reserve.liquidity.accumulated_protocol_fees_sf = Fraction::from(50_000_000u64).to_bits();
+    assert_eq!(reserve.unreserved_available_liquidity(), 0);
+    assert_eq!(reserve.calculate_redeem_fees(), 0);
+}
+
+#[test]
+fn test_withdraw_queue_rejects_dust_and_covered_tickets() {
+    let mut state = setup();
+    let owner = Pubkey::new_unique();
+    let mut withdraw_queue = WithdrawQueue::default();
+    deposit_liquidity(&mut state, 1_000_000_000);
+
+    // The available liquidity covers the withdrawal, it has to be redeemed directly
+    assert!(enqueue(&mut state, &mut withdraw_queue, owner, 100_000_000).is_err());
+
+    state.borrow(0, 0, 900_000_000).unwrap();
+    let min_ticket_amount = state.lending_market.min_initial_deposit_amount;
+    assert!(enqueue(
+        &mut state,
+        &mut withdraw_queue,
+        owner,
+        min_ticket_amount / 2
+    )
+    .is_err());
+    assert_eq!(withdraw_queue.tickets_count, 0);
+
+    enqueue(&mut state, &mut withdraw_queue, owner, 600_000_000).unwrap();
+    enqueue(
+        &mut state,
+        &mut withdraw_queue,
+        owner,
+        min_ticket_amount * 2,
+    )
+    .unwrap();
+    assert_eq!(withdraw_queue.tickets_count, 2);
+}
+
+#[test]
+fn test_withdraw_queue_fills_within_withdrawal_cap() {
+    let mut state = setup();
+    let owner = Pubkey::new_unique();
+    let mut withdraw_queue = WithdrawQueue::default();
+    deposit_liquidity(&mut state, 1_000_000_000);
+    state.borrow(0, 0, 900_000_000).unwrap();
+
+    enqueue(&mut state, &mut withdraw_queue, owner, 600_000_000).unwrap();
+    {
+        let mut reserve = state.reserves[0].account.borrow_mut();
+        reserve.config.deposit_withdrawal_cap.config_capacity = 50_000_000;
+        reserve
+            .config
+            .deposit_withdrawal_cap
+            .config_interval_length_seconds = 3_600;
+        reserve.config.deposit_withdrawal_cap.current_total = 0;
+        reserve
+            .config
+            .deposit_withdrawal_cap
+            .last_interval_start_timestamp = u64::try_from(state.clock.unix_timestamp).unwrap();
+    }
+
+    state.repay(0, 0, 500_000_000).unwrap();
+    let filled = fill(&state, &mut withdraw_queue);
+    assert!(filled > 0 && filled <= 50_000_000);
+    assert!(
+        state.reserves[0]
+            .account
+            .borrow()
+            .config
+            .deposit_withdrawal_cap
+            .current_total
+            <= 50_000_000
+    );
+    assert!(withdraw_queue.tickets()[0].collateral_amount > 0);
+
+    // Nothing more is filled until the cap interval rolls over
+    assert_eq!(fill(&state, &mut withdraw_queue), 0);
+    state.clock.unix_timestamp += 3_600;
+    assert!(fill(&state, &mut withdraw_queue) > 0);
+}
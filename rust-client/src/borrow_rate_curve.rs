@@ -0,0 +1,281 @@
+use std::fmt::Write;
+
+use anyhow::{anyhow, bail, Result};
+use klend::{
+    fraction::{Fraction, FractionExtra},
+    utils::{
+        borrow_rate_curve::{BorrowRateCurve, CurvePoint, MAX_UTILIZATION_RATE_BPS},
+        SLOTS_PER_YEAR,
+    },
+};
+
+pub const MAX_CURVE_POINTS: usize = 11;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResampledCurve {
+    pub curve: BorrowRateCurve,
+    pub max_error_bps: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CurveRow {
+    pub utilization_rate_bps: u32,
+    pub borrow_apr: f64,
+    pub borrow_apy: f64,
+    pub supply_apr: f64,
+    pub supply_apy: f64,
+}
+
+/// Two-slope curve: `base_rate_bps` at 0%, `optimal_rate_bps` at the kink and `max_rate_bps` at 100%.
+pub fn kinked_curve(
+    base_rate_bps: u32,
+    optimal_utilization_rate_bps: u32,
+    optimal_rate_bps: u32,
+    max_rate_bps: u32,
+) -> Result<BorrowRateCurve> {
+    from_points(&[
+        CurvePoint::new(0, base_rate_bps),
+        CurvePoint::new(optimal_utilization_rate_bps, optimal_rate_bps),
+        CurvePoint::new(MAX_UTILIZATION_RATE_BPS, max_rate_bps),
+    ])
+}
+
+pub fn three_slope_curve(
+    base_rate_bps: u32,
+    first_kink: CurvePoint,
+    second_kink: CurvePoint,
+    max_rate_bps: u32,
+) -> Result<BorrowRateCurve> {
+    from_points(&[
+        CurvePoint::new(0, base_rate_bps),
+        first_kink,
+        second_kink,
+        CurvePoint::new(MAX_UTILIZATION_RATE_BPS, max_rate_bps),
+    ])
+}
+
+/// Builds a curve going through `(utilization_rate_bps, borrow APY)` targets, converting each APY
+/// to the APR the program compounds per slot. Tables with more than 11 entries are resampled.
+pub fn curve_from_target_apys(
+    targets: &[(u32, f64)],
+    max_error_bps: f64,
+) -> Result<ResampledCurve> {
+    let points = targets
+        .iter()
+        .map(|(utilization_rate_bps, apy)| (*utilization_rate_bps, apy_to_apr(*apy) * 10_000.0))
+        .collect::<Vec<_>>();
+
+    match points.first() {
+        Some((0, _)) => {}
+        _ => bail!("Target table must start at 0% utilization"),
+    }
+    match points.last() {
+        Some((MAX_UTILIZATION_RATE_BPS, _)) => {}
+        _ => bail!("Target table must end at 100% utilization"),
+    }
+    if points.windows(2).any(|pts| pts[1].0 <= pts[0].0) {
+        bail!("Target table must be sorted by utilization rate");
+    }
+
+    resample_curve(
+        |utilization_rate_bps| {
+            let end = points
+                .iter()
+                .position(|(u, _)| *u >= utilization_rate_bps)
+                .unwrap();
+            let (end_u, end_rate) = points[end];
+            if end_u == utilization_rate_bps {
+                return end_rate;
+            }
+            let (start_u, start_rate) = points[end - 1];
+            start_rate
+                + (end_rate - start_rate) * f64::from(utilization_rate_bps - start_u)
+                    / f64::from(end_u - start_u)
+        },
+        max_error_bps,
+    )
+}
+
+/// Approximates an arbitrary non-decreasing `borrow_rate_bps(utilization_rate_bps)` function with
+/// at most 11 points, failing if the on-chain interpolation deviates by more than `max_error_bps`
+/// anywhere on the 0-100% range.
+pub fn resample_curve(
+    borrow_rate_bps: impl Fn(u32) -> f64,
+    max_error_bps: f64,
+) -> Result<ResampledCurve> {
+    let samples = (0..=MAX_UTILIZATION_RATE_BPS)
+        .map(borrow_rate_bps)
+        .collect::<Vec<_>>();
+
+    if samples.iter().any(|rate| !rate.is_finite() || *rate < 0.0) {
+        bail!("Borrow rate must be finite and positive over the whole utilization range");
+    }
+    if samples.windows(2).any(|rates| rates[1] < rates[0]) {
+        bail!("Borrow rate must be non-decreasing in utilization");
+    }
+    if samples[samples.len() - 1].round() > f64::from(u32::MAX) {
+        bail!("Borrow rate does not fit in a curve point");
+    }
+
+    let mut knots = vec![0, MAX_UTILIZATION_RATE_BPS];
+    while knots.len() < MAX_CURVE_POINTS {
+        let (worst_utilization, worst_error) = (0..=MAX_UTILIZATION_RATE_BPS)
+            .map(|u| (u, (interpolate(&knots, &samples, u) - samples[u as usize]).abs()))
+            .fold((0, 0.0), |worst, candidate| {
+                if candidate.1 > worst.1 {
+                    candidate
+                } else {
+                    worst
+                }
+            });
+        if worst_error < 0.5 {
+            break;
+        }
+        let position = knots.partition_point(|knot| *knot < worst_utilization);
+        knots.insert(position, worst_utilization);
+    }
+
+    let points = knots
+        .iter()
+        .map(|u| CurvePoint::new(*u, samples[*u as usize].round() as u32))
+        .collect::<Vec<_>>();
+    let curve = from_points(&points)?;
+
+    let mut max_error = 0.0_f64;
+    for (utilization_rate_bps, expected) in samples.iter().enumerate() {
+        let actual = curve_rate_bps(&curve, utilization_rate_bps as u32)?;
+        max_error = max_error.max((actual - expected).abs());
+    }
+    if max_error > max_error_bps {
+        bail!(
+            "Cannot fit the borrow rate curve in {MAX_CURVE_POINTS} points: max error {max_error:.2}bps > {max_error_bps}bps"
+        );
+    }
+
+    Ok(ResampledCurve {
+        curve,
+        max_error_bps: max_error,
+    })
+}
+
+/// Tabulates borrow and supply rates every `step_bps` of utilization, using the same split as
+/// `Reserve::accrue_interest`: the host fixed rate goes to the protocol and the variable part is
+/// shared with suppliers after `protocol_take_rate_pct`.
+pub fn curve_table(
+    curve: &BorrowRateCurve,
+    protocol_take_rate_pct: u8,
+    host_fixed_interest_rate_bps: u16,
+    step_bps: u32,
+) -> Result<Vec<CurveRow>> {
+    if step_bps == 0 {
+        bail!("Step must be greater than 0");
+    }
+    let host_fixed_rate = f64::from(host_fixed_interest_rate_bps) / 10_000.0;
+    let supplier_share = 1.0 - f64::from(protocol_take_rate_pct) / 100.0;
+
+    let mut utilizations = (0..MAX_UTILIZATION_RATE_BPS)
+        .step_by(step_bps as usize)
+        .collect::<Vec<_>>();
+    utilizations.push(MAX_UTILIZATION_RATE_BPS);
+
+    utilizations
+        .into_iter()
+        .map(|utilization_rate_bps| {
+            let variable_rate = curve_rate_bps(curve, utilization_rate_bps)? / 10_000.0;
+            let utilization_rate = f64::from(utilization_rate_bps) / 10_000.0;
+            let borrow_apr = variable_rate + host_fixed_rate;
+            let supply_apr = variable_rate * utilization_rate * supplier_share;
+            Ok(CurveRow {
+                utilization_rate_bps,
+                borrow_apr,
+                borrow_apy: apr_to_apy(borrow_apr),
+                supply_apr,
+                supply_apy: apr_to_apy(supply_apr),
+            })
+        })
+        .collect()
+}
+
+pub fn render_table(rows: &[CurveRow]) -> String {
+    let mut out = format!(
+        "{:>12} {:>12} {:>12} {:>12} {:>12}\n",
+        "Utilization", "Borrow APR", "Borrow APY", "Supply APR", "Supply APY"
+    );
+    for row in rows {
+        writeln!(
+            out,
+            "{:>11.2}% {:>11.2}% {:>11.2}% {:>11.2}% {:>11.2}%",
+            f64::from(row.utilization_rate_bps) / 100.0,
+            row.borrow_apr * 100.0,
+            row.borrow_apy * 100.0,
+            row.supply_apr * 100.0,
+            row.supply_apy * 100.0,
+        )
+        .unwrap();
+    }
+    out
+}
+
+pub fn render_csv(rows: &[CurveRow]) -> String {
+    let mut out = String::from("utilization_rate_bps,borrow_apr,borrow_apy,supply_apr,supply_apy\n");
+    for row in rows {
+        writeln!(
+            out,
+            "{},{:.6},{:.6},{:.6},{:.6}",
+            row.utilization_rate_bps, row.borrow_apr, row.borrow_apy, row.supply_apr, row.supply_apy,
+        )
+        .unwrap();
+    }
+    out
+}
+
+/// Parses `utilization_bps:rate_bps` pairs separated by commas, e.g. `0:100,8000:1500,10000:30000`.
+pub fn parse_curve_points(points: &str) -> Result<BorrowRateCurve> {
+    let points = points
+        .split(',')
+        .map(|point| {
+            let (utilization, rate) = point
+                .trim()
+                .split_once(':')
+                .ok_or_else(|| anyhow!("Invalid curve point {point}, expected utilization_bps:rate_bps"))?;
+            Ok(CurvePoint::new(utilization.parse()?, rate.parse()?))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    from_points(&points)
+}
+
+pub fn apr_to_apy(apr: f64) -> f64 {
+    let slots_per_year = SLOTS_PER_YEAR as f64;
+    ((apr / slots_per_year).ln_1p() * slots_per_year).exp_m1()
+}
+
+pub fn apy_to_apr(apy: f64) -> f64 {
+    let slots_per_year = SLOTS_PER_YEAR as f64;
+    (apy.ln_1p() / slots_per_year).exp_m1() * slots_per_year
+}
+
+fn from_points(points: &[CurvePoint]) -> Result<BorrowRateCurve> {
+    BorrowRateCurve::from_points(points)
+        .map_err(|e| anyhow!("Invalid borrow rate curve {points:?}: {e}"))
+}
+
+fn curve_rate_bps(curve: &BorrowRateCurve, utilization_rate_bps: u32) -> Result<f64> {
+    let rate = curve
+        .get_borrow_rate(Fraction::from_bps(utilization_rate_bps))
+        .map_err(|e| anyhow!("Cannot evaluate borrow rate curve: {e}"))?;
+    Ok(rate.to_num::<f64>() * 10_000.0)
+}
+
+fn interpolate(knots: &[u32], samples: &[f64], utilization_rate_bps: u32) -> f64 {
+    let end = knots.partition_point(|knot| *knot < utilization_rate_bps);
+    let end_u = knots[end];
+    if end_u == utilization_rate_bps {
+        return samples[end_u as usize].round();
+    }
+    let start_u = knots[end - 1];
+    let start_rate = samples[start_u as usize].round();
+    let end_rate = samples[end_u as usize].round();
+    start_rate
+        + (end_rate - start_rate) * f64::from(utilization_rate_bps - start_u)
+            / f64::from(end_u - start_u)
+}
@@ -0,0 +1,279 @@
+use klend::{
+    fraction::{Fraction, FractionExtra},
+    state::liquidation_operations::{
+        calculate_autodeleverage_bonus, calculate_autodeleverage_threshold,
+        check_liquidate_obligation, get_emode_max_liquidation_bonus,
+        select_individual_autodeleverage_reserve_config,
+    },
+    utils::{secs, SECONDS_PER_DAY},
+    LendingMarket, Obligation, Reserve,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeleveragingTrigger {
+    /// Obligation marked with `mark_obligation_for_deleveraging`, deleveraged down to its target LTV.
+    MarkedObligation,
+    /// Collateral reserve above its deposit limit, e.g. an obsolete reserve with a zeroed limit.
+    DepositLimitCrossed,
+    /// Debt reserve above its borrow limit.
+    BorrowLimitCrossed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DeleveragingSchedulePoint {
+    pub timestamp: u64,
+    pub ltv_threshold: Fraction,
+    pub liquidation_bonus: Fraction,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeleveragingSchedule {
+    pub trigger: DeleveragingTrigger,
+    /// End of the margin call period, from which the threshold and bonus start moving.
+    pub deleveraging_start_timestamp: u64,
+    pub user_ltv: Fraction,
+    /// First timestamp at which the obligation can be liquidated at current prices, if ever.
+    pub liquidatable_at: Option<u64>,
+    pub points: Vec<DeleveragingSchedulePoint>,
+}
+
+/// Projects the deleveraging LTV threshold and liquidation bonus of an obligation over time,
+/// for the collateral/debt reserve pair a liquidator would use.
+///
+/// Follows the same precedence as `get_liquidation_params`: an individually marked obligation
+/// first, then the collateral reserve deposit limit, then the debt reserve borrow limit.
+/// Returns `None` when no deleveraging applies. The obligation must be refreshed, prices are
+/// assumed to stay constant.
+pub fn deleveraging_schedule(
+    lending_market: &LendingMarket,
+    obligation: &Obligation,
+    collateral_reserve: &Reserve,
+    debt_reserve: &Reserve,
+    timestamp: u64,
+    step_secs: u64,
+    num_points: usize,
+) -> Option<DeleveragingSchedule> {
+    if !lending_market.is_autodeleverage_enabled() || obligation.deposited_value_sf == 0 {
+        return None;
+    }
+
+    let individual = individual_schedule(
+        lending_market,
+        obligation,
+        collateral_reserve,
+        debt_reserve,
+        timestamp,
+        step_secs,
+        num_points,
+    );
+    // A marked obligation already below its target LTV can still be deleveraged market-wide
+    let mut schedule = individual
+        .clone()
+        .filter(|schedule| schedule.liquidatable_at.is_some())
+        .or_else(|| {
+            market_wide_schedule(
+                DeleveragingTrigger::DepositLimitCrossed,
+                collateral_reserve,
+                obligation,
+                timestamp,
+                step_secs,
+                num_points,
+            )
+        })
+        .or_else(|| {
+            market_wide_schedule(
+                DeleveragingTrigger::BorrowLimitCrossed,
+                debt_reserve,
+                obligation,
+                timestamp,
+                step_secs,
+                num_points,
+            )
+        })
+        .or(individual)?;
+
+    // Already unhealthy obligations are liquidatable right away, regardless of deleveraging
+    if check_liquidate_obligation(
+        lending_market,
+        collateral_reserve,
+        debt_reserve,
+        obligation,
+        None,
+    )
+    .is_some()
+    {
+        schedule.liquidatable_at = Some(timestamp);
+    }
+    Some(schedule)
+}
+
+fn individual_schedule(
+    lending_market: &LendingMarket,
+    obligation: &Obligation,
+    collateral_reserve: &Reserve,
+    debt_reserve: &Reserve,
+    timestamp: u64,
+    step_secs: u64,
+    num_points: usize,
+) -> Option<DeleveragingSchedule> {
+    if !obligation.is_marked_for_deleveraging()
+        || lending_market.individual_autodeleverage_margin_call_period_secs == 0
+    {
+        return None;
+    }
+
+    let deleveraging_start_timestamp = obligation.autodeleverage_margin_call_started_timestamp
+        + lending_market.individual_autodeleverage_margin_call_period_secs;
+    let user_ltv = obligation.loan_to_value();
+    let ltv_threshold = Fraction::from_percent(obligation.autodeleverage_target_ltv_pct);
+    let selected_reserve_config = select_individual_autodeleverage_reserve_config(
+        &collateral_reserve.config,
+        &debt_reserve.config,
+    );
+    let emode_max_liquidation_bonus_bps = get_emode_max_liquidation_bonus(
+        lending_market,
+        &collateral_reserve.config,
+        &debt_reserve.config,
+        obligation,
+    );
+
+    let points = point_timestamps(
+        deleveraging_start_timestamp,
+        timestamp,
+        step_secs,
+        num_points,
+    )
+    .map(|point_timestamp| DeleveragingSchedulePoint {
+        timestamp: point_timestamp,
+        ltv_threshold,
+        liquidation_bonus: calculate_autodeleverage_bonus(
+            selected_reserve_config.deleveraging_bonus_increase_bps_per_day,
+            selected_reserve_config.max_liquidation_bonus_bps,
+            emode_max_liquidation_bonus_bps,
+            days_since(deleveraging_start_timestamp, point_timestamp),
+            &obligation.no_bf_loan_to_value(),
+        ),
+    })
+    .collect();
+
+    Some(DeleveragingSchedule {
+        trigger: DeleveragingTrigger::MarkedObligation,
+        deleveraging_start_timestamp,
+        user_ltv,
+        liquidatable_at: (user_ltv > ltv_threshold).then_some(deleveraging_start_timestamp),
+        points,
+    })
+}
+
+fn market_wide_schedule(
+    trigger: DeleveragingTrigger,
+    autodeleverage_reserve: &Reserve,
+    obligation: &Obligation,
+    timestamp: u64,
+    step_secs: u64,
+    num_points: usize,
+) -> Option<DeleveragingSchedule> {
+    if !autodeleverage_reserve.config.is_autodeleverage_enabled() {
+        return None;
+    }
+    let limit_crossed_timestamp = match trigger {
+        DeleveragingTrigger::DepositLimitCrossed
+            if autodeleverage_reserve.deposit_limit_crossed() =>
+        {
+            autodeleverage_reserve
+                .liquidity
+                .deposit_limit_crossed_timestamp
+        }
+        DeleveragingTrigger::BorrowLimitCrossed
+            if autodeleverage_reserve.borrow_limit_crossed() =>
+        {
+            autodeleverage_reserve
+                .liquidity
+                .borrow_limit_crossed_timestamp
+        }
+        _ => return None,
+    };
+    if limit_crossed_timestamp == 0 {
+        return None;
+    }
+
+    let deleveraging_start_timestamp = limit_crossed_timestamp
+        + autodeleverage_reserve
+            .config
+            .deleveraging_margin_call_period_secs;
+    let user_ltv = obligation.loan_to_value();
+    let threshold_at = |point_timestamp| {
+        calculate_autodeleverage_threshold(
+            autodeleverage_reserve,
+            days_since(deleveraging_start_timestamp, point_timestamp),
+        )
+    };
+
+    let points = point_timestamps(
+        deleveraging_start_timestamp,
+        timestamp,
+        step_secs,
+        num_points,
+    )
+    .map(|point_timestamp| DeleveragingSchedulePoint {
+        timestamp: point_timestamp,
+        ltv_threshold: threshold_at(point_timestamp),
+        liquidation_bonus: calculate_autodeleverage_bonus(
+            autodeleverage_reserve
+                .config
+                .deleveraging_bonus_increase_bps_per_day,
+            autodeleverage_reserve.config.max_liquidation_bonus_bps,
+            u16::MAX,
+            days_since(deleveraging_start_timestamp, point_timestamp),
+            &obligation.no_bf_loan_to_value(),
+        ),
+    })
+    .collect();
+
+    let daily_threshold_decrease = Fraction::from_bps(
+        autodeleverage_reserve
+            .config
+            .deleveraging_threshold_decrease_bps_per_day,
+    );
+    let liquidatable_at = if user_ltv >= threshold_at(deleveraging_start_timestamp) {
+        Some(deleveraging_start_timestamp)
+    } else if daily_threshold_decrease == Fraction::ZERO {
+        None
+    } else {
+        // Closed-form estimate, then settled on the exact second the threshold crosses the LTV
+        let days = (Fraction::ONE - user_ltv) / daily_threshold_decrease;
+        let mut crossing_timestamp =
+            deleveraging_start_timestamp + (days * u128::from(SECONDS_PER_DAY)).to_ceil::<u64>();
+        while user_ltv < threshold_at(crossing_timestamp) {
+            crossing_timestamp += 1;
+        }
+        while crossing_timestamp > deleveraging_start_timestamp
+            && user_ltv >= threshold_at(crossing_timestamp - 1)
+        {
+            crossing_timestamp -= 1;
+        }
+        Some(crossing_timestamp)
+    };
+
+    Some(DeleveragingSchedule {
+        trigger,
+        deleveraging_start_timestamp,
+        user_ltv,
+        liquidatable_at,
+        points,
+    })
+}
+
+fn point_timestamps(
+    deleveraging_start_timestamp: u64,
+    timestamp: u64,
+    step_secs: u64,
+    num_points: usize,
+) -> impl Iterator<Item = u64> {
+    let first_timestamp = deleveraging_start_timestamp.max(timestamp);
+    (0..num_points as u64).map(move |i| first_timestamp + i * step_secs)
+}
+
+fn days_since(deleveraging_start_timestamp: u64, timestamp: u64) -> Fraction {
+    secs::to_days_fractional(timestamp.saturating_sub(deleveraging_start_timestamp))
+}
@@ -0,0 +1,103 @@
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use klend::events::*;
+use solana_sdk::pubkey::Pubkey;
+
+const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+
+#[derive(Debug, Clone)]
+pub enum KlendEvent {
+    Deposit(DepositEvent),
+    Withdraw(WithdrawEvent),
+    Borrow(BorrowEvent),
+    Repay(RepayEvent),
+    Liquidation(LiquidationEvent),
+    FlashLoan(FlashLoanEvent),
+    ReserveConfigUpdated(ReserveConfigUpdated),
+    MarketConfigUpdated(MarketConfigUpdated),
+    SocializeLoss(SocializeLossEvent),
+    ElevationGroupChanged(ElevationGroupChanged),
+    InterestAccrued(InterestAccrued),
+    ObligationOwnerUpdated(ObligationOwnerUpdated),
+}
+
+fn decode<T: AnchorDeserialize>(mut data: &[u8]) -> Result<T> {
+    T::deserialize(&mut data).map_err(|e| anyhow!("Cannot deserialize event: {e}"))
+}
+
+/// Decodes the payload of an event (discriminator followed by the borsh serialized struct).
+/// Returns `None` for payloads that are not klend events.
+pub fn decode_event(data: &[u8]) -> Result<Option<KlendEvent>> {
+    if data.len() < 8 {
+        return Ok(None);
+    }
+    let (discriminator, data) = data.split_at(8);
+    let event = match discriminator {
+        d if d == DepositEvent::DISCRIMINATOR => KlendEvent::Deposit(decode(data)?),
+        d if d == WithdrawEvent::DISCRIMINATOR => KlendEvent::Withdraw(decode(data)?),
+        d if d == BorrowEvent::DISCRIMINATOR => KlendEvent::Borrow(decode(data)?),
+        d if d == RepayEvent::DISCRIMINATOR => KlendEvent::Repay(decode(data)?),
+        d if d == LiquidationEvent::DISCRIMINATOR => KlendEvent::Liquidation(decode(data)?),
+        d if d == FlashLoanEvent::DISCRIMINATOR => KlendEvent::FlashLoan(decode(data)?),
+        d if d == ReserveConfigUpdated::DISCRIMINATOR => {
+            KlendEvent::ReserveConfigUpdated(decode(data)?)
+        }
+        d if d == MarketConfigUpdated::DISCRIMINATOR => {
+            KlendEvent::MarketConfigUpdated(decode(data)?)
+        }
+        d if d == SocializeLossEvent::DISCRIMINATOR => KlendEvent::SocializeLoss(decode(data)?),
+        d if d == ElevationGroupChanged::DISCRIMINATOR => {
+            KlendEvent::ElevationGroupChanged(decode(data)?)
+        }
+        d if d == InterestAccrued::DISCRIMINATOR => KlendEvent::InterestAccrued(decode(data)?),
+        d if d == ObligationOwnerUpdated::DISCRIMINATOR => {
+            KlendEvent::ObligationOwnerUpdated(decode(data)?)
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(event))
+}
+
+/// Extracts the events emitted by the klend program from transaction logs, ignoring `Program data`
+/// lines logged by other programs (including programs invoked by klend through CPI).
+pub fn parse_logs(logs: &[String]) -> Result<Vec<KlendEvent>> {
+    let program_id = klend::ID.to_string();
+    let mut invocation_stack: Vec<String> = Vec::new();
+    let mut events = Vec::new();
+
+    for log in logs {
+        if let Some(data) = log.strip_prefix(PROGRAM_DATA_PREFIX) {
+            if invocation_stack.last() != Some(&program_id) {
+                continue;
+            }
+            let data = STANDARD
+                .decode(data)
+                .map_err(|e| anyhow!("Invalid base64 event data: {e}"))?;
+            if let Some(event) = decode_event(&data)? {
+                events.push(event);
+            }
+            continue;
+        }
+
+        let mut words = log.split_whitespace();
+        if words.next() != Some("Program") {
+            continue;
+        }
+        let (Some(program), Some(status)) = (words.next(), words.next()) else {
+            continue;
+        };
+        if program.parse::<Pubkey>().is_err() {
+            continue;
+        }
+        match status {
+            "invoke" => invocation_stack.push(program.to_string()),
+            "success" | "failed:" => {
+                invocation_stack.pop();
+            }
+            _ => {}
+        }
+    }
+
+    Ok(events)
+}
@@ -1,62 +1,67 @@
-use crate::KlendClient;
+use crate::{klend_instruction, KlendClient};
 use anchor_client::solana_sdk::{
-    instruction::Instruction,
+    instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
-    signature::Signature,
-    sysvar::{instructions::ID as INSTRUCTIONS_ID, Sysvar},
+    sysvar::instructions::ID as INSTRUCTIONS_ID,
 };
-use anchor_lang::prelude::*;
-use anyhow::Result;
-use klend::{self, utils::seeds};
+use klend::{self, utils::seeds, Reserve};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
 
 impl KlendClient {
+    /// The payer signs as the obligation owner or its delegate, the liquidity is always sent
+    /// to `obligation_owner`. `obligation_permission` is only required in permissioned
+    /// markets, see [`KlendClient::obligation_permission_account`], and is passed as a remaining
+    /// account, followed by the withdrawal caps of `obligation_owner` when the reserve caps
+    /// borrows per owner.
     pub fn borrow_obligation_liquidity(
         &self,
         obligation: &Pubkey,
+        obligation_owner: &Pubkey,
         borrow_reserve: &Pubkey,
+        reserve_state: &Reserve,
         liquidity_amount: u64,
-    ) -> Result<Signature> {
-        let obligation_data = self.program().account::<klend::Obligation>(*obligation)?;
-        let lending_market = obligation_data.lending_market;
+        obligation_permission: Option<Pubkey>,
+    ) -> Instruction {
+        let lending_market = reserve_state.lending_market;
+        let borrow_reserve_liquidity_mint = reserve_state.liquidity.mint_pubkey;
+        let token_program = reserve_state.liquidity.token_program;
 
-        let reserve_data = self.program().account::<klend::Reserve>(*borrow_reserve)?;
-        let borrow_reserve_liquidity_mint = reserve_data.liquidity.mint_pubkey;
-        let reserve_source_liquidity = reserve_data.liquidity.supply_vault;
-        let borrow_reserve_liquidity_fee_receiver = reserve_data.liquidity.fee_vault;
-
-        let (lending_market_authority, _) = Pubkey::find_program_address(
-            &[seeds::LENDING_MARKET_AUTH, lending_market.as_ref()],
-            &klend::ID,
-        );
-
-        // Get user's token account for the liquidity
-        let user_destination_liquidity = spl_associated_token_account::get_associated_token_address(
-            &self.payer_pubkey(),
+        // Get the obligation owner's token account for the liquidity
+        let user_destination_liquidity = get_associated_token_address_with_program_id(
+            obligation_owner,
             &borrow_reserve_liquidity_mint,
+            &token_program,
         );
 
         let accounts = klend::accounts::BorrowObligationLiquidity {
             owner: self.payer_pubkey(),
             obligation: *obligation,
             lending_market,
-            lending_market_authority,
+            lending_market_authority: seeds::pda::lending_market_auth(&lending_market),
             borrow_reserve: *borrow_reserve,
             borrow_reserve_liquidity_mint,
-            reserve_source_liquidity,
-            borrow_reserve_liquidity_fee_receiver,
+            reserve_source_liquidity: reserve_state.liquidity.supply_vault,
+            borrow_reserve_liquidity_fee_receiver: reserve_state.liquidity.fee_vault,
             user_destination_liquidity,
             referrer_token_state: None,
-            token_program: anchor_spl::token_interface::ID,
+            token_program,
             instruction_sysvar_account: INSTRUCTIONS_ID,
         };
 
-        let signature = self
-            .program()
-            .request()
-            .accounts(accounts)
-            .args(klend::instruction::BorrowObligationLiquidity { liquidity_amount })
-            .send()?;
-
-        self.send_and_confirm("borrow_obligation_liquidity", signature)
+        let mut ix = klend_instruction(
+            accounts,
+            klend::instruction::BorrowObligationLiquidity { liquidity_amount },
+        );
+        ix.accounts.extend(
+            obligation_permission.map(|obligation_permission| {
+                AccountMeta::new_readonly(obligation_permission, false)
+            }),
+        );
+        ix.accounts.extend(Self::owner_withdrawal_caps_account(
+            borrow_reserve,
+            &reserve_state.config.obligation_debt_withdrawal_cap,
+            obligation_owner,
+        ));
+        ix
     }
-} 
\ No newline at end of file
+}
@@ -0,0 +1,34 @@
+use crate::{
+    instructions::owner::obligation_farm_user_state_accounts, klend_instruction,
+    obligation::ObligationPositions, KlendClient,
+};
+use anchor_client::solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+use anyhow::{bail, Result};
+use klend::Reserve;
+
+impl KlendClient {
+    /// Closes an empty obligation and returns its rent to the owner. `reserves` are the reserves
+    /// the obligation used, the program checks the user states of their farms have no stake
+    /// left, see [`klend::utils::seeds::pda::obligation_farm_user_state`].
+    pub fn close_obligation(
+        &self,
+        obligation: &Pubkey,
+        obligation_state: &ObligationPositions,
+        reserves: &[(Pubkey, Reserve)],
+    ) -> Result<Instruction> {
+        if !obligation_state.view().deposits_empty() || !obligation_state.view().borrows_empty() {
+            bail!("Obligation {obligation} still has deposits or borrows");
+        }
+
+        let accounts = klend::accounts::CloseObligation {
+            owner: self.payer_pubkey(),
+            obligation: *obligation,
+            lending_market: obligation_state.obligation.lending_market,
+        };
+
+        let mut ix = klend_instruction(accounts, klend::instruction::CloseObligation {});
+        ix.accounts
+            .extend(obligation_farm_user_state_accounts(obligation, reserves));
+        Ok(ix)
+    }
+}
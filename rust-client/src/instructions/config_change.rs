@@ -0,0 +1,146 @@
+use crate::{klend_instruction, KlendClient};
+use anchor_client::solana_sdk::{instruction::Instruction, pubkey::Pubkey, system_program};
+use anyhow::{bail, Result};
+use klend::{
+    self, utils::seeds, PendingConfigChange, PendingConfigChangeKind, UpdateConfigMode,
+    UpdateLendingMarketMode, VALUE_BYTE_MAX_ARRAY_LEN_MARKET_UPDATE,
+};
+
+impl KlendClient {
+    /// Applies a reserve config change immediately, signed by the market owner or, for
+    /// risk-reducing changes, by the risk council.
+    pub fn update_reserve_config(
+        &self,
+        lending_market: &Pubkey,
+        reserve: &Pubkey,
+        mode: UpdateConfigMode,
+        value: Vec<u8>,
+    ) -> Instruction {
+        let accounts = klend::accounts::UpdateReserveConfig {
+            signer: self.payer_pubkey(),
+            lending_market: *lending_market,
+            reserve: *reserve,
+        };
+
+        klend_instruction(
+            accounts,
+            klend::instruction::UpdateReserveConfig {
+                mode: mode as u64,
+                value,
+                skip_validation: false,
+            },
+        )
+    }
+
+    pub fn update_lending_market(
+        &self,
+        lending_market: &Pubkey,
+        mode: UpdateLendingMarketMode,
+        value: &[u8],
+    ) -> Result<Instruction> {
+        if value.len() > VALUE_BYTE_MAX_ARRAY_LEN_MARKET_UPDATE {
+            bail!("Lending market update value is {} bytes long", value.len());
+        }
+        let mut padded_value = [0; VALUE_BYTE_MAX_ARRAY_LEN_MARKET_UPDATE];
+        padded_value[..value.len()].copy_from_slice(value);
+
+        let accounts = klend::accounts::UpdateLendingMarket {
+            lending_market_owner: self.payer_pubkey(),
+            lending_market: *lending_market,
+        };
+
+        Ok(klend_instruction(
+            accounts,
+            klend::instruction::UpdateLendingMarket {
+                mode: mode as u64,
+                value: padded_value,
+            },
+        ))
+    }
+
+    /// Queues a reserve config change behind the lending market config timelock.
+    pub fn queue_reserve_config_change(
+        &self,
+        lending_market: &Pubkey,
+        reserve: &Pubkey,
+        mode: UpdateConfigMode,
+        value: Vec<u8>,
+    ) -> Instruction {
+        self.queue_config_change(lending_market, Some(*reserve), mode as u64, value)
+    }
+
+    /// Queues a lending market config change behind the market config timelock.
+    pub fn queue_lending_market_config_change(
+        &self,
+        lending_market: &Pubkey,
+        mode: UpdateLendingMarketMode,
+        value: Vec<u8>,
+    ) -> Instruction {
+        self.queue_config_change(lending_market, None, mode as u64, value)
+    }
+
+    fn queue_config_change(
+        &self,
+        lending_market: &Pubkey,
+        reserve: Option<Pubkey>,
+        mode: u64,
+        value: Vec<u8>,
+    ) -> Instruction {
+        let target = reserve.unwrap_or(*lending_market);
+        let (pending_config_change, _) =
+            seeds::pda::pending_config_change(*lending_market, target, mode);
+
+        let accounts = klend::accounts::QueueConfigChange {
+            lending_market_owner: self.payer_pubkey(),
+            lending_market: *lending_market,
+            reserve,
+            pending_config_change,
+            system_program: system_program::ID,
+        };
+
+        klend_instruction(
+            accounts,
+            klend::instruction::QueueConfigChange { mode, value },
+        )
+    }
+
+    /// Applies a queued config change once its timelock has elapsed. Anyone can execute it.
+    pub fn execute_config_change(
+        &self,
+        pending_config_change: &Pubkey,
+        pending_state: &PendingConfigChange,
+    ) -> Result<Instruction> {
+        let reserve = match pending_state.kind()? {
+            PendingConfigChangeKind::Reserve => Some(pending_state.target),
+            PendingConfigChangeKind::LendingMarket => None,
+        };
+
+        let accounts = klend::accounts::ExecuteConfigChange {
+            lending_market: pending_state.lending_market,
+            reserve,
+            pending_config_change: *pending_config_change,
+            payer: pending_state.payer,
+        };
+
+        Ok(klend_instruction(
+            accounts,
+            klend::instruction::ExecuteConfigChange {},
+        ))
+    }
+
+    /// Drops a queued config change. Signed by the market owner or the risk council.
+    pub fn cancel_config_change(
+        &self,
+        pending_config_change: &Pubkey,
+        pending_state: &PendingConfigChange,
+    ) -> Instruction {
+        let accounts = klend::accounts::CancelConfigChange {
+            authority: self.payer_pubkey(),
+            lending_market: pending_state.lending_market,
+            pending_config_change: *pending_config_change,
+            payer: pending_state.payer,
+        };
+
+        klend_instruction(accounts, klend::instruction::CancelConfigChange {})
+    }
+}
@@ -1,49 +1,79 @@
-use crate::KlendClient;
+use crate::{klend_instruction, obligation::ObligationPositions, KlendClient};
 use anchor_client::solana_sdk::{
-    instruction::Instruction,
+    instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
-    signature::Signature,
-    system_program,
-    sysvar::{rent, Sysvar},
+    rent::Rent,
+    system_instruction, system_program,
+    sysvar::rent,
 };
-use anchor_lang::prelude::*;
-use anchor_spl::{
-    token::Token,
-    token_interface::{Mint, TokenAccount, TokenInterface},
-};
-use anyhow::Result;
+use anyhow::{bail, Result};
 use klend::{
     self,
-    state::{LendingMarket, Reserve, UserMetadata},
-    utils::seeds,
+    utils::{seeds, LENDING_MARKET_SIZE, RESERVE_SIZE},
     InitObligationArgs,
 };
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+pub fn user_metadata_address(owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[seeds::BASE_SEED_USER_METADATA, owner.as_ref()],
+        &klend::ID,
+    )
+    .0
+}
+
+pub fn obligation_address(
+    lending_market: &Pubkey,
+    obligation_owner: &Pubkey,
+    args: &InitObligationArgs,
+    seed1_account: &Pubkey,
+    seed2_account: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            &[args.tag],
+            &[args.id],
+            obligation_owner.as_ref(),
+            lending_market.as_ref(),
+            seed1_account.as_ref(),
+            seed2_account.as_ref(),
+        ],
+        &klend::ID,
+    )
+    .0
+}
 
 impl KlendClient {
-    pub fn init_lending_market(&self, quote_currency: [u8; 32]) -> Result<Signature> {
-        let lending_market = Keypair::new();
-        let (lending_market_authority, _bump) = Pubkey::find_program_address(
-            &[seeds::LENDING_MARKET_AUTH, lending_market.pubkey().as_ref()],
+    /// Creates the lending market account and initializes it, `lending_market` has to sign.
+    pub fn init_lending_market(
+        &self,
+        lending_market: &Pubkey,
+        quote_currency: [u8; 32],
+    ) -> Vec<Instruction> {
+        let space = LENDING_MARKET_SIZE + 8;
+        let create_account = system_instruction::create_account(
+            &self.payer_pubkey(),
+            lending_market,
+            Rent::default().minimum_balance(space),
+            space as u64,
             &klend::ID,
         );
 
         let accounts = klend::accounts::InitLendingMarket {
             lending_market_owner: self.payer_pubkey(),
-            lending_market: lending_market.pubkey(),
-            lending_market_authority,
+            lending_market: *lending_market,
+            lending_market_authority: seeds::pda::lending_market_auth(lending_market),
             system_program: system_program::ID,
             rent: rent::ID,
         };
 
-        let signature = self
-            .program()
-            .request()
-            .accounts(accounts)
-            .args(klend::instruction::InitLendingMarket { quote_currency })
-            .signer(&lending_market)
-            .send()?;
-
-        self.send_and_confirm("init_lending_market", signature)
+        vec![
+            create_account,
+            klend_instruction(
+                accounts,
+                klend::instruction::InitLendingMarket { quote_currency },
+            ),
+        ]
     }
 
     pub fn init_user_metadata(
@@ -51,167 +81,132 @@ impl KlendClient {
         owner: &Pubkey,
         user_lookup_table: Pubkey,
         referrer_user_metadata: Option<Pubkey>,
-    ) -> Result<Signature> {
-        let (user_metadata, _) = Pubkey::find_program_address(
-            &[klend::utils::seeds::BASE_SEED_USER_METADATA, owner.as_ref()],
-            &klend::ID,
-        );
-
-        let mut accounts = klend::accounts::InitUserMetadata {
+    ) -> Instruction {
+        let accounts = klend::accounts::InitUserMetadata {
             owner: *owner,
             fee_payer: self.payer_pubkey(),
-            user_metadata,
-            referrer_user_metadata: None,
+            user_metadata: user_metadata_address(owner),
+            referrer_user_metadata,
             rent: rent::ID,
             system_program: system_program::ID,
         };
 
-        if let Some(referrer) = referrer_user_metadata {
-            accounts.referrer_user_metadata = Some(referrer);
-        }
-
-        let signature = self
-            .program()
-            .request()
-            .accounts(accounts)
-            .args(klend::instruction::InitUserMetadata { user_lookup_table })
-            .send()?;
-
-        self.send_and_confirm("init_user_metadata", signature)
+        klend_instruction(
+            accounts,
+            klend::instruction::InitUserMetadata { user_lookup_table },
+        )
     }
 
+    /// `obligation_permission` is only required in permissioned markets, see
+    /// [`KlendClient::obligation_permission_account`], and is passed as a remaining account.
     pub fn init_obligation(
         &self,
         lending_market: &Pubkey,
         obligation_owner: &Pubkey,
-        tag: u8,
-        id: u8,
+        args: InitObligationArgs,
         seed1_account: &Pubkey,
         seed2_account: &Pubkey,
-    ) -> Result<Signature> {
-        let (owner_user_metadata, _) = Pubkey::find_program_address(
-            &[
-                klend::utils::seeds::BASE_SEED_USER_METADATA,
-                obligation_owner.as_ref(),
-            ],
-            &klend::ID,
-        );
-
-        let (obligation, _) = Pubkey::find_program_address(
-            &[
-                &[tag],
-                &[id],
-                obligation_owner.as_ref(),
-                lending_market.as_ref(),
-                seed1_account.as_ref(),
-                seed2_account.as_ref(),
-            ],
-            &klend::ID,
-        );
-
+        obligation_permission: Option<Pubkey>,
+    ) -> Instruction {
         let accounts = klend::accounts::InitObligation {
             obligation_owner: *obligation_owner,
             fee_payer: self.payer_pubkey(),
-            obligation,
+            obligation: obligation_address(
+                lending_market,
+                obligation_owner,
+                &args,
+                seed1_account,
+                seed2_account,
+            ),
             lending_market: *lending_market,
             seed1_account: *seed1_account,
             seed2_account: *seed2_account,
-            owner_user_metadata,
+            owner_user_metadata: user_metadata_address(obligation_owner),
             rent: rent::ID,
             system_program: system_program::ID,
         };
 
-        let args = InitObligationArgs { tag, id };
+        let mut ix = klend_instruction(accounts, klend::instruction::InitObligation { args });
+        ix.accounts.extend(
+            obligation_permission.map(|obligation_permission| {
+                AccountMeta::new_readonly(obligation_permission, false)
+            }),
+        );
+        ix
+    }
+
+    /// Reallocates an obligation to the extended layout, doubling its position slots.
+    /// The payer covers the extra rent.
+    pub fn extend_obligation(
+        &self,
+        obligation: &Pubkey,
+        obligation_state: &ObligationPositions,
+    ) -> Result<Instruction> {
+        if obligation_state.obligation.is_extended() {
+            bail!("Obligation {obligation} is already extended");
+        }
 
-        let signature = self
-            .program()
-            .request()
-            .accounts(accounts)
-            .args(klend::instruction::InitObligation { args })
-            .send()?;
+        let accounts = klend::accounts::ExtendObligation {
+            owner: obligation_state.obligation.owner,
+            fee_payer: self.payer_pubkey(),
+            obligation: *obligation,
+            lending_market: obligation_state.obligation.lending_market,
+            system_program: system_program::ID,
+        };
 
-        self.send_and_confirm("init_obligation", signature)
+        Ok(klend_instruction(
+            accounts,
+            klend::instruction::ExtendObligation {},
+        ))
     }
 
+    /// Creates the reserve account and initializes it, `reserve` has to sign. The initial
+    /// deposit required by the market is taken from the payer's liquidity token account.
     pub fn init_reserve(
         &self,
         lending_market: &Pubkey,
+        reserve: &Pubkey,
         reserve_liquidity_mint: &Pubkey,
-        initial_liquidity_amount: u64,
-    ) -> Result<Signature> {
-        let (lending_market_authority, _) = Pubkey::find_program_address(
-            &[seeds::LENDING_MARKET_AUTH, lending_market.as_ref()],
-            &klend::ID,
-        );
-
-        let reserve = Keypair::new();
-
-        let (reserve_liquidity_supply, _) = Pubkey::find_program_address(
-            &[
-                seeds::RESERVE_LIQ_SUPPLY,
-                lending_market.as_ref(),
-                reserve_liquidity_mint.as_ref(),
-            ],
-            &klend::ID,
-        );
-
-        let (fee_receiver, _) = Pubkey::find_program_address(
-            &[
-                seeds::FEE_RECEIVER,
-                lending_market.as_ref(),
-                reserve_liquidity_mint.as_ref(),
-            ],
-            &klend::ID,
-        );
-
-        let (reserve_collateral_mint, _) = Pubkey::find_program_address(
-            &[
-                seeds::RESERVE_COLL_MINT,
-                lending_market.as_ref(),
-                reserve_liquidity_mint.as_ref(),
-            ],
+        liquidity_token_program: &Pubkey,
+    ) -> Vec<Instruction> {
+        let space = RESERVE_SIZE + 8;
+        let create_account = system_instruction::create_account(
+            &self.payer_pubkey(),
+            reserve,
+            Rent::default().minimum_balance(space),
+            space as u64,
             &klend::ID,
         );
 
-        let (reserve_collateral_supply, _) = Pubkey::find_program_address(
-            &[
-                seeds::RESERVE_COLL_SUPPLY,
-                lending_market.as_ref(),
-                reserve_liquidity_mint.as_ref(),
-            ],
-            &klend::ID,
-        );
+        let pdas = seeds::pda::init_reserve_pdas(lending_market, reserve_liquidity_mint);
 
         // Get user's token account for the liquidity mint
-        let initial_liquidity_source = spl_associated_token_account::get_associated_token_address(
+        let initial_liquidity_source = get_associated_token_address_with_program_id(
             &self.payer_pubkey(),
             reserve_liquidity_mint,
+            liquidity_token_program,
         );
 
         let accounts = klend::accounts::InitReserve {
             lending_market_owner: self.payer_pubkey(),
             lending_market: *lending_market,
-            lending_market_authority,
-            reserve: reserve.pubkey(),
+            lending_market_authority: seeds::pda::lending_market_auth(lending_market),
+            reserve: *reserve,
             reserve_liquidity_mint: *reserve_liquidity_mint,
-            reserve_liquidity_supply,
-            fee_receiver,
-            reserve_collateral_mint,
-            reserve_collateral_supply,
+            reserve_liquidity_supply: pdas.liquidity_supply_vault,
+            fee_receiver: pdas.fee_vault,
+            reserve_collateral_mint: pdas.collateral_ctoken_mint,
+            reserve_collateral_supply: pdas.collateral_supply_vault,
             initial_liquidity_source,
             rent: rent::ID,
-            liquidity_token_program: anchor_spl::token_interface::ID,
+            liquidity_token_program: *liquidity_token_program,
             collateral_token_program: anchor_spl::token::ID,
             system_program: system_program::ID,
         };
 
-        let signature = self
-            .program()
-            .request()
-            .accounts(accounts)
-            .signer(&reserve)
-            .send()?;
-
-        self.send_and_confirm("init_reserve", signature)
+        vec![
+            create_account,
+            klend_instruction(accounts, klend::instruction::InitReserve {}),
+        ]
     }
-} 
\ No newline at end of file
+}
@@ -1,100 +1,90 @@
-use crate::KlendClient;
+use crate::{klend_instruction, obligation::ObligationPositions, KlendClient};
 use anchor_client::solana_sdk::{
-    instruction::Instruction,
+    instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
-    signature::Signature,
-    sysvar::{instructions::ID as INSTRUCTIONS_ID, Sysvar},
+    sysvar::instructions::ID as INSTRUCTIONS_ID,
 };
-use anchor_lang::prelude::*;
-use anyhow::Result;
-use klend::{self, utils::seeds};
+use klend::{self, utils::seeds, Reserve};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
 
 impl KlendClient {
+    #[allow(clippy::too_many_arguments)]
     pub fn liquidate_obligation_and_redeem_reserve_collateral(
         &self,
         obligation: &Pubkey,
+        obligation_state: &ObligationPositions,
         repay_reserve: &Pubkey,
+        repay_reserve_state: &Reserve,
         withdraw_reserve: &Pubkey,
+        withdraw_reserve_state: &Reserve,
         liquidity_amount: u64,
         min_acceptable_received_liquidity_amount: u64,
         max_allowed_ltv_override_percent: u64,
-    ) -> Result<Signature> {
-        let obligation_data = self.program().account::<klend::Obligation>(*obligation)?;
-        let lending_market = obligation_data.lending_market;
+    ) -> Instruction {
+        let lending_market = obligation_state.obligation.lending_market;
 
-        let repay_reserve_data = self.program().account::<klend::Reserve>(*repay_reserve)?;
-        let repay_reserve_liquidity_mint = repay_reserve_data.liquidity.mint_pubkey;
-        let repay_reserve_liquidity_supply = repay_reserve_data.liquidity.supply_vault;
+        let repay_reserve_liquidity_mint = repay_reserve_state.liquidity.mint_pubkey;
+        let repay_liquidity_token_program = repay_reserve_state.liquidity.token_program;
 
-        let withdraw_reserve_data = self.program().account::<klend::Reserve>(*withdraw_reserve)?;
-        let withdraw_reserve_liquidity_mint = withdraw_reserve_data.liquidity.mint_pubkey;
-        let withdraw_reserve_collateral_mint = withdraw_reserve_data.collateral.mint_pubkey;
-        let withdraw_reserve_collateral_supply = withdraw_reserve_data.collateral.supply_vault;
-        let withdraw_reserve_liquidity_supply = withdraw_reserve_data.liquidity.supply_vault;
-        let withdraw_reserve_liquidity_fee_receiver = withdraw_reserve_data.liquidity.fee_vault;
-
-        let (lending_market_authority, _) = Pubkey::find_program_address(
-            &[seeds::LENDING_MARKET_AUTH, lending_market.as_ref()],
-            &klend::ID,
-        );
+        let withdraw_reserve_liquidity_mint = withdraw_reserve_state.liquidity.mint_pubkey;
+        let withdraw_reserve_collateral_mint = withdraw_reserve_state.collateral.mint_pubkey;
+        let withdraw_liquidity_token_program = withdraw_reserve_state.liquidity.token_program;
 
         // Get user's token accounts
-        let user_source_liquidity = spl_associated_token_account::get_associated_token_address(
+        let user_source_liquidity = get_associated_token_address_with_program_id(
             &self.payer_pubkey(),
             &repay_reserve_liquidity_mint,
+            &repay_liquidity_token_program,
         );
-        let user_destination_collateral = spl_associated_token_account::get_associated_token_address(
+        let user_destination_collateral = get_associated_token_address_with_program_id(
             &self.payer_pubkey(),
             &withdraw_reserve_collateral_mint,
+            &anchor_spl::token::ID,
         );
-        let user_destination_liquidity = spl_associated_token_account::get_associated_token_address(
+        let user_destination_liquidity = get_associated_token_address_with_program_id(
             &self.payer_pubkey(),
             &withdraw_reserve_liquidity_mint,
+            &withdraw_liquidity_token_program,
         );
 
         let accounts = klend::accounts::LiquidateObligationAndRedeemReserveCollateral {
             liquidator: self.payer_pubkey(),
             obligation: *obligation,
             lending_market,
-            lending_market_authority,
+            lending_market_authority: seeds::pda::lending_market_auth(&lending_market),
             repay_reserve: *repay_reserve,
             repay_reserve_liquidity_mint,
-            repay_reserve_liquidity_supply,
+            repay_reserve_liquidity_supply: repay_reserve_state.liquidity.supply_vault,
             withdraw_reserve: *withdraw_reserve,
             withdraw_reserve_liquidity_mint,
             withdraw_reserve_collateral_mint,
-            withdraw_reserve_collateral_supply,
-            withdraw_reserve_liquidity_supply,
-            withdraw_reserve_liquidity_fee_receiver,
+            withdraw_reserve_collateral_supply: withdraw_reserve_state.collateral.supply_vault,
+            withdraw_reserve_liquidity_supply: withdraw_reserve_state.liquidity.supply_vault,
+            withdraw_reserve_liquidity_fee_receiver: withdraw_reserve_state.liquidity.fee_vault,
             user_source_liquidity,
             user_destination_collateral,
             user_destination_liquidity,
             collateral_token_program: anchor_spl::token::ID,
-            repay_liquidity_token_program: anchor_spl::token_interface::ID,
-            withdraw_liquidity_token_program: anchor_spl::token_interface::ID,
+            repay_liquidity_token_program,
+            withdraw_liquidity_token_program,
             instruction_sysvar_account: INSTRUCTIONS_ID,
         };
 
-        // Get all deposit reserves for remaining accounts
-        let mut remaining_accounts = Vec::new();
-        for deposit in obligation_data.deposits.iter() {
-            if deposit.deposit_reserve != Pubkey::default() {
-                remaining_accounts.push(AccountMeta::new(deposit.deposit_reserve, false));
-            }
-        }
-
-        let signature = self
-            .program()
-            .request()
-            .accounts(accounts)
-            .args(klend::instruction::LiquidateObligationAndRedeemReserveCollateral {
+        let mut ix = klend_instruction(
+            accounts,
+            klend::instruction::LiquidateObligationAndRedeemReserveCollateral {
                 liquidity_amount,
                 min_acceptable_received_liquidity_amount,
                 max_allowed_ltv_override_percent,
-            })
-            .accounts(remaining_accounts)
-            .send()?;
-
-        self.send_and_confirm("liquidate_obligation_and_redeem_reserve_collateral", signature)
+            },
+        );
+        // Deposit reserves of the obligation as remaining accounts
+        ix.accounts.extend(
+            obligation_state
+                .deposit_reserves()
+                .into_iter()
+                .map(|reserve| AccountMeta::new(reserve, false)),
+        );
+        ix
     }
-} 
\ No newline at end of file
+}
@@ -1,17 +1,29 @@
 pub mod borrow;
+pub mod close;
+pub mod config_change;
 pub mod deposit;
 pub mod init;
 pub mod liquidate;
+pub mod owner;
+pub mod owner_withdrawal_caps;
+pub mod permission;
 pub mod redeem;
 pub mod refresh;
 pub mod repay;
 pub mod withdraw;
+pub mod withdraw_queue;
 
 pub use borrow::*;
+pub use close::*;
+pub use config_change::*;
 pub use deposit::*;
 pub use init::*;
 pub use liquidate::*;
+pub use owner::*;
+pub use owner_withdrawal_caps::*;
+pub use permission::*;
 pub use redeem::*;
 pub use refresh::*;
 pub use repay::*;
 pub use withdraw::*;
+pub use withdraw_queue::*;
@@ -0,0 +1,131 @@
+use crate::{klend_instruction, obligation::ObligationPositions, KlendClient};
+use anchor_client::solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use anyhow::{bail, Result};
+use klend::{utils::seeds, Obligation, Reserve, ReserveFarmKind};
+
+/// Remaining accounts the program derives the obligation farm user states from: each reserve,
+/// followed by the user state of its collateral farm then the one of its debt farm.
+pub(crate) fn obligation_farm_user_state_accounts(
+    obligation: &Pubkey,
+    reserves: &[(Pubkey, Reserve)],
+) -> Vec<AccountMeta> {
+    let mut accounts = Vec::new();
+    for (reserve, reserve_state) in reserves {
+        accounts.push(AccountMeta::new_readonly(*reserve, false));
+        for mode in [ReserveFarmKind::Collateral, ReserveFarmKind::Debt] {
+            let farm = reserve_state.get_farm(mode);
+            if farm != Pubkey::default() {
+                let farm_user_state = seeds::pda::obligation_farm_user_state(farm, *obligation).0;
+                accounts.push(AccountMeta::new_readonly(farm_user_state, false));
+            }
+        }
+    }
+    accounts
+}
+
+/// The program refuses an ownership transfer unless every reserve of the obligation is passed.
+fn check_obligation_reserves_passed(
+    obligation: &Pubkey,
+    obligation_state: &ObligationPositions,
+    reserves: &[(Pubkey, Reserve)],
+) -> Result<()> {
+    for reserve in obligation_state
+        .deposit_reserves()
+        .into_iter()
+        .chain(obligation_state.borrow_reserves())
+    {
+        if !reserves.iter().any(|(passed, _)| *passed == reserve) {
+            bail!("Reserve {reserve} of obligation {obligation} must be passed");
+        }
+    }
+    Ok(())
+}
+
+impl KlendClient {
+    /// First step of an obligation ownership transfer, signed by the current owner.
+    /// Proposing `Pubkey::default()` cancels a pending transfer. `reserves` are the deposit and
+    /// borrow reserves of the obligation, the program checks none of their farms has a user
+    /// state for it.
+    pub fn propose_obligation_owner(
+        &self,
+        obligation: &Pubkey,
+        obligation_state: &ObligationPositions,
+        reserves: &[(Pubkey, Reserve)],
+        new_owner: &Pubkey,
+    ) -> Result<Instruction> {
+        check_obligation_reserves_passed(obligation, obligation_state, reserves)?;
+
+        let accounts = klend::accounts::ProposeObligationOwner {
+            owner: self.payer_pubkey(),
+            obligation: *obligation,
+            lending_market: obligation_state.obligation.lending_market,
+        };
+
+        let mut ix = klend_instruction(
+            accounts,
+            klend::instruction::ProposeObligationOwner {
+                new_owner: *new_owner,
+            },
+        );
+        ix.accounts
+            .extend(obligation_farm_user_state_accounts(obligation, reserves));
+        Ok(ix)
+    }
+
+    /// Second step of an obligation ownership transfer, signed by the proposed owner. Takes the
+    /// same `reserves` as [`Self::propose_obligation_owner`].
+    pub fn update_obligation_owner(
+        &self,
+        obligation: &Pubkey,
+        obligation_state: &ObligationPositions,
+        reserves: &[(Pubkey, Reserve)],
+    ) -> Result<Instruction> {
+        if obligation_state.obligation.owner_cached != self.payer_pubkey() {
+            bail!(
+                "Obligation {obligation} ownership is not proposed to {}",
+                self.payer_pubkey()
+            );
+        }
+        check_obligation_reserves_passed(obligation, obligation_state, reserves)?;
+
+        let accounts = klend::accounts::UpdateObligationOwner {
+            owner_cached: self.payer_pubkey(),
+            obligation: *obligation,
+            lending_market: obligation_state.obligation.lending_market,
+        };
+
+        let mut ix = klend_instruction(accounts, klend::instruction::UpdateObligationOwner {});
+        ix.accounts
+            .extend(obligation_farm_user_state_accounts(obligation, reserves));
+        Ok(ix)
+    }
+
+    /// Grants `delegate` the given permissions on an obligation, capped at `max_ltv_pct` for
+    /// borrows and withdrawals. Passing `Pubkey::default()` revokes the current delegate.
+    pub fn update_obligation_delegate(
+        &self,
+        obligation: &Pubkey,
+        obligation_state: &Obligation,
+        delegate: &Pubkey,
+        permissions: klend::ObligationDelegatePermissions,
+        max_ltv_pct: u8,
+    ) -> Instruction {
+        let accounts = klend::accounts::UpdateObligationDelegate {
+            owner: self.payer_pubkey(),
+            obligation: *obligation,
+            lending_market: obligation_state.lending_market,
+        };
+
+        klend_instruction(
+            accounts,
+            klend::instruction::UpdateObligationDelegate {
+                delegate: *delegate,
+                permissions: permissions.bits(),
+                max_ltv_pct,
+            },
+        )
+    }
+}
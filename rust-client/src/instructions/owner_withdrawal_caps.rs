@@ -0,0 +1,42 @@
+use crate::{klend_instruction, KlendClient};
+use anchor_client::solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+use klend::{self, utils::seeds, ObligationWithdrawalCapConfig, Reserve};
+
+impl KlendClient {
+    /// Creates the withdrawal cap accumulators of `owner` on a reserve, required to withdraw
+    /// or borrow once the reserve caps owners. Paid by the payer, anyone can create them.
+    pub fn init_owner_withdrawal_caps(
+        &self,
+        reserve: &Pubkey,
+        reserve_state: &Reserve,
+        owner: &Pubkey,
+    ) -> Instruction {
+        let (owner_withdrawal_caps, _) = seeds::pda::owner_withdrawal_caps(*reserve, *owner);
+
+        let accounts = klend::accounts::InitOwnerWithdrawalCaps {
+            payer: self.payer_pubkey(),
+            owner: *owner,
+            lending_market: reserve_state.lending_market,
+            reserve: *reserve,
+            owner_withdrawal_caps,
+            system_program: system_program::ID,
+        };
+
+        klend_instruction(accounts, klend::instruction::InitOwnerWithdrawalCaps {})
+    }
+
+    /// Passed last to withdrawals and borrows while the reserve caps owners.
+    pub(crate) fn owner_withdrawal_caps_account(
+        reserve: &Pubkey,
+        cap_config: &ObligationWithdrawalCapConfig,
+        owner: &Pubkey,
+    ) -> Option<AccountMeta> {
+        cap_config
+            .is_enabled()
+            .then(|| AccountMeta::new(seeds::pda::owner_withdrawal_caps(*reserve, *owner).0, false))
+    }
+}
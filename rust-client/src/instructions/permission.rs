@@ -0,0 +1,60 @@
+use crate::{klend_instruction, KlendClient};
+use anchor_client::solana_sdk::{instruction::Instruction, pubkey::Pubkey, system_program};
+use klend::{self, utils::seeds, LendingMarket};
+
+impl KlendClient {
+    /// Issues an obligation permission to `owner` in a permissioned market. Signed by the
+    /// market owner or the risk council.
+    pub fn init_obligation_permission(
+        &self,
+        lending_market: &Pubkey,
+        owner: &Pubkey,
+    ) -> Instruction {
+        let (obligation_permission, _) = seeds::pda::obligation_permission(*lending_market, *owner);
+
+        let accounts = klend::accounts::InitObligationPermission {
+            authority: self.payer_pubkey(),
+            payer: self.payer_pubkey(),
+            lending_market: *lending_market,
+            owner: *owner,
+            obligation_permission,
+            system_program: system_program::ID,
+        };
+
+        klend_instruction(accounts, klend::instruction::InitObligationPermission {})
+    }
+
+    /// Revokes or reinstates the obligation permission of `owner`. A revoked owner can still
+    /// repay but cannot open obligations or borrow.
+    pub fn update_obligation_permission(
+        &self,
+        lending_market: &Pubkey,
+        owner: &Pubkey,
+        revoked: bool,
+    ) -> Instruction {
+        let (obligation_permission, _) = seeds::pda::obligation_permission(*lending_market, *owner);
+
+        let accounts = klend::accounts::UpdateObligationPermission {
+            authority: self.payer_pubkey(),
+            lending_market: *lending_market,
+            obligation_permission,
+        };
+
+        klend_instruction(
+            accounts,
+            klend::instruction::UpdateObligationPermission { revoked },
+        )
+    }
+
+    /// Obligation permission account to pass for `owner`, only when the market requires one.
+    pub fn obligation_permission_account(
+        &self,
+        lending_market: &Pubkey,
+        market_state: &LendingMarket,
+        owner: &Pubkey,
+    ) -> Option<Pubkey> {
+        market_state
+            .is_obligation_permission_required()
+            .then(|| seeds::pda::obligation_permission(*lending_market, *owner).0)
+    }
+}
@@ -1,95 +1,68 @@
-use crate::KlendClient;
+use crate::{klend_instruction, KlendClient};
 use anchor_client::solana_sdk::{
-    instruction::Instruction,
-    pubkey::Pubkey,
-    signature::Signature,
-    sysvar::{instructions::ID as INSTRUCTIONS_ID, Sysvar},
+    instruction::Instruction, pubkey::Pubkey, sysvar::instructions::ID as INSTRUCTIONS_ID,
 };
-use anchor_lang::prelude::*;
-use anyhow::Result;
-use klend::{self, utils::seeds};
+use klend::{self, utils::seeds, Reserve};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
 
 impl KlendClient {
     pub fn redeem_reserve_collateral(
         &self,
         reserve: &Pubkey,
+        reserve_state: &Reserve,
         collateral_amount: u64,
-    ) -> Result<Signature> {
-        let reserve_data = self.program().account::<klend::Reserve>(*reserve)?;
-        let lending_market = reserve_data.lending_market;
-        let reserve_liquidity_mint = reserve_data.liquidity.mint_pubkey;
-        let reserve_collateral_mint = reserve_data.collateral.mint_pubkey;
-        let reserve_liquidity_supply = reserve_data.liquidity.supply_vault;
-
-        let (lending_market_authority, _) = Pubkey::find_program_address(
-            &[seeds::LENDING_MARKET_AUTH, lending_market.as_ref()],
-            &klend::ID,
-        );
+    ) -> Instruction {
+        let lending_market = reserve_state.lending_market;
+        let reserve_liquidity_mint = reserve_state.liquidity.mint_pubkey;
+        let reserve_collateral_mint = reserve_state.collateral.mint_pubkey;
+        let liquidity_token_program = reserve_state.liquidity.token_program;
 
         // Get user's token accounts
-        let user_source_collateral = spl_associated_token_account::get_associated_token_address(
+        let user_source_collateral = get_associated_token_address_with_program_id(
             &self.payer_pubkey(),
             &reserve_collateral_mint,
+            &anchor_spl::token::ID,
         );
-        let user_destination_liquidity = spl_associated_token_account::get_associated_token_address(
+        let user_destination_liquidity = get_associated_token_address_with_program_id(
             &self.payer_pubkey(),
             &reserve_liquidity_mint,
+            &liquidity_token_program,
         );
 
         let accounts = klend::accounts::RedeemReserveCollateral {
             owner: self.payer_pubkey(),
             lending_market,
             reserve: *reserve,
-            lending_market_authority,
+            lending_market_authority: seeds::pda::lending_market_auth(&lending_market),
             reserve_liquidity_mint,
             reserve_collateral_mint,
-            reserve_liquidity_supply,
+            reserve_liquidity_supply: reserve_state.liquidity.supply_vault,
             user_source_collateral,
             user_destination_liquidity,
             collateral_token_program: anchor_spl::token::ID,
-            liquidity_token_program: anchor_spl::token_interface::ID,
+            liquidity_token_program,
             instruction_sysvar_account: INSTRUCTIONS_ID,
         };
 
-        let signature = self
-            .program()
-            .request()
-            .accounts(accounts)
-            .args(klend::instruction::RedeemReserveCollateral { collateral_amount })
-            .send()?;
-
-        self.send_and_confirm("redeem_reserve_collateral", signature)
+        klend_instruction(
+            accounts,
+            klend::instruction::RedeemReserveCollateral { collateral_amount },
+        )
     }
 
-    pub fn redeem_fees(&self, reserve: &Pubkey) -> Result<Signature> {
-        let reserve_data = self.program().account::<klend::Reserve>(*reserve)?;
-        let lending_market = reserve_data.lending_market;
-        let reserve_liquidity_mint = reserve_data.liquidity.mint_pubkey;
-        let reserve_liquidity_fee_receiver = reserve_data.liquidity.fee_vault;
-        let reserve_supply_liquidity = reserve_data.liquidity.supply_vault;
-
-        let (lending_market_authority, _) = Pubkey::find_program_address(
-            &[seeds::LENDING_MARKET_AUTH, lending_market.as_ref()],
-            &klend::ID,
-        );
+    pub fn redeem_fees(&self, reserve: &Pubkey, reserve_state: &Reserve) -> Instruction {
+        let lending_market = reserve_state.lending_market;
 
         let accounts = klend::accounts::RedeemFees {
             reserve: *reserve,
-            reserve_liquidity_mint,
-            reserve_liquidity_fee_receiver,
-            reserve_supply_liquidity,
+            reserve_liquidity_mint: reserve_state.liquidity.mint_pubkey,
+            reserve_liquidity_fee_receiver: reserve_state.liquidity.fee_vault,
+            reserve_supply_liquidity: reserve_state.liquidity.supply_vault,
             lending_market,
-            lending_market_authority,
-            token_program: anchor_spl::token_interface::ID,
+            lending_market_authority: seeds::pda::lending_market_auth(&lending_market),
+            token_program: reserve_state.liquidity.token_program,
         };
 
-        let signature = self
-            .program()
-            .request()
-            .accounts(accounts)
-            .args(klend::instruction::RedeemFees {})
-            .send()?;
-
-        self.send_and_confirm("redeem_fees", signature)
+        klend_instruction(accounts, klend::instruction::RedeemFees {})
     }
-} 
\ No newline at end of file
+}
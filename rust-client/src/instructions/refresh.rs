@@ -1,24 +1,17 @@
-use crate::KlendClient;
-use anchor_client::solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Signature};
-use anchor_lang::prelude::*;
-use anyhow::Result;
-use klend::{self, MaxReservesAsCollateralCheck};
+use crate::{klend_instruction, obligation::ObligationPositions, KlendClient};
+use anchor_client::solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use klend::{self, Reserve};
 
 impl KlendClient {
-    pub fn refresh_reserve(&self, reserve: &Pubkey) -> Result<Signature> {
-        let reserve_data = self.program().account::<klend::Reserve>(*reserve)?;
-        let lending_market = reserve_data.lending_market;
-        let token_info = reserve_data.config.token_info;
-
-        // Determine which price oracles to include based on token_info
-        let pyth_oracle = token_info.pyth_oracle;
-        let switchboard_price_oracle = token_info.switchboard_price_oracle;
-        let switchboard_twap_oracle = token_info.switchboard_twap_oracle;
-        let scope_prices = token_info.scope_price_id;
+    pub fn refresh_reserve(&self, reserve: &Pubkey, reserve_state: &Reserve) -> Instruction {
+        let token_info = &reserve_state.config.token_info;
 
         let mut accounts = klend::accounts::RefreshReserve {
             reserve: *reserve,
-            lending_market,
+            lending_market: reserve_state.lending_market,
             pyth_oracle: None,
             switchboard_price_oracle: None,
             switchboard_twap_oracle: None,
@@ -26,59 +19,71 @@ impl KlendClient {
         };
 
         // Add the price oracles that are configured
-        if pyth_oracle != Pubkey::default() {
-            accounts.pyth_oracle = Some(pyth_oracle);
-        }
-        if switchboard_price_oracle != Pubkey::default() {
-            accounts.switchboard_price_oracle = Some(switchboard_price_oracle);
+        if token_info.pyth_configuration.is_enabled() {
+            accounts.pyth_oracle = Some(token_info.pyth_configuration.price);
         }
-        if switchboard_twap_oracle != Pubkey::default() {
-            accounts.switchboard_twap_oracle = Some(switchboard_twap_oracle);
+        if token_info.switchboard_configuration.is_enabled() {
+            accounts.switchboard_price_oracle =
+                Some(token_info.switchboard_configuration.price_aggregator);
+            accounts.switchboard_twap_oracle =
+                Some(token_info.switchboard_configuration.twap_aggregator);
         }
-        if scope_prices != Pubkey::default() {
-            accounts.scope_prices = Some(scope_prices);
+        if token_info.scope_configuration.is_enabled() {
+            accounts.scope_prices = Some(token_info.scope_configuration.price_feed);
         }
 
-        let signature = self
-            .program()
-            .request()
-            .accounts(accounts)
-            .args(klend::instruction::RefreshReserve {})
-            .send()?;
-
-        self.send_and_confirm("refresh_reserve", signature)
+        let mut ix = klend_instruction(accounts, klend::instruction::RefreshReserve {});
+        // Derived prices read the base reserve and the stake pool from the remaining accounts
+        if token_info.derived_price_configuration.is_enabled() {
+            ix.accounts.extend([
+                AccountMeta::new_readonly(
+                    token_info.derived_price_configuration.base_reserve,
+                    false,
+                ),
+                AccountMeta::new_readonly(token_info.derived_price_configuration.stake_pool, false),
+            ]);
+        }
+        ix
     }
 
     pub fn refresh_obligation(
         &self,
         obligation: &Pubkey,
-        deposit_reserves: &[Pubkey],
-        borrow_reserves: &[Pubkey],
-    ) -> Result<Signature> {
-        let obligation_data = self.program().account::<klend::Obligation>(*obligation)?;
-        let lending_market = obligation_data.lending_market;
-
+        obligation_state: &ObligationPositions,
+    ) -> Instruction {
         let accounts = klend::accounts::RefreshObligation {
             obligation: *obligation,
-            lending_market,
+            lending_market: obligation_state.obligation.lending_market,
         };
 
-        // Combine deposit and borrow reserves for remaining accounts
-        let mut remaining_accounts = Vec::new();
-        for reserve in deposit_reserves.iter().chain(borrow_reserves.iter()) {
-            remaining_accounts.push(AccountMeta::new(*reserve, false));
-        }
-
-        let signature = self
-            .program()
-            .request()
-            .accounts(accounts)
-            .args(klend::instruction::RefreshObligation {
-                max_reserves_as_collateral_check: MaxReservesAsCollateralCheck::Perform,
-            })
-            .accounts(remaining_accounts)
-            .send()?;
+        let mut ix = klend_instruction(accounts, klend::instruction::RefreshObligation {});
+        // Deposit reserves then borrow reserves, in position order
+        ix.accounts.extend(
+            obligation_state
+                .deposit_reserves()
+                .into_iter()
+                .chain(obligation_state.borrow_reserves())
+                .map(|reserve| AccountMeta::new(reserve, false)),
+        );
+        ix
+    }
 
-        self.send_and_confirm("refresh_obligation", signature)
+    /// Prepends to `ix` the refreshes an obligation action has to follow in the same
+    /// transaction: `reserves` in order, with the reserve of the action last, then the
+    /// obligation.
+    pub fn with_obligation_refresh(
+        &self,
+        obligation: &Pubkey,
+        obligation_state: &ObligationPositions,
+        reserves: &[(Pubkey, Reserve)],
+        ix: Instruction,
+    ) -> Vec<Instruction> {
+        let mut ixs: Vec<Instruction> = reserves
+            .iter()
+            .map(|(reserve, reserve_state)| self.refresh_reserve(reserve, reserve_state))
+            .collect();
+        ixs.push(self.refresh_obligation(obligation, obligation_state));
+        ixs.push(ix);
+        ixs
     }
-} 
\ No newline at end of file
+}
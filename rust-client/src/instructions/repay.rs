@@ -1,52 +1,46 @@
-use crate::KlendClient;
+use crate::{klend_instruction, KlendClient};
 use anchor_client::solana_sdk::{
-    instruction::Instruction,
-    pubkey::Pubkey,
-    signature::Signature,
-    sysvar::{instructions::ID as INSTRUCTIONS_ID, Sysvar},
+    instruction::Instruction, pubkey::Pubkey, sysvar::instructions::ID as INSTRUCTIONS_ID,
 };
-use anchor_lang::prelude::*;
-use anyhow::Result;
-use klend::{self, utils::seeds};
+use klend::{self, Reserve};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
 
 impl KlendClient {
     pub fn repay_obligation_liquidity(
         &self,
         obligation: &Pubkey,
         repay_reserve: &Pubkey,
+        reserve_state: &Reserve,
         liquidity_amount: u64,
-    ) -> Result<Signature> {
-        let obligation_data = self.program().account::<klend::Obligation>(*obligation)?;
-        let lending_market = obligation_data.lending_market;
-
-        let reserve_data = self.program().account::<klend::Reserve>(*repay_reserve)?;
-        let repay_reserve_liquidity_mint = reserve_data.liquidity.mint_pubkey;
-        let reserve_destination_liquidity = reserve_data.liquidity.supply_vault;
+    ) -> Instruction {
+        let reserve_liquidity_mint = reserve_state.liquidity.mint_pubkey;
+        let token_program = reserve_state.liquidity.token_program;
 
         // Get user's token account for the liquidity
-        let user_source_liquidity = spl_associated_token_account::get_associated_token_address(
+        let user_source_liquidity = get_associated_token_address_with_program_id(
             &self.payer_pubkey(),
-            &repay_reserve_liquidity_mint,
+            &reserve_liquidity_mint,
+            &token_program,
         );
 
         let accounts = klend::accounts::RepayObligationLiquidity {
             owner: self.payer_pubkey(),
             obligation: *obligation,
-            lending_market,
+            lending_market: reserve_state.lending_market,
             repay_reserve: *repay_reserve,
+            reserve_liquidity_mint,
+            reserve_destination_liquidity: reserve_state.liquidity.supply_vault,
             user_source_liquidity,
-            reserve_destination_liquidity,
-            token_program: anchor_spl::token_interface::ID,
+            token_program,
             instruction_sysvar_account: INSTRUCTIONS_ID,
         };
 
-        let signature = self
-            .program()
-            .request()
-            .accounts(accounts)
-            .args(klend::instruction::RepayObligationLiquidity { liquidity_amount })
-            .send()?;
-
-        self.send_and_confirm("repay_obligation_liquidity", signature)
+        let mut ix = klend_instruction(
+            accounts,
+            klend::instruction::RepayObligationLiquidity { liquidity_amount },
+        );
+        ix.accounts
+            .extend(Self::withdraw_queue_account(repay_reserve, reserve_state));
+        ix
     }
-} 
\ No newline at end of file
+}
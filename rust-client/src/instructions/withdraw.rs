@@ -1,120 +1,103 @@
-use crate::KlendClient;
+use crate::{klend_instruction, KlendClient};
 use anchor_client::solana_sdk::{
-    instruction::Instruction,
-    pubkey::Pubkey,
-    signature::Signature,
-    sysvar::{instructions::ID as INSTRUCTIONS_ID, Sysvar},
+    instruction::Instruction, pubkey::Pubkey, sysvar::instructions::ID as INSTRUCTIONS_ID,
 };
-use anchor_lang::prelude::*;
-use anyhow::Result;
-use klend::{self, utils::seeds, LtvMaxWithdrawalCheck};
+use klend::{self, utils::seeds, Reserve};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
 
 impl KlendClient {
+    /// The payer signs as the obligation owner or its delegate, the collateral is always sent
+    /// to `obligation_owner`.
     pub fn withdraw_obligation_collateral(
         &self,
         obligation: &Pubkey,
+        obligation_owner: &Pubkey,
         withdraw_reserve: &Pubkey,
+        reserve_state: &Reserve,
         collateral_amount: u64,
-    ) -> Result<Signature> {
-        let obligation_data = self.program().account::<klend::Obligation>(*obligation)?;
-        let lending_market = obligation_data.lending_market;
+    ) -> Instruction {
+        let lending_market = reserve_state.lending_market;
 
-        let reserve_data = self.program().account::<klend::Reserve>(*withdraw_reserve)?;
-        let reserve_collateral_mint = reserve_data.collateral.mint_pubkey;
-        let reserve_source_collateral = reserve_data.collateral.supply_vault;
-
-        let (lending_market_authority, _) = Pubkey::find_program_address(
-            &[seeds::LENDING_MARKET_AUTH, lending_market.as_ref()],
-            &klend::ID,
-        );
-
-        // Get user's token account for the collateral
-        let user_destination_collateral = spl_associated_token_account::get_associated_token_address(
-            &self.payer_pubkey(),
-            &reserve_collateral_mint,
+        // Get the obligation owner's token account for the collateral
+        let user_destination_collateral = get_associated_token_address_with_program_id(
+            obligation_owner,
+            &reserve_state.collateral.mint_pubkey,
+            &anchor_spl::token::ID,
         );
 
         let accounts = klend::accounts::WithdrawObligationCollateral {
             owner: self.payer_pubkey(),
             obligation: *obligation,
             lending_market,
-            lending_market_authority,
+            lending_market_authority: seeds::pda::lending_market_auth(&lending_market),
             withdraw_reserve: *withdraw_reserve,
-            reserve_source_collateral,
+            reserve_source_collateral: reserve_state.collateral.supply_vault,
             user_destination_collateral,
             token_program: anchor_spl::token::ID,
             instruction_sysvar_account: INSTRUCTIONS_ID,
         };
 
-        let signature = self
-            .program()
-            .request()
-            .accounts(accounts)
-            .args(klend::instruction::WithdrawObligationCollateral {
-                collateral_amount,
-                ltv_max_withdrawal_check: LtvMaxWithdrawalCheck::Perform,
-            })
-            .send()?;
-
-        self.send_and_confirm("withdraw_obligation_collateral", signature)
+        let mut ix = klend_instruction(
+            accounts,
+            klend::instruction::WithdrawObligationCollateral { collateral_amount },
+        );
+        ix.accounts.extend(Self::owner_withdrawal_caps_account(
+            withdraw_reserve,
+            &reserve_state.config.obligation_deposit_withdrawal_cap,
+            obligation_owner,
+        ));
+        ix
     }
 
+    /// The payer signs as the obligation owner or its delegate, the liquidity is always sent
+    /// to `obligation_owner`.
     pub fn withdraw_obligation_collateral_and_redeem_reserve_collateral(
         &self,
         obligation: &Pubkey,
+        obligation_owner: &Pubkey,
         withdraw_reserve: &Pubkey,
+        reserve_state: &Reserve,
         collateral_amount: u64,
-    ) -> Result<Signature> {
-        let obligation_data = self.program().account::<klend::Obligation>(*obligation)?;
-        let lending_market = obligation_data.lending_market;
-
-        let reserve_data = self.program().account::<klend::Reserve>(*withdraw_reserve)?;
-        let reserve_collateral_mint = reserve_data.collateral.mint_pubkey;
-        let reserve_liquidity_mint = reserve_data.liquidity.mint_pubkey;
-        let reserve_source_collateral = reserve_data.collateral.supply_vault;
-        let reserve_liquidity_supply = reserve_data.liquidity.supply_vault;
+    ) -> Instruction {
+        let lending_market = reserve_state.lending_market;
+        let reserve_liquidity_mint = reserve_state.liquidity.mint_pubkey;
+        let liquidity_token_program = reserve_state.liquidity.token_program;
 
-        let (lending_market_authority, _) = Pubkey::find_program_address(
-            &[seeds::LENDING_MARKET_AUTH, lending_market.as_ref()],
-            &klend::ID,
-        );
-
-        // Get user's token accounts
-        let user_destination_collateral = spl_associated_token_account::get_associated_token_address(
-            &self.payer_pubkey(),
-            &reserve_collateral_mint,
-        );
-        let user_destination_liquidity = spl_associated_token_account::get_associated_token_address(
-            &self.payer_pubkey(),
+        // Get the obligation owner's token account for the liquidity
+        let user_destination_liquidity = get_associated_token_address_with_program_id(
+            obligation_owner,
             &reserve_liquidity_mint,
+            &liquidity_token_program,
         );
 
         let accounts = klend::accounts::WithdrawObligationCollateralAndRedeemReserveCollateral {
             owner: self.payer_pubkey(),
             obligation: *obligation,
             lending_market,
-            lending_market_authority,
+            lending_market_authority: seeds::pda::lending_market_auth(&lending_market),
             withdraw_reserve: *withdraw_reserve,
-            reserve_collateral_mint,
-            reserve_source_collateral,
-            reserve_liquidity_supply,
-            user_destination_collateral,
+            reserve_liquidity_mint,
+            reserve_source_collateral: reserve_state.collateral.supply_vault,
+            reserve_collateral_mint: reserve_state.collateral.mint_pubkey,
+            reserve_liquidity_supply: reserve_state.liquidity.supply_vault,
             user_destination_liquidity,
+            placeholder_user_destination_collateral: None,
             collateral_token_program: anchor_spl::token::ID,
-            liquidity_token_program: anchor_spl::token_interface::ID,
+            liquidity_token_program,
             instruction_sysvar_account: INSTRUCTIONS_ID,
         };
 
-        let signature = self
-            .program()
-            .request()
-            .accounts(accounts)
-            .args(klend::instruction::WithdrawObligationCollateralAndRedeemReserveCollateral {
+        let mut ix = klend_instruction(
+            accounts,
+            klend::instruction::WithdrawObligationCollateralAndRedeemReserveCollateral {
                 collateral_amount,
-                ltv_max_withdrawal_check: LtvMaxWithdrawalCheck::Perform,
-            })
-            .send()?;
-
-        self.send_and_confirm("withdraw_obligation_collateral_and_redeem_reserve_collateral", signature)
+            },
+        );
+        ix.accounts.extend(Self::owner_withdrawal_caps_account(
+            withdraw_reserve,
+            &reserve_state.config.obligation_deposit_withdrawal_cap,
+            obligation_owner,
+        ));
+        ix
     }
-} 
\ No newline at end of file
+}
@@ -0,0 +1,144 @@
+use crate::{klend_instruction, KlendClient};
+use anchor_client::solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+use anyhow::Result;
+use klend::{self, utils::seeds, Reserve, WithdrawTicket};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+impl KlendClient {
+    pub fn init_withdraw_queue(&self, reserve: &Pubkey, reserve_state: &Reserve) -> Instruction {
+        let (withdraw_queue, _) = seeds::pda::withdraw_queue(*reserve);
+
+        let accounts = klend::accounts::InitWithdrawQueue {
+            lending_market_owner: self.payer_pubkey(),
+            lending_market: reserve_state.lending_market,
+            reserve: *reserve,
+            withdraw_queue,
+            system_program: system_program::ID,
+        };
+
+        klend_instruction(accounts, klend::instruction::InitWithdrawQueue {})
+    }
+
+    /// Locks cTokens into a withdraw queue ticket, filled as liquidity comes back into the reserve.
+    pub fn enqueue_withdrawal(
+        &self,
+        reserve: &Pubkey,
+        reserve_state: &Reserve,
+        collateral_amount: u64,
+    ) -> Instruction {
+        let reserve_collateral_mint = reserve_state.collateral.mint_pubkey;
+        let (withdraw_queue, _) = seeds::pda::withdraw_queue(*reserve);
+
+        let user_source_collateral = get_associated_token_address_with_program_id(
+            &self.payer_pubkey(),
+            &reserve_collateral_mint,
+            &anchor_spl::token::ID,
+        );
+
+        let accounts = klend::accounts::EnqueueWithdrawal {
+            owner: self.payer_pubkey(),
+            lending_market: reserve_state.lending_market,
+            reserve: *reserve,
+            withdraw_queue,
+            reserve_collateral_mint,
+            user_source_collateral,
+            collateral_token_program: anchor_spl::token::ID,
+        };
+
+        klend_instruction(
+            accounts,
+            klend::instruction::EnqueueWithdrawal { collateral_amount },
+        )
+    }
+
+    /// Returns the unfilled cTokens of a ticket, already filled liquidity stays claimable.
+    pub fn cancel_withdrawal(
+        &self,
+        reserve: &Pubkey,
+        reserve_state: &Reserve,
+        ticket_id: u64,
+    ) -> Instruction {
+        let lending_market = reserve_state.lending_market;
+        let reserve_collateral_mint = reserve_state.collateral.mint_pubkey;
+        let (withdraw_queue, _) = seeds::pda::withdraw_queue(*reserve);
+
+        let user_destination_collateral = get_associated_token_address_with_program_id(
+            &self.payer_pubkey(),
+            &reserve_collateral_mint,
+            &anchor_spl::token::ID,
+        );
+
+        let accounts = klend::accounts::CancelWithdrawal {
+            owner: self.payer_pubkey(),
+            lending_market,
+            lending_market_authority: seeds::pda::lending_market_auth(&lending_market),
+            reserve: *reserve,
+            withdraw_queue,
+            reserve_collateral_mint,
+            user_destination_collateral,
+            collateral_token_program: anchor_spl::token::ID,
+        };
+
+        klend_instruction(accounts, klend::instruction::CancelWithdrawal { ticket_id })
+    }
+
+    /// Fills the queue with the liquidity available and transfers the payer's filled tickets.
+    pub fn claim_withdrawal(&self, reserve: &Pubkey, reserve_state: &Reserve) -> Instruction {
+        let lending_market = reserve_state.lending_market;
+        let reserve_liquidity_mint = reserve_state.liquidity.mint_pubkey;
+        let liquidity_token_program = reserve_state.liquidity.token_program;
+        let (withdraw_queue, _) = seeds::pda::withdraw_queue(*reserve);
+
+        let user_destination_liquidity = get_associated_token_address_with_program_id(
+            &self.payer_pubkey(),
+            &reserve_liquidity_mint,
+            &liquidity_token_program,
+        );
+
+        let accounts = klend::accounts::ClaimWithdrawal {
+            owner: self.payer_pubkey(),
+            lending_market,
+            lending_market_authority: seeds::pda::lending_market_auth(&lending_market),
+            reserve: *reserve,
+            withdraw_queue,
+            reserve_liquidity_mint,
+            reserve_liquidity_supply: reserve_state.liquidity.supply_vault,
+            user_destination_liquidity,
+            liquidity_token_program,
+        };
+
+        klend_instruction(accounts, klend::instruction::ClaimWithdrawal {})
+    }
+
+    /// Passed last to deposits and repays while the reserve has queued withdrawals, so the
+    /// liquidity coming in fills the tickets.
+    pub(crate) fn withdraw_queue_account(
+        reserve: &Pubkey,
+        reserve_state: &Reserve,
+    ) -> Option<AccountMeta> {
+        (reserve_state.withdraw_queue_collateral_amount > 0)
+            .then(|| AccountMeta::new(seeds::pda::withdraw_queue(*reserve).0, false))
+    }
+
+    pub fn withdraw_tickets(
+        &self,
+        reserve: &Pubkey,
+        owner: &Pubkey,
+    ) -> Result<Vec<WithdrawTicket>> {
+        let (withdraw_queue, _) = seeds::pda::withdraw_queue(*reserve);
+        let queue_data = self
+            .program()
+            .account::<klend::WithdrawQueue>(withdraw_queue)?;
+
+        Ok(queue_data
+            .tickets()
+            .iter()
+            .filter(|ticket| ticket.owner == *owner)
+            .copied()
+            .collect())
+    }
+}
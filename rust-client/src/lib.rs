@@ -1,40 +1,53 @@
+pub mod borrow_rate_curve;
+pub mod deleveraging;
+pub mod events;
 pub mod fee_estimation;
 pub mod instructions;
+pub mod obligation;
+pub mod rate_history;
 pub mod rpc;
 pub mod utils;
 
 use anchor_client::solana_sdk::{
     commitment_config::CommitmentConfig,
+    instruction::Instruction,
     pubkey::Pubkey,
     signature::{Keypair, Signature},
     signer::Signer,
 };
 use anchor_client::{Client as AnchorClient, Cluster, Program};
+use anchor_lang::{InstructionData, ToAccountMetas};
 use anyhow::Result;
-use std::rc::Rc;
+use obligation::{decode_obligation, ObligationPositions};
+use std::{cell::OnceCell, rc::Rc};
 
 pub use klend;
 
 pub struct KlendClient {
-    program: Program<Rc<Keypair>>,
+    rpc_url: String,
     payer: Rc<Keypair>,
+    /// Only connected on first use, building instructions does not need the RPC.
+    program: OnceCell<Program<Rc<Keypair>>>,
 }
 
 impl KlendClient {
     pub fn new(rpc_url: &str, payer: Keypair) -> Self {
-        let payer = Rc::new(payer);
-        let client = AnchorClient::new_with_options(
-            Cluster::Custom(rpc_url.to_string(), rpc_url.to_string()),
-            payer.clone(),
-            CommitmentConfig::confirmed(),
-        );
-        let program = client.program(klend::ID).unwrap();
-
-        Self { program, payer }
+        Self {
+            rpc_url: rpc_url.to_string(),
+            payer: Rc::new(payer),
+            program: OnceCell::new(),
+        }
     }
 
     pub fn program(&self) -> &Program<Rc<Keypair>> {
-        &self.program
+        self.program.get_or_init(|| {
+            let client = AnchorClient::new_with_options(
+                Cluster::Custom(self.rpc_url.clone(), self.rpc_url.clone()),
+                self.payer.clone(),
+                CommitmentConfig::confirmed(),
+            );
+            client.program(klend::ID).unwrap()
+        })
     }
 
     pub fn payer(&self) -> &Keypair {
@@ -45,10 +58,76 @@ impl KlendClient {
         self.payer.pubkey()
     }
 
-    pub fn send_and_confirm(&self, tx_name: &str, signature: Signature) -> Result<Signature> {
+    /// Fetches an obligation along with its extension, if any.
+    pub fn get_obligation(&self, obligation: &Pubkey) -> Result<ObligationPositions> {
+        let data = self.program().rpc().get_account_data(obligation)?;
+        decode_obligation(&data)
+    }
+
+    pub fn get_reserve(&self, reserve: &Pubkey) -> Result<klend::Reserve> {
+        Ok(self.program().account::<klend::Reserve>(*reserve)?)
+    }
+
+    /// Fetches every reserve to refresh before acting on an obligation: its deposit and borrow
+    /// reserves, with the reserve of the action last as the program requires.
+    pub fn get_obligation_refresh_reserves(
+        &self,
+        obligation_state: &ObligationPositions,
+        action_reserve: &Pubkey,
+    ) -> Result<Vec<(Pubkey, klend::Reserve)>> {
+        obligation_state
+            .refresh_reserves(action_reserve)
+            .into_iter()
+            .map(|reserve| Ok((reserve, self.get_reserve(&reserve)?)))
+            .collect()
+    }
+
+    /// Fetches the deposit and borrow reserves of an obligation, each once.
+    pub fn get_obligation_reserves(
+        &self,
+        obligation_state: &ObligationPositions,
+    ) -> Result<Vec<(Pubkey, klend::Reserve)>> {
+        let mut reserves: Vec<(Pubkey, klend::Reserve)> = Vec::new();
+        for reserve in obligation_state
+            .deposit_reserves()
+            .into_iter()
+            .chain(obligation_state.borrow_reserves())
+        {
+            if !reserves.iter().any(|(fetched, _)| *fetched == reserve) {
+                reserves.push((reserve, self.get_reserve(&reserve)?));
+            }
+        }
+        Ok(reserves)
+    }
+
+    /// Sends the instructions in a single transaction signed by the payer and `signers`.
+    pub fn send_instructions(
+        &self,
+        tx_name: &str,
+        ixs: Vec<Instruction>,
+        signers: &[&Keypair],
+    ) -> Result<Signature> {
         println!("Sending transaction: {}", tx_name);
-        let signature = self.program.rpc().confirm_transaction(&signature)?;
+        let mut request = self.program().request();
+        for ix in ixs {
+            request = request.instruction(ix);
+        }
+        for signer in signers {
+            request = request.signer(*signer);
+        }
+        let signature = request.send()?;
         println!("Transaction confirmed: {}", signature);
         Ok(signature)
     }
-} 
\ No newline at end of file
+}
+
+pub(crate) fn klend_instruction(
+    accounts: impl ToAccountMetas,
+    args: impl InstructionData,
+) -> Instruction {
+    Instruction {
+        program_id: klend::ID,
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
@@ -1,8 +1,9 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
-use klend_client::{KlendClient, klend};
+use klend_client::{borrow_rate_curve, KlendClient, klend};
 use solana_sdk::{
     signature::{read_keypair_file, Keypair},
+    signer::Signer,
     pubkey::Pubkey,
 };
 use std::str::FromStr;
@@ -13,8 +14,9 @@ struct Cli {
     #[clap(short, long, default_value = "https://api.mainnet-beta.solana.com")]
     rpc_url: String,
     
+    /// Required by the commands sending transactions
     #[clap(short, long)]
-    keypair_path: String,
+    keypair_path: Option<String>,
     
     #[clap(subcommand)]
     command: Commands,
@@ -22,6 +24,32 @@ struct Cli {
 
 #[derive(Subcommand)]
 enum Commands {
+    #[clap(flatten)]
+    Client(ClientCommands),
+
+    /// Print a borrow rate curve and the matching supply rates
+    BorrowRateCurve {
+        /// Comma separated utilization_bps:rate_bps points, e.g. 0:0,8000:1000,10000:5000
+        #[clap(long)]
+        points: String,
+
+        #[clap(long, default_value = "0")]
+        protocol_take_rate_pct: u8,
+
+        #[clap(long, default_value = "0")]
+        host_fixed_interest_rate_bps: u16,
+
+        #[clap(long, default_value = "500")]
+        step_bps: u32,
+
+        #[clap(long)]
+        csv: bool,
+    },
+}
+
+// Commands sending transactions or reading accounts through the RPC client
+#[derive(Subcommand)]
+enum ClientCommands {
     /// Initialize a new lending market
     InitLendingMarket {
         #[clap(long)]
@@ -35,9 +63,6 @@ enum Commands {
         
         #[clap(long)]
         liquidity_mint: String,
-        
-        #[clap(long)]
-        liquidity_amount: u64,
     },
     
     /// Initialize a new obligation
@@ -94,6 +119,110 @@ enum Commands {
         amount: u64,
     },
     
+    /// Close an empty obligation and reclaim its rent
+    CloseObligation {
+        #[clap(long)]
+        obligation: String,
+        
+        /// Reserves the obligation used, their farm user states are checked, repeat for each reserve
+        #[clap(long)]
+        reserve: Vec<String>,
+    },
+    
+    /// Switch an obligation to the extended layout with more position slots
+    ExtendObligation {
+        #[clap(long)]
+        obligation: String,
+    },
+    
+    /// Propose a new owner for an obligation, signed by the current owner
+    ProposeObligationOwner {
+        #[clap(long)]
+        obligation: String,
+        
+        #[clap(long)]
+        new_owner: String,
+    },
+    
+    /// Accept a proposed obligation ownership transfer, signed by the new owner
+    UpdateObligationOwner {
+        #[clap(long)]
+        obligation: String,
+    },
+    
+    /// Set or revoke (with the default pubkey) the delegate of an obligation
+    UpdateObligationDelegate {
+        #[clap(long)]
+        obligation: String,
+        
+        #[clap(long)]
+        delegate: String,
+        
+        /// Bitmask of delegate permissions: 2 deposit, 4 rebalance
+        #[clap(long, default_value = "0")]
+        permissions: u8,
+        
+        #[clap(long, default_value = "0")]
+        max_ltv_pct: u8,
+    },
+    
+    /// Issue an obligation permission to an owner in a permissioned market
+    InitObligationPermission {
+        #[clap(long)]
+        lending_market: String,
+        
+        #[clap(long)]
+        owner: String,
+    },
+    
+    /// Revoke or reinstate the obligation permission of an owner
+    UpdateObligationPermission {
+        #[clap(long)]
+        lending_market: String,
+        
+        #[clap(long)]
+        owner: String,
+        
+        #[clap(long)]
+        revoked: bool,
+    },
+    
+    /// Execute a queued config change whose timelock has elapsed
+    ExecuteConfigChange {
+        #[clap(long)]
+        pending_config_change: String,
+    },
+    
+    /// Cancel a queued config change, signed by the market owner or risk council
+    CancelConfigChange {
+        #[clap(long)]
+        pending_config_change: String,
+    },
+    
+    /// Lock cTokens in the withdraw queue of a reserve until liquidity comes back
+    EnqueueWithdrawal {
+        #[clap(long)]
+        reserve: String,
+        
+        #[clap(long)]
+        collateral_amount: u64,
+    },
+    
+    /// Cancel a withdraw queue ticket and get back its unfilled cTokens
+    CancelWithdrawal {
+        #[clap(long)]
+        reserve: String,
+        
+        #[clap(long)]
+        ticket_id: u64,
+    },
+    
+    /// Claim the liquidity of the filled withdraw queue tickets
+    ClaimWithdrawal {
+        #[clap(long)]
+        reserve: String,
+    },
+    
     /// Refresh a reserve
     RefreshReserve {
         #[clap(long)]
@@ -103,12 +232,34 @@ enum Commands {
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
-    
-    let keypair = read_keypair_file(&cli.keypair_path)?;
-    let client = KlendClient::new(&cli.rpc_url, keypair);
-    
+
     match cli.command {
-        Commands::InitLendingMarket { quote_currency } => {
+        Commands::BorrowRateCurve { points, protocol_take_rate_pct, host_fixed_interest_rate_bps, step_bps, csv } => {
+            let curve = borrow_rate_curve::parse_curve_points(&points)?;
+            let rows = borrow_rate_curve::curve_table(&curve, protocol_take_rate_pct, host_fixed_interest_rate_bps, step_bps)?;
+            if csv {
+                print!("{}", borrow_rate_curve::render_csv(&rows));
+            } else {
+                print!("{}", borrow_rate_curve::render_table(&rows));
+            }
+        },
+
+        Commands::Client(command) => {
+            let keypair_path = cli.keypair_path
+                .ok_or_else(|| anyhow::anyhow!("--keypair-path is required for this command"))?;
+            let keypair = read_keypair_file(&keypair_path)
+                .map_err(|e| anyhow::anyhow!("Failed to read keypair {}: {e}", keypair_path))?;
+            let client = KlendClient::new(&cli.rpc_url, keypair);
+            run_client_command(&client, command)?;
+        },
+    }
+
+    Ok(())
+}
+
+fn run_client_command(client: &KlendClient, command: ClientCommands) -> Result<()> {
+    match command {
+        ClientCommands::InitLendingMarket { quote_currency } => {
             let quote_currency_bytes = if quote_currency.len() <= 32 {
                 let mut bytes = [0u8; 32];
                 bytes[..quote_currency.len()].copy_from_slice(quote_currency.as_bytes());
@@ -117,19 +268,24 @@ fn main() -> Result<()> {
                 return Err(anyhow::anyhow!("Quote currency string too long"));
             };
             
-            let signature = client.init_lending_market(quote_currency_bytes)?;
-            println!("Initialized lending market: {}", signature);
+            let lending_market = Keypair::new();
+            let ixs = client.init_lending_market(&lending_market.pubkey(), quote_currency_bytes);
+            let signature = client.send_instructions("init_lending_market", ixs, &[&lending_market])?;
+            println!("Initialized lending market {}: {}", lending_market.pubkey(), signature);
         },
         
-        Commands::InitReserve { lending_market, liquidity_mint, liquidity_amount } => {
+        ClientCommands::InitReserve { lending_market, liquidity_mint } => {
             let lending_market = Pubkey::from_str(&lending_market)?;
             let liquidity_mint = Pubkey::from_str(&liquidity_mint)?;
+            let liquidity_token_program = client.program().rpc().get_account(&liquidity_mint)?.owner;
             
-            let signature = client.init_reserve(&lending_market, &liquidity_mint, liquidity_amount)?;
-            println!("Initialized reserve: {}", signature);
+            let reserve = Keypair::new();
+            let ixs = client.init_reserve(&lending_market, &reserve.pubkey(), &liquidity_mint, &liquidity_token_program);
+            let signature = client.send_instructions("init_reserve", ixs, &[&reserve])?;
+            println!("Initialized reserve {}: {}", reserve.pubkey(), signature);
         },
         
-        Commands::InitObligation { lending_market, owner, tag, id, seed1, seed2 } => {
+        ClientCommands::InitObligation { lending_market, owner, tag, id, seed1, seed2 } => {
             let lending_market = Pubkey::from_str(&lending_market)?;
             let owner = match owner {
                 Some(owner) => Pubkey::from_str(&owner)?,
@@ -146,37 +302,181 @@ fn main() -> Result<()> {
                 None => Pubkey::default(),
             };
             
-            let signature = client.init_obligation(&lending_market, &owner, tag, id, &seed1, &seed2)?;
+            let market_state = client.program().account::<klend::LendingMarket>(lending_market)?;
+            let obligation_permission = client.obligation_permission_account(&lending_market, &market_state, &owner);
+            let ix = client.init_obligation(&lending_market, &owner, klend::InitObligationArgs { tag, id }, &seed1, &seed2, obligation_permission);
+            let signature = client.send_instructions("init_obligation", vec![ix], &[])?;
             println!("Initialized obligation: {}", signature);
         },
         
-        Commands::DepositReserveLiquidity { reserve, amount } => {
+        ClientCommands::DepositReserveLiquidity { reserve, amount } => {
             let reserve = Pubkey::from_str(&reserve)?;
+            let reserve_state = client.get_reserve(&reserve)?;
             
-            let signature = client.deposit_reserve_liquidity(&reserve, amount)?;
+            let ixs = vec![
+                client.refresh_reserve(&reserve, &reserve_state),
+                client.deposit_reserve_liquidity(&reserve, &reserve_state, amount),
+            ];
+            let signature = client.send_instructions("deposit_reserve_liquidity", ixs, &[])?;
             println!("Deposited liquidity: {}", signature);
         },
         
-        Commands::BorrowObligationLiquidity { obligation, reserve, amount } => {
+        ClientCommands::BorrowObligationLiquidity { obligation, reserve, amount } => {
             let obligation = Pubkey::from_str(&obligation)?;
             let reserve = Pubkey::from_str(&reserve)?;
+            let obligation_state = client.get_obligation(&obligation)?;
+            let reserves = client.get_obligation_refresh_reserves(&obligation_state, &reserve)?;
+            let (_, reserve_state) = reserves.last().unwrap();
+            let lending_market = reserve_state.lending_market;
+            let market_state = client.program().account::<klend::LendingMarket>(lending_market)?;
+            let obligation_permission = client.obligation_permission_account(&lending_market, &market_state, &obligation_state.obligation.owner);
             
-            let signature = client.borrow_obligation_liquidity(&obligation, &reserve, amount)?;
+            let ix = client.borrow_obligation_liquidity(&obligation, &obligation_state.obligation.owner, &reserve, reserve_state, amount, obligation_permission);
+            let ixs = client.with_obligation_refresh(&obligation, &obligation_state, &reserves, ix);
+            let signature = client.send_instructions("borrow_obligation_liquidity", ixs, &[])?;
             println!("Borrowed liquidity: {}", signature);
         },
         
-        Commands::RepayObligationLiquidity { obligation, reserve, amount } => {
+        ClientCommands::RepayObligationLiquidity { obligation, reserve, amount } => {
             let obligation = Pubkey::from_str(&obligation)?;
             let reserve = Pubkey::from_str(&reserve)?;
+            let obligation_state = client.get_obligation(&obligation)?;
+            let reserves = client.get_obligation_refresh_reserves(&obligation_state, &reserve)?;
+            let (_, reserve_state) = reserves.last().unwrap();
             
-            let signature = client.repay_obligation_liquidity(&obligation, &reserve, amount)?;
+            let ix = client.repay_obligation_liquidity(&obligation, &reserve, reserve_state, amount);
+            let ixs = client.with_obligation_refresh(&obligation, &obligation_state, &reserves, ix);
+            let signature = client.send_instructions("repay_obligation_liquidity", ixs, &[])?;
             println!("Repaid liquidity: {}", signature);
         },
         
-        Commands::RefreshReserve { reserve } => {
+        ClientCommands::CloseObligation { obligation, reserve } => {
+            let obligation = Pubkey::from_str(&obligation)?;
+            let reserves = reserve
+                .iter()
+                .map(|reserve| {
+                    let reserve = Pubkey::from_str(reserve)?;
+                    Ok((reserve, client.get_reserve(&reserve)?))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let obligation_state = client.get_obligation(&obligation)?;
+            
+            let ix = client.close_obligation(&obligation, &obligation_state, &reserves)?;
+            let signature = client.send_instructions("close_obligation", vec![ix], &[])?;
+            println!("Closed obligation: {}", signature);
+        },
+        
+        ClientCommands::ExtendObligation { obligation } => {
+            let obligation = Pubkey::from_str(&obligation)?;
+            let obligation_state = client.get_obligation(&obligation)?;
+            
+            let ix = client.extend_obligation(&obligation, &obligation_state)?;
+            let signature = client.send_instructions("extend_obligation", vec![ix], &[])?;
+            println!("Extended obligation: {}", signature);
+        },
+        
+        ClientCommands::ProposeObligationOwner { obligation, new_owner } => {
+            let obligation = Pubkey::from_str(&obligation)?;
+            let new_owner = Pubkey::from_str(&new_owner)?;
+            let obligation_state = client.get_obligation(&obligation)?;
+            let reserves = client.get_obligation_reserves(&obligation_state)?;
+            
+            let ix = client.propose_obligation_owner(&obligation, &obligation_state, &reserves, &new_owner)?;
+            let signature = client.send_instructions("propose_obligation_owner", vec![ix], &[])?;
+            println!("Proposed obligation owner: {}", signature);
+        },
+        
+        ClientCommands::UpdateObligationOwner { obligation } => {
+            let obligation = Pubkey::from_str(&obligation)?;
+            let obligation_state = client.get_obligation(&obligation)?;
+            let reserves = client.get_obligation_reserves(&obligation_state)?;
+            
+            let ix = client.update_obligation_owner(&obligation, &obligation_state, &reserves)?;
+            let signature = client.send_instructions("update_obligation_owner", vec![ix], &[])?;
+            println!("Updated obligation owner: {}", signature);
+        },
+        
+        ClientCommands::UpdateObligationDelegate { obligation, delegate, permissions, max_ltv_pct } => {
+            let obligation = Pubkey::from_str(&obligation)?;
+            let delegate = Pubkey::from_str(&delegate)?;
+            let permissions = klend::ObligationDelegatePermissions::from_bits(permissions)
+                .ok_or_else(|| anyhow::anyhow!("Invalid delegate permissions {permissions}"))?;
+            let obligation_state = client.get_obligation(&obligation)?.obligation;
+            
+            let ix = client.update_obligation_delegate(&obligation, &obligation_state, &delegate, permissions, max_ltv_pct);
+            let signature = client.send_instructions("update_obligation_delegate", vec![ix], &[])?;
+            println!("Updated obligation delegate: {}", signature);
+        },
+        
+        ClientCommands::InitObligationPermission { lending_market, owner } => {
+            let lending_market = Pubkey::from_str(&lending_market)?;
+            let owner = Pubkey::from_str(&owner)?;
+            
+            let ix = client.init_obligation_permission(&lending_market, &owner);
+            let signature = client.send_instructions("init_obligation_permission", vec![ix], &[])?;
+            println!("Issued obligation permission: {}", signature);
+        },
+        
+        ClientCommands::UpdateObligationPermission { lending_market, owner, revoked } => {
+            let lending_market = Pubkey::from_str(&lending_market)?;
+            let owner = Pubkey::from_str(&owner)?;
+            
+            let ix = client.update_obligation_permission(&lending_market, &owner, revoked);
+            let signature = client.send_instructions("update_obligation_permission", vec![ix], &[])?;
+            println!("Updated obligation permission: {}", signature);
+        },
+        
+        ClientCommands::ExecuteConfigChange { pending_config_change } => {
+            let pending_config_change = Pubkey::from_str(&pending_config_change)?;
+            let pending_state = client.program().account::<klend::PendingConfigChange>(pending_config_change)?;
+            
+            let ix = client.execute_config_change(&pending_config_change, &pending_state)?;
+            let signature = client.send_instructions("execute_config_change", vec![ix], &[])?;
+            println!("Executed config change: {}", signature);
+        },
+        
+        ClientCommands::CancelConfigChange { pending_config_change } => {
+            let pending_config_change = Pubkey::from_str(&pending_config_change)?;
+            let pending_state = client.program().account::<klend::PendingConfigChange>(pending_config_change)?;
+            
+            let ix = client.cancel_config_change(&pending_config_change, &pending_state);
+            let signature = client.send_instructions("cancel_config_change", vec![ix], &[])?;
+            println!("Cancelled config change: {}", signature);
+        },
+        
+        ClientCommands::EnqueueWithdrawal { reserve, collateral_amount } => {
+            let reserve = Pubkey::from_str(&reserve)?;
+            let reserve_state = client.get_reserve(&reserve)?;
+            
+            let ix = client.enqueue_withdrawal(&reserve, &reserve_state, collateral_amount);
+            let signature = client.send_instructions("enqueue_withdrawal", vec![ix], &[])?;
+            println!("Enqueued withdrawal: {}", signature);
+        },
+        
+        ClientCommands::CancelWithdrawal { reserve, ticket_id } => {
+            let reserve = Pubkey::from_str(&reserve)?;
+            let reserve_state = client.get_reserve(&reserve)?;
+            
+            let ix = client.cancel_withdrawal(&reserve, &reserve_state, ticket_id);
+            let signature = client.send_instructions("cancel_withdrawal", vec![ix], &[])?;
+            println!("Cancelled withdrawal: {}", signature);
+        },
+        
+        ClientCommands::ClaimWithdrawal { reserve } => {
+            let reserve = Pubkey::from_str(&reserve)?;
+            let reserve_state = client.get_reserve(&reserve)?;
+            
+            let ix = client.claim_withdrawal(&reserve, &reserve_state);
+            let signature = client.send_instructions("claim_withdrawal", vec![ix], &[])?;
+            println!("Claimed withdrawal: {}", signature);
+        },
+        
+        ClientCommands::RefreshReserve { reserve } => {
             let reserve = Pubkey::from_str(&reserve)?;
+            let reserve_state = client.get_reserve(&reserve)?;
             
-            let signature = client.refresh_reserve(&reserve)?;
+            let ix = client.refresh_reserve(&reserve, &reserve_state);
+            let signature = client.send_instructions("refresh_reserve", vec![ix], &[])?;
             println!("Refreshed reserve: {}", signature);
         },
     }
@@ -0,0 +1,82 @@
+use anchor_lang::{prelude::Pubkey, Discriminator};
+use anyhow::{bail, Result};
+use klend::{
+    utils::{OBLIGATION_EXTENSION_SIZE, OBLIGATION_SIZE},
+    Obligation, ObligationExtension, ObligationRef, ObligationView,
+};
+
+/// Obligation decoded from raw account data, with its extension when the obligation uses the
+/// extended layout.
+#[derive(Debug, Clone, Copy)]
+pub struct ObligationPositions {
+    pub obligation: Obligation,
+    pub extension: Option<ObligationExtension>,
+}
+
+impl ObligationPositions {
+    /// View over the positions of both layouts, indexed the same way as on-chain.
+    pub fn view(&self) -> ObligationRef<'_> {
+        ObligationView::new(&self.obligation, self.extension.as_ref())
+    }
+
+    pub fn deposit_reserves(&self) -> Vec<Pubkey> {
+        self.view()
+            .deposits()
+            .map(|deposit| deposit.deposit_reserve)
+            .filter(|reserve| *reserve != Pubkey::default())
+            .collect()
+    }
+
+    pub fn borrow_reserves(&self) -> Vec<Pubkey> {
+        self.view()
+            .borrows()
+            .map(|borrow| borrow.borrow_reserve)
+            .filter(|reserve| *reserve != Pubkey::default())
+            .collect()
+    }
+
+    /// Reserves to refresh before an action on `action_reserve`, each once and the action
+    /// reserve last.
+    pub fn refresh_reserves(&self, action_reserve: &Pubkey) -> Vec<Pubkey> {
+        let mut reserves: Vec<Pubkey> = Vec::new();
+        for reserve in self
+            .deposit_reserves()
+            .into_iter()
+            .chain(self.borrow_reserves())
+        {
+            if reserve != *action_reserve && !reserves.contains(&reserve) {
+                reserves.push(reserve);
+            }
+        }
+        reserves.push(*action_reserve);
+        reserves
+    }
+}
+
+pub fn decode_obligation(data: &[u8]) -> Result<ObligationPositions> {
+    if data.len() < 8 + OBLIGATION_SIZE || data[..8] != Obligation::discriminator() {
+        bail!("Account data is not an obligation");
+    }
+    let obligation: Obligation = bytemuck::pod_read_unaligned(&data[8..8 + OBLIGATION_SIZE]);
+
+    let extension = if obligation.is_extended() {
+        let extension_data = &data[8 + OBLIGATION_SIZE..];
+        if extension_data.len() < OBLIGATION_EXTENSION_SIZE {
+            bail!(
+                "Extended obligation data is {} bytes, expected {}",
+                data.len(),
+                8 + OBLIGATION_SIZE + OBLIGATION_EXTENSION_SIZE
+            );
+        }
+        Some(bytemuck::pod_read_unaligned(
+            &extension_data[..OBLIGATION_EXTENSION_SIZE],
+        ))
+    } else {
+        None
+    };
+
+    Ok(ObligationPositions {
+        obligation,
+        extension,
+    })
+}
@@ -0,0 +1,65 @@
+use anyhow::{anyhow, Result};
+use klend::{
+    fraction::{BigFraction, Fraction},
+    utils::SLOTS_PER_YEAR,
+    RateHistorySample, Reserve,
+};
+
+use crate::borrow_rate_curve::apr_to_apy;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateHistoryPoint {
+    pub slot: u64,
+    pub utilization_rate_bps: u32,
+    pub borrow_apr: f64,
+    pub borrow_apy: f64,
+    pub supply_apr: f64,
+    pub supply_apy: f64,
+    /// Borrow APY actually compounded since the previous sample, from `cumulative_borrow_rate_bsf`.
+    pub realized_borrow_apy: Option<f64>,
+}
+
+/// Decodes the reserve's rate history ring buffer into a time series, oldest sample first.
+///
+/// Supply rates are derived with the reserve's current `protocol_take_rate_pct` and
+/// `host_fixed_interest_rate_bps`, the same split as `borrow_rate_curve::curve_table`.
+pub fn rate_history(reserve: &Reserve) -> Result<Vec<RateHistoryPoint>> {
+    let host_fixed_rate = f64::from(reserve.config.host_fixed_interest_rate_bps) / 10_000.0;
+    let supplier_share = 1.0 - f64::from(reserve.config.protocol_take_rate_pct) / 100.0;
+
+    let mut previous: Option<&RateHistorySample> = None;
+    reserve
+        .rate_history
+        .samples()
+        .map(|sample| {
+            let variable_rate = f64::from(sample.borrow_rate_bps) / 10_000.0;
+            let utilization_rate = f64::from(sample.utilization_rate_bps) / 10_000.0;
+            let borrow_apr = variable_rate + host_fixed_rate;
+            let supply_apr = variable_rate * utilization_rate * supplier_share;
+            let realized_borrow_apy = previous
+                .map(|previous| realized_apy(previous, sample))
+                .transpose()?;
+            previous = Some(sample);
+
+            Ok(RateHistoryPoint {
+                slot: sample.slot,
+                utilization_rate_bps: sample.utilization_rate_bps,
+                borrow_apr,
+                borrow_apy: apr_to_apy(borrow_apr),
+                supply_apr,
+                supply_apy: apr_to_apy(supply_apr),
+                realized_borrow_apy,
+            })
+        })
+        .collect()
+}
+
+fn realized_apy(previous: &RateHistorySample, sample: &RateHistorySample) -> Result<f64> {
+    let slots_elapsed = sample.slot - previous.slot;
+    let growth = BigFraction::from_bits(sample.cumulative_borrow_rate_bsf)
+        / BigFraction::from_bits(previous.cumulative_borrow_rate_bsf);
+    let growth: f64 = Fraction::try_from(growth)
+        .map_err(|e| anyhow!("Cumulative borrow rate growth overflow: {e:?}"))?
+        .to_num();
+    Ok((growth.ln() * SLOTS_PER_YEAR as f64 / slots_elapsed as f64).exp_m1())
+}
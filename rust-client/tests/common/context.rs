@@ -0,0 +1,132 @@
+use anchor_lang::{prelude::Pubkey, AccountDeserialize, ZeroCopy};
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    clock::Clock,
+    instruction::Instruction,
+    signature::Keypair,
+    signer::Signer,
+    system_instruction,
+    transaction::Transaction,
+};
+
+pub const KLEND_PROGRAM_NAME: &str = "kamino_lending";
+
+pub struct KlendTestContext {
+    pub context: ProgramTestContext,
+}
+
+impl KlendTestContext {
+    pub async fn new() -> Self {
+        if std::env::var("SBF_OUT_DIR").is_err() {
+            std::env::set_var(
+                "SBF_OUT_DIR",
+                concat!(env!("CARGO_MANIFEST_DIR"), "/../target/deploy"),
+            );
+        }
+
+        let mut program_test = ProgramTest::new(KLEND_PROGRAM_NAME, klend::ID, None);
+        program_test.prefer_bpf(true);
+        program_test.set_compute_max_units(1_400_000);
+
+        let context = program_test.start_with_context().await;
+
+        Self { context }
+    }
+
+    pub fn payer(&self) -> &Keypair {
+        &self.context.payer
+    }
+
+    pub fn payer_pubkey(&self) -> Pubkey {
+        self.context.payer.pubkey()
+    }
+
+    pub async fn process(
+        &mut self,
+        ixs: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), BanksClientError> {
+        // A fresh blockhash per transaction, so retrying an identical transaction is not
+        // deduplicated by the bank
+        let recent_blockhash = self.context.get_new_latest_blockhash().await.unwrap();
+
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend(signers.iter().filter(|s| s.pubkey() != self.payer_pubkey()));
+
+        let tx = Transaction::new_signed_with_payer(
+            ixs,
+            Some(&self.payer_pubkey()),
+            &all_signers,
+            recent_blockhash,
+        );
+
+        self.context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn create_program_account(
+        &mut self,
+        account: &Keypair,
+        space: usize,
+    ) -> Result<(), BanksClientError> {
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let ix = system_instruction::create_account(
+            &self.payer_pubkey(),
+            &account.pubkey(),
+            rent.minimum_balance(space),
+            space as u64,
+            &klend::ID,
+        );
+        self.process(&[ix], &[account]).await
+    }
+
+    pub async fn get_account(&mut self, address: &Pubkey) -> Option<Account> {
+        self.context
+            .banks_client
+            .get_account(*address)
+            .await
+            .unwrap()
+    }
+
+    pub async fn get_anchor_account<T: AccountDeserialize>(&mut self, address: &Pubkey) -> T {
+        let account = self
+            .get_account(address)
+            .await
+            .unwrap_or_else(|| panic!("Account {address} not found"));
+        T::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    pub async fn get_zero_copy_account<T: ZeroCopy>(&mut self, address: &Pubkey) -> T {
+        let account = self
+            .get_account(address)
+            .await
+            .unwrap_or_else(|| panic!("Account {address} not found"));
+        let size = std::mem::size_of::<T>();
+        bytemuck::pod_read_unaligned(&account.data[8..8 + size])
+    }
+
+    pub fn set_account(&mut self, address: &Pubkey, account: Account) {
+        self.context
+            .set_account(address, &AccountSharedData::from(account));
+    }
+
+    pub async fn get_clock(&mut self) -> Clock {
+        self.context
+            .banks_client
+            .get_sysvar::<Clock>()
+            .await
+            .unwrap()
+    }
+
+    pub async fn warp_slots(&mut self, slots: u64) {
+        let clock = self.get_clock().await;
+        self.context.warp_to_slot(clock.slot + slots).unwrap();
+    }
+
+    pub async fn warp_seconds(&mut self, seconds: i64) {
+        self.warp_slots(1).await;
+        let mut clock = self.get_clock().await;
+        clock.unix_timestamp += seconds;
+        self.context.set_sysvar(&clock);
+    }
+}
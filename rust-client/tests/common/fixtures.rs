@@ -0,0 +1,376 @@
+use anchor_lang::{prelude::Pubkey, AnchorSerialize};
+use klend::{
+    state::{ReserveConfig, ReserveFees, ReserveStatus},
+    utils::{borrow_rate_curve::BorrowRateCurve, seeds, NULL_PUBKEY},
+    InitObligationArgs, LendingMarket, Reserve, UpdateConfigMode,
+};
+use klend_client::{
+    instructions::obligation_address,
+    obligation::{decode_obligation, ObligationPositions},
+    KlendClient,
+};
+use solana_program_test::BanksClientError;
+use solana_sdk::{
+    instruction::Instruction, program_pack::Pack, signature::Keypair, signer::Signer,
+    system_instruction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account,
+};
+
+use super::KlendTestContext;
+
+#[derive(Debug, Clone, Copy)]
+pub struct MarketFixture {
+    pub lending_market: Pubkey,
+    pub owner: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ReserveFixture {
+    pub reserve: Pubkey,
+    pub lending_market: Pubkey,
+    pub liquidity_mint: Pubkey,
+    pub liquidity_supply: Pubkey,
+    pub fee_receiver: Pubkey,
+    pub collateral_mint: Pubkey,
+    pub collateral_supply: Pubkey,
+    pub pyth_oracle: Pubkey,
+    pub decimals: u8,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ObligationFixture {
+    pub obligation: Pubkey,
+    pub lending_market: Pubkey,
+    pub owner: Pubkey,
+    pub obligation_permission: Option<Pubkey>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReserveSpec {
+    pub symbol: &'static str,
+    pub decimals: u8,
+    pub price: i64,
+    pub price_exp: u32,
+    pub loan_to_value_pct: u8,
+    pub liquidation_threshold_pct: u8,
+    pub borrow_rate_curve: BorrowRateCurve,
+    pub deposit_limit: u64,
+    pub borrow_limit: u64,
+}
+
+impl Default for ReserveSpec {
+    fn default() -> Self {
+        Self {
+            symbol: "USDC",
+            decimals: 6,
+            price: 1_000_000,
+            price_exp: 6,
+            loan_to_value_pct: 75,
+            liquidation_threshold_pct: 85,
+            borrow_rate_curve: BorrowRateCurve::new_flat(500),
+            deposit_limit: u64::MAX,
+            borrow_limit: u64::MAX,
+        }
+    }
+}
+
+impl ReserveSpec {
+    pub fn to_reserve_config(&self, pyth_oracle: &Pubkey) -> ReserveConfig {
+        let mut config = ReserveConfig {
+            status: ReserveStatus::Active.into(),
+            loan_to_value_pct: self.loan_to_value_pct,
+            liquidation_threshold_pct: self.liquidation_threshold_pct,
+            min_liquidation_bonus_bps: 200,
+            max_liquidation_bonus_bps: 500,
+            bad_debt_liquidation_bonus_bps: 10,
+            protocol_take_rate_pct: 10,
+            protocol_liquidation_fee_pct: 10,
+            borrow_factor_pct: 100,
+            fees: ReserveFees::default(),
+            borrow_rate_curve: self.borrow_rate_curve,
+            deposit_limit: self.deposit_limit,
+            borrow_limit: self.borrow_limit,
+            borrow_limit_outside_elevation_group: u64::MAX,
+            ..Default::default()
+        };
+
+        let token_info = &mut config.token_info;
+        token_info.name[..self.symbol.len()].copy_from_slice(self.symbol.as_bytes());
+        token_info.max_age_price_seconds = 60;
+        token_info.max_age_twap_seconds = 120;
+        token_info.scope_configuration.price_feed = NULL_PUBKEY;
+        token_info.pyth_configuration.price = *pyth_oracle;
+
+        config
+    }
+}
+
+impl KlendTestContext {
+    pub async fn create_mint(&mut self, decimals: u8) -> Pubkey {
+        let mint = Keypair::new();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let ixs = [
+            system_instruction::create_account(
+                &self.payer_pubkey(),
+                &mint.pubkey(),
+                rent.minimum_balance(spl_token::state::Mint::LEN),
+                spl_token::state::Mint::LEN as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_mint(
+                &spl_token::ID,
+                &mint.pubkey(),
+                &self.payer_pubkey(),
+                None,
+                decimals,
+            )
+            .unwrap(),
+        ];
+        self.process(&ixs, &[&mint]).await.unwrap();
+        mint.pubkey()
+    }
+
+    pub async fn create_ata(&mut self, owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+        let ata = get_associated_token_address(owner, mint);
+        if self.get_account(&ata).await.is_none() {
+            let ix =
+                create_associated_token_account(&self.payer_pubkey(), owner, mint, &spl_token::ID);
+            self.process(&[ix], &[]).await.unwrap();
+        }
+        ata
+    }
+
+    pub async fn mint_to(&mut self, mint: &Pubkey, owner: &Pubkey, amount: u64) -> Pubkey {
+        let ata = self.create_ata(owner, mint).await;
+        let ix = spl_token::instruction::mint_to(
+            &spl_token::ID,
+            mint,
+            &ata,
+            &self.payer_pubkey(),
+            &[],
+            amount,
+        )
+        .unwrap();
+        self.process(&[ix], &[]).await.unwrap();
+        ata
+    }
+
+    pub async fn token_balance(&mut self, token_account: &Pubkey) -> u64 {
+        let account = self.get_account(token_account).await.unwrap();
+        spl_token::state::Account::unpack(&account.data)
+            .unwrap()
+            .amount
+    }
+
+    pub async fn create_funded_user(&mut self, lamports: u64) -> Keypair {
+        let user = Keypair::new();
+        let ix = system_instruction::transfer(&self.payer_pubkey(), &user.pubkey(), lamports);
+        self.process(&[ix], &[]).await.unwrap();
+        user
+    }
+
+    /// Client signing as `signer`, only used to build instructions processed by the banks client.
+    pub fn client(&self, signer: &Keypair) -> KlendClient {
+        KlendClient::new(
+            "http://127.0.0.1:8899",
+            Keypair::from_bytes(&signer.to_bytes()).unwrap(),
+        )
+    }
+
+    pub async fn setup_market(&mut self) -> MarketFixture {
+        let lending_market = Keypair::new();
+        let owner = self.payer_pubkey();
+        let ixs = self
+            .client(self.payer())
+            .init_lending_market(&lending_market.pubkey(), [0; 32]);
+        self.process(&ixs, &[&lending_market]).await.unwrap();
+
+        MarketFixture {
+            lending_market: lending_market.pubkey(),
+            owner,
+        }
+    }
+
+    pub async fn setup_reserve(
+        &mut self,
+        market: &MarketFixture,
+        spec: &ReserveSpec,
+    ) -> ReserveFixture {
+        let lending_market = market.lending_market;
+        let liquidity_mint = self.create_mint(spec.decimals).await;
+
+        let market_state: LendingMarket = self.get_zero_copy_account(&lending_market).await;
+        self.mint_to(
+            &liquidity_mint,
+            &market.owner,
+            market_state.min_initial_deposit_amount,
+        )
+        .await;
+
+        let reserve = Keypair::new();
+        let ixs = self.client(self.payer()).init_reserve(
+            &lending_market,
+            &reserve.pubkey(),
+            &liquidity_mint,
+            &spl_token::ID,
+        );
+        self.process(&ixs, &[&reserve]).await.unwrap();
+
+        let pyth_oracle = Pubkey::new_unique();
+        self.set_pyth_price(&pyth_oracle, spec.price, spec.price_exp)
+            .await;
+
+        let pdas = seeds::pda::init_reserve_pdas(&lending_market, &liquidity_mint);
+        let fixture = ReserveFixture {
+            reserve: reserve.pubkey(),
+            lending_market,
+            liquidity_mint,
+            liquidity_supply: pdas.liquidity_supply_vault,
+            fee_receiver: pdas.fee_vault,
+            collateral_mint: pdas.collateral_ctoken_mint,
+            collateral_supply: pdas.collateral_supply_vault,
+            pyth_oracle,
+            decimals: spec.decimals,
+        };
+
+        self.update_reserve_config(market, &fixture, spec.to_reserve_config(&pyth_oracle))
+            .await
+            .unwrap();
+
+        fixture
+    }
+
+    pub async fn update_reserve_config(
+        &mut self,
+        market: &MarketFixture,
+        reserve: &ReserveFixture,
+        config: ReserveConfig,
+    ) -> Result<(), BanksClientError> {
+        let ix = self.client(self.payer()).update_reserve_config(
+            &market.lending_market,
+            &reserve.reserve,
+            UpdateConfigMode::UpdateEntireReserveConfig,
+            config.try_to_vec().unwrap(),
+        );
+        self.process(&[ix], &[]).await
+    }
+
+    pub async fn setup_obligation(
+        &mut self,
+        market: &MarketFixture,
+        owner: &Keypair,
+    ) -> ObligationFixture {
+        let market_state: LendingMarket = self.get_zero_copy_account(&market.lending_market).await;
+        let client = self.client(owner);
+        let obligation_permission = client.obligation_permission_account(
+            &market.lending_market,
+            &market_state,
+            &owner.pubkey(),
+        );
+        let args = InitObligationArgs { tag: 0, id: 0 };
+        let obligation = obligation_address(
+            &market.lending_market,
+            &owner.pubkey(),
+            &args,
+            &Pubkey::default(),
+            &Pubkey::default(),
+        );
+
+        let ixs = [
+            client.init_user_metadata(&owner.pubkey(), Pubkey::default(), None),
+            client.init_obligation(
+                &market.lending_market,
+                &owner.pubkey(),
+                args,
+                &Pubkey::default(),
+                &Pubkey::default(),
+                obligation_permission,
+            ),
+        ];
+        self.process(&ixs, &[owner]).await.unwrap();
+
+        ObligationFixture {
+            obligation,
+            lending_market: market.lending_market,
+            owner: owner.pubkey(),
+            obligation_permission,
+        }
+    }
+
+    pub async fn refresh_reserve(
+        &mut self,
+        reserve: &ReserveFixture,
+    ) -> Result<(), BanksClientError> {
+        let reserve_state = self.get_reserve(reserve).await;
+        let ix = self
+            .client(self.payer())
+            .refresh_reserve(&reserve.reserve, &reserve_state);
+        self.process(&[ix], &[]).await
+    }
+
+    /// Processes the instruction built by `build_ix` for the reserve state, after refreshing
+    /// the reserve. Signed by `user`.
+    pub async fn process_with_reserve_refresh(
+        &mut self,
+        user: &Keypair,
+        reserve: &ReserveFixture,
+        build_ix: impl FnOnce(&KlendClient, &Reserve) -> Instruction,
+    ) -> Result<(), BanksClientError> {
+        let reserve_state = self.get_reserve(reserve).await;
+        let client = self.client(user);
+        let ixs = [
+            client.refresh_reserve(&reserve.reserve, &reserve_state),
+            build_ix(&client, &reserve_state),
+        ];
+        self.process(&ixs, &[user]).await
+    }
+
+    /// Processes the instruction built by `build_ix` for the state of `action_reserve`, after
+    /// the refreshes of the obligation and its reserves. Signed by `user`.
+    pub async fn process_with_obligation_refresh(
+        &mut self,
+        user: &Keypair,
+        obligation: &ObligationFixture,
+        action_reserve: &ReserveFixture,
+        build_ix: impl FnOnce(&KlendClient, &Reserve) -> Instruction,
+    ) -> Result<(), BanksClientError> {
+        let obligation_state = self.get_obligation_positions(obligation).await;
+        let mut reserves = Vec::new();
+        for reserve in obligation_state.refresh_reserves(&action_reserve.reserve) {
+            let reserve_state: Reserve = self.get_zero_copy_account(&reserve).await;
+            reserves.push((reserve, reserve_state));
+        }
+
+        let client = self.client(user);
+        let (_, action_reserve_state) = reserves.last().unwrap();
+        let ix = build_ix(&client, action_reserve_state);
+        let ixs = client.with_obligation_refresh(
+            &obligation.obligation,
+            &obligation_state,
+            &reserves,
+            ix,
+        );
+        self.process(&ixs, &[user]).await
+    }
+
+    pub async fn get_reserve(&mut self, reserve: &ReserveFixture) -> Reserve {
+        self.get_zero_copy_account(&reserve.reserve).await
+    }
+
+    pub async fn get_obligation(&mut self, obligation: &ObligationFixture) -> klend::Obligation {
+        self.get_zero_copy_account(&obligation.obligation).await
+    }
+
+    pub async fn get_obligation_positions(
+        &mut self,
+        obligation: &ObligationFixture,
+    ) -> ObligationPositions {
+        let account = self
+            .get_account(&obligation.obligation)
+            .await
+            .unwrap_or_else(|| panic!("Obligation {} not found", obligation.obligation));
+        decode_obligation(&account.data).unwrap()
+    }
+}
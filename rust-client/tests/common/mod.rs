@@ -0,0 +1,8 @@
+#![allow(dead_code)]
+
+pub mod context;
+pub mod fixtures;
+pub mod oracles;
+
+pub use context::*;
+pub use fixtures::*;
@@ -0,0 +1,220 @@
+use anchor_lang::{prelude::Pubkey, AccountSerialize, Discriminator, Owner};
+use klend::{fraction::Fraction, utils::SPL_STAKE_POOL_PROGRAM_ID, PriceStatusFlags, Reserve};
+use pyth_solana_receiver_sdk::price_update::{PriceFeedMessage, PriceUpdateV2, VerificationLevel};
+use sbod_itf::accounts::PullFeedAccountData;
+use scope::OraclePrices as ScopePrices;
+use solana_sdk::{
+    account::Account,
+    clock::{Clock, DEFAULT_MS_PER_SLOT},
+};
+
+use super::KlendTestContext;
+
+const SWITCHBOARD_DECIMALS: u32 = 18;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MockPrice {
+    pub price: i64,
+    pub exp: u32,
+    pub conf: u64,
+    pub timestamp: i64,
+}
+
+impl MockPrice {
+    pub fn new(price: i64, exp: u32, timestamp: i64) -> Self {
+        Self {
+            price,
+            exp,
+            conf: 0,
+            timestamp,
+        }
+    }
+
+    pub fn with_conf(self, conf: u64) -> Self {
+        Self { conf, ..self }
+    }
+
+    pub fn with_timestamp(self, timestamp: i64) -> Self {
+        Self { timestamp, ..self }
+    }
+
+    pub fn with_price(self, price: i64) -> Self {
+        Self { price, ..self }
+    }
+
+    pub fn aged(self, seconds: i64) -> Self {
+        Self {
+            timestamp: self.timestamp - seconds,
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MockOracle {
+    pub price: MockPrice,
+    pub twap: MockPrice,
+}
+
+impl MockOracle {
+    pub fn new(price: i64, exp: u32, timestamp: i64) -> Self {
+        let price = MockPrice::new(price, exp, timestamp);
+        Self { price, twap: price }
+    }
+
+    pub fn with_twap(self, twap: MockPrice) -> Self {
+        Self { twap, ..self }
+    }
+}
+
+pub fn pyth_price_update_account(oracle: &MockOracle, clock: &Clock) -> Account {
+    pyth_price_update_account_with_verification(oracle, clock, VerificationLevel::Full)
+}
+
+pub fn pyth_price_update_account_with_verification(
+    oracle: &MockOracle,
+    clock: &Clock,
+    verification_level: VerificationLevel,
+) -> Account {
+    pyth_price_update_account_posted_at(oracle, verification_level, clock.slot)
+}
+
+pub fn pyth_partially_verified_price_update_account(
+    oracle: &MockOracle,
+    clock: &Clock,
+    num_signatures: u8,
+) -> Account {
+    pyth_price_update_account_with_verification(
+        oracle,
+        clock,
+        VerificationLevel::Partial { num_signatures },
+    )
+}
+
+pub fn pyth_price_update_account_posted_at(
+    oracle: &MockOracle,
+    verification_level: VerificationLevel,
+    posted_slot: u64,
+) -> Account {
+    let MockOracle { price, twap } = oracle;
+    let price_update = PriceUpdateV2 {
+        write_authority: Pubkey::default(),
+        verification_level,
+        price_message: PriceFeedMessage {
+            feed_id: [0; 32],
+            price: price.price,
+            conf: price.conf,
+            exponent: -(price.exp as i32),
+            publish_time: price.timestamp,
+            prev_publish_time: price.timestamp,
+            ema_price: twap.price,
+            ema_conf: twap.conf,
+        },
+        posted_slot,
+    };
+
+    let mut data = Vec::with_capacity(PriceUpdateV2::LEN);
+    price_update.try_serialize(&mut data).unwrap();
+    data.resize(PriceUpdateV2::LEN, 0);
+
+    oracle_account(data, pyth_solana_receiver_sdk::ID)
+}
+
+pub fn switchboard_pull_feed_account(price: &MockPrice, clock: &Clock) -> Account {
+    let mut feed: PullFeedAccountData = bytemuck::Zeroable::zeroed();
+
+    let scale = 10_i128.pow(SWITCHBOARD_DECIMALS - price.exp);
+    feed.result.value = i128::from(price.price) * scale;
+    feed.result.std_dev = i128::from(price.conf) * scale;
+    feed.result.num_samples = 1;
+
+    let age_seconds = u64::try_from(clock.unix_timestamp - price.timestamp).unwrap_or(0);
+    let age_slots = age_seconds * 1000 / DEFAULT_MS_PER_SLOT;
+    feed.result.slot = clock.slot.saturating_sub(age_slots).max(1);
+
+    let mut data = PullFeedAccountData::discriminator().to_vec();
+    data.extend_from_slice(bytemuck::bytes_of(&feed));
+
+    oracle_account(data, PullFeedAccountData::owner())
+}
+
+pub fn scope_prices_account(prices: &[(u16, MockPrice)]) -> Account {
+    let mut oracle_prices: ScopePrices = bytemuck::Zeroable::zeroed();
+    for (index, price) in prices {
+        let dated_price = &mut oracle_prices.prices[usize::from(*index)];
+        dated_price.price.value = u64::try_from(price.price).unwrap();
+        dated_price.price.exp = price.exp.into();
+        dated_price.unix_timestamp = u64::try_from(price.timestamp).unwrap();
+        dated_price.index = *index;
+    }
+
+    let mut data = ScopePrices::discriminator().to_vec();
+    data.extend_from_slice(bytemuck::bytes_of(&oracle_prices));
+
+    oracle_account(data, ScopePrices::owner())
+}
+
+pub fn reserve_account_with_price(
+    lending_market: &Pubkey,
+    price: Fraction,
+    clock: &Clock,
+    price_status: PriceStatusFlags,
+) -> Account {
+    let mut reserve = Reserve::default();
+    reserve.lending_market = *lending_market;
+    reserve.liquidity.market_price_sf = price.to_bits();
+    reserve.liquidity.market_price_last_updated_ts = u64::try_from(clock.unix_timestamp).unwrap();
+    reserve.last_update.update_slot(clock.slot, price_status);
+
+    let mut data = Reserve::discriminator().to_vec();
+    data.extend_from_slice(bytemuck::bytes_of(&reserve));
+
+    oracle_account(data, klend::ID)
+}
+
+pub fn stake_pool_account(
+    total_lamports: u64,
+    pool_token_supply: u64,
+    last_update_epoch: u64,
+) -> Account {
+    // Only the SPL stake pool fields read by the program are set
+    let mut data = vec![0; 282];
+    data[0] = 1;
+    data[258..266].copy_from_slice(&total_lamports.to_le_bytes());
+    data[266..274].copy_from_slice(&pool_token_supply.to_le_bytes());
+    data[274..282].copy_from_slice(&last_update_epoch.to_le_bytes());
+
+    oracle_account(data, SPL_STAKE_POOL_PROGRAM_ID)
+}
+
+fn oracle_account(data: Vec<u8>, owner: Pubkey) -> Account {
+    Account {
+        lamports: u32::MAX as u64,
+        data,
+        owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+impl KlendTestContext {
+    pub async fn set_pyth_price(&mut self, oracle: &Pubkey, price: i64, exp: u32) {
+        let clock = self.get_clock().await;
+        let mock = MockOracle::new(price, exp, clock.unix_timestamp);
+        self.set_pyth_oracle(oracle, &mock).await;
+    }
+
+    pub async fn set_pyth_oracle(&mut self, oracle: &Pubkey, mock: &MockOracle) {
+        let clock = self.get_clock().await;
+        self.set_account(oracle, pyth_price_update_account(mock, &clock));
+    }
+
+    pub async fn set_switchboard_feed(&mut self, feed: &Pubkey, price: &MockPrice) {
+        let clock = self.get_clock().await;
+        self.set_account(feed, switchboard_pull_feed_account(price, &clock));
+    }
+
+    pub async fn set_scope_prices(&mut self, oracle_prices: &Pubkey, prices: &[(u16, MockPrice)]) {
+        self.set_account(oracle_prices, scope_prices_account(prices));
+    }
+}
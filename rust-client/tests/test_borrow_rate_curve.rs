@@ -0,0 +1,121 @@
+use klend::{
+    fraction::{Fraction, FractionExtra},
+    utils::borrow_rate_curve::{BorrowRateCurve, CurvePoint},
+};
+use klend_client::borrow_rate_curve::{
+    apr_to_apy, apy_to_apr, curve_from_target_apys, curve_table, kinked_curve, parse_curve_points,
+    render_csv, resample_curve, three_slope_curve,
+};
+
+#[test]
+fn test_kinked_curve_matches_legacy_parameters() {
+    let curve = kinked_curve(100, 8_000, 1_000, 5_000).unwrap();
+    assert_eq!(
+        curve,
+        BorrowRateCurve::from_legacy_parameters(80, 1, 10, 50)
+    );
+
+    assert!(kinked_curve(1_000, 8_000, 500, 5_000).is_err());
+    assert!(kinked_curve(0, 10_001, 500, 5_000).is_err());
+}
+
+#[test]
+fn test_three_slope_curve() {
+    let curve = three_slope_curve(
+        0,
+        CurvePoint::new(7_000, 400),
+        CurvePoint::new(9_000, 1_200),
+        10_000,
+    )
+    .unwrap();
+
+    assert_eq!(
+        curve.get_borrow_rate(Fraction::from_percent(70)).unwrap(),
+        Fraction::from_bps(400)
+    );
+    assert_eq!(
+        curve
+            .get_borrow_rate(Fraction::from_percent(80))
+            .unwrap()
+            .to_bps::<u64>(),
+        Some(800)
+    );
+    assert_eq!(
+        curve.get_borrow_rate(Fraction::ONE).unwrap(),
+        Fraction::from_bps(10_000)
+    );
+}
+
+#[test]
+fn test_resample_valid_curve_is_exact() {
+    let curve = parse_curve_points("0:100,5000:700,8000:1500,10000:30000").unwrap();
+    let resampled = resample_curve(
+        |u| {
+            curve
+                .get_borrow_rate(Fraction::from_bps(u))
+                .unwrap()
+                .to_num::<f64>()
+                * 10_000.0
+        },
+        0.01,
+    )
+    .unwrap();
+
+    assert_eq!(resampled.curve, curve);
+}
+
+#[test]
+fn test_resample_smooth_curve_within_bound() {
+    let quadratic = |u: u32| {
+        let u = f64::from(u) / 10_000.0;
+        100.0 + 20_000.0 * u * u
+    };
+    let resampled = resample_curve(quadratic, 100.0).unwrap();
+    assert!(resampled.max_error_bps <= 100.0);
+    resampled.curve.validate().unwrap();
+
+    assert!(resample_curve(quadratic, 1.0).is_err());
+    assert!(resample_curve(|u| 1_000.0 - f64::from(u) / 100.0, 100.0).is_err());
+}
+
+#[test]
+fn test_apr_apy_round_trip() {
+    for apr in [0.0, 0.01, 0.1, 0.5, 3.0] {
+        assert!((apy_to_apr(apr_to_apy(apr)) - apr).abs() < 1e-9);
+        assert!(apr_to_apy(apr) >= apr);
+    }
+}
+
+#[test]
+fn test_curve_from_target_apys() {
+    let targets = [(0, 0.0), (8_000, 0.1), (10_000, 1.0)];
+    let resampled = curve_from_target_apys(&targets, 1.0).unwrap();
+
+    let rate_at_kink = resampled
+        .curve
+        .get_borrow_rate(Fraction::from_percent(80))
+        .unwrap()
+        .to_num::<f64>();
+    assert!((apr_to_apy(rate_at_kink) - 0.1).abs() < 1e-3);
+
+    assert!(curve_from_target_apys(&[(100, 0.0), (10_000, 1.0)], 1.0).is_err());
+    assert!(curve_from_target_apys(&[(0, 0.5), (10_000, 0.1)], 1.0).is_err());
+}
+
+#[test]
+fn test_curve_table_supply_rates() {
+    let curve = BorrowRateCurve::new_flat(1_000);
+    let rows = curve_table(&curve, 20, 50, 2_500).unwrap();
+
+    assert_eq!(rows.len(), 5);
+    let full = rows.last().unwrap();
+    assert_eq!(full.utilization_rate_bps, 10_000);
+    assert!((full.borrow_apr - 0.105).abs() < 1e-9);
+    assert!((full.supply_apr - 0.08).abs() < 1e-9);
+    assert_eq!(rows[0].supply_apr, 0.0);
+
+    let csv = render_csv(&rows);
+    assert_eq!(csv.lines().count(), 6);
+    assert!(csv.starts_with("utilization_rate_bps,borrow_apr,borrow_apy,supply_apr,supply_apy\n"));
+    assert!(csv.lines().nth(3).unwrap().starts_with("5000,0.105000,"));
+}
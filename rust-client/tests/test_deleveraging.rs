@@ -0,0 +1,149 @@
+use klend::{
+    fraction::{Fraction, FractionExtra},
+    utils::{MIN_AUTODELEVERAGE_BONUS_BPS, SECONDS_PER_DAY},
+    LendingMarket, Obligation, Reserve,
+};
+use klend_client::deleveraging::{deleveraging_schedule, DeleveragingTrigger};
+
+const NOW: u64 = 1_700_000_000;
+const MARGIN_CALL_PERIOD_SECS: u64 = 3_600;
+
+fn lending_market() -> LendingMarket {
+    LendingMarket {
+        autodeleverage_enabled: 1,
+        individual_autodeleverage_margin_call_period_secs: MARGIN_CALL_PERIOD_SECS,
+        ..Default::default()
+    }
+}
+
+fn reserve() -> Reserve {
+    let mut reserve = Reserve::default();
+    reserve.config.max_liquidation_bonus_bps = 1_000;
+    reserve.config.deleveraging_bonus_increase_bps_per_day = 100;
+    reserve.config.deleveraging_threshold_decrease_bps_per_day = 1_000;
+    reserve.config.deleveraging_margin_call_period_secs = MARGIN_CALL_PERIOD_SECS;
+    reserve.config.deposit_limit = u64::MAX;
+    reserve.config.borrow_limit = u64::MAX;
+    reserve
+}
+
+fn obligation(ltv_pct: u64) -> Obligation {
+    let mut obligation = Obligation::default();
+    obligation.deposited_value_sf = Fraction::from_num(1_000).to_bits();
+    obligation.borrow_factor_adjusted_debt_value_sf = Fraction::from_num(ltv_pct * 10).to_bits();
+    obligation.borrowed_assets_market_value_sf = obligation.borrow_factor_adjusted_debt_value_sf;
+    obligation.unhealthy_borrow_value_sf = Fraction::from_num(900).to_bits();
+    obligation
+}
+
+#[test]
+fn test_marked_obligation_schedule() {
+    let mut obligation = obligation(60);
+    obligation.mark_for_deleveraging(NOW, 50);
+    let (collateral_reserve, debt_reserve) = (reserve(), reserve());
+
+    let schedule = deleveraging_schedule(
+        &lending_market(),
+        &obligation,
+        &collateral_reserve,
+        &debt_reserve,
+        NOW,
+        SECONDS_PER_DAY,
+        3,
+    )
+    .unwrap();
+
+    let deleveraging_start = NOW + MARGIN_CALL_PERIOD_SECS;
+    assert_eq!(schedule.trigger, DeleveragingTrigger::MarkedObligation);
+    assert_eq!(schedule.deleveraging_start_timestamp, deleveraging_start);
+    assert_eq!(schedule.liquidatable_at, Some(deleveraging_start));
+
+    let bonuses_bps: Vec<u64> = schedule
+        .points
+        .iter()
+        .map(|point| point.liquidation_bonus.to_bps().unwrap())
+        .collect();
+    assert_eq!(
+        bonuses_bps,
+        vec![
+            MIN_AUTODELEVERAGE_BONUS_BPS,
+            MIN_AUTODELEVERAGE_BONUS_BPS + 100,
+            MIN_AUTODELEVERAGE_BONUS_BPS + 200,
+        ]
+    );
+    assert!(schedule
+        .points
+        .iter()
+        .all(|point| point.ltv_threshold == Fraction::from_percent(50)));
+}
+
+#[test]
+fn test_deposit_limit_crossed_schedule() {
+    let obligation = obligation(60);
+    let mut collateral_reserve = reserve();
+    collateral_reserve.config.autodeleverage_enabled = 1;
+    collateral_reserve.config.deposit_limit = 0;
+    collateral_reserve.liquidity.available_amount = 1;
+    collateral_reserve.liquidity.deposit_limit_crossed_timestamp = NOW;
+
+    let schedule = deleveraging_schedule(
+        &lending_market(),
+        &obligation,
+        &collateral_reserve,
+        &reserve(),
+        NOW,
+        SECONDS_PER_DAY,
+        5,
+    )
+    .unwrap();
+
+    // The threshold decreases by 10% a day from 100%, crossing the 60% LTV after 4 days
+    let deleveraging_start = NOW + MARGIN_CALL_PERIOD_SECS;
+    assert_eq!(schedule.trigger, DeleveragingTrigger::DepositLimitCrossed);
+    // Within a second, the daily decrease is not exactly representable
+    let liquidatable_at = schedule.liquidatable_at.unwrap();
+    assert!(liquidatable_at.abs_diff(deleveraging_start + 4 * SECONDS_PER_DAY) <= 1);
+    assert_eq!(schedule.points[0].timestamp, deleveraging_start);
+    assert_eq!(schedule.points[0].ltv_threshold, Fraction::ONE);
+    assert_eq!(
+        schedule.points[4].ltv_threshold.to_bps::<u64>().unwrap(),
+        6_000
+    );
+    assert_eq!(
+        schedule.points[4]
+            .liquidation_bonus
+            .to_bps::<u64>()
+            .unwrap(),
+        MIN_AUTODELEVERAGE_BONUS_BPS + 400
+    );
+}
+
+#[test]
+fn test_no_deleveraging_schedule() {
+    let (collateral_reserve, debt_reserve) = (reserve(), reserve());
+    assert!(deleveraging_schedule(
+        &lending_market(),
+        &obligation(60),
+        &collateral_reserve,
+        &debt_reserve,
+        NOW,
+        SECONDS_PER_DAY,
+        3,
+    )
+    .is_none());
+
+    // Unhealthy obligations are liquidatable immediately, whatever the deleveraging schedule
+    let mut obligation = obligation(95);
+    obligation.mark_for_deleveraging(NOW, 50);
+    let schedule = deleveraging_schedule(
+        &lending_market(),
+        &obligation,
+        &collateral_reserve,
+        &debt_reserve,
+        NOW,
+        SECONDS_PER_DAY,
+        3,
+    )
+    .unwrap();
+    assert_eq!(schedule.liquidatable_at, Some(NOW));
+}
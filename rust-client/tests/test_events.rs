@@ -0,0 +1,116 @@
+use anchor_lang::Event;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use klend::events::{DepositEvent, LiquidationEvent, ObligationOwnerUpdated, ReserveConfigUpdated};
+use klend_client::events::{decode_event, parse_logs, KlendEvent};
+use solana_sdk::pubkey::Pubkey;
+
+fn program_data(event: &impl Event) -> String {
+    format!("Program data: {}", STANDARD.encode(event.data()))
+}
+
+#[test]
+fn test_decode_event_round_trip() {
+    let event = LiquidationEvent {
+        lending_market: Pubkey::new_unique(),
+        obligation: Pubkey::new_unique(),
+        liquidator: Pubkey::new_unique(),
+        repay_reserve: Pubkey::new_unique(),
+        withdraw_reserve: Pubkey::new_unique(),
+        repay_amount: 1_000,
+        withdraw_collateral_amount: 900,
+        withdraw_liquidity_amount: 1_050,
+        liquidation_bonus_bps: 500,
+        protocol_fee: 10,
+    };
+
+    match decode_event(&event.data()).unwrap() {
+        Some(KlendEvent::Liquidation(decoded)) => {
+            assert_eq!(decoded.obligation, event.obligation);
+            assert_eq!(decoded.liquidation_bonus_bps, 500);
+            assert_eq!(decoded.protocol_fee, 10);
+        }
+        other => panic!("Unexpected event {other:?}"),
+    }
+
+    assert!(decode_event(&[1, 2, 3, 4, 5, 6, 7, 8, 9])
+        .unwrap()
+        .is_none());
+    assert!(decode_event(&event.data()[..20]).is_err());
+}
+
+#[test]
+fn test_parse_logs_ignores_other_programs() {
+    let deposit = DepositEvent {
+        lending_market: Pubkey::new_unique(),
+        reserve: Pubkey::new_unique(),
+        obligation: Pubkey::default(),
+        owner: Pubkey::new_unique(),
+        liquidity_amount: 100,
+        collateral_amount: 95,
+    };
+    let config_update = ReserveConfigUpdated {
+        lending_market: deposit.lending_market,
+        reserve: deposit.reserve,
+        mode: 24,
+        offset: 16,
+        old_value: vec![1, 2],
+        new_value: vec![3, 4],
+    };
+    let other_program = Pubkey::new_unique();
+
+    let logs = vec![
+        format!("Program {} invoke [1]", other_program),
+        program_data(&deposit),
+        format!("Program {} success", other_program),
+        format!("Program {} invoke [1]", klend::ID),
+        "Program log: Instruction: DepositReserveLiquidity".to_string(),
+        format!("Program {} invoke [2]", anchor_spl::token::ID),
+        program_data(&deposit),
+        format!("Program {} success", anchor_spl::token::ID),
+        program_data(&deposit),
+        format!(
+            "Program {} consumed 40000 of 200000 compute units",
+            klend::ID
+        ),
+        format!("Program {} success", klend::ID),
+        format!("Program {} invoke [1]", klend::ID),
+        program_data(&config_update),
+        format!("Program {} failed: custom program error: 0x1770", klend::ID),
+    ];
+
+    let events = parse_logs(&logs).unwrap();
+    assert_eq!(events.len(), 2);
+    match &events[0] {
+        KlendEvent::Deposit(event) => {
+            assert_eq!(event.reserve, deposit.reserve);
+            assert_eq!(event.collateral_amount, 95);
+        }
+        other => panic!("Unexpected event {other:?}"),
+    }
+    match &events[1] {
+        KlendEvent::ReserveConfigUpdated(event) => {
+            assert_eq!(event.offset, 16);
+            assert_eq!(event.new_value, vec![3, 4]);
+        }
+        other => panic!("Unexpected event {other:?}"),
+    }
+}
+
+#[test]
+fn test_decode_obligation_owner_updated() {
+    let event = ObligationOwnerUpdated {
+        lending_market: Pubkey::new_unique(),
+        obligation: Pubkey::new_unique(),
+        previous_owner: Pubkey::new_unique(),
+        new_owner: Pubkey::new_unique(),
+    };
+
+    match decode_event(&event.data()).unwrap() {
+        Some(KlendEvent::ObligationOwnerUpdated(decoded)) => {
+            assert_eq!(decoded.obligation, event.obligation);
+            assert_eq!(decoded.previous_owner, event.previous_owner);
+            assert_eq!(decoded.new_owner, event.new_owner);
+        }
+        other => panic!("Unexpected event {other:?}"),
+    }
+}
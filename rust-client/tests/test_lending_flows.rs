@@ -0,0 +1,1279 @@
+mod common;
+
+use common::{oracles::MockOracle, KlendTestContext, ReserveSpec};
+use klend::{
+    state::ReserveStatus,
+    utils::{seeds, CONFIG_CHANGE_EXPIRY_SECS, OBLIGATION_EXTENSION_SIZE, OBLIGATION_SIZE},
+    InitObligationArgs, ObligationDelegatePermissions, ObligationPermission,
+    ObligationWithdrawalCapConfig, OwnerWithdrawalCaps, PendingConfigChange, PriceStatusFlags,
+    Reserve, UpdateConfigMode, UpdateLendingMarketMode,
+};
+use klend_client::KlendClient;
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey, pubkey::Pubkey, signer::Signer};
+
+const FARMS_PROGRAM_ID: Pubkey = pubkey!("FarmsPZpWu9i7Kky8tPN37rs2TpmMrAZrC7S7vJa91Hr");
+
+fn sol_spec() -> ReserveSpec {
+    ReserveSpec {
+        symbol: "SOL",
+        decimals: 9,
+        price: 100_000_000,
+        price_exp: 6,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_init_market_and_reserve() {
+    let mut ctx = KlendTestContext::new().await;
+    let market = ctx.setup_market().await;
+    let usdc = ctx.setup_reserve(&market, &ReserveSpec::default()).await;
+
+    let market_state: klend::LendingMarket =
+        ctx.get_zero_copy_account(&market.lending_market).await;
+    assert_eq!(market_state.lending_market_owner, market.owner);
+
+    let reserve = ctx.get_reserve(&usdc).await;
+    assert_eq!(reserve.lending_market, market.lending_market);
+    assert_eq!(reserve.config.status(), ReserveStatus::Active);
+    assert_eq!(reserve.config.token_info.symbol(), "USDC");
+    assert_eq!(
+        reserve.liquidity.available_amount,
+        market_state.min_initial_deposit_amount
+    );
+}
+
+#[tokio::test]
+async fn test_deposit_and_redeem_reserve_liquidity() {
+    let mut ctx = KlendTestContext::new().await;
+    let market = ctx.setup_market().await;
+    let usdc = ctx.setup_reserve(&market, &ReserveSpec::default()).await;
+
+    let user = ctx.create_funded_user(LAMPORTS_PER_SOL).await;
+    let user_liquidity = ctx
+        .mint_to(&usdc.liquidity_mint, &user.pubkey(), 1_000_000_000)
+        .await;
+    let user_collateral = ctx.create_ata(&user.pubkey(), &usdc.collateral_mint).await;
+
+    ctx.process_with_reserve_refresh(&user, &usdc, |client, usdc_state| {
+        client.deposit_reserve_liquidity(&usdc.reserve, usdc_state, 500_000_000)
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(ctx.token_balance(&user_liquidity).await, 500_000_000);
+    let collateral_amount = ctx.token_balance(&user_collateral).await;
+    assert_eq!(collateral_amount, 500_000_000);
+
+    ctx.process_with_reserve_refresh(&user, &usdc, |client, usdc_state| {
+        client.redeem_reserve_collateral(&usdc.reserve, usdc_state, collateral_amount)
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(ctx.token_balance(&user_liquidity).await, 1_000_000_000);
+    assert_eq!(ctx.token_balance(&user_collateral).await, 0);
+}
+
+#[tokio::test]
+async fn test_borrow_repay_withdraw() {
+    let mut ctx = KlendTestContext::new().await;
+    let market = ctx.setup_market().await;
+    let usdc = ctx.setup_reserve(&market, &ReserveSpec::default()).await;
+    let sol = ctx.setup_reserve(&market, &sol_spec()).await;
+
+    let lender = ctx.create_funded_user(LAMPORTS_PER_SOL).await;
+    ctx.mint_to(&usdc.liquidity_mint, &lender.pubkey(), 10_000_000_000)
+        .await;
+    ctx.create_ata(&lender.pubkey(), &usdc.collateral_mint)
+        .await;
+    ctx.process_with_reserve_refresh(&lender, &usdc, |client, usdc_state| {
+        client.deposit_reserve_liquidity(&usdc.reserve, usdc_state, 10_000_000_000)
+    })
+    .await
+    .unwrap();
+
+    let borrower = ctx.create_funded_user(LAMPORTS_PER_SOL).await;
+    let borrower_sol = ctx
+        .mint_to(
+            &sol.liquidity_mint,
+            &borrower.pubkey(),
+            10 * LAMPORTS_PER_SOL,
+        )
+        .await;
+    let borrower_usdc = ctx
+        .create_ata(&borrower.pubkey(), &usdc.liquidity_mint)
+        .await;
+    let obligation = ctx.setup_obligation(&market, &borrower).await;
+
+    ctx.process_with_obligation_refresh(&borrower, &obligation, &sol, |client, sol_state| {
+        client.deposit_reserve_liquidity_and_obligation_collateral(
+            &obligation.obligation,
+            &sol.reserve,
+            sol_state,
+            10 * LAMPORTS_PER_SOL,
+        )
+    })
+    .await
+    .unwrap();
+    assert_eq!(ctx.token_balance(&borrower_sol).await, 0);
+
+    ctx.process_with_obligation_refresh(&borrower, &obligation, &usdc, |client, usdc_state| {
+        client.borrow_obligation_liquidity(
+            &obligation.obligation,
+            &obligation.owner,
+            &usdc.reserve,
+            usdc_state,
+            500_000_000,
+            None,
+        )
+    })
+    .await
+    .unwrap();
+    assert_eq!(ctx.token_balance(&borrower_usdc).await, 500_000_000);
+
+    let obligation_state = ctx.get_obligation(&obligation).await;
+    assert_eq!(obligation_state.borrows[0].borrow_reserve, usdc.reserve);
+    assert!(obligation_state.has_debt != 0);
+
+    ctx.mint_to(&usdc.liquidity_mint, &borrower.pubkey(), 1_000_000)
+        .await;
+    ctx.process_with_obligation_refresh(&borrower, &obligation, &usdc, |client, usdc_state| {
+        client.repay_obligation_liquidity(
+            &obligation.obligation,
+            &usdc.reserve,
+            usdc_state,
+            u64::MAX,
+        )
+    })
+    .await
+    .unwrap();
+
+    let obligation_state = ctx.get_obligation_positions(&obligation).await;
+    assert!(obligation_state.view().borrows_empty());
+
+    ctx.process_with_obligation_refresh(&borrower, &obligation, &sol, |client, sol_state| {
+        client.withdraw_obligation_collateral_and_redeem_reserve_collateral(
+            &obligation.obligation,
+            &obligation.owner,
+            &sol.reserve,
+            sol_state,
+            u64::MAX,
+        )
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(
+        ctx.token_balance(&borrower_sol).await,
+        10 * LAMPORTS_PER_SOL
+    );
+    // Withdrawing the last deposit of an obligation without debt closes it
+    assert!(ctx.get_account(&obligation.obligation).await.is_none());
+}
+
+#[tokio::test]
+async fn test_owner_withdrawal_caps() {
+    let mut ctx = KlendTestContext::new().await;
+    let market = ctx.setup_market().await;
+    let sol = ctx.setup_reserve(&market, &sol_spec()).await;
+
+    let user = ctx.create_funded_user(LAMPORTS_PER_SOL).await;
+    ctx.mint_to(&sol.liquidity_mint, &user.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .await;
+    let obligation = ctx.setup_obligation(&market, &user).await;
+    ctx.process_with_obligation_refresh(&user, &obligation, &sol, |client, sol_state| {
+        client.deposit_reserve_liquidity_and_obligation_collateral(
+            &obligation.obligation,
+            &sol.reserve,
+            sol_state,
+            10 * LAMPORTS_PER_SOL,
+        )
+    })
+    .await
+    .unwrap();
+
+    let mut account = ctx.get_account(&sol.reserve).await.unwrap();
+    let size = std::mem::size_of::<Reserve>();
+    let mut capped_reserve: Reserve = bytemuck::pod_read_unaligned(&account.data[8..8 + size]);
+    capped_reserve.config.obligation_deposit_withdrawal_cap = ObligationWithdrawalCapConfig {
+        config_capacity: (5 * LAMPORTS_PER_SOL).try_into().unwrap(),
+        config_interval_length_seconds: 60 * 60,
+    };
+    account.data[8..8 + size].copy_from_slice(bytemuck::bytes_of(&capped_reserve));
+    ctx.set_account(&sol.reserve, account);
+
+    let withdraw = |client: &KlendClient, sol_state: &Reserve, amount| {
+        client.withdraw_obligation_collateral_and_redeem_reserve_collateral(
+            &obligation.obligation,
+            &obligation.owner,
+            &sol.reserve,
+            sol_state,
+            amount,
+        )
+    };
+
+    // The accumulators of the owner must exist once the reserve caps owners
+    let res = ctx
+        .process_with_obligation_refresh(&user, &obligation, &sol, |client, sol_state| {
+            withdraw(client, sol_state, LAMPORTS_PER_SOL)
+        })
+        .await;
+    assert!(res.is_err());
+
+    let ix =
+        ctx.client(&user)
+            .init_owner_withdrawal_caps(&sol.reserve, &capped_reserve, &user.pubkey());
+    ctx.process(&[ix], &[&user]).await.unwrap();
+
+    ctx.process_with_obligation_refresh(&user, &obligation, &sol, |client, sol_state| {
+        withdraw(client, sol_state, 4 * LAMPORTS_PER_SOL)
+    })
+    .await
+    .unwrap();
+    let res = ctx
+        .process_with_obligation_refresh(&user, &obligation, &sol, |client, sol_state| {
+            withdraw(client, sol_state, 2 * LAMPORTS_PER_SOL)
+        })
+        .await;
+    assert!(res.is_err());
+
+    let (owner_withdrawal_caps, _) = seeds::pda::owner_withdrawal_caps(sol.reserve, user.pubkey());
+    let caps: OwnerWithdrawalCaps = ctx.get_zero_copy_account(&owner_withdrawal_caps).await;
+    assert_eq!(caps.owner, user.pubkey());
+    assert_eq!(
+        caps.deposit_withdrawal_cap.current_total,
+        i64::try_from(4 * LAMPORTS_PER_SOL).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_borrow_above_ltv_fails() {
+    let mut ctx = KlendTestContext::new().await;
+    let market = ctx.setup_market().await;
+    let usdc = ctx.setup_reserve(&market, &ReserveSpec::default()).await;
+    let sol = ctx.setup_reserve(&market, &sol_spec()).await;
+
+    let lender = ctx.create_funded_user(LAMPORTS_PER_SOL).await;
+    ctx.mint_to(&usdc.liquidity_mint, &lender.pubkey(), 10_000_000_000)
+        .await;
+    ctx.create_ata(&lender.pubkey(), &usdc.collateral_mint)
+        .await;
+    ctx.process_with_reserve_refresh(&lender, &usdc, |client, usdc_state| {
+        client.deposit_reserve_liquidity(&usdc.reserve, usdc_state, 10_000_000_000)
+    })
+    .await
+    .unwrap();
+
+    let borrower = ctx.create_funded_user(LAMPORTS_PER_SOL).await;
+    ctx.mint_to(&sol.liquidity_mint, &borrower.pubkey(), LAMPORTS_PER_SOL)
+        .await;
+    ctx.create_ata(&borrower.pubkey(), &usdc.liquidity_mint)
+        .await;
+    let obligation = ctx.setup_obligation(&market, &borrower).await;
+
+    ctx.process_with_obligation_refresh(&borrower, &obligation, &sol, |client, sol_state| {
+        client.deposit_reserve_liquidity_and_obligation_collateral(
+            &obligation.obligation,
+            &sol.reserve,
+            sol_state,
+            LAMPORTS_PER_SOL,
+        )
+    })
+    .await
+    .unwrap();
+
+    let res = ctx
+        .process_with_obligation_refresh(&borrower, &obligation, &usdc, |client, usdc_state| {
+            client.borrow_obligation_liquidity(
+                &obligation.obligation,
+                &obligation.owner,
+                &usdc.reserve,
+                usdc_state,
+                80_000_000,
+                None,
+            )
+        })
+        .await;
+    assert!(res.is_err());
+}
+
+#[tokio::test]
+async fn test_close_obligation() {
+    let mut ctx = KlendTestContext::new().await;
+    let market = ctx.setup_market().await;
+    let sol = ctx.setup_reserve(&market, &sol_spec()).await;
+
+    let borrower = ctx.create_funded_user(LAMPORTS_PER_SOL).await;
+    ctx.mint_to(&sol.liquidity_mint, &borrower.pubkey(), LAMPORTS_PER_SOL)
+        .await;
+    let obligation = ctx.setup_obligation(&market, &borrower).await;
+    let empty_obligation_state = ctx.get_obligation_positions(&obligation).await;
+    ctx.process_with_obligation_refresh(&borrower, &obligation, &sol, |client, sol_state| {
+        client.deposit_reserve_liquidity_and_obligation_collateral(
+            &obligation.obligation,
+            &sol.reserve,
+            sol_state,
+            LAMPORTS_PER_SOL,
+        )
+    })
+    .await
+    .unwrap();
+
+    // The client refuses to close an obligation with positions, and so does the program
+    let client = ctx.client(&borrower);
+    let obligation_state = ctx.get_obligation_positions(&obligation).await;
+    assert!(client
+        .close_obligation(&obligation.obligation, &obligation_state, &[])
+        .is_err());
+    let ix = client
+        .close_obligation(&obligation.obligation, &empty_obligation_state, &[])
+        .unwrap();
+    let res = ctx.process(&[ix], &[&borrower]).await;
+    assert!(res.is_err());
+
+    let owner = ctx.create_funded_user(LAMPORTS_PER_SOL).await;
+    let obligation = ctx.setup_obligation(&market, &owner).await;
+    let obligation_rent = ctx
+        .get_account(&obligation.obligation)
+        .await
+        .unwrap()
+        .lamports;
+    let owner_lamports = ctx.get_account(&owner.pubkey()).await.unwrap().lamports;
+
+    let obligation_state = ctx.get_obligation_positions(&obligation).await;
+    let ix = ctx
+        .client(&owner)
+        .close_obligation(&obligation.obligation, &obligation_state, &[])
+        .unwrap();
+    ctx.process(&[ix], &[&owner]).await.unwrap();
+
+    assert!(ctx.get_account(&obligation.obligation).await.is_none());
+    assert_eq!(
+        ctx.get_account(&owner.pubkey()).await.unwrap().lamports,
+        owner_lamports + obligation_rent
+    );
+
+    // The farm user states of the reserves passed cannot be left out
+    let farm = Pubkey::new_unique();
+    let mut account = ctx.get_account(&sol.reserve).await.unwrap();
+    let size = std::mem::size_of::<Reserve>();
+    let mut farmed_reserve: Reserve = bytemuck::pod_read_unaligned(&account.data[8..8 + size]);
+    farmed_reserve.farm_debt = farm;
+    account.data[8..8 + size].copy_from_slice(bytemuck::bytes_of(&farmed_reserve));
+    ctx.set_account(&sol.reserve, account);
+    let reserves = [(sol.reserve, farmed_reserve)];
+
+    let farmer = ctx.create_funded_user(LAMPORTS_PER_SOL).await;
+    let obligation = ctx.setup_obligation(&market, &farmer).await;
+    let obligation_state = ctx.get_obligation_positions(&obligation).await;
+    let client = ctx.client(&farmer);
+    let mut ix = client
+        .close_obligation(&obligation.obligation, &obligation_state, &reserves)
+        .unwrap();
+    let farm_user_state = ix.accounts.pop().unwrap();
+    let res = ctx.process(&[ix.clone()], &[&farmer]).await;
+    assert!(res.is_err());
+
+    // An initialized farm user state has to be a farm user state of the obligation
+    ctx.set_account(
+        &farm_user_state.pubkey,
+        solana_sdk::account::Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![1; 64],
+            owner: FARMS_PROGRAM_ID,
+            ..Default::default()
+        },
+    );
+    ix.accounts.push(farm_user_state);
+    let res = ctx.process(&[ix], &[&farmer]).await;
+    assert!(res.is_err());
+    assert!(ctx.get_account(&obligation.obligation).await.is_some());
+
+    // A farm user state that was never initialized holds no stake
+    let other_farmer = ctx.create_funded_user(LAMPORTS_PER_SOL).await;
+    let obligation = ctx.setup_obligation(&market, &other_farmer).await;
+    let obligation_state = ctx.get_obligation_positions(&obligation).await;
+    let ix = ctx
+        .client(&other_farmer)
+        .close_obligation(&obligation.obligation, &obligation_state, &reserves)
+        .unwrap();
+    ctx.process(&[ix], &[&other_farmer]).await.unwrap();
+    assert!(ctx.get_account(&obligation.obligation).await.is_none());
+}
+
+#[tokio::test]
+async fn test_extend_obligation() {
+    let mut ctx = KlendTestContext::new().await;
+    let market = ctx.setup_market().await;
+    let sol = ctx.setup_reserve(&market, &sol_spec()).await;
+
+    let owner = ctx.create_funded_user(LAMPORTS_PER_SOL).await;
+    ctx.mint_to(&sol.liquidity_mint, &owner.pubkey(), LAMPORTS_PER_SOL)
+        .await;
+    let obligation = ctx.setup_obligation(&market, &owner).await;
+    let client = ctx.client(ctx.payer());
+    let unextended_state = ctx.get_obligation_positions(&obligation).await;
+
+    let ix = client
+        .extend_obligation(&obligation.obligation, &unextended_state)
+        .unwrap();
+    ctx.process(&[ix], &[&owner]).await.unwrap();
+
+    let account = ctx.get_account(&obligation.obligation).await.unwrap();
+    assert_eq!(
+        account.data.len(),
+        8 + OBLIGATION_SIZE + OBLIGATION_EXTENSION_SIZE
+    );
+    let obligation_state = ctx.get_obligation_positions(&obligation).await;
+    assert!(obligation_state.obligation.is_extended());
+    assert!(obligation_state.extension.is_some());
+
+    // Extending twice is refused by the client, and by the program
+    assert!(client
+        .extend_obligation(&obligation.obligation, &obligation_state)
+        .is_err());
+    let ix = client
+        .extend_obligation(&obligation.obligation, &unextended_state)
+        .unwrap();
+    let res = ctx.process(&[ix], &[&owner]).await;
+    assert!(res.is_err());
+
+    ctx.process_with_obligation_refresh(&owner, &obligation, &sol, |client, sol_state| {
+        client.deposit_reserve_liquidity_and_obligation_collateral(
+            &obligation.obligation,
+            &sol.reserve,
+            sol_state,
+            LAMPORTS_PER_SOL,
+        )
+    })
+    .await
+    .unwrap();
+
+    let obligation_state = ctx.get_obligation_positions(&obligation).await;
+    assert_eq!(obligation_state.view().deposits_count(), 1);
+    assert_eq!(
+        obligation_state.view().collateral(0).deposit_reserve,
+        sol.reserve
+    );
+}
+
+#[tokio::test]
+async fn test_obligation_owner_transfer() {
+    let mut ctx = KlendTestContext::new().await;
+    let market = ctx.setup_market().await;
+
+    let owner = ctx.create_funded_user(LAMPORTS_PER_SOL).await;
+    let new_owner = ctx.create_funded_user(LAMPORTS_PER_SOL).await;
+    let obligation = ctx.setup_obligation(&market, &owner).await;
+    let new_owner_client = ctx.client(&new_owner);
+
+    // Nothing was proposed yet, the client refuses to accept and so does the program
+    let obligation_state = ctx.get_obligation_positions(&obligation).await;
+    assert!(new_owner_client
+        .update_obligation_owner(&obligation.obligation, &obligation_state, &[])
+        .is_err());
+    let mut forged_state = ctx.get_obligation_positions(&obligation).await;
+    forged_state.obligation.owner_cached = new_owner.pubkey();
+    let ix = new_owner_client
+        .update_obligation_owner(&obligation.obligation, &forged_state, &[])
+        .unwrap();
+    let res = ctx.process(&[ix], &[&new_owner]).await;
+    assert!(res.is_err());
+
+    let ix = ctx
+        .client(&owner)
+        .propose_obligation_owner(
+            &obligation.obligation,
+            &obligation_state,
+            &[],
+            &new_owner.pubkey(),
+        )
+        .unwrap();
+    ctx.process(&[ix], &[&owner]).await.unwrap();
+    let obligation_state = ctx.get_obligation_positions(&obligation).await;
+    assert_eq!(obligation_state.obligation.owner_cached, new_owner.pubkey());
+
+    let ix = new_owner_client
+        .update_obligation_owner(&obligation.obligation, &obligation_state, &[])
+        .unwrap();
+    ctx.process(&[ix], &[&new_owner]).await.unwrap();
+
+    let obligation_state = ctx.get_obligation(&obligation).await;
+    assert_eq!(obligation_state.owner, new_owner.pubkey());
+    assert_eq!(obligation_state.owner_cached, Pubkey::default());
+
+    // The previous owner lost control, the new one can operate the obligation
+    let obligation_state = ctx.get_obligation_positions(&obligation).await;
+    let ix = ctx
+        .client(&owner)
+        .close_obligation(&obligation.obligation, &obligation_state, &[])
+        .unwrap();
+    let res = ctx.process(&[ix], &[&owner]).await;
+    assert!(res.is_err());
+    let ix = new_owner_client
+        .close_obligation(&obligation.obligation, &obligation_state, &[])
+        .unwrap();
+    ctx.process(&[ix], &[&new_owner]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_obligation_owner_transfer_with_farm_user_states() {
+    let mut ctx = KlendTestContext::new().await;
+    let market = ctx.setup_market().await;
+    let sol = ctx.setup_reserve(&market, &sol_spec()).await;
+
+    let owner = ctx.create_funded_user(LAMPORTS_PER_SOL).await;
+    let new_owner = ctx.create_funded_user(LAMPORTS_PER_SOL).await;
+    ctx.mint_to(&sol.liquidity_mint, &owner.pubkey(), LAMPORTS_PER_SOL)
+        .await;
+    let obligation = ctx.setup_obligation(&market, &owner).await;
+    ctx.process_with_obligation_refresh(&owner, &obligation, &sol, |client, sol_state| {
+        client.deposit_reserve_liquidity_and_obligation_collateral(
+            &obligation.obligation,
+            &sol.reserve,
+            sol_state,
+            LAMPORTS_PER_SOL,
+        )
+    })
+    .await
+    .unwrap();
+
+    // The reserve gets a collateral farm after the deposit
+    let farm = Pubkey::new_unique();
+    let mut account = ctx.get_account(&sol.reserve).await.unwrap();
+    let size = std::mem::size_of::<Reserve>();
+    let mut farmed_reserve: Reserve = bytemuck::pod_read_unaligned(&account.data[8..8 + size]);
+    farmed_reserve.farm_collateral = farm;
+    account.data[8..8 + size].copy_from_slice(bytemuck::bytes_of(&farmed_reserve));
+    ctx.set_account(&sol.reserve, account);
+
+    let client = ctx.client(&owner);
+    let obligation_state = ctx.get_obligation_positions(&obligation).await;
+    let reserves = [(sol.reserve, farmed_reserve)];
+
+    // The reserves of the obligation can't be left out
+    assert!(client
+        .propose_obligation_owner(
+            &obligation.obligation,
+            &obligation_state,
+            &[],
+            &new_owner.pubkey(),
+        )
+        .is_err());
+    let mut ix = client
+        .propose_obligation_owner(
+            &obligation.obligation,
+            &obligation_state,
+            &reserves,
+            &new_owner.pubkey(),
+        )
+        .unwrap();
+    let farm_user_state = ix.accounts.pop().unwrap();
+    let res = ctx.process(&[ix.clone()], &[&owner]).await;
+    assert!(res.is_err());
+
+    // A farm user state that was never initialized keeps no owner
+    ix.accounts.push(farm_user_state.clone());
+    ctx.process(&[ix], &[&owner]).await.unwrap();
+
+    // Once initialized, it keeps the current owner and the transfer is refused
+    ctx.set_account(
+        &farm_user_state.pubkey,
+        solana_sdk::account::Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![1; 64],
+            owner: FARMS_PROGRAM_ID,
+            ..Default::default()
+        },
+    );
+    let obligation_state = ctx.get_obligation_positions(&obligation).await;
+    let ix = ctx
+        .client(&new_owner)
+        .update_obligation_owner(&obligation.obligation, &obligation_state, &reserves)
+        .unwrap();
+    let res = ctx.process(&[ix], &[&new_owner]).await;
+    assert!(res.is_err());
+    assert_eq!(ctx.get_obligation(&obligation).await.owner, owner.pubkey());
+}
+
+#[tokio::test]
+async fn test_obligation_delegate() {
+    let mut ctx = KlendTestContext::new().await;
+    let market = ctx.setup_market().await;
+    let usdc = ctx.setup_reserve(&market, &ReserveSpec::default()).await;
+    let sol = ctx.setup_reserve(&market, &sol_spec()).await;
+
+    let lender = ctx.create_funded_user(LAMPORTS_PER_SOL).await;
+    ctx.mint_to(&usdc.liquidity_mint, &lender.pubkey(), 10_000_000_000)
+        .await;
+    ctx.create_ata(&lender.pubkey(), &usdc.collateral_mint)
+        .await;
+    ctx.process_with_reserve_refresh(&lender, &usdc, |client, usdc_state| {
+        client.deposit_reserve_liquidity(&usdc.reserve, usdc_state, 10_000_000_000)
+    })
+    .await
+    .unwrap();
+
+    let owner = ctx.create_funded_user(LAMPORTS_PER_SOL).await;
+    ctx.mint_to(&sol.liquidity_mint, &owner.pubkey(), LAMPORTS_PER_SOL)
+        .await;
+    let obligation = ctx.setup_obligation(&market, &owner).await;
+    ctx.process_with_obligation_refresh(&owner, &obligation, &sol, |client, sol_state| {
+        client.deposit_reserve_liquidity_and_obligation_collateral(
+            &obligation.obligation,
+            &sol.reserve,
+            sol_state,
+            LAMPORTS_PER_SOL,
+        )
+    })
+    .await
+    .unwrap();
+
+    let owner_usdc = ctx.create_ata(&owner.pubkey(), &usdc.liquidity_mint).await;
+    let delegate = ctx.create_funded_user(LAMPORTS_PER_SOL).await;
+    let delegate_usdc = ctx
+        .create_ata(&delegate.pubkey(), &usdc.liquidity_mint)
+        .await;
+    ctx.create_ata(&delegate.pubkey(), &sol.liquidity_mint)
+        .await;
+    let borrow_to = |destination_owner: Pubkey| {
+        move |client: &KlendClient, usdc_state: &Reserve, amount| {
+            client.borrow_obligation_liquidity(
+                &obligation.obligation,
+                &destination_owner,
+                &usdc.reserve,
+                usdc_state,
+                amount,
+                None,
+            )
+        }
+    };
+    let delegate_borrow = borrow_to(obligation.owner);
+    let owner_client = ctx.client(&owner);
+
+    // Without the rebalance permission the delegate cannot borrow
+    let obligation_state = ctx.get_obligation(&obligation).await;
+    let ix = owner_client.update_obligation_delegate(
+        &obligation.obligation,
+        &obligation_state,
+        &delegate.pubkey(),
+        ObligationDelegatePermissions::DEPOSIT,
+        30,
+    );
+    ctx.process(&[ix], &[&owner]).await.unwrap();
+    let res = ctx
+        .process_with_obligation_refresh(&delegate, &obligation, &usdc, |client, usdc_state| {
+            delegate_borrow(client, usdc_state, 20_000_000)
+        })
+        .await;
+    assert!(res.is_err());
+
+    let ix = owner_client.update_obligation_delegate(
+        &obligation.obligation,
+        &obligation_state,
+        &delegate.pubkey(),
+        ObligationDelegatePermissions::REBALANCE,
+        30,
+    );
+    ctx.process(&[ix], &[&owner]).await.unwrap();
+
+    // The delegate cannot route borrowed or withdrawn funds to its own accounts
+    let res = ctx
+        .process_with_obligation_refresh(&delegate, &obligation, &usdc, |client, usdc_state| {
+            borrow_to(delegate.pubkey())(client, usdc_state, 20_000_000)
+        })
+        .await;
+    assert!(res.is_err());
+    let res = ctx
+        .process_with_obligation_refresh(&delegate, &obligation, &sol, |client, sol_state| {
+            client.withdraw_obligation_collateral_and_redeem_reserve_collateral(
+                &obligation.obligation,
+                &delegate.pubkey(),
+                &sol.reserve,
+                sol_state,
+                LAMPORTS_PER_SOL / 10,
+            )
+        })
+        .await;
+    assert!(res.is_err());
+
+    ctx.process_with_obligation_refresh(&delegate, &obligation, &usdc, |client, usdc_state| {
+        delegate_borrow(client, usdc_state, 20_000_000)
+    })
+    .await
+    .unwrap();
+    assert_eq!(ctx.token_balance(&owner_usdc).await, 20_000_000);
+    assert_eq!(ctx.token_balance(&delegate_usdc).await, 0);
+
+    // The delegate is capped below the reserve LTV, the owner is not
+    let res = ctx
+        .process_with_obligation_refresh(&delegate, &obligation, &usdc, |client, usdc_state| {
+            delegate_borrow(client, usdc_state, 20_000_000)
+        })
+        .await;
+    assert!(res.is_err());
+
+    // Revoking the delegate removes its access
+    let ix = owner_client.update_obligation_delegate(
+        &obligation.obligation,
+        &obligation_state,
+        &Pubkey::default(),
+        ObligationDelegatePermissions::empty(),
+        0,
+    );
+    ctx.process(&[ix], &[&owner]).await.unwrap();
+    let obligation_state = ctx.get_obligation(&obligation).await;
+    assert_eq!(obligation_state.delegate, Pubkey::default());
+    let res = ctx
+        .process_with_obligation_refresh(&delegate, &obligation, &usdc, |client, usdc_state| {
+            delegate_borrow(client, usdc_state, 1_000_000)
+        })
+        .await;
+    assert!(res.is_err());
+}
+
+#[tokio::test]
+async fn test_permissioned_market() {
+    let mut ctx = KlendTestContext::new().await;
+    let market = ctx.setup_market().await;
+    let usdc = ctx.setup_reserve(&market, &ReserveSpec::default()).await;
+    let sol = ctx.setup_reserve(&market, &sol_spec()).await;
+    let market_owner_client = ctx.client(ctx.payer());
+    let ix = market_owner_client
+        .update_lending_market(
+            &market.lending_market,
+            UpdateLendingMarketMode::UpdateObligationPermissionRequired,
+            &[1],
+        )
+        .unwrap();
+    ctx.process(&[ix], &[]).await.unwrap();
+
+    let lender = ctx.create_funded_user(LAMPORTS_PER_SOL).await;
+    ctx.mint_to(&usdc.liquidity_mint, &lender.pubkey(), 10_000_000_000)
+        .await;
+    ctx.create_ata(&lender.pubkey(), &usdc.collateral_mint)
+        .await;
+    ctx.process_with_reserve_refresh(&lender, &usdc, |client, usdc_state| {
+        client.deposit_reserve_liquidity(&usdc.reserve, usdc_state, 10_000_000_000)
+    })
+    .await
+    .unwrap();
+
+    let borrower = ctx.create_funded_user(LAMPORTS_PER_SOL).await;
+    ctx.mint_to(&sol.liquidity_mint, &borrower.pubkey(), LAMPORTS_PER_SOL)
+        .await;
+    ctx.create_ata(&borrower.pubkey(), &usdc.liquidity_mint)
+        .await;
+
+    // Obligations cannot be opened without a permission
+    let borrower_client = ctx.client(&borrower);
+    let ixs = [
+        borrower_client.init_user_metadata(&borrower.pubkey(), Pubkey::default(), None),
+        borrower_client.init_obligation(
+            &market.lending_market,
+            &borrower.pubkey(),
+            InitObligationArgs { tag: 0, id: 0 },
+            &Pubkey::default(),
+            &Pubkey::default(),
+            None,
+        ),
+    ];
+    let res = ctx.process(&ixs, &[&borrower]).await;
+    assert!(res.is_err());
+
+    let ix =
+        market_owner_client.init_obligation_permission(&market.lending_market, &borrower.pubkey());
+    ctx.process(&[ix], &[]).await.unwrap();
+    let obligation = ctx.setup_obligation(&market, &borrower).await;
+    assert!(obligation.obligation_permission.is_some());
+
+    let borrow = |client: &KlendClient, usdc_state: &Reserve, amount, obligation_permission| {
+        client.borrow_obligation_liquidity(
+            &obligation.obligation,
+            &obligation.owner,
+            &usdc.reserve,
+            usdc_state,
+            amount,
+            obligation_permission,
+        )
+    };
+    ctx.process_with_obligation_refresh(&borrower, &obligation, &sol, |client, sol_state| {
+        client.deposit_reserve_liquidity_and_obligation_collateral(
+            &obligation.obligation,
+            &sol.reserve,
+            sol_state,
+            LAMPORTS_PER_SOL,
+        )
+    })
+    .await
+    .unwrap();
+    ctx.process_with_obligation_refresh(&borrower, &obligation, &usdc, |client, usdc_state| {
+        borrow(
+            client,
+            usdc_state,
+            20_000_000,
+            obligation.obligation_permission,
+        )
+    })
+    .await
+    .unwrap();
+
+    // Borrowing without passing the permission is refused
+    let res = ctx
+        .process_with_obligation_refresh(&borrower, &obligation, &usdc, |client, usdc_state| {
+            borrow(client, usdc_state, 1_000_000, None)
+        })
+        .await;
+    assert!(res.is_err());
+
+    // Revocation blocks new borrows but not repayment
+    let ix = market_owner_client.update_obligation_permission(
+        &market.lending_market,
+        &borrower.pubkey(),
+        true,
+    );
+    ctx.process(&[ix], &[]).await.unwrap();
+    let permission: ObligationPermission = ctx
+        .get_zero_copy_account(&obligation.obligation_permission.unwrap())
+        .await;
+    assert!(permission.is_revoked());
+    let res = ctx
+        .process_with_obligation_refresh(&borrower, &obligation, &usdc, |client, usdc_state| {
+            borrow(
+                client,
+                usdc_state,
+                1_000_000,
+                obligation.obligation_permission,
+            )
+        })
+        .await;
+    assert!(res.is_err());
+
+    ctx.mint_to(&usdc.liquidity_mint, &borrower.pubkey(), 1_000_000)
+        .await;
+    ctx.process_with_obligation_refresh(&borrower, &obligation, &usdc, |client, usdc_state| {
+        client.repay_obligation_liquidity(
+            &obligation.obligation,
+            &usdc.reserve,
+            usdc_state,
+            u64::MAX,
+        )
+    })
+    .await
+    .unwrap();
+    let obligation_state = ctx.get_obligation_positions(&obligation).await;
+    assert!(obligation_state.view().borrows_empty());
+
+    let ix = market_owner_client.update_obligation_permission(
+        &market.lending_market,
+        &borrower.pubkey(),
+        false,
+    );
+    ctx.process(&[ix], &[]).await.unwrap();
+    ctx.process_with_obligation_refresh(&borrower, &obligation, &usdc, |client, usdc_state| {
+        borrow(
+            client,
+            usdc_state,
+            1_000_000,
+            obligation.obligation_permission,
+        )
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_config_timelock() {
+    let mut ctx = KlendTestContext::new().await;
+    let market = ctx.setup_market().await;
+    let usdc = ctx.setup_reserve(&market, &ReserveSpec::default()).await;
+    let client = ctx.client(ctx.payer());
+    let ix = client
+        .update_lending_market(
+            &market.lending_market,
+            UpdateLendingMarketMode::UpdateConfigTimelockSecs,
+            &3_600_u64.to_le_bytes(),
+        )
+        .unwrap();
+    ctx.process(&[ix], &[]).await.unwrap();
+
+    // Risk-increasing changes have to be queued, risk-reducing ones stay immediate
+    let ix = client.update_reserve_config(
+        &market.lending_market,
+        &usdc.reserve,
+        UpdateConfigMode::UpdateLoanToValuePct,
+        vec![80],
+    );
+    let res = ctx.process(&[ix], &[]).await;
+    assert!(res.is_err());
+    let ix = client.update_reserve_config(
+        &market.lending_market,
+        &usdc.reserve,
+        UpdateConfigMode::UpdateLoanToValuePct,
+        vec![70],
+    );
+    ctx.process(&[ix], &[]).await.unwrap();
+    assert_eq!(ctx.get_reserve(&usdc).await.config.loan_to_value_pct, 70);
+
+    let ltv_mode = UpdateConfigMode::UpdateLoanToValuePct;
+    let threshold_mode = UpdateConfigMode::UpdateLiquidationThresholdPct;
+    let ixs = [
+        client.queue_reserve_config_change(
+            &market.lending_market,
+            &usdc.reserve,
+            ltv_mode,
+            vec![80],
+        ),
+        client.queue_reserve_config_change(
+            &market.lending_market,
+            &usdc.reserve,
+            threshold_mode,
+            vec![90],
+        ),
+    ];
+    ctx.process(&ixs, &[]).await.unwrap();
+
+    let (pending_ltv_change, _) =
+        seeds::pda::pending_config_change(market.lending_market, usdc.reserve, ltv_mode as u64);
+    let (pending_threshold_change, _) = seeds::pda::pending_config_change(
+        market.lending_market,
+        usdc.reserve,
+        threshold_mode as u64,
+    );
+    let pending_ltv_state: PendingConfigChange =
+        ctx.get_zero_copy_account(&pending_ltv_change).await;
+    let pending_threshold_state: PendingConfigChange =
+        ctx.get_zero_copy_account(&pending_threshold_change).await;
+    let execute_ixs = [
+        client
+            .execute_config_change(&pending_ltv_change, &pending_ltv_state)
+            .unwrap(),
+        client
+            .execute_config_change(&pending_threshold_change, &pending_threshold_state)
+            .unwrap(),
+    ];
+    let res = ctx.process(&execute_ixs[..1], &[]).await;
+    assert!(res.is_err());
+
+    ctx.warp_seconds(3_600).await;
+    ctx.process(&execute_ixs, &[]).await.unwrap();
+    let reserve = ctx.get_reserve(&usdc).await;
+    assert_eq!(reserve.config.loan_to_value_pct, 80);
+    assert_eq!(reserve.config.liquidation_threshold_pct, 90);
+    assert!(ctx.get_account(&pending_ltv_change).await.is_none());
+
+    // Lowering the timelock itself is timelocked and can be cancelled while queued
+    let ix = client
+        .update_lending_market(
+            &market.lending_market,
+            UpdateLendingMarketMode::UpdateConfigTimelockSecs,
+            &0_u64.to_le_bytes(),
+        )
+        .unwrap();
+    let res = ctx.process(&[ix], &[]).await;
+    assert!(res.is_err());
+
+    let timelock_mode = UpdateLendingMarketMode::UpdateConfigTimelockSecs;
+    let ix = client.queue_lending_market_config_change(
+        &market.lending_market,
+        timelock_mode,
+        0_u64.to_le_bytes().to_vec(),
+    );
+    ctx.process(&[ix], &[]).await.unwrap();
+    let (pending_timelock_change, _) = seeds::pda::pending_config_change(
+        market.lending_market,
+        market.lending_market,
+        timelock_mode as u64,
+    );
+    let pending_timelock_state: PendingConfigChange =
+        ctx.get_zero_copy_account(&pending_timelock_change).await;
+    let ix = client.cancel_config_change(&pending_timelock_change, &pending_timelock_state);
+    ctx.process(&[ix], &[]).await.unwrap();
+    assert!(ctx.get_account(&pending_timelock_change).await.is_none());
+
+    let market_state: klend::LendingMarket =
+        ctx.get_zero_copy_account(&market.lending_market).await;
+    assert_eq!(market_state.config_timelock_secs, 3_600);
+    // A change left queued past its expiry can no longer be executed
+    let ix = client.queue_reserve_config_change(
+        &market.lending_market,
+        &usdc.reserve,
+        ltv_mode,
+        vec![75],
+    );
+    ctx.process(&[ix], &[]).await.unwrap();
+    let pending_ltv_state: PendingConfigChange =
+        ctx.get_zero_copy_account(&pending_ltv_change).await;
+    ctx.warp_seconds((3_600 + CONFIG_CHANGE_EXPIRY_SECS + 1) as i64)
+        .await;
+    let ix = client
+        .execute_config_change(&pending_ltv_change, &pending_ltv_state)
+        .unwrap();
+    let res = ctx.process(&[ix], &[]).await;
+    assert!(res.is_err());
+    assert_eq!(ctx.get_reserve(&usdc).await.config.loan_to_value_pct, 80);
+}
+
+#[tokio::test]
+async fn test_risk_council_reserve_config() {
+    let mut ctx = KlendTestContext::new().await;
+    let market = ctx.setup_market().await;
+    let usdc = ctx.setup_reserve(&market, &ReserveSpec::default()).await;
+    let risk_council = ctx.create_funded_user(LAMPORTS_PER_SOL).await;
+    let outsider = ctx.create_funded_user(LAMPORTS_PER_SOL).await;
+    let owner_client = ctx.client(ctx.payer());
+    let ix = owner_client
+        .update_lending_market(
+            &market.lending_market,
+            UpdateLendingMarketMode::UpdateRiskCouncil,
+            risk_council.pubkey().as_ref(),
+        )
+        .unwrap();
+    ctx.process(&[ix], &[]).await.unwrap();
+
+    let council_client = ctx.client(&risk_council);
+    let council_update =
+        |mode, value| {
+            [council_client.update_reserve_config(
+                &market.lending_market,
+                &usdc.reserve,
+                mode,
+                value,
+            )]
+        };
+
+    // Only risk-reducing changes are allowed to the risk council
+    ctx.process(
+        &council_update(UpdateConfigMode::UpdateLoanToValuePct, vec![60]),
+        &[&risk_council],
+    )
+    .await
+    .unwrap();
+    let res = ctx
+        .process(
+            &council_update(UpdateConfigMode::UpdateLoanToValuePct, vec![65]),
+            &[&risk_council],
+        )
+        .await;
+    assert!(res.is_err());
+    let res = ctx
+        .process(
+            &council_update(UpdateConfigMode::UpdateProtocolTakeRate, vec![5]),
+            &[&risk_council],
+        )
+        .await;
+    assert!(res.is_err());
+
+    ctx.process(
+        &council_update(
+            UpdateConfigMode::UpdateBorrowLimit,
+            0_u64.to_le_bytes().to_vec(),
+        ),
+        &[&risk_council],
+    )
+    .await
+    .unwrap();
+    ctx.process(
+        &council_update(
+            UpdateConfigMode::UpdateBlockBorrowingAboveUtilizationPct,
+            vec![80],
+        ),
+        &[&risk_council],
+    )
+    .await
+    .unwrap();
+    ctx.process(
+        &council_update(
+            UpdateConfigMode::UpdateReserveStatus,
+            vec![ReserveStatus::Obsolete as u8],
+        ),
+        &[&risk_council],
+    )
+    .await
+    .unwrap();
+    let res = ctx
+        .process(
+            &council_update(
+                UpdateConfigMode::UpdateReserveStatus,
+                vec![ReserveStatus::Active as u8],
+            ),
+            &[&risk_council],
+        )
+        .await;
+    assert!(res.is_err());
+
+    let reserve = ctx.get_reserve(&usdc).await;
+    assert_eq!(reserve.config.loan_to_value_pct, 60);
+    assert_eq!(reserve.config.borrow_limit, 0);
+    assert_eq!(
+        reserve.config.utilization_limit_block_borrowing_above_pct,
+        80
+    );
+    assert_eq!(reserve.config.status(), ReserveStatus::Obsolete);
+
+    // Other signers cannot update the reserve at all, the owner is unrestricted
+    let ix = ctx.client(&outsider).update_reserve_config(
+        &market.lending_market,
+        &usdc.reserve,
+        UpdateConfigMode::UpdateLoanToValuePct,
+        vec![50],
+    );
+    let res = ctx.process(&[ix], &[&outsider]).await;
+    assert!(res.is_err());
+    let ix = owner_client.update_reserve_config(
+        &market.lending_market,
+        &usdc.reserve,
+        UpdateConfigMode::UpdateReserveStatus,
+        vec![ReserveStatus::Active as u8],
+    );
+    ctx.process(&[ix], &[]).await.unwrap();
+    assert_eq!(
+        ctx.get_reserve(&usdc).await.config.status(),
+        ReserveStatus::Active
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_queue_filled_by_repay() {
+    let mut ctx = KlendTestContext::new().await;
+    let market = ctx.setup_market().await;
+    let usdc = ctx.setup_reserve(&market, &ReserveSpec::default()).await;
+    let sol = ctx.setup_reserve(&market, &sol_spec()).await;
+
+    let usdc_state = ctx.get_reserve(&usdc).await;
+    let ix = ctx
+        .client(ctx.payer())
+        .init_withdraw_queue(&usdc.reserve, &usdc_state);
+    ctx.process(&[ix], &[]).await.unwrap();
+
+    let lender = ctx.create_funded_user(LAMPORTS_PER_SOL).await;
+    let lender_usdc = ctx
+        .mint_to(&usdc.liquidity_mint, &lender.pubkey(), 1_000_000_000)
+        .await;
+    ctx.create_ata(&lender.pubkey(), &usdc.collateral_mint)
+        .await;
+    ctx.process_with_reserve_refresh(&lender, &usdc, |client, usdc_state| {
+        client.deposit_reserve_liquidity(&usdc.reserve, usdc_state, 1_000_000_000)
+    })
+    .await
+    .unwrap();
+
+    let borrower = ctx.create_funded_user(LAMPORTS_PER_SOL).await;
+    ctx.mint_to(
+        &sol.liquidity_mint,
+        &borrower.pubkey(),
+        10 * LAMPORTS_PER_SOL,
+    )
+    .await;
+    ctx.create_ata(&borrower.pubkey(), &usdc.liquidity_mint)
+        .await;
+    let obligation = ctx.setup_obligation(&market, &borrower).await;
+    ctx.process_with_obligation_refresh(&borrower, &obligation, &sol, |client, sol_state| {
+        client.deposit_reserve_liquidity_and_obligation_collateral(
+            &obligation.obligation,
+            &sol.reserve,
+            sol_state,
+            10 * LAMPORTS_PER_SOL,
+        )
+    })
+    .await
+    .unwrap();
+    ctx.process_with_obligation_refresh(&borrower, &obligation, &usdc, |client, usdc_state| {
+        client.borrow_obligation_liquidity(
+            &obligation.obligation,
+            &obligation.owner,
+            &usdc.reserve,
+            usdc_state,
+            700_000_000,
+            None,
+        )
+    })
+    .await
+    .unwrap();
+
+    // The available liquidity does not cover the withdrawal, it is queued
+    ctx.process_with_reserve_refresh(&lender, &usdc, |client, usdc_state| {
+        client.enqueue_withdrawal(&usdc.reserve, usdc_state, 500_000_000)
+    })
+    .await
+    .unwrap();
+    let usdc_state = ctx.get_reserve(&usdc).await;
+    assert_eq!(usdc_state.withdraw_queue_collateral_amount, 500_000_000);
+
+    // The queue has pending tickets, a repay leaving it out is rejected
+    ctx.mint_to(&usdc.liquidity_mint, &borrower.pubkey(), 1_000_000)
+        .await;
+    let res = ctx
+        .process_with_obligation_refresh(&borrower, &obligation, &usdc, |client, usdc_state| {
+            let mut ix = client.repay_obligation_liquidity(
+                &obligation.obligation,
+                &usdc.reserve,
+                usdc_state,
+                u64::MAX,
+            );
+            ix.accounts.pop();
+            ix
+        })
+        .await;
+    assert!(res.is_err());
+
+    // The repay passes the queue and its liquidity fills the ticket
+    ctx.process_with_obligation_refresh(&borrower, &obligation, &usdc, |client, usdc_state| {
+        client.repay_obligation_liquidity(
+            &obligation.obligation,
+            &usdc.reserve,
+            usdc_state,
+            u64::MAX,
+        )
+    })
+    .await
+    .unwrap();
+    let (withdraw_queue, _) = seeds::pda::withdraw_queue(usdc.reserve);
+    let queue_state: klend::WithdrawQueue = ctx.get_zero_copy_account(&withdraw_queue).await;
+    assert_eq!(queue_state.tickets()[0].collateral_amount, 0);
+    assert!(queue_state.tickets()[0].filled_liquidity_amount >= 500_000_000);
+
+    ctx.process_with_reserve_refresh(&lender, &usdc, |client, usdc_state| {
+        client.claim_withdrawal(&usdc.reserve, usdc_state)
+    })
+    .await
+    .unwrap();
+    assert!(ctx.token_balance(&lender_usdc).await >= 500_000_000);
+    let usdc_state = ctx.get_reserve(&usdc).await;
+    assert_eq!(usdc_state.withdraw_queue_collateral_amount, 0);
+}
+
+#[tokio::test]
+async fn test_refresh_reserve_tracks_oracle_updates() {
+    let mut ctx = KlendTestContext::new().await;
+    let market = ctx.setup_market().await;
+    let usdc = ctx.setup_reserve(&market, &ReserveSpec::default()).await;
+
+    ctx.refresh_reserve(&usdc).await.unwrap();
+    let reserve = ctx.get_reserve(&usdc).await;
+    assert_eq!(
+        reserve.last_update.get_price_status(),
+        PriceStatusFlags::ALL_CHECKS
+    );
+
+    ctx.warp_seconds(120).await;
+    let clock = ctx.get_clock().await;
+    let stale = MockOracle::new(1_000_000, 6, clock.unix_timestamp - 61);
+    ctx.set_pyth_oracle(&usdc.pyth_oracle, &stale).await;
+    ctx.refresh_reserve(&usdc).await.unwrap();
+    let reserve = ctx.get_reserve(&usdc).await;
+    assert_eq!(
+        reserve.last_update.get_price_status(),
+        PriceStatusFlags::ALL_CHECKS - PriceStatusFlags::PRICE_AGE_CHECKED
+    );
+
+    ctx.set_pyth_price(&usdc.pyth_oracle, 1_000_000, 6).await;
+    ctx.warp_slots(1).await;
+    ctx.refresh_reserve(&usdc).await.unwrap();
+    let reserve = ctx.get_reserve(&usdc).await;
+    assert_eq!(
+        reserve.last_update.get_price_status(),
+        PriceStatusFlags::ALL_CHECKS
+    );
+}
@@ -0,0 +1,62 @@
+use anchor_lang::{prelude::Pubkey, Discriminator};
+use klend::{
+    utils::{OBLIGATION_EXTENSION_SIZE, OBLIGATION_SIZE},
+    Obligation, ObligationCollateral, ObligationExtension, ObligationLayout,
+};
+use klend_client::obligation::decode_obligation;
+
+fn account_data(obligation: &Obligation, extension: Option<&ObligationExtension>) -> Vec<u8> {
+    let mut data = Obligation::discriminator().to_vec();
+    data.extend_from_slice(bytemuck::bytes_of(obligation));
+    if let Some(extension) = extension {
+        data.extend_from_slice(bytemuck::bytes_of(extension));
+    }
+    data
+}
+
+#[test]
+fn test_decode_standard_obligation() {
+    let mut obligation = Obligation::default();
+    let reserve = Pubkey::new_unique();
+    obligation.deposits[3] = ObligationCollateral::new(reserve);
+
+    let decoded = decode_obligation(&account_data(&obligation, None)).unwrap();
+    assert!(decoded.extension.is_none());
+    assert_eq!(decoded.view().deposits().count(), 8);
+    assert_eq!(
+        decoded
+            .view()
+            .position_of_collateral_in_deposits(reserve)
+            .unwrap(),
+        3
+    );
+}
+
+#[test]
+fn test_decode_extended_obligation() {
+    let obligation = Obligation {
+        layout_version: ObligationLayout::Extended.into(),
+        ..Default::default()
+    };
+    let mut extension = ObligationExtension::default();
+    let reserve = Pubkey::new_unique();
+    extension.deposits[1] = ObligationCollateral::new(reserve);
+
+    let data = account_data(&obligation, Some(&extension));
+    assert_eq!(data.len(), 8 + OBLIGATION_SIZE + OBLIGATION_EXTENSION_SIZE);
+
+    let decoded = decode_obligation(&data).unwrap();
+    assert!(decoded.extension.is_some());
+    assert_eq!(decoded.view().deposits().count(), 16);
+    assert_eq!(decoded.view().deposits_count(), 1);
+    assert_eq!(
+        decoded
+            .view()
+            .position_of_collateral_in_deposits(reserve)
+            .unwrap(),
+        9
+    );
+
+    assert!(decode_obligation(&data[..8 + OBLIGATION_SIZE]).is_err());
+    assert!(decode_obligation(&data[8..]).is_err());
+}
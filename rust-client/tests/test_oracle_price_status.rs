@@ -0,0 +1,776 @@
+mod common;
+
+use anchor_lang::prelude::{AccountInfo, Pubkey};
+use common::oracles::{
+    pyth_partially_verified_price_update_account, pyth_price_update_account,
+    pyth_price_update_account_posted_at, reserve_account_with_price, scope_prices_account,
+    stake_pool_account, switchboard_pull_feed_account, MockOracle, MockPrice,
+};
+use klend::{
+    fraction::Fraction,
+    utils::{get_derived_price, get_price, GetPriceResult, NULL_PUBKEY},
+    PriceAggregationMode, PriceSource, PriceStatusFlags, TokenInfo,
+};
+use pyth_solana_receiver_sdk::price_update::VerificationLevel;
+use solana_sdk::{account::Account, account_info::IntoAccountInfo, clock::Clock};
+
+const NOW: i64 = 1_700_000_000;
+const SLOT: u64 = 250_000_000;
+
+fn clock() -> Clock {
+    Clock {
+        slot: SLOT,
+        unix_timestamp: NOW,
+        ..Default::default()
+    }
+}
+
+fn token_info() -> TokenInfo {
+    let mut token_info = TokenInfo::default();
+    token_info.name[..4].copy_from_slice(b"TEST");
+    token_info.max_age_price_seconds = 60;
+    token_info.max_age_twap_seconds = 600;
+    token_info.scope_configuration.price_feed = NULL_PUBKEY;
+    token_info
+}
+
+fn with_pyth(mut token_info: TokenInfo, pyth: &Pubkey) -> TokenInfo {
+    token_info.pyth_configuration.price = *pyth;
+    token_info
+}
+
+#[derive(Default)]
+struct Oracles {
+    pyth: Option<(Pubkey, Account)>,
+    switchboard_price: Option<(Pubkey, Account)>,
+    switchboard_twap: Option<(Pubkey, Account)>,
+    scope: Option<(Pubkey, Account)>,
+}
+
+impl Oracles {
+    fn get_price(&mut self, token_info: &TokenInfo) -> anchor_lang::Result<Option<GetPriceResult>> {
+        fn info(oracle: &mut Option<(Pubkey, Account)>) -> Option<AccountInfo> {
+            oracle
+                .as_mut()
+                .map(|(key, account)| (&*key, account).into_account_info())
+        }
+
+        let pyth = info(&mut self.pyth);
+        let switchboard_price = info(&mut self.switchboard_price);
+        let switchboard_twap = info(&mut self.switchboard_twap);
+        let scope = info(&mut self.scope);
+
+        get_price(
+            token_info,
+            pyth.as_ref(),
+            switchboard_price.as_ref(),
+            switchboard_twap.as_ref(),
+            scope.as_ref(),
+            &clock(),
+        )
+    }
+}
+
+fn status_of(oracles: &mut Oracles, token_info: &TokenInfo) -> PriceStatusFlags {
+    oracles.get_price(token_info).unwrap().unwrap().status
+}
+
+#[test]
+fn test_pyth_fresh_price_passes_all_checks() {
+    let pyth = Pubkey::new_unique();
+    let oracle = MockOracle::new(100_000_000, 6, NOW);
+    let mut oracles = Oracles {
+        pyth: Some((pyth, pyth_price_update_account(&oracle, &clock()))),
+        ..Default::default()
+    };
+
+    let res = oracles
+        .get_price(&with_pyth(token_info(), &pyth))
+        .unwrap()
+        .unwrap();
+    assert_eq!(res.status, PriceStatusFlags::ALL_CHECKS);
+    assert_eq!(res.price, Fraction::from_num(100));
+    assert_eq!(res.timestamp, NOW as u64);
+}
+
+#[test]
+fn test_pyth_stale_price_fails_age_check() {
+    let pyth = Pubkey::new_unique();
+    let oracle = MockOracle::new(100_000_000, 6, NOW - 61);
+    let mut oracles = Oracles {
+        pyth: Some((pyth, pyth_price_update_account(&oracle, &clock()))),
+        ..Default::default()
+    };
+
+    let status = status_of(&mut oracles, &with_pyth(token_info(), &pyth));
+    assert_eq!(
+        status,
+        PriceStatusFlags::ALL_CHECKS - PriceStatusFlags::PRICE_AGE_CHECKED
+    );
+}
+
+#[test]
+fn test_pyth_wide_confidence_fails_confidence_check() {
+    let pyth = Pubkey::new_unique();
+    let price = MockPrice::new(100_000_000, 6, NOW).with_conf(3_000_000);
+    let oracle = MockOracle {
+        price,
+        twap: price.with_conf(0),
+    };
+    let mut oracles = Oracles {
+        pyth: Some((pyth, pyth_price_update_account(&oracle, &clock()))),
+        ..Default::default()
+    };
+
+    let mut token_info = with_pyth(token_info(), &pyth);
+    let status = status_of(&mut oracles, &token_info);
+    assert_eq!(
+        status,
+        PriceStatusFlags::ALL_CHECKS - PriceStatusFlags::PRICE_CONFIDENCE_CHECKED
+    );
+    assert!(status.contains(PriceStatusFlags::LIQUIDATION_CHECKS));
+
+    token_info.max_confidence_bps = 300;
+    assert_eq!(
+        status_of(&mut oracles, &token_info),
+        PriceStatusFlags::ALL_CHECKS
+    );
+
+    token_info.max_confidence_bps = 299;
+    assert_eq!(
+        status_of(&mut oracles, &token_info),
+        PriceStatusFlags::ALL_CHECKS - PriceStatusFlags::PRICE_CONFIDENCE_CHECKED
+    );
+
+    assert!(token_info.is_valid());
+    token_info.max_confidence_bps = 10_001;
+    assert!(!token_info.is_valid());
+}
+
+#[test]
+fn test_pyth_wide_twap_confidence_fails_twap_check() {
+    let pyth = Pubkey::new_unique();
+    let price = MockPrice::new(100_000_000, 6, NOW);
+    let oracle = MockOracle::new(100_000_000, 6, NOW).with_twap(price.with_conf(3_000_000));
+    let mut oracles = Oracles {
+        pyth: Some((pyth, pyth_price_update_account(&oracle, &clock()))),
+        ..Default::default()
+    };
+
+    let mut token_info = with_pyth(token_info(), &pyth);
+    token_info.max_twap_divergence_bps = 500;
+    assert_eq!(
+        status_of(&mut oracles, &token_info),
+        PriceStatusFlags::ALL_CHECKS - PriceStatusFlags::TWAP_CHECKED
+    );
+
+    token_info.max_confidence_bps = 300;
+    assert_eq!(
+        status_of(&mut oracles, &token_info),
+        PriceStatusFlags::ALL_CHECKS
+    );
+}
+
+#[test]
+fn test_pyth_verification_level_policy() {
+    let pyth = Pubkey::new_unique();
+    let oracle = MockOracle::new(100_000_000, 6, NOW);
+    let mut token_info = with_pyth(token_info(), &pyth);
+    let mut oracles = Oracles {
+        pyth: Some((
+            pyth,
+            pyth_partially_verified_price_update_account(&oracle, &clock(), 5),
+        )),
+        ..Default::default()
+    };
+
+    assert!(oracles.get_price(&token_info).is_err());
+
+    token_info.pyth_min_verification_signatures = 5;
+    assert_eq!(
+        status_of(&mut oracles, &token_info),
+        PriceStatusFlags::ALL_CHECKS
+    );
+
+    token_info.pyth_min_verification_signatures = 6;
+    assert!(oracles.get_price(&token_info).is_err());
+
+    oracles.pyth = Some((pyth, pyth_price_update_account(&oracle, &clock())));
+    assert_eq!(
+        status_of(&mut oracles, &token_info),
+        PriceStatusFlags::ALL_CHECKS
+    );
+}
+
+#[test]
+fn test_pyth_posted_slot_lag_policy() {
+    let pyth = Pubkey::new_unique();
+    let oracle = MockOracle::new(100_000_000, 6, NOW);
+    let mut token_info = with_pyth(token_info(), &pyth);
+    let mut oracles = Oracles {
+        pyth: Some((
+            pyth,
+            pyth_price_update_account_posted_at(&oracle, VerificationLevel::Full, SLOT - 100),
+        )),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        status_of(&mut oracles, &token_info),
+        PriceStatusFlags::ALL_CHECKS
+    );
+
+    token_info.pyth_max_posted_slot_lag = 100;
+    assert_eq!(
+        status_of(&mut oracles, &token_info),
+        PriceStatusFlags::ALL_CHECKS
+    );
+
+    token_info.pyth_max_posted_slot_lag = 99;
+    assert!(oracles.get_price(&token_info).is_err());
+}
+
+#[test]
+fn test_pyth_twap_divergence() {
+    let pyth = Pubkey::new_unique();
+    let mut token_info = with_pyth(token_info(), &pyth);
+    token_info.max_twap_divergence_bps = 500;
+
+    let oracle = MockOracle::new(100_000_000, 6, NOW);
+    let mut oracles = Oracles {
+        pyth: Some((pyth, pyth_price_update_account(&oracle, &clock()))),
+        ..Default::default()
+    };
+    assert_eq!(
+        status_of(&mut oracles, &token_info),
+        PriceStatusFlags::ALL_CHECKS
+    );
+
+    let divergent = oracle.with_twap(oracle.twap.with_price(90_000_000));
+    oracles.pyth = Some((pyth, pyth_price_update_account(&divergent, &clock())));
+    assert_eq!(
+        status_of(&mut oracles, &token_info),
+        PriceStatusFlags::ALL_CHECKS - PriceStatusFlags::TWAP_CHECKED
+    );
+}
+
+#[test]
+fn test_heuristic_and_usage_flags() {
+    let pyth = Pubkey::new_unique();
+    let oracle = MockOracle::new(100_000_000, 6, NOW);
+    let mut oracles = Oracles {
+        pyth: Some((pyth, pyth_price_update_account(&oracle, &clock()))),
+        ..Default::default()
+    };
+
+    let mut token_info = with_pyth(token_info(), &pyth);
+    token_info.heuristic.lower = 110;
+    token_info.heuristic.upper = 200;
+    token_info.heuristic.exp = 0;
+    assert_eq!(
+        status_of(&mut oracles, &token_info),
+        PriceStatusFlags::ALL_CHECKS - PriceStatusFlags::HEURISTIC_CHECKED
+    );
+
+    token_info.heuristic.lower = 50;
+    token_info.block_price_usage = 1;
+    assert_eq!(
+        status_of(&mut oracles, &token_info),
+        PriceStatusFlags::ALL_CHECKS - PriceStatusFlags::PRICE_USAGE_ALLOWED
+    );
+}
+
+#[test]
+fn test_switchboard_price_and_twap() {
+    let price_feed = Pubkey::new_unique();
+    let twap_feed = Pubkey::new_unique();
+    let mut token_info = token_info();
+    token_info.switchboard_configuration.price_aggregator = price_feed;
+    token_info.switchboard_configuration.twap_aggregator = twap_feed;
+    token_info.max_twap_divergence_bps = 500;
+
+    let price = MockPrice::new(25_000_000, 6, NOW);
+    let mut oracles = Oracles {
+        switchboard_price: Some((price_feed, switchboard_pull_feed_account(&price, &clock()))),
+        switchboard_twap: Some((twap_feed, switchboard_pull_feed_account(&price, &clock()))),
+        ..Default::default()
+    };
+
+    let res = oracles.get_price(&token_info).unwrap().unwrap();
+    assert_eq!(res.status, PriceStatusFlags::ALL_CHECKS);
+    assert_eq!(res.price, Fraction::from_num(25));
+
+    let stale_twap = price.aged(620);
+    oracles.switchboard_twap = Some((
+        twap_feed,
+        switchboard_pull_feed_account(&stale_twap, &clock()),
+    ));
+    assert_eq!(
+        status_of(&mut oracles, &token_info),
+        PriceStatusFlags::ALL_CHECKS - PriceStatusFlags::TWAP_AGE_CHECKED
+    );
+}
+
+#[test]
+fn test_switchboard_wide_confidence_fails_confidence_check() {
+    let price_feed = Pubkey::new_unique();
+    let mut token_info = token_info();
+    token_info.switchboard_configuration.price_aggregator = price_feed;
+
+    let price = MockPrice::new(25_000_000, 6, NOW).with_conf(1_000_000);
+    let mut oracles = Oracles {
+        switchboard_price: Some((price_feed, switchboard_pull_feed_account(&price, &clock()))),
+        ..Default::default()
+    };
+
+    let res = oracles.get_price(&token_info).unwrap().unwrap();
+    assert_eq!(res.price, Fraction::from_num(25));
+    assert_eq!(
+        res.status,
+        PriceStatusFlags::ALL_CHECKS - PriceStatusFlags::PRICE_CONFIDENCE_CHECKED
+    );
+
+    token_info.max_confidence_bps = 500;
+    assert_eq!(
+        status_of(&mut oracles, &token_info),
+        PriceStatusFlags::ALL_CHECKS
+    );
+}
+
+#[test]
+fn test_scope_chain_and_twap() {
+    let scope_prices = Pubkey::new_unique();
+    let mut token_info = token_info();
+    token_info.scope_configuration.price_feed = scope_prices;
+    token_info.scope_configuration.price_chain = [1, 2, u16::MAX, u16::MAX];
+    token_info.scope_configuration.twap_chain = [3, u16::MAX, u16::MAX, u16::MAX];
+    token_info.max_twap_divergence_bps = 500;
+
+    let mut oracles = Oracles {
+        scope: Some((
+            scope_prices,
+            scope_prices_account(&[
+                (1, MockPrice::new(2_000, 3, NOW)),
+                (2, MockPrice::new(150, 2, NOW - 10)),
+                (3, MockPrice::new(3_000_000, 6, NOW - 20)),
+            ]),
+        )),
+        ..Default::default()
+    };
+
+    let res = oracles.get_price(&token_info).unwrap().unwrap();
+    assert_eq!(res.status, PriceStatusFlags::ALL_CHECKS);
+    assert_eq!(res.price, Fraction::from_num(3));
+    assert_eq!(res.timestamp, (NOW - 10) as u64);
+    assert!(token_info.is_valid());
+
+    // Scope does not publish a confidence interval, the check fails closed once required
+    token_info.require_confidence = 1;
+    assert_eq!(
+        status_of(&mut oracles, &token_info),
+        PriceStatusFlags::ALL_CHECKS
+            - PriceStatusFlags::PRICE_CONFIDENCE_CHECKED
+            - PriceStatusFlags::TWAP_CHECKED
+    );
+}
+
+#[test]
+fn test_scope_price_of_reserve_created_before_require_confidence() {
+    let scope_prices = Pubkey::new_unique();
+    let mut token_info = token_info();
+    token_info.scope_configuration.price_feed = scope_prices;
+    token_info.scope_configuration.price_chain = [1, u16::MAX, u16::MAX, u16::MAX];
+    token_info.scope_configuration.twap_chain = [2, u16::MAX, u16::MAX, u16::MAX];
+    token_info.max_twap_divergence_bps = 500;
+    token_info.require_confidence = 1;
+
+    // The flag is carved from reserved bytes, zero on every reserve created before it
+    let mut bytes = bytemuck::bytes_of(&token_info).to_vec();
+    let flag_offset = std::ptr::addr_of!(token_info.require_confidence) as usize
+        - std::ptr::addr_of!(token_info) as usize;
+    bytes[flag_offset..flag_offset + 4].fill(0);
+    let token_info: TokenInfo = bytemuck::pod_read_unaligned(&bytes);
+    assert!(!token_info.is_confidence_required());
+    assert!(token_info.is_valid());
+
+    let mut oracles = Oracles {
+        scope: Some((
+            scope_prices,
+            scope_prices_account(&[
+                (1, MockPrice::new(2_000, 3, NOW)),
+                (2, MockPrice::new(2_010, 3, NOW - 20)),
+            ]),
+        )),
+        ..Default::default()
+    };
+    assert_eq!(
+        status_of(&mut oracles, &token_info),
+        PriceStatusFlags::ALL_CHECKS
+    );
+}
+
+#[test]
+fn test_most_recent_source_wins() {
+    let pyth = Pubkey::new_unique();
+    let scope_prices = Pubkey::new_unique();
+    let mut token_info = with_pyth(token_info(), &pyth);
+    token_info.scope_configuration.price_feed = scope_prices;
+    token_info.scope_configuration.price_chain = [0, u16::MAX, u16::MAX, u16::MAX];
+
+    let mut oracles = Oracles {
+        pyth: Some((
+            pyth,
+            pyth_price_update_account(&MockOracle::new(100_000_000, 6, NOW - 30), &clock()),
+        )),
+        scope: Some((
+            scope_prices,
+            scope_prices_account(&[(0, MockPrice::new(101_000_000, 6, NOW - 5))]),
+        )),
+        ..Default::default()
+    };
+
+    let res = oracles.get_price(&token_info).unwrap().unwrap();
+    assert_eq!(res.price, Fraction::from_num(101));
+    assert_eq!(res.timestamp, (NOW - 5) as u64);
+}
+
+fn with_all_sources(
+    token_info: TokenInfo,
+    pyth: &Pubkey,
+    switchboard_feed: &Pubkey,
+    scope_prices: &Pubkey,
+) -> TokenInfo {
+    let mut token_info = with_pyth(token_info, pyth);
+    token_info.switchboard_configuration.price_aggregator = *switchboard_feed;
+    token_info.scope_configuration.price_feed = *scope_prices;
+    token_info.scope_configuration.price_chain = [0, u16::MAX, u16::MAX, u16::MAX];
+    token_info
+}
+
+fn all_oracles(
+    (pyth, pyth_price): (Pubkey, i64),
+    (switchboard_feed, switchboard_price): (Pubkey, i64),
+    (scope_prices, scope_price): (Pubkey, i64),
+) -> Oracles {
+    Oracles {
+        pyth: Some((
+            pyth,
+            pyth_price_update_account(&MockOracle::new(pyth_price, 6, NOW - 30), &clock()),
+        )),
+        switchboard_price: Some((
+            switchboard_feed,
+            switchboard_pull_feed_account(&MockPrice::new(switchboard_price, 6, NOW), &clock()),
+        )),
+        scope: Some((
+            scope_prices,
+            scope_prices_account(&[(0, MockPrice::new(scope_price, 6, NOW - 5))]),
+        )),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_median_aggregation() {
+    let (pyth, switchboard_feed, scope_prices) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let mut token_info = with_all_sources(token_info(), &pyth, &switchboard_feed, &scope_prices);
+    token_info.price_aggregation_mode = PriceAggregationMode::Median.into();
+    token_info.max_price_sources_divergence_bps = 200;
+
+    let mut oracles = all_oracles(
+        (pyth, 100_000_000),
+        (switchboard_feed, 101_000_000),
+        (scope_prices, 130_000_000),
+    );
+    let res = oracles.get_price(&token_info).unwrap().unwrap();
+    assert_eq!(res.price, Fraction::from_num(101));
+    assert_eq!(res.timestamp, (NOW - 30) as u64);
+    assert_eq!(res.status, PriceStatusFlags::ALL_CHECKS);
+
+    oracles.switchboard_price = None;
+    let res = oracles.get_price(&token_info).unwrap().unwrap();
+    assert_eq!(res.price, Fraction::from_num(115));
+    assert_eq!(
+        res.status,
+        PriceStatusFlags::ALL_CHECKS - PriceStatusFlags::PRICE_SOURCES_AGREED
+    );
+}
+
+#[test]
+fn test_primary_with_deviation_guard_aggregation() {
+    let (pyth, switchboard_feed, scope_prices) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let mut token_info = with_all_sources(token_info(), &pyth, &switchboard_feed, &scope_prices);
+    token_info.price_aggregation_mode = PriceAggregationMode::PrimaryWithDeviationGuard.into();
+    token_info.primary_price_source = PriceSource::Pyth.into();
+    token_info.max_price_sources_divergence_bps = 200;
+
+    let mut oracles = all_oracles(
+        (pyth, 100_000_000),
+        (switchboard_feed, 110_000_000),
+        (scope_prices, 101_000_000),
+    );
+    let res = oracles.get_price(&token_info).unwrap().unwrap();
+    assert_eq!(res.price, Fraction::from_num(100));
+    assert_eq!(res.timestamp, (NOW - 30) as u64);
+    assert_eq!(res.status, PriceStatusFlags::ALL_CHECKS);
+
+    oracles.scope = Some((
+        scope_prices,
+        scope_prices_account(&[(0, MockPrice::new(90_000_000, 6, NOW - 5))]),
+    ));
+    assert_eq!(
+        status_of(&mut oracles, &token_info),
+        PriceStatusFlags::ALL_CHECKS - PriceStatusFlags::PRICE_SOURCES_AGREED
+    );
+
+    oracles.pyth = None;
+    let res = oracles.get_price(&token_info).unwrap().unwrap();
+    assert_eq!(res.price, Fraction::from_num(110));
+    assert!(!res.status.contains(PriceStatusFlags::PRICE_SOURCES_AGREED));
+    assert!(res.status.contains(PriceStatusFlags::LIQUIDATION_CHECKS));
+}
+
+#[test]
+fn test_price_aggregation_config_validation() {
+    let (pyth, switchboard_feed, scope_prices) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let mut token_info = with_pyth(token_info(), &pyth);
+    assert!(token_info.is_price_aggregation_config_valid());
+
+    token_info.price_aggregation_mode = PriceAggregationMode::Median.into();
+    token_info.max_price_sources_divergence_bps = 200;
+    assert!(!token_info.is_price_aggregation_config_valid());
+
+    token_info = with_all_sources(token_info, &pyth, &switchboard_feed, &scope_prices);
+    assert!(token_info.is_price_aggregation_config_valid());
+
+    token_info.max_price_sources_divergence_bps = 0;
+    assert!(!token_info.is_price_aggregation_config_valid());
+
+    token_info.max_price_sources_divergence_bps = 200;
+    token_info.price_aggregation_mode = PriceAggregationMode::PrimaryWithDeviationGuard.into();
+    token_info.primary_price_source = 3;
+    assert!(!token_info.is_price_aggregation_config_valid());
+
+    token_info.primary_price_source = PriceSource::Switchboard.into();
+    assert!(token_info.is_price_aggregation_config_valid());
+
+    token_info.switchboard_configuration.price_aggregator = NULL_PUBKEY;
+    assert!(!token_info.is_price_aggregation_config_valid());
+
+    token_info.price_aggregation_mode = 3;
+    assert!(!token_info.is_price_aggregation_config_valid());
+}
+
+#[test]
+fn test_fixed_price_passes_all_checks() {
+    let mut token_info = token_info();
+    token_info.fixed_price_configuration.value = 1_000_000;
+    token_info.fixed_price_configuration.exp = 6;
+    assert!(token_info.is_valid());
+
+    let res = Oracles::default().get_price(&token_info).unwrap().unwrap();
+    assert_eq!(res.price, Fraction::ONE);
+    assert_eq!(res.timestamp, NOW as u64);
+    assert_eq!(res.status, PriceStatusFlags::ALL_CHECKS);
+
+    token_info.heuristic.lower = 2;
+    assert_eq!(
+        status_of(&mut Oracles::default(), &token_info),
+        PriceStatusFlags::ALL_CHECKS - PriceStatusFlags::HEURISTIC_CHECKED
+    );
+}
+
+#[test]
+fn test_derived_price_from_base_reserve_and_stake_pool() {
+    let reserve = Pubkey::new_unique();
+    let lending_market = Pubkey::new_unique();
+    let base_reserve = Pubkey::new_unique();
+    let stake_pool = Pubkey::new_unique();
+    let mut token_info = token_info();
+    token_info.derived_price_configuration.base_reserve = base_reserve;
+    token_info.derived_price_configuration.stake_pool = stake_pool;
+    assert!(token_info.is_valid());
+
+    let get_derived = |base: Account, pool: Account| {
+        let (mut base, mut pool) = ((base_reserve, base), (stake_pool, pool));
+        let base_info = (&base.0, &mut base.1).into_account_info();
+        let pool_info = (&pool.0, &mut pool.1).into_account_info();
+        assert!(token_info.check_derived_price_acc_matches(Some(&base_info), Some(&pool_info)));
+        get_derived_price(
+            &token_info,
+            &reserve,
+            &lending_market,
+            &base_info,
+            &pool_info,
+            &clock(),
+        )
+    };
+    let base_reserve_account = |price_status| {
+        reserve_account_with_price(
+            &lending_market,
+            Fraction::from_num(100),
+            &clock(),
+            price_status,
+        )
+    };
+
+    let res = get_derived(
+        base_reserve_account(PriceStatusFlags::ALL_CHECKS),
+        stake_pool_account(1_250_000_000, 1_000_000_000, 0),
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(res.price, Fraction::from_num(125));
+    assert_eq!(res.status, PriceStatusFlags::ALL_CHECKS);
+
+    // Checks failed by the base price carry over to the derived price
+    let res = get_derived(
+        base_reserve_account(PriceStatusFlags::ALL_CHECKS - PriceStatusFlags::PRICE_SOURCES_AGREED),
+        stake_pool_account(1_250_000_000, 1_000_000_000, 0),
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(
+        res.status,
+        PriceStatusFlags::ALL_CHECKS - PriceStatusFlags::PRICE_SOURCES_AGREED
+    );
+
+    let stale_clock = Clock {
+        slot: SLOT - 1,
+        ..clock()
+    };
+    assert!(get_derived(
+        reserve_account_with_price(
+            &lending_market,
+            Fraction::from_num(100),
+            &stale_clock,
+            PriceStatusFlags::ALL_CHECKS
+        ),
+        stake_pool_account(1_250_000_000, 1_000_000_000, 0),
+    )
+    .is_err());
+
+    assert!(get_derived(
+        base_reserve_account(PriceStatusFlags::ALL_CHECKS),
+        stake_pool_account(1_250_000_000, 1_000_000_000, 1),
+    )
+    .is_err());
+}
+
+#[test]
+fn test_derived_price_rejects_forged_accounts() {
+    let reserve = Pubkey::new_unique();
+    let lending_market = Pubkey::new_unique();
+    let base_reserve = Pubkey::new_unique();
+    let stake_pool = Pubkey::new_unique();
+    let mut token_info = token_info();
+    token_info.derived_price_configuration.base_reserve = base_reserve;
+    token_info.derived_price_configuration.stake_pool = stake_pool;
+
+    let get_derived = |reserve: Pubkey,
+                       (base_key, base): (Pubkey, Account),
+                       (pool_key, pool): (Pubkey, Account)| {
+        let (mut base, mut pool) = ((base_key, base), (pool_key, pool));
+        let base_info = (&base.0, &mut base.1).into_account_info();
+        let pool_info = (&pool.0, &mut pool.1).into_account_info();
+        get_derived_price(
+            &token_info,
+            &reserve,
+            &lending_market,
+            &base_info,
+            &pool_info,
+            &clock(),
+        )
+    };
+    let base_reserve_account = |lending_market: &Pubkey| {
+        reserve_account_with_price(
+            lending_market,
+            Fraction::from_num(100),
+            &clock(),
+            PriceStatusFlags::ALL_CHECKS,
+        )
+    };
+    let stake_pool_account = || stake_pool_account(1_250_000_000, 1_000_000_000, 0);
+
+    assert!(get_derived(
+        reserve,
+        (base_reserve, base_reserve_account(&lending_market)),
+        (stake_pool, stake_pool_account()),
+    )
+    .is_ok());
+
+    // A stake pool with the expected layout but not owned by the stake pool program
+    let mut forged_pool = stake_pool_account();
+    forged_pool.owner = Pubkey::new_unique();
+    assert!(get_derived(
+        reserve,
+        (base_reserve, base_reserve_account(&lending_market)),
+        (stake_pool, forged_pool),
+    )
+    .is_err());
+
+    // Accounts other than the ones stored in the reserve config
+    assert!(get_derived(
+        reserve,
+        (base_reserve, base_reserve_account(&lending_market)),
+        (Pubkey::new_unique(), stake_pool_account()),
+    )
+    .is_err());
+    assert!(get_derived(
+        reserve,
+        (Pubkey::new_unique(), base_reserve_account(&lending_market)),
+        (stake_pool, stake_pool_account()),
+    )
+    .is_err());
+
+    // A base reserve from another market or the refreshed reserve itself
+    assert!(get_derived(
+        reserve,
+        (base_reserve, base_reserve_account(&Pubkey::new_unique())),
+        (stake_pool, stake_pool_account()),
+    )
+    .is_err());
+    assert!(get_derived(
+        base_reserve,
+        (base_reserve, base_reserve_account(&lending_market)),
+        (stake_pool, stake_pool_account()),
+    )
+    .is_err());
+}
+
+#[test]
+fn test_oracle_free_config_validation() {
+    let mut token_info = token_info();
+    token_info.fixed_price_configuration.value = 1;
+    assert!(token_info.is_valid());
+
+    token_info.pyth_configuration.price = Pubkey::new_unique();
+    assert!(!token_info.is_valid());
+    token_info.pyth_configuration.price = Pubkey::default();
+
+    token_info.max_twap_divergence_bps = 100;
+    assert!(!token_info.is_valid());
+    token_info.max_twap_divergence_bps = 0;
+
+    token_info.derived_price_configuration.base_reserve = Pubkey::new_unique();
+    token_info.derived_price_configuration.stake_pool = Pubkey::new_unique();
+    assert!(!token_info.is_valid());
+
+    token_info.fixed_price_configuration.value = 0;
+    assert!(token_info.is_valid());
+    assert!(!token_info.check_derived_price_acc_matches(None, None));
+
+    token_info.derived_price_configuration.stake_pool = Pubkey::default();
+    assert!(!token_info.is_valid());
+}
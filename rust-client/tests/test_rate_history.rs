@@ -0,0 +1,104 @@
+use anchor_lang::prelude::Clock;
+use klend::{
+    fraction::{BigFraction, Fraction},
+    lending_market::lending_operations,
+    Reserve, RATE_HISTORY_CAPACITY,
+};
+use klend_client::{
+    borrow_rate_curve::{apr_to_apy, kinked_curve},
+    rate_history::rate_history,
+};
+
+const INTERVAL_SLOTS: u64 = 100;
+const START_SLOT: u64 = 1_000;
+
+fn reserve() -> Reserve {
+    let mut reserve = Reserve::default();
+    reserve.config.borrow_rate_curve = kinked_curve(0, 8_000, 1_000, 5_000).unwrap();
+    reserve.config.protocol_take_rate_pct = 10;
+    reserve.config.rate_history_sample_interval_slots = INTERVAL_SLOTS;
+    reserve.liquidity.available_amount = 500_000_000;
+    reserve.liquidity.borrowed_amount_sf = Fraction::from_num(500_000_000).to_bits();
+    reserve.liquidity.cumulative_borrow_rate_bsf = BigFraction::from(Fraction::ONE).into();
+    reserve.last_update.update_slot(START_SLOT, None);
+    reserve
+}
+
+fn refresh(reserve: &mut Reserve, slot: u64) {
+    let clock = Clock {
+        slot,
+        ..Default::default()
+    };
+    lending_operations::refresh_reserve(reserve, &clock, None, 0).unwrap();
+}
+
+#[test]
+fn test_samples_are_taken_at_interval() {
+    let mut reserve = reserve();
+    for slot in (START_SLOT + 10..=START_SLOT + 3 * INTERVAL_SLOTS).step_by(10) {
+        refresh(&mut reserve, slot);
+    }
+
+    let history = rate_history(&reserve).unwrap();
+    let slots: Vec<_> = history.iter().map(|point| point.slot).collect();
+    assert_eq!(
+        slots,
+        vec![
+            START_SLOT + 10,
+            START_SLOT + 10 + INTERVAL_SLOTS,
+            START_SLOT + 10 + 2 * INTERVAL_SLOTS
+        ]
+    );
+
+    let first = &history[0];
+    assert_eq!(first.realized_borrow_apy, None);
+    assert_eq!(first.borrow_apy, apr_to_apy(first.borrow_apr));
+    assert_eq!(
+        first.supply_apr,
+        first.borrow_apr * f64::from(first.utilization_rate_bps) / 10_000.0 * 0.9
+    );
+
+    // Interest compounded between samples matches the sampled rate
+    let second = &history[1];
+    let realized = second.realized_borrow_apy.unwrap();
+    assert!((realized - first.borrow_apy).abs() < 1e-4);
+}
+
+#[test]
+fn test_ring_buffer_keeps_latest_samples_in_order() {
+    let mut reserve = reserve();
+    let num_samples = RATE_HISTORY_CAPACITY as u64 + 5;
+    for i in 1..=num_samples {
+        refresh(&mut reserve, START_SLOT + i * INTERVAL_SLOTS);
+    }
+
+    let history = rate_history(&reserve).unwrap();
+    assert_eq!(history.len(), RATE_HISTORY_CAPACITY);
+    assert_eq!(history[0].slot, START_SLOT + 6 * INTERVAL_SLOTS);
+    assert_eq!(
+        history.last().unwrap().slot,
+        START_SLOT + num_samples * INTERVAL_SLOTS
+    );
+    assert!(history
+        .windows(2)
+        .all(|pair| pair[1].slot - pair[0].slot == INTERVAL_SLOTS));
+}
+
+#[test]
+fn test_disabled_rate_history_records_nothing() {
+    let mut reserve = reserve();
+    reserve.config.rate_history_sample_interval_slots = 0;
+    refresh(&mut reserve, START_SLOT + INTERVAL_SLOTS);
+
+    assert!(rate_history(&reserve).unwrap().is_empty());
+}
+
+#[test]
+fn test_large_sample_interval_does_not_overflow() {
+    let mut reserve = reserve();
+    reserve.config.rate_history_sample_interval_slots = u64::MAX;
+    refresh(&mut reserve, START_SLOT + 10);
+    refresh(&mut reserve, START_SLOT + 20);
+
+    assert_eq!(rate_history(&reserve).unwrap().len(), 1);
+}